@@ -0,0 +1,108 @@
+use drink::{
+    frame_support::sp_runtime::app_crypto::sp_core::{sr25519, Pair},
+    AccountId32, Ss58Codec,
+};
+
+use crate::app_state::AppState;
+
+/// Default SS58 prefix (generic Substrate).
+pub const DEFAULT_SS58_PREFIX: u16 = 42;
+
+/// Aliases of the well-known development accounts (derived from `//Alice`, `//Bob`, etc.).
+pub const DEV_ACCOUNTS: [&str; 6] = ["alice", "bob", "charlie", "dave", "eve", "ferdie"];
+
+/// Development account behind `alias` (case-insensitive), if it is one of `DEV_ACCOUNTS`.
+fn dev_account(alias: &str) -> Option<AccountId32> {
+    let alias = alias.to_lowercase();
+    if !DEV_ACCOUNTS.contains(&alias.as_str()) {
+        return None;
+    }
+
+    let seed = format!("//{}{}", alias[..1].to_uppercase(), &alias[1..]);
+    let pair = sr25519::Pair::from_string(&seed, None).expect("Dev seed should be valid");
+    Some(pair.public().into())
+}
+
+impl AppState {
+    /// SS58 form of `account`, using the currently configured prefix.
+    pub fn ss58(&self, account: &AccountId32) -> String {
+        account.to_ss58check_with_version(self.chain_info.ss58_prefix.into())
+    }
+
+    /// Parse an address given in the SS58 form (with any prefix), as a dev account alias (`alice`,
+    /// `bob`, ...) or as the name of a deployed contract. A contract name refers to the most
+    /// recently deployed contract with that name.
+    pub fn parse_address(&self, input: &str) -> Result<AccountId32, String> {
+        if let Some(account) = dev_account(input) {
+            return Ok(account);
+        }
+        if let Some(contract) = self
+            .contracts
+            .get_all()
+            .iter()
+            .rev()
+            .find(|contract| contract.name == input)
+        {
+            return Ok(contract.address.clone());
+        }
+
+        AccountId32::from_ss58check_with_version(input)
+            .map(|(account, _)| account)
+            .map_err(|err| format!("Invalid address `{input}`: {err:?}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use drink::fixtures::Fixture;
+
+    use super::*;
+    use crate::app_state::Contract;
+
+    const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+
+    #[test]
+    fn addresses_are_formatted_with_the_configured_prefix() {
+        let mut app_state = AppState::default();
+        let bob = app_state.parse_address(BOB).unwrap();
+        assert_eq!(app_state.ss58(&bob), BOB);
+
+        app_state.chain_info.ss58_prefix = 0;
+        assert_eq!(
+            app_state.ss58(&bob),
+            bob.to_ss58check_with_version(0.into())
+        );
+        assert_ne!(app_state.ss58(&bob), BOB);
+    }
+
+    #[test]
+    fn addresses_are_parsed_in_every_supported_form() {
+        let mut app_state = AppState::default();
+        let bob = app_state.parse_address(BOB).unwrap();
+
+        assert_eq!(app_state.parse_address("Bob"), Ok(bob.clone()));
+        let polkadot_form = bob.to_ss58check_with_version(0.into());
+        assert_eq!(app_state.parse_address(&polkadot_form), Ok(bob.clone()));
+
+        app_state.contracts.add(Contract {
+            name: "flipper".to_string(),
+            address: bob.clone(),
+            base_path: Default::default(),
+            package: None,
+            transcoder: Arc::clone(&Fixture::Dummy.bundle().transcoder),
+        });
+        assert_eq!(app_state.parse_address("flipper"), Ok(bob));
+    }
+
+    #[test]
+    fn unknown_addresses_are_rejected() {
+        let app_state = AppState::default();
+        assert!(app_state.parse_address("mallory").is_err());
+        // There is no H160 form of accounts in the contracts pallet.
+        assert!(app_state
+            .parse_address("0x0102030405060708090a0b0c0d0e0f1011121314")
+            .is_err());
+    }
+}
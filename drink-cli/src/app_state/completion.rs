@@ -0,0 +1,242 @@
+use clap::CommandFactory;
+
+use crate::{
+    app_state::{address::DEV_ACCOUNTS, AppState, EntryKind},
+    cli::CliCommand,
+};
+
+/// Context-sensitive candidates offered by the completer (besides the command names).
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct CompletionSources {
+    /// Message labels of the current contract.
+    pub messages: Vec<String>,
+    /// Constructor labels of the current contract.
+    pub constructors: Vec<String>,
+    /// Account aliases and deployed contract names.
+    pub accounts: Vec<String>,
+}
+
+/// An ongoing tab completion of the last word of the user input.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Completion {
+    /// Input preceding the completed word.
+    base: String,
+    candidates: Vec<String>,
+    index: usize,
+}
+
+impl Completion {
+    /// Start completing the last word of `input`. Returns `None` if there is nothing to offer.
+    pub fn start(input: &str, sources: &CompletionSources) -> Option<Self> {
+        let (base, partial) = match input.rfind(char::is_whitespace) {
+            Some(idx) => input.split_at(idx + 1),
+            None => ("", input),
+        };
+        let preceding = base.split_whitespace().collect::<Vec<_>>();
+
+        let mut candidates = Vec::new();
+        for candidate in candidates_for(&preceding, sources) {
+            if candidate.starts_with(partial) && !candidates.contains(&candidate) {
+                candidates.push(candidate);
+            }
+        }
+
+        (!candidates.is_empty()).then(|| Completion {
+            base: base.to_string(),
+            candidates,
+            index: 0,
+        })
+    }
+
+    /// Whether there is exactly one candidate (so there is nothing to cycle through).
+    pub fn is_unique(&self) -> bool {
+        self.candidates.len() == 1
+    }
+
+    /// Move to the next candidate, wrapping around.
+    pub fn advance(&mut self) {
+        self.index = (self.index + 1) % self.candidates.len();
+    }
+
+    /// User input with the last word replaced by the current candidate.
+    pub fn current(&self) -> String {
+        format!("{}{}", self.base, self.candidates[self.index])
+    }
+}
+
+/// Candidates for the word following `preceding` words.
+fn candidates_for(preceding: &[&str], sources: &CompletionSources) -> Vec<String> {
+    let Some((first, rest)) = preceding.split_first() else {
+        return command_names();
+    };
+
+    if preceding.last() == Some(&"--constructor") {
+        return sources.constructors.clone();
+    }
+    if preceding.last() == Some(&"--address") {
+        return sources.accounts.clone();
+    }
+
+    match (resolve_command(first).as_deref(), rest.len()) {
+        (Some("call"), 0) => sources.messages.clone(),
+        (Some("set-actor" | "add-tokens"), 0) => sources.accounts.clone(),
+        (Some("set-confirm"), 0) => vec!["on".to_string(), "off".to_string()],
+        _ => vec![],
+    }
+}
+
+fn command_names() -> Vec<String> {
+    CliCommand::command()
+        .get_subcommands()
+        .map(|command| command.get_name().to_string())
+        .filter(|name| name != "help")
+        .collect()
+}
+
+/// Full name of the command called as `word` (either by its name or by its alias).
+fn resolve_command(word: &str) -> Option<String> {
+    CliCommand::command()
+        .find_subcommand(word)
+        .map(|command| command.get_name().to_string())
+}
+
+impl AppState {
+    /// Complete the user input, or cycle to the next candidate if a completion is in progress.
+    ///
+    /// A unique candidate is accepted right away (followed by a space), so that the next `Tab`
+    /// starts completing the following word.
+    pub fn complete_input(&mut self) {
+        let completion = match self.ui_state.completion.take() {
+            Some(mut completion) => {
+                completion.advance();
+                completion
+            }
+            None => {
+                let sources = self.completion_sources();
+                let input = self.ui_state.user_input.current_input();
+                let Some(completion) = Completion::start(input, &sources) else {
+                    return;
+                };
+                if completion.is_unique() {
+                    let completed = format!("{} ", completion.current());
+                    self.ui_state.user_input.set(completed);
+                    return;
+                }
+                completion
+            }
+        };
+
+        self.ui_state.user_input.set(completion.current());
+        self.ui_state.completion = Some(completion);
+    }
+
+    fn completion_sources(&self) -> CompletionSources {
+        let entries = self
+            .contracts
+            .current_contract()
+            .map(|contract| contract.metadata_entries())
+            .unwrap_or_default();
+        let labels = |kind: EntryKind| {
+            entries
+                .iter()
+                .filter(|entry| entry.kind == kind)
+                .map(|entry| entry.label.clone())
+                .collect()
+        };
+
+        let accounts = DEV_ACCOUNTS
+            .iter()
+            .map(|alias| alias.to_string())
+            .chain(
+                self.contracts
+                    .get_all()
+                    .iter()
+                    .map(|contract| contract.name.clone()),
+            )
+            .collect();
+
+        CompletionSources {
+            messages: labels(EntryKind::Message),
+            constructors: labels(EntryKind::Constructor),
+            accounts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sources() -> CompletionSources {
+        CompletionSources {
+            messages: vec!["flip".to_string(), "get".to_string()],
+            constructors: vec!["new".to_string(), "new_default".to_string()],
+            accounts: vec![
+                "alice".to_string(),
+                "bob".to_string(),
+                "flipper".to_string(),
+            ],
+        }
+    }
+
+    fn cycle(input: &str) -> Vec<String> {
+        let Some(mut completion) = Completion::start(input, &sources()) else {
+            return vec![];
+        };
+        (0..completion.candidates.len())
+            .map(|_| {
+                let current = completion.current();
+                completion.advance();
+                current
+            })
+            .collect()
+    }
+
+    #[test]
+    fn command_names_are_completed() {
+        assert_eq!(
+            cycle("se"),
+            [
+                "set-actor",
+                "set-gas-limit",
+                "set-ss58-prefix",
+                "set-confirm"
+            ]
+        );
+        assert!(cycle("").contains(&"deploy".to_string()));
+        assert!(!cycle("").contains(&"help".to_string()));
+    }
+
+    #[test]
+    fn messages_and_constructors_are_completed() {
+        assert_eq!(cycle("call "), ["call flip", "call get"]);
+        assert_eq!(cycle("call f"), ["call flip"]);
+        assert_eq!(
+            cycle("d --constructor new_"),
+            ["d --constructor new_default"]
+        );
+        assert!(cycle("call flip ").is_empty());
+    }
+
+    #[test]
+    fn accounts_are_completed() {
+        assert_eq!(
+            cycle("set-actor "),
+            ["set-actor alice", "set-actor bob", "set-actor flipper"]
+        );
+        assert_eq!(
+            cycle("call get --address fl"),
+            ["call get --address flipper"]
+        );
+        assert_eq!(cycle("add-tokens b"), ["add-tokens bob"]);
+    }
+
+    #[test]
+    fn cycling_wraps_around() {
+        let mut completion = Completion::start("call ", &sources()).unwrap();
+        completion.advance();
+        completion.advance();
+        assert_eq!(completion.current(), "call flip");
+        assert!(Completion::start("unknown ", &sources()).is_none());
+    }
+}
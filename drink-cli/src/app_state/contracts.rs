@@ -4,16 +4,24 @@ use contract_transcode::ContractMessageTranscoder;
 use drink::AccountId32;
 use ContractIndex::NoContracts;
 
-use crate::app_state::ContractIndex::CurrentContract;
+use crate::app_state::{metadata::read_entries, ContractIndex::CurrentContract, MetadataEntry};
 
 pub struct Contract {
     pub name: String,
     pub address: AccountId32,
     pub base_path: PathBuf,
-    #[allow(dead_code)]
+    /// Workspace package the contract was built from, if it was deployed with `--package`.
+    pub package: Option<String>,
     pub transcoder: Arc<ContractMessageTranscoder>,
 }
 
+impl Contract {
+    /// Constructors and messages of the contract, as described by its metadata.
+    pub fn metadata_entries(&self) -> Vec<MetadataEntry> {
+        read_entries(&self.transcoder)
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
 pub enum ContractIndex {
     #[default]
@@ -0,0 +1,206 @@
+use contract_transcode::ContractMessageTranscoder;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum EntryKind {
+    Constructor,
+    Message,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Argument {
+    pub name: String,
+    pub ty: String,
+}
+
+/// A single constructor or message of a contract, as described by its metadata.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct MetadataEntry {
+    pub kind: EntryKind,
+    pub label: String,
+    pub args: Vec<Argument>,
+    pub mutates: bool,
+    pub payable: bool,
+    pub docs: Vec<String>,
+}
+
+impl MetadataEntry {
+    /// Short, single-line description of the entry, e.g. `message transfer(to: AccountId, value:
+    /// u128) [mut, payable]`.
+    pub fn signature(&self) -> String {
+        let kind = match self.kind {
+            EntryKind::Constructor => "constructor",
+            EntryKind::Message => "message",
+        };
+        let args = self
+            .args
+            .iter()
+            .map(|arg| format!("{}: {}", arg.name, arg.ty))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut flags = vec![];
+        if self.mutates {
+            flags.push("mut");
+        }
+        if self.payable {
+            flags.push("payable");
+        }
+        let flags = match flags.is_empty() {
+            true => String::new(),
+            false => format!(" [{}]", flags.join(", ")),
+        };
+
+        format!("{kind} {}({args}){flags}", self.label)
+    }
+
+    /// Command template that can be used to invoke this entry, e.g. `call transfer <to: AccountId>
+    /// <value: u128>`.
+    pub fn template(&self) -> String {
+        let command = match self.kind {
+            EntryKind::Constructor => format!("deploy --constructor {}", self.label),
+            EntryKind::Message => format!("call {}", self.label),
+        };
+
+        self.args.iter().fold(command, |acc, arg| {
+            format!("{acc} <{}: {}>", arg.name, arg.ty)
+        })
+    }
+}
+
+/// Lists all constructors and messages from the contract metadata.
+pub fn read_entries(transcoder: &ContractMessageTranscoder) -> Vec<MetadataEntry> {
+    let spec = transcoder.metadata().spec();
+
+    let constructors = spec.constructors().iter().map(|c| MetadataEntry {
+        kind: EntryKind::Constructor,
+        label: c.label().clone(),
+        args: c
+            .args()
+            .iter()
+            .map(|arg| Argument {
+                name: arg.label().clone(),
+                ty: arg.ty().display_name().to_string(),
+            })
+            .collect(),
+        mutates: true,
+        payable: *c.payable(),
+        docs: c.docs().to_vec(),
+    });
+
+    let messages = spec.messages().iter().map(|m| MetadataEntry {
+        kind: EntryKind::Message,
+        label: m.label().clone(),
+        args: m
+            .args()
+            .iter()
+            .map(|arg| Argument {
+                name: arg.label().clone(),
+                ty: arg.ty().display_name().to_string(),
+            })
+            .collect(),
+        mutates: m.mutates(),
+        payable: m.payable(),
+        docs: m.docs().to_vec(),
+    });
+
+    constructors.chain(messages).collect()
+}
+
+/// State of the metadata browser panel.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct MetadataBrowser {
+    highlighted: usize,
+}
+
+impl MetadataBrowser {
+    pub fn highlighted(&self) -> usize {
+        self.highlighted
+    }
+
+    pub fn reset(&mut self) {
+        self.highlighted = 0;
+    }
+
+    pub fn highlight_next(&mut self, entries_count: usize) {
+        if self.highlighted + 1 < entries_count {
+            self.highlighted += 1;
+        }
+    }
+
+    pub fn highlight_prev(&mut self) {
+        self.highlighted = self.highlighted.saturating_sub(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer() -> MetadataEntry {
+        MetadataEntry {
+            kind: EntryKind::Message,
+            label: "transfer".to_string(),
+            args: vec![
+                Argument {
+                    name: "to".to_string(),
+                    ty: "AccountId".to_string(),
+                },
+                Argument {
+                    name: "value".to_string(),
+                    ty: "u128".to_string(),
+                },
+            ],
+            mutates: true,
+            payable: true,
+            docs: vec![],
+        }
+    }
+
+    #[test]
+    fn templates_list_the_arguments() {
+        assert_eq!(
+            transfer().template(),
+            "call transfer <to: AccountId> <value: u128>"
+        );
+
+        let constructor = MetadataEntry {
+            kind: EntryKind::Constructor,
+            label: "new".to_string(),
+            args: vec![],
+            mutates: true,
+            payable: false,
+            docs: vec![],
+        };
+        assert_eq!(constructor.template(), "deploy --constructor new");
+    }
+
+    #[test]
+    fn signatures_show_the_flags() {
+        assert_eq!(
+            transfer().signature(),
+            "message transfer(to: AccountId, value: u128) [mut, payable]"
+        );
+
+        let getter = MetadataEntry {
+            mutates: false,
+            payable: false,
+            ..transfer()
+        };
+        assert_eq!(
+            getter.signature(),
+            "message transfer(to: AccountId, value: u128)"
+        );
+    }
+
+    #[test]
+    fn highlight_stays_within_the_entries() {
+        let mut browser = MetadataBrowser::default();
+        browser.highlight_prev();
+        assert_eq!(browser.highlighted(), 0);
+        browser.highlight_next(2);
+        browser.highlight_next(2);
+        assert_eq!(browser.highlighted(), 1);
+        browser.reset();
+        assert_eq!(browser.highlighted(), 0);
+    }
+}
@@ -1,33 +1,59 @@
 use std::{env, path::PathBuf};
 
+pub use address::DEFAULT_SS58_PREFIX;
+pub use completion::Completion;
 pub use contracts::{Contract, ContractIndex, ContractRegistry};
-use drink::{minimal::MinimalSandbox, session::Session, AccountId32, Sandbox, Weight};
+use drink::{
+    minimal::MinimalSandbox, sandbox_api::prelude::*, session::Session, AccountId32, Sandbox,
+    Weight,
+};
+pub use metadata::{read_entries, EntryKind, MetadataBrowser, MetadataEntry};
+pub use packages::{PackageArtifacts, PackageRegistry};
 pub use user_input::UserInput;
 
-use crate::app_state::output::Output;
+use crate::{app_state::output::Output, cli::CliCommand};
 
+mod address;
+mod completion;
 mod contracts;
+mod metadata;
 mod output;
+mod packages;
 pub mod print;
 mod user_input;
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct ChainInfo {
     pub block_height: u32,
+    /// Timestamp of the current block, in milliseconds.
+    pub timestamp: u64,
     pub actor: AccountId32,
+    pub actor_balance: u128,
     pub gas_limit: Weight,
+    pub ss58_prefix: u16,
 }
 
 impl Default for ChainInfo {
     fn default() -> Self {
         Self {
             block_height: 0,
+            timestamp: 0,
             actor: MinimalSandbox::default_actor(),
+            actor_balance: 0,
             gas_limit: MinimalSandbox::default_gas_limit(),
+            ss58_prefix: DEFAULT_SS58_PREFIX,
         }
     }
 }
 
+/// Confirm-before-execute mode for state-changing commands.
+#[derive(Default)]
+pub struct Confirmation {
+    pub enabled: bool,
+    /// Command waiting for the user to confirm it.
+    pub pending: Option<CliCommand>,
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
 pub enum Mode {
     #[default]
@@ -41,9 +67,13 @@ pub struct UiState {
     pub mode: Mode,
 
     pub user_input: UserInput,
+    /// Tab completion in progress, reset by any other key.
+    pub completion: Option<Completion>,
     pub output: Output,
 
     pub show_help: bool,
+    pub show_metadata: bool,
+    pub metadata_browser: MetadataBrowser,
 }
 
 impl UiState {
@@ -55,8 +85,11 @@ impl UiState {
             cwd,
             mode: Default::default(),
             user_input: Default::default(),
+            completion: None,
             output: Default::default(),
             show_help: false,
+            show_metadata: false,
+            metadata_browser: Default::default(),
         }
     }
 }
@@ -72,16 +105,33 @@ pub struct AppState {
     pub chain_info: ChainInfo,
     pub ui_state: UiState,
     pub contracts: ContractRegistry,
+    pub packages: PackageRegistry,
+    pub confirmation: Confirmation,
 }
 
 impl AppState {
     pub fn new(cwd_override: Option<PathBuf>) -> Self {
-        AppState {
+        let mut app_state = AppState {
             session: Session::default(),
             chain_info: Default::default(),
             ui_state: UiState::new(cwd_override),
             contracts: Default::default(),
-        }
+            packages: Default::default(),
+            confirmation: Default::default(),
+        };
+        app_state.refresh_chain_info();
+        app_state
+    }
+
+    /// Query the sandbox for the current chain state (block, timestamp, actor and its balance).
+    pub fn refresh_chain_info(&mut self) {
+        let actor = self.session.get_actor();
+        let sandbox = self.session.sandbox();
+
+        self.chain_info.block_height = sandbox.block_number();
+        self.chain_info.timestamp = sandbox.get_timestamp();
+        self.chain_info.actor_balance = sandbox.free_balance(&actor);
+        self.chain_info.actor = actor;
     }
 }
 
@@ -0,0 +1,26 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// Artifacts produced by building a single workspace package.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct PackageArtifacts {
+    pub contract_name: String,
+    pub base_path: PathBuf,
+    pub wasm: PathBuf,
+    pub metadata: PathBuf,
+}
+
+/// Packages that have been built during the session, by their name.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct PackageRegistry {
+    packages: BTreeMap<String, PackageArtifacts>,
+}
+
+impl PackageRegistry {
+    pub fn register(&mut self, package: String, artifacts: PackageArtifacts) {
+        self.packages.insert(package, artifacts);
+    }
+
+    pub fn get(&self, package: &str) -> Option<&PackageArtifacts> {
+        self.packages.get(package)
+    }
+}
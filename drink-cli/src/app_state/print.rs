@@ -1,4 +1,7 @@
-use drink::{pallet_contracts::ContractResult, sandbox_api::contracts_api::decode_debug_buffer};
+use drink::{
+    pallet_contracts::{ContractResult, StorageDeposit},
+    sandbox_api::contracts_api::decode_debug_buffer,
+};
 use ratatui::{
     style::{Color, Modifier, Style},
     text::Span,
@@ -58,3 +61,10 @@ pub fn format_contract_action<R, E>(result: &ContractResult<R, u128, E>) -> Stri
 
     output
 }
+
+pub fn format_storage_deposit(deposit: &StorageDeposit<u128>) -> String {
+    match deposit {
+        StorageDeposit::Charge(amount) => format!("{amount} charged"),
+        StorageDeposit::Refund(amount) => format!("{amount} refunded"),
+    }
+}
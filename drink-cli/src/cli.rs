@@ -1,5 +1,7 @@
-use clap::Parser;
-use drink::{AccountId32, Ss58Codec};
+use clap::{Parser, ValueEnum};
+
+/// Maximum length of a deployment salt, in bytes.
+const SALT_LEN: usize = 32;
 
 #[derive(Parser)]
 pub enum CliCommand {
@@ -16,33 +18,84 @@ pub enum CliCommand {
         count: u32,
     },
     AddTokens {
-        #[clap(value_parser = AccountId32::from_ss58check)]
-        recipient: AccountId32,
+        recipient: String,
         value: u128,
     },
     SetActor {
-        #[clap(value_parser = AccountId32::from_ss58check)]
-        actor: AccountId32,
+        actor: String,
     },
     SetGasLimit {
         ref_time: u64,
         proof_size: u64,
     },
+    SetSs58Prefix {
+        prefix: u16,
+    },
+    SetConfirm {
+        state: Toggle,
+    },
 
     #[clap(alias = "b")]
-    Build,
+    Build {
+        package: Option<String>,
+    },
     #[clap(alias = "d")]
     Deploy {
         #[clap(long, default_value = "new")]
         constructor: String,
         args: Vec<String>,
-        #[clap(long, default_values_t = Vec::<u8>::new(), value_delimiter = ',')]
-        salt: Vec<u8>,
+        /// Value transferred to the contract (for payable constructors).
+        #[clap(long)]
+        value: Option<u128>,
+        /// `0x`-prefixed hex, up to 32 bytes (right-padded with zeros).
+        #[clap(long, value_parser = parse_salt)]
+        salt: Option<[u8; SALT_LEN]>,
+        #[clap(long)]
+        package: Option<String>,
     },
     Call {
         message: String,
         args: Vec<String>,
+        #[clap(long)]
+        address: Option<String>,
     },
+    #[clap(alias = "m")]
+    Messages,
+    Version,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, ValueEnum)]
+pub enum Toggle {
+    On,
+    Off,
+}
+
+/// Parse a `0x`-prefixed hex salt of at most `SALT_LEN` bytes, right-padded with zeros.
+fn parse_salt(input: &str) -> Result<[u8; SALT_LEN], String> {
+    let hex = input
+        .strip_prefix("0x")
+        .ok_or_else(|| format!("Salt must be `0x`-prefixed hex, got `{input}`"))?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid hex in salt `{input}`"));
+    }
+    if hex.len() % 2 != 0 {
+        return Err(format!(
+            "Salt must have an even number of hex digits, got `{input}`"
+        ));
+    }
+    if hex.len() / 2 > SALT_LEN {
+        return Err(format!(
+            "Salt can have at most {SALT_LEN} bytes, got {}",
+            hex.len() / 2
+        ));
+    }
+
+    let mut salt = [0u8; SALT_LEN];
+    for (idx, byte) in salt.iter_mut().take(hex.len() / 2).enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * idx..2 * idx + 2], 16)
+            .map_err(|_| format!("Invalid hex in salt `{input}`"))?;
+    }
+    Ok(salt)
 }
 
 #[cfg(test)]
@@ -54,4 +107,36 @@ mod tests {
         use clap::CommandFactory;
         CliCommand::command().debug_assert()
     }
+
+    #[test]
+    fn salt_is_right_padded() {
+        let mut expected = [0u8; SALT_LEN];
+        expected[..2].copy_from_slice(&[0xab, 0x01]);
+        assert_eq!(parse_salt("0xab01"), Ok(expected));
+        assert_eq!(parse_salt("0x"), Ok([0u8; SALT_LEN]));
+    }
+
+    #[test]
+    fn invalid_salt_is_rejected() {
+        assert!(parse_salt("ab01").is_err());
+        assert!(parse_salt("0xabc").is_err());
+        assert!(parse_salt("0xzz").is_err());
+        assert!(parse_salt(&format!("0x{}", "00".repeat(SALT_LEN + 1))).is_err());
+    }
+
+    #[test]
+    fn non_ascii_salt_is_rejected() {
+        assert!(parse_salt("0xaéa").is_err());
+        assert!(parse_salt("0xé").is_err());
+    }
+
+    #[test]
+    fn confirmation_toggle_is_parsed() {
+        let command = CliCommand::try_parse_from(["", "set-confirm", "on"]).unwrap();
+        assert!(matches!(
+            command,
+            CliCommand::SetConfirm { state: Toggle::On }
+        ));
+        assert!(CliCommand::try_parse_from(["", "set-confirm", "maybe"]).is_err());
+    }
 }
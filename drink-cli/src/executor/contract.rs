@@ -4,16 +4,59 @@ use std::{
     sync::Arc,
 };
 
+use cargo_metadata::{Metadata, MetadataCommand};
 use contract_build::{BuildMode, ExecuteArgs, ManifestPath, OptimizationPasses, Verbosity};
 use contract_transcode::ContractMessageTranscoder;
+use drink::{
+    sandbox_api::prelude::*,
+    session::{abi::AbiKind, ContractBundle},
+    AccountId32,
+};
 
 use crate::{
-    app_state::{print::format_contract_action, AppState, Contract},
+    app_state::{
+        print::{format_contract_action, format_storage_deposit},
+        read_entries, AppState, Contract, EntryKind, MetadataEntry, PackageArtifacts,
+    },
     executor::error::BuildError,
 };
 
-fn build_result(app_state: &mut AppState) -> Result<String, BuildError> {
-    let path_to_cargo_toml = app_state.ui_state.cwd.join(Path::new("Cargo.toml"));
+/// Resolve the manifest of the contract to be built: either the one in the current directory or
+/// the one of the workspace member called `package`.
+fn resolve_manifest(app_state: &AppState, package: Option<&str>) -> Result<PathBuf, BuildError> {
+    let Some(package) = package else {
+        return Ok(app_state.ui_state.cwd.join(Path::new("Cargo.toml")));
+    };
+
+    let metadata = MetadataCommand::new()
+        .current_dir(&app_state.ui_state.cwd)
+        .no_deps()
+        .exec()
+        .map_err(|err| BuildError::WorkspaceMetadata { err })?;
+
+    member_manifest(&metadata, package).ok_or_else(|| BuildError::PackageNotFound {
+        package: package.to_string(),
+    })
+}
+
+/// Find the manifest of the workspace member called `package`.
+fn member_manifest(metadata: &Metadata, package: &str) -> Option<PathBuf> {
+    metadata
+        .packages
+        .iter()
+        .find(|pkg| pkg.name == package && metadata.workspace_members.contains(&pkg.id))
+        .map(|pkg| pkg.manifest_path.clone().into_std_path_buf())
+}
+
+fn build_result(
+    app_state: &mut AppState,
+    package: Option<&str>,
+) -> Result<PackageArtifacts, BuildError> {
+    let path_to_cargo_toml = resolve_manifest(app_state, package)?;
+    let base_path = path_to_cargo_toml
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| app_state.ui_state.cwd.clone());
     let manifest_path = ManifestPath::new(path_to_cargo_toml.clone()).map_err(|err| {
         BuildError::InvalidManifest {
             manifest_path: path_to_cargo_toml,
@@ -29,82 +72,270 @@ fn build_result(app_state: &mut AppState) -> Result<String, BuildError> {
         ..Default::default()
     };
 
-    contract_build::execute(args)
-        .map_err(|err| BuildError::BuildFailed { err })?
+    let result = contract_build::execute(args).map_err(|err| BuildError::BuildFailed { err })?;
+
+    let wasm = result
         .dest_wasm
         .ok_or(BuildError::WasmNotGenerated)?
         .canonicalize()
-        .map_err(|err| BuildError::InvalidDestPath { err })
-        .map(|pb| pb.to_string_lossy().to_string())
+        .map_err(|err| BuildError::InvalidDestPath { err })?;
+    let metadata = result
+        .metadata_result
+        .ok_or(BuildError::MetadataNotGenerated)?
+        .dest_metadata
+        .canonicalize()
+        .map_err(|err| BuildError::InvalidDestPath { err })?;
+    let contract_name = wasm
+        .file_stem()
+        .expect("Wasm artifact should have a file name")
+        .to_string_lossy()
+        .to_string();
+
+    Ok(PackageArtifacts {
+        contract_name,
+        base_path,
+        wasm,
+        metadata,
+    })
 }
 
-/// Build the contract in the current directory.
-pub fn build(app_state: &mut AppState) {
-    match build_result(app_state) {
-        Ok(res) => app_state.print(&format!("Contract built successfully {res}")),
+/// Build the contract in the current directory or, if `package` is given, the workspace member
+/// with that name.
+pub fn build(app_state: &mut AppState, package: Option<String>) {
+    match build_result(app_state, package.as_deref()) {
+        Ok(artifacts) => {
+            app_state.print(&format!(
+                "Contract built successfully {}",
+                artifacts.wasm.to_string_lossy()
+            ));
+            if let Some(package) = package {
+                app_state.packages.register(package, artifacts);
+            }
+        }
         Err(msg) => app_state.print_error(&format!("{msg}")),
     }
 }
 
-pub fn deploy(app_state: &mut AppState, constructor: String, args: Vec<String>, salt: Vec<u8>) {
+/// Locate the artifacts to deploy: either those registered for `package` or those found in the
+/// current directory.
+fn find_artifacts(
+    app_state: &mut AppState,
+    package: Option<&str>,
+) -> Result<PackageArtifacts, String> {
+    if let Some(package) = package {
+        return app_state.packages.get(package).cloned().ok_or_else(|| {
+            format!("Package `{package}` has not been built yet (use `build {package}`)")
+        });
+    }
+
+    let cwd = app_state.ui_state.cwd.clone();
+    let (contract_name, wasm) =
+        find_wasm_blob(&cwd).ok_or_else(|| "Failed to find contract file".to_string())?;
+    let metadata = cwd.join(format!("target/ink/{contract_name}.json"));
+
+    Ok(PackageArtifacts {
+        contract_name,
+        base_path: cwd,
+        wasm,
+        metadata,
+    })
+}
+
+/// Locate the artifacts to deploy and load the contract code and metadata.
+fn load_artifacts(
+    app_state: &mut AppState,
+    package: Option<&str>,
+) -> Result<(PackageArtifacts, Vec<u8>, Arc<ContractMessageTranscoder>), String> {
+    let artifacts = find_artifacts(app_state, package)?;
+
     // Get raw contract bytes
-    let Some((contract_name, contract_file)) = find_wasm_blob(&app_state.ui_state.cwd) else {
-        app_state.print_error("Failed to find contract file");
-        return;
+    let contract_bytes =
+        fs::read(&artifacts.wasm).map_err(|err| format!("Failed to read contract bytes\n{err}"))?;
+
+    // Read contract metadata and prepare transcoder
+    let transcoder = ContractMessageTranscoder::load(&artifacts.metadata)
+        .map_err(|_| "Failed to create transcoder from metadata file.".to_string())?;
+
+    Ok((artifacts, contract_bytes, Arc::new(transcoder)))
+}
+
+/// Resolve the address of the contract to call: either the given one or the current contract.
+fn resolve_address(app_state: &AppState, address: Option<&str>) -> Result<AccountId32, String> {
+    match address {
+        Some(address) => app_state.parse_address(address),
+        None => app_state
+            .contracts
+            .current_contract()
+            .map(|contract| contract.address.clone())
+            .ok_or_else(|| "No deployed contract".to_string()),
+    }
+}
+
+/// Describe the invocation of `label` with `args`, naming the arguments after the metadata entry
+/// (if it is known), e.g. `transfer(to: 5Grw..., value: 10)`.
+fn describe_invocation(
+    entries: &[MetadataEntry],
+    kind: EntryKind,
+    label: &str,
+    args: &[String],
+) -> String {
+    let entry = entries
+        .iter()
+        .find(|entry| entry.kind == kind && entry.label == label);
+    let args = args
+        .iter()
+        .enumerate()
+        .map(|(i, arg)| match entry.and_then(|entry| entry.args.get(i)) {
+            Some(argument) => format!("{}: {arg}", argument.name),
+            None => arg.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("{label}({args})")
+}
+
+/// Summary of a deployment, shown before it is confirmed.
+pub fn deploy_summary(
+    app_state: &mut AppState,
+    constructor: &str,
+    args: &[String],
+    value: Option<u128>,
+    salt: Vec<u8>,
+    package: Option<&str>,
+) -> String {
+    let (artifacts, contract_bytes, transcoder) = match load_artifacts(app_state, package) {
+        Ok(loaded) => loaded,
+        Err(err) => return format!("Deploy `{constructor}` (cannot be estimated: {err})"),
     };
 
-    let contract_bytes = match fs::read(contract_file) {
-        Ok(bytes) => bytes,
-        Err(err) => {
-            app_state.print_error(&format!("Failed to read contract bytes\n{err}"));
-            return;
-        }
+    let invocation = describe_invocation(
+        &read_entries(&transcoder),
+        EntryKind::Constructor,
+        constructor,
+        args,
+    );
+    let gas = match app_state.session.dry_run_deployment(
+        ContractBundle {
+            wasm: contract_bytes,
+            transcoder,
+            abi: AbiKind::Scale,
+        },
+        constructor,
+        args,
+        salt,
+        value,
+    ) {
+        Ok(result) => format!("{:?}", result.gas_required),
+        Err(err) => format!("unknown ({err})"),
     };
 
-    // Read contract metadata and prepare transcoder
-    let metadata_path = app_state
-        .ui_state
-        .cwd
-        .join(format!("target/ink/{contract_name}.json"));
+    format!(
+        "Deploy `{}` with {invocation}\nValue: {}\nEstimated gas: {gas}",
+        artifacts.contract_name,
+        value.unwrap_or_default()
+    )
+}
 
-    let Ok(transcoder) = ContractMessageTranscoder::load(metadata_path) else {
-        app_state.print_error("Failed to create transcoder from metadata file.");
-        return;
+/// Summary of a contract call, shown before it is confirmed. `None` if the message is known not to
+/// mutate the contract state (queries need no confirmation).
+pub fn call_summary(
+    app_state: &mut AppState,
+    message: &str,
+    args: &[String],
+    address: Option<&str>,
+) -> Option<String> {
+    let address = match resolve_address(app_state, address) {
+        Ok(address) => address,
+        Err(err) => return Some(format!("Call `{message}` (cannot be estimated: {err})")),
+    };
+
+    let entries = app_state
+        .contracts
+        .get_all()
+        .iter()
+        .find(|contract| contract.address == address)
+        .map(Contract::metadata_entries)
+        .unwrap_or_default();
+    let is_query = entries
+        .iter()
+        .any(|entry| entry.kind == EntryKind::Message && entry.label == message && !entry.mutates);
+    if is_query {
+        return None;
+    }
+
+    let invocation = describe_invocation(&entries, EntryKind::Message, message, args);
+    let gas = match app_state
+        .session
+        .dry_run_call(address.clone(), message, args, None)
+    {
+        Ok(result) => format!("{:?}", result.gas_required),
+        Err(err) => format!("unknown ({err})"),
     };
-    let transcoder = Arc::new(transcoder);
+
+    Some(format!(
+        "Call {invocation} on {}\nValue: 0\nEstimated gas: {gas}",
+        app_state.ss58(&address)
+    ))
+}
+
+pub fn deploy(
+    app_state: &mut AppState,
+    constructor: String,
+    args: Vec<String>,
+    value: Option<u128>,
+    salt: Vec<u8>,
+    package: Option<String>,
+) {
+    let (artifacts, contract_bytes, transcoder) =
+        match load_artifacts(app_state, package.as_deref()) {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                app_state.print_error(&err);
+                return;
+            }
+        };
 
     match app_state.session.deploy(
         contract_bytes,
         &constructor,
         args.as_slice(),
         salt,
-        None,
+        value,
         &transcoder,
     ) {
         Ok(address) => {
+            let formatted_address = app_state.ss58(&address);
+            let balance = app_state.session.sandbox().free_balance(&address);
             app_state.contracts.add(Contract {
-                name: contract_name,
+                name: artifacts.contract_name,
                 address,
-                base_path: app_state.ui_state.cwd.clone(),
+                base_path: artifacts.base_path,
+                package,
                 transcoder,
             });
-            app_state.print("Contract deployed successfully");
+            app_state.print(&format!(
+                "Contract deployed successfully at {formatted_address}\nStarting balance: {balance}"
+            ));
         }
         Err(err) => app_state.print_error(&format!("Failed to deploy contract\n{err}")),
     }
 
     if let Some(info) = app_state.session.record().deploy_results().last() {
+        let storage_deposit = format_storage_deposit(&info.storage_deposit);
+        app_state.print(&format!("Storage deposit: {storage_deposit}"));
         app_state.print(&format_contract_action(info));
     }
 }
 
-pub fn call(app_state: &mut AppState, message: String, args: Vec<String>) {
-    let Some(contract) = app_state.contracts.current_contract() else {
-        app_state.print_error("No deployed contract");
-        return;
+pub fn call(app_state: &mut AppState, message: String, args: Vec<String>, address: Option<String>) {
+    let address = match resolve_address(app_state, address.as_deref()) {
+        Ok(address) => address,
+        Err(err) => {
+            app_state.print_error(&err);
+            return;
+        }
     };
 
-    let address = contract.address.clone();
     match app_state
         .session
         .call_with_address::<_, ()>(address, &message, &args, None)
@@ -118,6 +349,22 @@ pub fn call(app_state: &mut AppState, message: String, args: Vec<String>) {
     }
 }
 
+pub fn messages(app_state: &mut AppState) {
+    let Some(contract) = app_state.contracts.current_contract() else {
+        app_state.print_error("No deployed contract");
+        return;
+    };
+
+    let mut output = String::new();
+    for entry in contract.metadata_entries() {
+        output.push_str(&format!("{}\n", entry.signature()));
+        for doc in &entry.docs {
+            output.push_str(&format!("    {}\n", doc.trim()));
+        }
+    }
+    app_state.print(output.trim_end());
+}
+
 fn find_wasm_blob(cwd: &Path) -> Option<(String, PathBuf)> {
     let Ok(entries) = fs::read_dir(cwd.join("target/ink")) else {
         return None;
@@ -137,3 +384,30 @@ fn find_wasm_blob(cwd: &Path) -> Option<(String, PathBuf)> {
 
     Some((raw_name, file.path()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace_metadata() -> Metadata {
+        MetadataCommand::new()
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .no_deps()
+            .exec()
+            .expect("Workspace metadata should be available")
+    }
+
+    #[test]
+    fn workspace_members_are_found() {
+        let manifest = member_manifest(&workspace_metadata(), "drink-cli");
+        assert_eq!(
+            manifest,
+            Some(Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml"))
+        );
+    }
+
+    #[test]
+    fn unknown_packages_are_not_found() {
+        assert_eq!(member_manifest(&workspace_metadata(), "flipper"), None);
+    }
+}
@@ -7,10 +7,16 @@ pub(crate) enum BuildError {
         manifest_path: std::path::PathBuf,
         err: anyhow::Error,
     },
+    #[error("Failed to read workspace metadata: {err}")]
+    WorkspaceMetadata { err: cargo_metadata::Error },
+    #[error("Package `{package}` not found in the workspace")]
+    PackageNotFound { package: String },
     #[error("Contract build failed: {err}")]
     BuildFailed { err: anyhow::Error },
     #[error("Wasm code artifact not generated")]
     WasmNotGenerated,
+    #[error("Metadata artifact not generated")]
+    MetadataNotGenerated,
     #[error("Invalid destination bundle path: {err}")]
     InvalidDestPath { err: std::io::Error },
 }
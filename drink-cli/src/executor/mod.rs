@@ -7,12 +7,24 @@ use anyhow::Result;
 use clap::Parser;
 use drink::{sandbox_api::prelude::*, AccountId32, Weight};
 
-use crate::{app_state::AppState, cli::CliCommand};
+use crate::{
+    app_state::AppState,
+    cli::{CliCommand, Toggle},
+};
 
 pub fn execute(app_state: &mut AppState) -> Result<()> {
     let command = app_state.ui_state.user_input.current_input().to_string();
     app_state.print_command(&command);
 
+    if let Some(pending) = app_state.confirmation.pending.take() {
+        match command.trim() {
+            "y" => run(app_state, pending)?,
+            _ => app_state.print("Command cancelled"),
+        }
+        app_state.refresh_chain_info();
+        return Ok(());
+    }
+
     let command = command
         .split_ascii_whitespace()
         .map(|a| a.trim())
@@ -25,6 +37,22 @@ pub fn execute(app_state: &mut AppState) -> Result<()> {
         }
     };
 
+    if app_state.confirmation.enabled {
+        if let Some(summary) = confirmation_summary(app_state, &cli_command) {
+            app_state.print(&format!(
+                "{summary}\nType `y` to proceed (anything else cancels)"
+            ));
+            app_state.confirmation.pending = Some(cli_command);
+            return Ok(());
+        }
+    }
+
+    run(app_state, cli_command)?;
+    app_state.refresh_chain_info();
+    Ok(())
+}
+
+fn run(app_state: &mut AppState, cli_command: CliCommand) -> Result<()> {
     match cli_command {
         CliCommand::Clear => app_state.ui_state.output.clear(),
         CliCommand::ChangeDir { path } => {
@@ -40,11 +68,17 @@ pub fn execute(app_state: &mut AppState) -> Result<()> {
         }
 
         CliCommand::NextBlock { count } => build_blocks(app_state, count),
-        CliCommand::AddTokens { recipient, value } => add_tokens(app_state, recipient, value)?,
-        CliCommand::SetActor { actor } => {
-            app_state.chain_info.actor = actor;
-            app_state.print("Actor was set");
-        }
+        CliCommand::AddTokens { recipient, value } => match app_state.parse_address(&recipient) {
+            Ok(recipient) => add_tokens(app_state, recipient, value)?,
+            Err(err) => app_state.print_error(&err),
+        },
+        CliCommand::SetActor { actor } => match app_state.parse_address(&actor) {
+            Ok(actor) => {
+                app_state.session.set_actor(actor);
+                app_state.print("Actor was set");
+            }
+            Err(err) => app_state.print_error(&err),
+        },
         CliCommand::SetGasLimit {
             ref_time,
             proof_size,
@@ -52,21 +86,78 @@ pub fn execute(app_state: &mut AppState) -> Result<()> {
             app_state.chain_info.gas_limit = Weight::from_parts(ref_time, proof_size);
             app_state.print("Gas limit was set");
         }
+        CliCommand::SetSs58Prefix { prefix } => {
+            app_state.chain_info.ss58_prefix = prefix;
+            app_state.print("SS58 prefix was set");
+        }
+        CliCommand::SetConfirm { state } => {
+            app_state.confirmation.enabled = state == Toggle::On;
+            app_state.print(&format!("Confirmation mode is {state:?}"));
+        }
 
-        CliCommand::Build => contract::build(app_state),
+        CliCommand::Build { package } => contract::build(app_state, package),
         CliCommand::Deploy {
             constructor,
             args,
+            value,
             salt,
-        } => contract::deploy(app_state, constructor, args, salt),
-        CliCommand::Call { message, args } => contract::call(app_state, message, args),
+            package,
+        } => contract::deploy(
+            app_state,
+            constructor,
+            args,
+            value,
+            salt.map(Vec::from).unwrap_or_default(),
+            package,
+        ),
+        CliCommand::Call {
+            message,
+            args,
+            address,
+        } => contract::call(app_state, message, args, address),
+        CliCommand::Messages => contract::messages(app_state),
+        CliCommand::Version => {
+            let report = app_state.session.environment();
+            app_state.print(&report.to_string());
+        }
     }
 
     Ok(())
 }
 
+/// Summary of a state-changing command, shown before it is executed in the confirmation mode.
+/// `None` for commands that do not need to be confirmed (including calls to query messages).
+fn confirmation_summary(app_state: &mut AppState, cli_command: &CliCommand) -> Option<String> {
+    match cli_command {
+        CliCommand::Deploy {
+            constructor,
+            args,
+            value,
+            salt,
+            package,
+        } => Some(contract::deploy_summary(
+            app_state,
+            constructor,
+            args,
+            *value,
+            salt.map(Vec::from).unwrap_or_default(),
+            package.as_deref(),
+        )),
+        CliCommand::Call {
+            message,
+            args,
+            address,
+        } => contract::call_summary(app_state, message, args, address.as_deref()),
+        CliCommand::AddTokens { recipient, value } => {
+            Some(format!("Add {value} tokens to {recipient}"))
+        }
+        CliCommand::NextBlock { count } => Some(format!("Build {count} block(s)")),
+        _ => None,
+    }
+}
+
 fn build_blocks(app_state: &mut AppState, count: u32) {
-    app_state.chain_info.block_height = app_state.session.sandbox().build_blocks(count);
+    app_state.session.sandbox().build_blocks(count);
     app_state.print(&format!("{count} blocks built"));
 }
 
@@ -76,6 +167,36 @@ fn add_tokens(app_state: &mut AppState, recipient: AccountId32, value: u128) ->
         .sandbox()
         .mint_into(&recipient, value)
         .map_err(|err| anyhow::format_err!("Failed to add token: {err:?}"))?;
-    app_state.print(&format!("{value} tokens added to {recipient}",));
+    app_state.print(&format!(
+        "{value} tokens added to {}",
+        app_state.ss58(&recipient)
+    ));
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_command(app_state: &mut AppState, command: &str) {
+        app_state.ui_state.user_input.set(command.to_string());
+        execute(app_state).expect("Command should not fail");
+    }
+
+    #[test]
+    fn chain_info_is_refreshed_after_every_command() {
+        let mut app_state = AppState::default();
+        let height = app_state.chain_info.block_height;
+
+        run_command(&mut app_state, "next-block 3");
+        assert_eq!(app_state.chain_info.block_height, height + 3);
+
+        run_command(&mut app_state, "set-actor bob");
+        let bob = app_state.parse_address("bob").unwrap();
+        assert_eq!(app_state.chain_info.actor, bob);
+
+        let balance = app_state.chain_info.actor_balance;
+        run_command(&mut app_state, "add-tokens bob 1000");
+        assert_eq!(app_state.chain_info.actor_balance, balance + 1000);
+    }
+}
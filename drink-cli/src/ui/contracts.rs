@@ -23,8 +23,17 @@ pub(super) fn build(app_state: &mut AppState) -> impl Widget {
                 _ => Style::default(),
             };
 
+            let package = match &contract.package {
+                Some(package) => format!(" ({package})"),
+                None => String::new(),
+            };
+
             ListItem::new(Line::from(Span::styled(
-                format!("{} / {}", contract.name, &contract.address.to_string()[..8],),
+                format!(
+                    "{}{package} / {}",
+                    contract.name,
+                    &app_state.ss58(&contract.address)[..8],
+                ),
                 style,
             )))
         })
@@ -7,20 +7,30 @@ use crate::{app_state::AppState, ui::layout::section};
 
 pub(super) fn build(app_state: &mut AppState) -> impl Widget {
     let current_contract_info = match app_state.contracts.current_contract() {
-        Some(contract) => format!("name: {} | address: {}", contract.name, contract.address),
+        Some(contract) => format!(
+            "name: {} | address: {}",
+            contract.name,
+            app_state.ss58(&contract.address)
+        ),
         None => "No deployed contract".to_string(),
     };
 
     Paragraph::new(format!(
         r#"Current working directory: {}
 Block height: {}
+Timestamp: {}
 Deployed contracts: {}
 Current actor: {}
+Actor balance: {}
+SS58 prefix: {}
 Current contract: {{ {} }}"#,
         app_state.ui_state.cwd.to_str().unwrap(),
         app_state.chain_info.block_height,
+        app_state.chain_info.timestamp,
         app_state.contracts.count(),
-        app_state.chain_info.actor,
+        app_state.ss58(&app_state.chain_info.actor),
+        app_state.chain_info.actor_balance,
+        app_state.chain_info.ss58_prefix,
         current_contract_info
     ))
     .alignment(Alignment::Left)
@@ -18,6 +18,10 @@ pub(super) fn build(app_state: &AppState) -> impl Widget {
             " to quit. Press ",
             "'h'",
             " to see help. Press ",
+            "'m'",
+            " to browse contract metadata (arrows to select, ",
+            "'Enter'",
+            " to use). Press ",
             "'i'",
             " to enter editing mode.",
         ]),
@@ -26,7 +30,7 @@ pub(super) fn build(app_state: &AppState) -> impl Widget {
             "'Esc'",
             " to quit editing mode. Use ",
             "'Tab'",
-            " to switch between deployed contracts.",
+            " to complete the input (or, on empty input, to switch between deployed contracts).",
         ]),
     };
 
@@ -11,14 +11,21 @@ pub(super) fn build(_app_state: &AppState) -> impl Widget {
         command("cd <dir>", "change directory do <dir>"),
         command("clear / c", "clear output tab"),
         command(
-            "build / b",
-            "build contract from the sources in the current directory",
+            "build / b [package]",
+            "build contract from the sources in the current directory (or workspace <package>)",
         ),
         command(
-            "deploy / d [--constructor <name>] [--salt <salt>]",
-            "deploy contract using <constructor> (`new` by default) and <salt> (empty by default)",
+            "deploy / d [--constructor <name>] [--value <amount>] [--salt <0x..>] [--package <package>]",
+            "deploy contract using <constructor> (`new` by default), endowed with <amount> and with <salt> (hex, up to 32 bytes, empty by default)",
+        ),
+        command(
+            "call <message> [--address <address>]",
+            "call message of the current contract (or the one at <address>)",
+        ),
+        command(
+            "messages / m",
+            "list constructors and messages of the current contract",
         ),
-        command("call <message>", "call contract's message"),
         command(
             "next-block / nb [count]",
             "build next <count> blocks (by default a single block)",
@@ -35,6 +42,18 @@ pub(super) fn build(_app_state: &AppState) -> impl Widget {
             "set-gas-limit <ref_time> <proof_size>",
             "set gas limits to <ref_time> and <proof_size>",
         ),
+        command(
+            "set-ss58-prefix <prefix>",
+            "display addresses in the SS58 format with <prefix>; addresses can be given in SS58 form, as a dev account alias (alice, bob, charlie, dave, eve, ferdie) or as a deployed contract name",
+        ),
+        command(
+            "set-confirm on|off",
+            "require confirmation (`y`) of deployments, mutating calls, minting and block building",
+        ),
+        command(
+            "version",
+            "show the versions of drink and of the crates it is built with (for bug reports)",
+        ),
     ])
     .block(section("Help"))
 }
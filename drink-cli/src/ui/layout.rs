@@ -1,16 +1,17 @@
 use ratatui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Margin},
+    text::Line,
     widgets::{Block, BorderType, Borders, Padding},
     Frame,
 };
 
 use crate::{
     app_state::AppState,
-    ui::{contracts, current_env, footer, help, output, user_input},
+    ui::{contracts, current_env, footer, help, metadata, output, user_input},
 };
 
-pub(super) fn section(title: &str) -> Block {
+pub(super) fn section<'a, T: Into<Line<'a>>>(title: T) -> Block<'a> {
     Block::default()
         .title(title)
         .borders(Borders::ALL)
@@ -48,6 +49,13 @@ pub(super) fn layout<B: Backend>(f: &mut Frame<B>, app_state: &mut AppState) {
 
     if app_state.ui_state.show_help {
         f.render_widget(help::build(app_state), chunks[1]);
+    } else if app_state.ui_state.show_metadata {
+        let metadata_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(65), Constraint::Percentage(35)].as_ref())
+            .split(chunks[1]);
+        f.render_widget(metadata::build_entries(app_state), metadata_chunks[0]);
+        f.render_widget(metadata::build_docs(app_state), metadata_chunks[1]);
     } else {
         app_state
             .ui_state
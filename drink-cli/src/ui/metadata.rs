@@ -0,0 +1,65 @@
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{List, ListItem, Paragraph, Widget, Wrap},
+};
+
+use crate::{app_state::AppState, ui::layout::section};
+
+pub(super) fn build_entries(app_state: &AppState) -> impl Widget {
+    let highlighted = app_state.ui_state.metadata_browser.highlighted();
+    let entries = app_state
+        .contracts
+        .current_contract()
+        .map(|contract| contract.metadata_entries())
+        .unwrap_or_default();
+
+    let items = entries
+        .iter()
+        .enumerate()
+        .map(|(idx, entry)| {
+            let style = match idx == highlighted {
+                true => Style::default().bg(Color::White).fg(Color::Black),
+                false => Style::default(),
+            };
+            ListItem::new(Line::from(Span::styled(entry.signature(), style)))
+        })
+        .collect::<Vec<_>>();
+
+    let title = match app_state.contracts.current_contract() {
+        Some(contract) => format!("Metadata of `{}`", contract.name),
+        None => "Metadata (no deployed contract)".to_string(),
+    };
+
+    List::new(items).block(section(title))
+}
+
+pub(super) fn build_docs(app_state: &AppState) -> impl Widget {
+    let docs = app_state
+        .contracts
+        .current_contract()
+        .and_then(|contract| {
+            contract
+                .metadata_entries()
+                .get(app_state.ui_state.metadata_browser.highlighted())
+                .cloned()
+        })
+        .map(|entry| {
+            let mut lines = vec![Line::from(Span::styled(
+                entry.template(),
+                Style::default().fg(Color::Green),
+            ))];
+            lines.extend(
+                entry
+                    .docs
+                    .iter()
+                    .map(|doc| Line::from(doc.trim().to_string())),
+            );
+            lines
+        })
+        .unwrap_or_default();
+
+    Paragraph::new(docs)
+        .wrap(Wrap { trim: false })
+        .block(section("Docs"))
+}
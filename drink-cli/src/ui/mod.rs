@@ -3,6 +3,7 @@ mod current_env;
 mod footer;
 mod help;
 mod layout;
+mod metadata;
 mod output;
 mod user_input;
 
@@ -61,6 +62,10 @@ fn run_ui_app(terminal: &mut Terminal, cwd_override: Option<PathBuf>) -> Result<
 
         let mode = &mut app_state.ui_state.mode;
         if let Event::Key(key) = event::read()? {
+            if key.code != KeyCode::Tab {
+                app_state.ui_state.completion = None;
+            }
+
             match (*mode, key.code) {
                 (_, KeyCode::Esc) => *mode = Managing,
 
@@ -68,9 +73,43 @@ fn run_ui_app(terminal: &mut Terminal, cwd_override: Option<PathBuf>) -> Result<
                 (Managing, KeyCode::Char('i')) => {
                     *mode = Drinking;
                     app_state.ui_state.show_help = false;
+                    app_state.ui_state.show_metadata = false;
                 }
                 (Managing, KeyCode::Char('h')) => {
-                    app_state.ui_state.show_help = !app_state.ui_state.show_help
+                    app_state.ui_state.show_help = !app_state.ui_state.show_help;
+                    app_state.ui_state.show_metadata = false;
+                }
+                (Managing, KeyCode::Char('m')) => {
+                    app_state.ui_state.show_metadata = !app_state.ui_state.show_metadata;
+                    app_state.ui_state.show_help = false;
+                    app_state.ui_state.metadata_browser.reset();
+                }
+                (Managing, KeyCode::Down) if app_state.ui_state.show_metadata => {
+                    let entries_count = app_state
+                        .contracts
+                        .current_contract()
+                        .map(|contract| contract.metadata_entries().len())
+                        .unwrap_or_default();
+                    app_state
+                        .ui_state
+                        .metadata_browser
+                        .highlight_next(entries_count);
+                }
+                (Managing, KeyCode::Up) if app_state.ui_state.show_metadata => {
+                    app_state.ui_state.metadata_browser.highlight_prev()
+                }
+                (Managing, KeyCode::Enter) if app_state.ui_state.show_metadata => {
+                    let template = app_state.contracts.current_contract().and_then(|contract| {
+                        contract
+                            .metadata_entries()
+                            .get(app_state.ui_state.metadata_browser.highlighted())
+                            .map(|entry| entry.template())
+                    });
+                    if let Some(template) = template {
+                        app_state.ui_state.user_input.set(template);
+                        app_state.ui_state.mode = Drinking;
+                        app_state.ui_state.show_metadata = false;
+                    }
                 }
                 (Managing, KeyCode::Down) => app_state.ui_state.output.scroll_down(),
                 (Managing, KeyCode::Up) => app_state.ui_state.output.scroll_up(),
@@ -79,7 +118,9 @@ fn run_ui_app(terminal: &mut Terminal, cwd_override: Option<PathBuf>) -> Result<
                 (Drinking, KeyCode::Backspace) => {
                     app_state.ui_state.user_input.pop();
                 }
-                (Drinking, KeyCode::Tab) => {
+                (Drinking, KeyCode::Tab)
+                    if app_state.ui_state.user_input.current_input().is_empty() =>
+                {
                     let prev_path = match app_state.contracts.current_contract() {
                         Some(c) => c.base_path.clone(),
                         None => continue,
@@ -98,6 +139,7 @@ fn run_ui_app(terminal: &mut Terminal, cwd_override: Option<PathBuf>) -> Result<
                         app_state.ui_state.user_input.set(String::new());
                     }
                 }
+                (Drinking, KeyCode::Tab) => app_state.complete_input(),
                 (Drinking, KeyCode::Up) => app_state.ui_state.user_input.prev_input(),
                 (Drinking, KeyCode::Down) => app_state.ui_state.user_input.next_input(),
                 (Drinking, KeyCode::Enter) => {
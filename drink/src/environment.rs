@@ -0,0 +1,123 @@
+//! The versions and features that drink has been built with, for bug reports.
+//!
+//! ```rust, ignore
+//! let report = drink::environment_report();
+//! println!("{report}"); // drink 0.18.0, ink_sandbox 5.1.0, pallet-contracts ..., one per line
+//! ```
+//!
+//! The report is also appended to the panic messages of drink's internal errors.
+
+use std::fmt;
+
+use frame_support::traits::{CrateVersion, Get, PalletInfoAccess};
+
+use crate::{minimal::MinimalSandboxRuntime, pallet_contracts};
+
+/// The version of `ink_sandbox` that drink depends on (the requirement from the manifest: the
+/// crate doesn't expose its version).
+const INK_SANDBOX_VERSION: &str = "5.1.0";
+
+/// Versions of drink and of the crates it has been built with, together with the runtime of the
+/// sandbox and the enabled features.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct EnvironmentReport {
+    /// The version of drink.
+    pub drink_version: String,
+    /// The version of `ink_sandbox`.
+    pub ink_sandbox_version: String,
+    /// The version of `pallet-contracts` compiled into the runtime.
+    pub pallet_contracts_version: String,
+    /// The version of `frame-system` compiled into the runtime.
+    pub frame_system_version: String,
+    /// The spec name of the runtime (empty for runtimes without a version, like the minimal one).
+    pub runtime_spec_name: String,
+    /// The spec version of the runtime.
+    pub runtime_spec_version: u32,
+    /// The enabled features of drink.
+    pub features: Vec<&'static str>,
+}
+
+impl EnvironmentReport {
+    /// The report for the sandbox runtime `R`.
+    pub fn for_runtime<R: pallet_contracts::Config>() -> Self {
+        let version = <R as frame_system::Config>::Version::get();
+        Self {
+            drink_version: env!("CARGO_PKG_VERSION").to_string(),
+            ink_sandbox_version: INK_SANDBOX_VERSION.to_string(),
+            pallet_contracts_version: display_version(
+                pallet_contracts::Pallet::<R>::crate_version(),
+            ),
+            frame_system_version: display_version(frame_system::Pallet::<R>::crate_version()),
+            runtime_spec_name: version.spec_name.to_string(),
+            runtime_spec_version: version.spec_version,
+            features: enabled_features(),
+        }
+    }
+
+    /// The report as JSON, with the same fields.
+    #[cfg(feature = "session")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "session")))]
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "drink_version": self.drink_version,
+            "ink_sandbox_version": self.ink_sandbox_version,
+            "pallet_contracts_version": self.pallet_contracts_version,
+            "frame_system_version": self.frame_system_version,
+            "runtime_spec_name": self.runtime_spec_name,
+            "runtime_spec_version": self.runtime_spec_version,
+            "features": self.features,
+        })
+    }
+}
+
+impl fmt::Display for EnvironmentReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "drink {}", self.drink_version)?;
+        writeln!(f, "ink_sandbox {}", self.ink_sandbox_version)?;
+        writeln!(f, "pallet-contracts {}", self.pallet_contracts_version)?;
+        writeln!(f, "frame-system {}", self.frame_system_version)?;
+        let spec_name = match self.runtime_spec_name.as_str() {
+            "" => "<unnamed>",
+            name => name,
+        };
+        writeln!(
+            f,
+            "runtime {spec_name} (spec version {})",
+            self.runtime_spec_version
+        )?;
+        write!(f, "features: {}", self.features.join(", "))
+    }
+}
+
+/// The report for the minimal sandbox runtime (see `Session::environment` for other runtimes).
+pub fn environment_report() -> EnvironmentReport {
+    EnvironmentReport::for_runtime::<MinimalSandboxRuntime>()
+}
+
+/// Panics with `message`, about a broken internal invariant of drink, followed by the environment
+/// report, so that bug reports are self-describing.
+#[track_caller]
+pub(crate) fn internal_error(message: impl fmt::Display) -> ! {
+    panic!(
+        "{message}\n\nThis is most likely a bug in drink. Please include the following in the \
+         report:\n{}",
+        environment_report()
+    )
+}
+
+fn display_version(version: CrateVersion) -> String {
+    format!("{}.{}.{}", version.major, version.minor, version.patch)
+}
+
+fn enabled_features() -> Vec<&'static str> {
+    [
+        ("std", cfg!(feature = "std")),
+        ("session", cfg!(feature = "session")),
+        ("macros", cfg!(feature = "macros")),
+        ("tracing-logs", cfg!(feature = "tracing-logs")),
+        ("chaos", cfg!(feature = "chaos")),
+    ]
+    .into_iter()
+    .filter_map(|(feature, enabled)| enabled.then_some(feature))
+    .collect()
+}
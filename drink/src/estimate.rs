@@ -0,0 +1,123 @@
+//! Gas and storage deposit estimates of contract calls, e.g. for deployment pipelines that use
+//! drink as a library to prepare transactions for a real chain.
+//!
+//! ```rust, ignore
+//! use drink::estimate::{estimate_call, CallState};
+//!
+//! let state = CallState::Imported { path: "chain-state.bin".into(), address: token.clone() };
+//! let estimate = estimate_call(bundle, state, "PSP22::total_supply", NO_ARGS, alice)?;
+//! println!("{}", estimate.to_json());
+//! ```
+//!
+//! Every estimate spins up its own session (see `Session`) and throws it away afterwards, so no
+//! knowledge of sessions or records is needed.
+
+use std::{fmt::Debug, path::PathBuf};
+
+use contract_transcode::Value;
+use frame_support::weights::Weight;
+use ink_sandbox::{api::prelude::*, AccountId32};
+use serde_json::json;
+
+use crate::{
+    minimal::MinimalSandbox,
+    record::signed_storage_deposit,
+    session::{error::SessionError, ContractBundle, Session, NO_ENDOWMENT, NO_SALT},
+};
+
+/// Balance given to the estimating actor, so that the estimate doesn't depend on its funds.
+const ACTOR_FUNDS: u128 = 1_000_000_000_000_000_000;
+
+/// The chain state in which a call is estimated.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum CallState {
+    /// A fresh sandbox, with the contract deployed with `constructor(args)` by the default actor.
+    Fresh {
+        /// Constructor label.
+        constructor: String,
+        /// Constructor arguments.
+        args: Vec<String>,
+    },
+    /// The state written by `Session::export_state` to `path`, with the contract at `address`.
+    Imported {
+        /// Path of the state file.
+        path: PathBuf,
+        /// Address of the contract.
+        address: AccountId32,
+    },
+}
+
+/// The estimated cost of a contract call.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Estimate {
+    /// Gas required by the call, i.e. the minimal gas limit for the call to succeed.
+    pub gas_required: Weight,
+    /// Storage deposit: positive for a charge, negative for a refund.
+    pub storage_deposit: i128,
+    /// Whether the contract reverted the call.
+    pub reverted: bool,
+    /// The value returned by the message (the error, if the call was reverted).
+    pub returned: Value,
+}
+
+impl Estimate {
+    /// The estimate as JSON: `gas_required` with `ref_time` and `proof_size`, `storage_deposit`
+    /// (as a decimal string, since it doesn't have to fit in JSON numbers), `reverted` and
+    /// `returned` (displayed as by `cargo contract`).
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "gas_required": {
+                "ref_time": self.gas_required.ref_time(),
+                "proof_size": self.gas_required.proof_size(),
+            },
+            "storage_deposit": self.storage_deposit.to_string(),
+            "reverted": self.reverted,
+            "returned": self.returned.to_string(),
+        })
+    }
+}
+
+/// Estimates the call of `message` with `args`, performed by `actor`, of the contract from
+/// `bundle` in `state`.
+///
+/// The call is a dry run without the storage deposit limit, made in the minimal sandbox (see
+/// `MinimalSandbox`). The actor is funded before the call. Fails if the contract can't be
+/// deployed, the state can't be imported, the call can't be encoded or the pallet rejects the
+/// call. A reverted call is still estimated.
+pub fn estimate_call<S: AsRef<str> + Debug>(
+    bundle: ContractBundle,
+    state: CallState,
+    message: &str,
+    args: &[S],
+    actor: AccountId32,
+) -> Result<Estimate, SessionError> {
+    let mut session = Session::<MinimalSandbox>::default();
+    let transcoder = bundle.transcoder.clone();
+    let address = match state {
+        CallState::Fresh { constructor, args } => {
+            session.deploy_bundle(bundle, &constructor, &args, NO_SALT, NO_ENDOWMENT)?
+        }
+        CallState::Imported { path, address } => {
+            session.import_state(path)?;
+            session.set_transcoder(address.clone(), &transcoder);
+            address
+        }
+    };
+    session
+        .sandbox()
+        .mint_into(&actor, ACTOR_FUNDS)
+        .expect("Funding the actor should succeed");
+
+    let result = session.dry_run_call_as(actor, address, message, args, NO_ENDOWMENT)?;
+    let data = result.result.map_err(SessionError::CallFailed)?;
+    let returned = transcoder
+        .decode_message_return(message, &mut data.data.as_slice())
+        .map_err(|err| SessionError::Decoding(err.to_string()))?;
+
+    Ok(Estimate {
+        gas_required: result.gas_required,
+        storage_deposit: signed_storage_deposit(&result.storage_deposit),
+        reverted: data.did_revert(),
+        returned,
+    })
+}
@@ -0,0 +1,683 @@
+//! Small test contracts shipped with drink, usable without `cargo-contract` (and without building
+//! anything).
+//!
+//! Every fixture is available as raw code (`Fixture::wasm`), e.g. for sandbox-level tests, or (with
+//! the `session` feature) as a `ContractBundle` with ink! metadata, so that it can be deployed and
+//! called through the `Session` API:
+//!
+//! ```rust, ignore
+//! let mut session = Session::<MinimalSandbox>::default();
+//! session.deploy_bundle(Fixture::Toggler.bundle(), "new", &["false"], NO_SALT, NO_ENDOWMENT)?;
+//! session.call::<_, ()>("toggle", NO_ARGS, NO_ENDOWMENT)??;
+//! assert!(session.call::<_, bool>("get", NO_ARGS, NO_ENDOWMENT)??);
+//! ```
+//!
+//! The contracts follow the ink! conventions (selectors, return values, events), so they can stand
+//! in for real ink! contracts, e.g. as callees of the contract under test.
+
+#[cfg(feature = "session")]
+use serde_json::{json, Map, Value};
+
+use crate::environment::internal_error;
+#[cfg(feature = "session")]
+use crate::session::ContractBundle;
+
+/// A contract shipped with drink.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Fixture {
+    /// Does nothing. The constructor is `new()`, every call (e.g. `noop()`) succeeds.
+    Dummy,
+    /// Stores a `bool`. The constructor is `new(init: bool)`, the messages are `toggle()` and
+    /// `get() -> bool`.
+    Toggler,
+    /// Emits events. The constructor is `new()`, the message `emit(value: u32)` emits
+    /// `Emitted { value: u32 }`.
+    EventEmitter,
+    /// Forwards every call (with its input and value) to another contract and returns its output.
+    /// The constructor is `new(callee: AccountId)`.
+    ///
+    /// The proxy has no messages of its own: set the transcoder of the callee for the proxy
+    /// address (`Session::set_transcoder`) to call the callee's messages through it.
+    Proxy,
+    /// Delegates every call (with its input) to the code with a given hash, which runs as the
+    /// proxy: with its storage, balance and address. The constructor is `new(code_hash: Hash)`.
+    ///
+    /// Like `Proxy`, it has no messages of its own. Since the events of the delegated code are
+    /// emitted by the proxy, alias the transcoder of the proxy to a contract running the same code
+    /// (`Session::alias_transcoder`) to both call its messages and decode its events.
+    DelegateProxy,
+    /// Calls another contract with arbitrary input, allowing it to re-enter the relay. The
+    /// constructor is `new()`, the message `relay(callee: AccountId, input: Vec<u8>)` calls
+    /// `callee` with `input` (including the selector) and returns its output, trapping if the
+    /// callee fails. `input` must be shorter than 1024 bytes.
+    Relay,
+    /// Calls another contract on behalf of the caller, e.g. to test how a contract behaves when
+    /// called by a contract rather than by an account. The constructor is `new()`, the payable
+    /// message `forward(callee: AccountId, data: Vec<u8>, value: Balance)` calls `callee` with
+    /// `data` (including the selector), transferring `value` from the forwarder's balance, and
+    /// returns its output. `forward_twice` (with the same arguments) makes the call twice and
+    /// returns the output of the second one. If the callee reverts, the forwarder reverts with its
+    /// output; if it traps, so does the forwarder. `data` must be shorter than 1024 bytes.
+    Forwarder,
+}
+
+impl Fixture {
+    /// All the fixtures.
+    pub const ALL: [Fixture; 7] = [
+        Fixture::Dummy,
+        Fixture::Toggler,
+        Fixture::EventEmitter,
+        Fixture::Proxy,
+        Fixture::DelegateProxy,
+        Fixture::Relay,
+        Fixture::Forwarder,
+    ];
+
+    /// The name of the fixture contract, as in its metadata.
+    pub fn name(self) -> &'static str {
+        match self {
+            Fixture::Dummy => "Dummy",
+            Fixture::Toggler => "Toggler",
+            Fixture::EventEmitter => "EventEmitter",
+            Fixture::Proxy => "Proxy",
+            Fixture::DelegateProxy => "DelegateProxy",
+            Fixture::Relay => "Relay",
+            Fixture::Forwarder => "Forwarder",
+        }
+    }
+
+    /// The code of the fixture contract.
+    pub fn wasm(self) -> Vec<u8> {
+        let wat = match self {
+            Fixture::Dummy => DUMMY,
+            Fixture::Toggler => TOGGLER,
+            Fixture::EventEmitter => EVENT_EMITTER,
+            Fixture::Proxy => PROXY,
+            Fixture::DelegateProxy => DELEGATE_PROXY,
+            Fixture::Relay => RELAY,
+            Fixture::Forwarder => FORWARDER,
+        };
+        wat::parse_str(wat).unwrap_or_else(|err| {
+            internal_error(format!("Fixture contract should be valid: {err}"))
+        })
+    }
+
+    /// The fixture contract together with its ink! metadata.
+    #[cfg(feature = "session")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "session")))]
+    pub fn bundle(self) -> ContractBundle {
+        ContractBundle::from_parts(self.wasm(), self.abi()).unwrap_or_else(|err| {
+            internal_error(format!("Fixture metadata should be valid: {err:?}"))
+        })
+    }
+
+    /// The ink! metadata (version 5) of the fixture contract.
+    #[cfg(feature = "session")]
+    fn abi(self) -> Map<String, Value> {
+        let (constructor_args, messages, events, fields) = match self {
+            Fixture::Dummy => (
+                json!([]),
+                json!([message("noop", "0x7bf06545", json!([]), false, UNIT_RESULT)]),
+                json!([]),
+                json!([]),
+            ),
+            Fixture::Toggler => (
+                json!([arg("init", "bool", BOOL)]),
+                json!([
+                    message("toggle", "0x8dd592ec", json!([]), true, UNIT_RESULT),
+                    message("get", "0x2f865bd9", json!([]), false, BOOL_RESULT),
+                ]),
+                json!([]),
+                json!([{ "name": "value", "type": BOOL, "typeName": "bool" }]),
+            ),
+            Fixture::EventEmitter => (
+                json!([]),
+                json!([message(
+                    "emit",
+                    "0x07329efa",
+                    json!([arg("value", "u32", U32)]),
+                    false,
+                    UNIT_RESULT
+                )]),
+                json!([{
+                    "args": [{
+                        "docs": [],
+                        "indexed": false,
+                        "label": "value",
+                        "type": { "displayName": ["u32"], "type": U32 },
+                    }],
+                    "docs": [],
+                    "label": "Emitted",
+                    "module_path": "drink::fixtures",
+                    "signature_topic": EMITTED_SIGNATURE_TOPIC,
+                }]),
+                json!([]),
+            ),
+            Fixture::Proxy => (
+                json!([arg("callee", "AccountId", ACCOUNT_ID)]),
+                json!([]),
+                json!([]),
+                json!([{ "name": "callee", "type": ACCOUNT_ID, "typeName": "AccountId" }]),
+            ),
+            Fixture::DelegateProxy => (
+                json!([arg("code_hash", "Hash", HASH)]),
+                json!([]),
+                json!([]),
+                json!([{ "name": "code_hash", "type": HASH, "typeName": "Hash" }]),
+            ),
+            Fixture::Relay => (
+                json!([]),
+                json!([message(
+                    "relay",
+                    "0xd2cdf8a3",
+                    json!([
+                        arg("callee", "AccountId", ACCOUNT_ID),
+                        arg("input", "Vec", BYTES)
+                    ]),
+                    true,
+                    UNIT_RESULT
+                )]),
+                json!([]),
+                json!([]),
+            ),
+            Fixture::Forwarder => {
+                let args = json!([
+                    arg("callee", "AccountId", ACCOUNT_ID),
+                    arg("data", "Vec", BYTES),
+                    arg("value", "Balance", BALANCE),
+                ]);
+                (
+                    json!([]),
+                    json!([
+                        payable(message(
+                            "forward",
+                            "0x45753c2b",
+                            args.clone(),
+                            true,
+                            UNIT_RESULT
+                        )),
+                        payable(message(
+                            "forward_twice",
+                            "0xdec6681f",
+                            args,
+                            true,
+                            UNIT_RESULT
+                        )),
+                    ]),
+                    json!([]),
+                    json!([]),
+                )
+            }
+        };
+
+        let layout_fields = fields
+            .as_array()
+            .expect("Fields should be an array")
+            .iter()
+            .map(|field| {
+                let (name, ty) = (&field["name"], &field["type"]);
+                json!({ "layout": { "leaf": { "key": "0x00000000", "ty": ty } }, "name": name })
+            })
+            .collect::<Vec<_>>();
+
+        let abi = json!({
+            "spec": {
+                "constructors": [{
+                    "args": constructor_args,
+                    "default": false,
+                    "docs": [],
+                    "label": "new",
+                    "payable": false,
+                    "returnType": {
+                        "displayName": ["ink_primitives", "ConstructorResult"],
+                        "type": UNIT_RESULT,
+                    },
+                    "selector": "0x9bae9d5e",
+                }],
+                "docs": [],
+                "environment": {
+                    "accountId": { "displayName": ["AccountId"], "type": ACCOUNT_ID },
+                    "balance": { "displayName": ["Balance"], "type": BALANCE },
+                    "blockNumber": { "displayName": ["BlockNumber"], "type": U32 },
+                    "chainExtension": { "displayName": ["ChainExtension"], "type": 12 },
+                    "hash": { "displayName": ["Hash"], "type": HASH },
+                    "maxEventTopics": 4,
+                    "staticBufferSize": 16384,
+                    "timestamp": { "displayName": ["Timestamp"], "type": 6 },
+                },
+                "events": events,
+                "lang_error": { "displayName": ["ink", "LangError"], "type": 9 },
+                "messages": messages,
+            },
+            "storage": {
+                "root": {
+                    "layout": { "struct": { "fields": layout_fields, "name": self.name() } },
+                    "root_key": "0x00000000",
+                    "ty": 13,
+                },
+            },
+            "types": [
+                { "id": BOOL, "type": { "def": { "primitive": "bool" } } },
+                { "id": 1, "type": { "def": { "primitive": "u8" } } },
+                { "id": 2, "type": { "def": { "array": { "len": 32, "type": 1 } } } },
+                {
+                    "id": ACCOUNT_ID,
+                    "type": {
+                        "def": {
+                            "composite": { "fields": [{ "type": 2, "typeName": "[u8; 32]" }] },
+                        },
+                        "path": ["ink_primitives", "types", "AccountId"],
+                    },
+                },
+                {
+                    "id": HASH,
+                    "type": {
+                        "def": {
+                            "composite": { "fields": [{ "type": 2, "typeName": "[u8; 32]" }] },
+                        },
+                        "path": ["ink_primitives", "types", "Hash"],
+                    },
+                },
+                { "id": BALANCE, "type": { "def": { "primitive": "u128" } } },
+                { "id": 6, "type": { "def": { "primitive": "u64" } } },
+                { "id": U32, "type": { "def": { "primitive": "u32" } } },
+                { "id": 8, "type": { "def": { "tuple": [] } } },
+                {
+                    "id": 9,
+                    "type": {
+                        "def": {
+                            "variant": {
+                                "variants": [{ "index": 1, "name": "CouldNotReadInput" }],
+                            },
+                        },
+                        "path": ["ink_primitives", "LangError"],
+                    },
+                },
+                result_type(UNIT_RESULT, 8),
+                result_type(BOOL_RESULT, BOOL),
+                {
+                    "id": 12,
+                    "type": {
+                        "def": { "variant": {} },
+                        "path": ["ink_env", "types", "NoChainExtension"],
+                    },
+                },
+                {
+                    "id": 13,
+                    "type": {
+                        "def": { "composite": { "fields": fields } },
+                        "path": ["drink", "fixtures", self.name()],
+                    },
+                },
+                { "id": BYTES, "type": { "def": { "sequence": { "type": 1 } } } },
+            ],
+            "version": 5,
+        });
+
+        match abi {
+            Value::Object(abi) => abi,
+            _ => unreachable!("Metadata is an object"),
+        }
+    }
+}
+
+// Ids of the types in the metadata registry of the fixtures, which are referred to more than once.
+#[cfg(feature = "session")]
+const BOOL: u32 = 0;
+#[cfg(feature = "session")]
+const ACCOUNT_ID: u32 = 3;
+#[cfg(feature = "session")]
+const HASH: u32 = 4;
+#[cfg(feature = "session")]
+const BALANCE: u32 = 5;
+#[cfg(feature = "session")]
+const U32: u32 = 7;
+#[cfg(feature = "session")]
+const UNIT_RESULT: u32 = 10;
+#[cfg(feature = "session")]
+const BOOL_RESULT: u32 = 11;
+#[cfg(feature = "session")]
+const BYTES: u32 = 14;
+
+/// `blake2_256("Emitted(u32)")`, as computed by ink!.
+#[cfg(feature = "session")]
+const EMITTED_SIGNATURE_TOPIC: &str =
+    "0xa9b5224022eaec9887e6689e1a28326393c63d4a7a564fa7932177c960dca558";
+
+#[cfg(feature = "session")]
+fn arg(label: &str, display_name: &str, ty: u32) -> Value {
+    json!({ "label": label, "type": { "displayName": [display_name], "type": ty } })
+}
+
+#[cfg(feature = "session")]
+fn message(label: &str, selector: &str, args: Value, mutates: bool, return_type: u32) -> Value {
+    json!({
+        "args": args,
+        "default": false,
+        "docs": [],
+        "label": label,
+        "mutates": mutates,
+        "payable": false,
+        "returnType": { "displayName": ["ink", "MessageResult"], "type": return_type },
+        "selector": selector,
+    })
+}
+
+/// Marks `message` as payable.
+#[cfg(feature = "session")]
+fn payable(mut message: Value) -> Value {
+    message["payable"] = json!(true);
+    message
+}
+
+/// `Result<ok, LangError>`.
+#[cfg(feature = "session")]
+fn result_type(id: u32, ok: u32) -> Value {
+    json!({
+        "id": id,
+        "type": {
+            "def": {
+                "variant": {
+                    "variants": [
+                        { "fields": [{ "type": ok }], "index": 0, "name": "Ok" },
+                        { "fields": [{ "type": 9 }], "index": 1, "name": "Err" },
+                    ],
+                },
+            },
+            "params": [{ "name": "T", "type": ok }, { "name": "E", "type": 9 }],
+            "path": ["Result"],
+        },
+    })
+}
+
+/// Every call returns `Ok(())`.
+const DUMMY: &str = r#"
+(module
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; Memory layout:
+	;; [0, 1)     returned `Ok(())`
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(call $seal_return (i32.const 0) (i32.const 0) (i32.const 1))
+	)
+)"#;
+
+/// The constructor stores the initial value (the byte after the selector). `toggle` flips it and
+/// any other message returns it.
+const TOGGLER: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_set_storage" (func $seal_set_storage (param i32 i32 i32)))
+	(import "seal0" "seal_get_storage" (func $seal_get_storage (param i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; Memory layout:
+	;; [0, 32)    storage key (zeros)
+	;; [32, 36)   buffer length
+	;; [36, 37)   returned `Ok` (zero)
+	;; [37, 38)   stored value
+	;; [64, 96)   input (selector and arguments)
+
+	(func (export "deploy")
+		(i32.store (i32.const 32) (i32.const 32))
+		(call $seal_input (i32.const 64) (i32.const 32))
+		(call $seal_set_storage (i32.const 0) (i32.const 68) (i32.const 1))
+	)
+
+	(func (export "call")
+		(i32.store (i32.const 32) (i32.const 1))
+		(drop (call $seal_get_storage (i32.const 0) (i32.const 37) (i32.const 32)))
+		(i32.store (i32.const 32) (i32.const 32))
+		(call $seal_input (i32.const 64) (i32.const 32))
+
+		;; `toggle` (selector 0x8dd592ec, little-endian when loaded).
+		(if (i32.eq (i32.load (i32.const 64)) (i32.const 0xec92d58d))
+			(then
+				(i32.store8 (i32.const 37) (i32.eqz (i32.load8_u (i32.const 37))))
+				(call $seal_set_storage (i32.const 0) (i32.const 37) (i32.const 1))
+				(call $seal_return (i32.const 0) (i32.const 36) (i32.const 1))
+			)
+		)
+		(call $seal_return (i32.const 0) (i32.const 36) (i32.const 2))
+	)
+)"#;
+
+/// Every call emits `Emitted` with the four bytes after the selector as the value.
+const EVENT_EMITTER: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_deposit_event" (func $seal_deposit_event (param i32 i32 i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; Memory layout:
+	;; [0, 4)     buffer length
+	;; [4, 5)     returned `Ok(())`
+	;; [32, 65)   encoded topics: just the signature topic of `Emitted`
+	;; [96, 128)  input (selector and the value)
+
+	(data (i32.const 32)
+		"\04"
+		"\a9\b5\22\40\22\ea\ec\98\87\e6\68\9e\1a\28\32\63"
+		"\93\c6\3d\4a\7a\56\4f\a7\93\21\77\c9\60\dc\a5\58"
+	)
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(i32.store (i32.const 0) (i32.const 32))
+		(call $seal_input (i32.const 96) (i32.const 0))
+		(call $seal_deposit_event (i32.const 32) (i32.const 33) (i32.const 100) (i32.const 4))
+		(call $seal_return (i32.const 0) (i32.const 4) (i32.const 1))
+	)
+)"#;
+
+/// The constructor stores the callee (the 32 bytes after the selector). Every call is forwarded
+/// there as a tail call, so the output (and the revert flag) of the callee becomes the output of
+/// the proxy.
+const PROXY: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_set_storage" (func $seal_set_storage (param i32 i32 i32)))
+	(import "seal0" "seal_get_storage" (func $seal_get_storage (param i32 i32 i32) (result i32)))
+	(import "seal0" "seal_value_transferred" (func $seal_value_transferred (param i32 i32)))
+	(import "seal1" "seal_call" (func $seal_call (param i32 i32 i64 i32 i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; Memory layout:
+	;; [0, 32)    storage key (zeros)
+	;; [32, 36)   buffer length
+	;; [60, 64)   constructor selector
+	;; [64, 96)   address of the callee
+	;; [96, 112)  transferred value
+
+	(func (export "deploy")
+		(i32.store (i32.const 32) (i32.const 36))
+		(call $seal_input (i32.const 60) (i32.const 32))
+		(call $seal_set_storage (i32.const 0) (i32.const 64) (i32.const 32))
+	)
+
+	(func (export "call")
+		(i32.store (i32.const 32) (i32.const 32))
+		(drop (call $seal_get_storage (i32.const 0) (i32.const 64) (i32.const 32)))
+		(i32.store (i32.const 32) (i32.const 16))
+		(call $seal_value_transferred (i32.const 96) (i32.const 32))
+		;; Flags: FORWARD_INPUT | TAIL_CALL. Gas: 0 means all the remaining gas. No output buffer
+		;; (`u32::MAX`), since the output is returned directly.
+		(drop (call $seal_call
+			(i32.const 5)
+			(i32.const 64)
+			(i64.const 0)
+			(i32.const 96)
+			(i32.const 0)
+			(i32.const 0)
+			(i32.const -1)
+			(i32.const 0)
+		))
+		;; A tail call returns only if the callee trapped.
+		(unreachable)
+	)
+)"#;
+
+/// The constructor stores the code hash (the 32 bytes after the selector). Every call is delegated
+/// to that code as a tail call, so the output (and the revert flag) of the code becomes the output
+/// of the proxy. The code hash is kept under a key of ones, so that the delegated code (e.g.
+/// `Toggler`, storing under the key of zeros) doesn't overwrite it.
+const DELEGATE_PROXY: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_set_storage" (func $seal_set_storage (param i32 i32 i32)))
+	(import "seal0" "seal_get_storage" (func $seal_get_storage (param i32 i32 i32) (result i32)))
+	(import "seal0" "seal_delegate_call" (func $seal_delegate_call (param i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; Memory layout:
+	;; [0, 32)    storage key (ones)
+	;; [32, 36)   buffer length
+	;; [60, 64)   constructor selector
+	;; [64, 96)   code hash
+
+	(data (i32.const 0)
+		"\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff"
+		"\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff\ff"
+	)
+
+	(func (export "deploy")
+		(i32.store (i32.const 32) (i32.const 36))
+		(call $seal_input (i32.const 60) (i32.const 32))
+		(call $seal_set_storage (i32.const 0) (i32.const 64) (i32.const 32))
+	)
+
+	(func (export "call")
+		(i32.store (i32.const 32) (i32.const 32))
+		(drop (call $seal_get_storage (i32.const 0) (i32.const 64) (i32.const 32)))
+		;; Flags: FORWARD_INPUT | TAIL_CALL (the input arguments are ignored). No output buffer
+		;; (`u32::MAX`), since the output is returned directly.
+		(drop (call $seal_delegate_call
+			(i32.const 5)
+			(i32.const 64)
+			(i32.const 0)
+			(i32.const 0)
+			(i32.const -1)
+			(i32.const 0)
+		))
+		;; A tail call returns only if the delegated code trapped.
+		(unreachable)
+	)
+)"#;
+
+/// The input of `relay` is the selector, the callee and the SCALE-encoded input for the callee
+/// (only the one- and two-byte compact length prefixes are supported). The callee's output is
+/// returned as is.
+const RELAY: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "seal1" "seal_call" (func $seal_call (param i32 i32 i64 i32 i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; Memory layout:
+	;; [0, 16)        transferred value (zero)
+	;; [32, 36)       input buffer length
+	;; [36, 40)       output buffer length
+	;; [128, 132)     selector
+	;; [132, 164)     address of the callee
+	;; [164, 1152)    length prefix and input for the callee
+	;; [2048, 3072)   output of the callee
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(local $prefix i32)
+		(local $length i32)
+		(i32.store (i32.const 32) (i32.const 1024))
+		(call $seal_input (i32.const 128) (i32.const 32))
+		(if (i32.eqz (i32.and (i32.load8_u (i32.const 164)) (i32.const 3)))
+			(then
+				(local.set $prefix (i32.const 1))
+				(local.set $length (i32.shr_u (i32.load8_u (i32.const 164)) (i32.const 2))))
+			(else
+				(local.set $prefix (i32.const 2))
+				(local.set $length (i32.shr_u (i32.load16_u (i32.const 164)) (i32.const 2)))))
+		(i32.store (i32.const 36) (i32.const 1024))
+		;; Flags: ALLOW_REENTRY. Gas: 0 means all the remaining gas.
+		(if (call $seal_call
+				(i32.const 8)
+				(i32.const 132)
+				(i64.const 0)
+				(i32.const 0)
+				(i32.add (i32.const 164) (local.get $prefix))
+				(local.get $length)
+				(i32.const 2048)
+				(i32.const 36))
+			(then (unreachable)))
+		(call $seal_return (i32.const 0) (i32.const 2048) (i32.load (i32.const 36)))
+	)
+)"#;
+
+/// The input of `forward` and `forward_twice` is the selector, the callee, the SCALE-encoded data
+/// for the callee (only the one- and two-byte compact length prefixes are supported) and the value.
+/// The callee's output is returned as is, with the revert flag set if the callee reverted.
+const FORWARDER: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "seal1" "seal_call" (func $seal_call (param i32 i32 i64 i32 i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; Memory layout:
+	;; [32, 36)       input buffer length
+	;; [36, 40)       output buffer length
+	;; [128, 132)     selector
+	;; [132, 164)     address of the callee
+	;; [164, 1208)    length prefix and data for the callee, followed by the value
+	;; [2048, 3072)   output of the callee
+
+	;; Calls the callee, returning the return code of `seal_call`.
+	(func $forward (param $data i32) (param $length i32) (result i32)
+		(i32.store (i32.const 36) (i32.const 1024))
+		;; Flags: ALLOW_REENTRY. Gas: 0 means all the remaining gas.
+		(call $seal_call
+			(i32.const 8)
+			(i32.const 132)
+			(i64.const 0)
+			(i32.add (local.get $data) (local.get $length))
+			(local.get $data)
+			(local.get $length)
+			(i32.const 2048)
+			(i32.const 36))
+	)
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(local $data i32)
+		(local $length i32)
+		(local $code i32)
+		(i32.store (i32.const 32) (i32.const 1080))
+		(call $seal_input (i32.const 128) (i32.const 32))
+		(if (i32.eqz (i32.and (i32.load8_u (i32.const 164)) (i32.const 3)))
+			(then
+				(local.set $data (i32.const 165))
+				(local.set $length (i32.shr_u (i32.load8_u (i32.const 164)) (i32.const 2))))
+			(else
+				(local.set $data (i32.const 166))
+				(local.set $length (i32.shr_u (i32.load16_u (i32.const 164)) (i32.const 2)))))
+		(local.set $code (call $forward (local.get $data) (local.get $length)))
+		;; `forward_twice` (selector 0xdec6681f) makes the second call only if the first succeeded.
+		(if (i32.and
+				(i32.eqz (local.get $code))
+				(i32.eq (i32.load (i32.const 128)) (i32.const 0x1f68c6de)))
+			(then (local.set $code (call $forward (local.get $data) (local.get $length)))))
+		;; Return code 2 is `CalleeReverted`: revert with the callee's output.
+		(if (i32.eq (local.get $code) (i32.const 2))
+			(then (call $seal_return (i32.const 1) (i32.const 2048) (i32.load (i32.const 36)))))
+		(if (local.get $code) (then (unreachable)))
+		(call $seal_return (i32.const 0) (i32.const 2048) (i32.load (i32.const 36)))
+	)
+)"#;
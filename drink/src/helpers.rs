@@ -0,0 +1,3 @@
+//! Ready-made building blocks for testing contracts that depend on standard contracts.
+
+pub mod psp22;
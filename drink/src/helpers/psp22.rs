@@ -0,0 +1,279 @@
+//! A mocked PSP22 token, for testing contracts that depend on a token without building a real one.
+//!
+//! ```rust, ignore
+//! let token = Psp22Mock::new().with_balance(alice.clone(), 100);
+//! let token_address = session.mocking_api().deploy(token.mock());
+//! let dex = session.deploy_bundle(
+//!     dex_bundle,
+//!     "new",
+//!     &[token_address.to_string()],
+//!     NO_SALT,
+//!     NO_ENDOWMENT,
+//! )?;
+//!
+//! // The mock cannot see who calls it, so it has to be told.
+//! token.set_caller(dex.clone());
+//! session.call::<_, ()>("deposit", &["100"], NO_ENDOWMENT)??;
+//!
+//! assert_psp22_balance(&mut session, &token_address, &dex, 100);
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use ink_sandbox::{AccountId32, AccountIdFor, Sandbox};
+use parity_scale_codec::{Decode, Encode};
+
+use crate::{
+    errors::MessageResult,
+    pallet_contracts::Config,
+    session::{
+        mock::{mock_message, selector_of, ContractMock},
+        Session,
+    },
+};
+
+/// The errors returned by the PSP22 messages (the same encoding as in the PSP22 standard).
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub enum Psp22Error {
+    /// Custom error, also used by the mock when the caller hasn't been set.
+    Custom(String),
+    /// The balance of the sender is too low.
+    InsufficientBalance,
+    /// The allowance of the caller is too low.
+    InsufficientAllowance,
+    /// The recipient is the zero address.
+    ZeroRecipientAddress,
+    /// The sender is the zero address.
+    ZeroSenderAddress,
+    /// The recipient rejected the transfer.
+    SafeTransferCheckFailed(String),
+}
+
+/// The state of a mocked token.
+struct Psp22State<AccountId> {
+    caller: Option<AccountId>,
+    total_supply: u128,
+    balances: BTreeMap<AccountId, u128>,
+    allowances: BTreeMap<(AccountId, AccountId), u128>,
+}
+
+impl<AccountId: Ord + Clone> Psp22State<AccountId> {
+    fn balance_of(&self, who: &AccountId) -> u128 {
+        self.balances.get(who).copied().unwrap_or_default()
+    }
+
+    fn allowance(&self, owner: &AccountId, spender: &AccountId) -> u128 {
+        self.allowances
+            .get(&(owner.clone(), spender.clone()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn caller(&self) -> Result<AccountId, Psp22Error> {
+        self.caller
+            .clone()
+            .ok_or_else(|| Psp22Error::Custom("Psp22Mock: the caller has not been set".to_string()))
+    }
+
+    fn transfer(&mut self, from: AccountId, to: AccountId, value: u128) -> Result<(), Psp22Error> {
+        let from_balance = self.balance_of(&from);
+        if from_balance < value {
+            return Err(Psp22Error::InsufficientBalance);
+        }
+        if from != to {
+            self.balances.insert(from, from_balance - value);
+            *self.balances.entry(to).or_default() += value;
+        }
+        Ok(())
+    }
+}
+
+/// An in-memory PSP22 token, deployed as a `ContractMock` (see `Psp22Mock::mock`).
+///
+/// All the standard PSP22 messages are supported (except for the metadata extension). Clones
+/// share the state, so the balances can be inspected and modified while the mock is deployed.
+///
+/// A mock cannot see the caller of a message (the interception interface passes only the input),
+/// so `transfer`, `transfer_from`, `approve`, `increase_allowance` and `decrease_allowance` act on
+/// behalf of the account set with `set_caller`. Until it is set, they return
+/// `Psp22Error::Custom`.
+#[derive(frame_support::CloneNoBound)]
+pub struct Psp22Mock<AccountId = AccountId32> {
+    state: Arc<Mutex<Psp22State<AccountId>>>,
+}
+
+impl<AccountId: Ord + Clone + Encode + Decode + Send + 'static> Default for Psp22Mock<AccountId> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<AccountId: Ord + Clone + Encode + Decode + Send + 'static> Psp22Mock<AccountId> {
+    /// Creates a token without any supply.
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(Psp22State {
+                caller: None,
+                total_supply: 0,
+                balances: BTreeMap::new(),
+                allowances: BTreeMap::new(),
+            })),
+        }
+    }
+
+    /// Mints `amount` to `who` and returns updated `self`.
+    pub fn with_balance(self, who: AccountId, amount: u128) -> Self {
+        self.mint(who, amount);
+        self
+    }
+
+    /// Mints `amount` to `who`, increasing the total supply.
+    pub fn mint(&self, who: AccountId, amount: u128) {
+        let mut state = self.state();
+        state.total_supply += amount;
+        *state.balances.entry(who).or_default() += amount;
+    }
+
+    /// Sets the account on behalf of which the mutating messages are executed (normally, the
+    /// contract under test). Returns the previous one.
+    pub fn set_caller(&self, caller: AccountId) -> Option<AccountId> {
+        self.state().caller.replace(caller)
+    }
+
+    /// Returns the current balance of `who`.
+    pub fn balance_of(&self, who: &AccountId) -> u128 {
+        self.state().balance_of(who)
+    }
+
+    /// Returns the current allowance of `spender` over the tokens of `owner`.
+    pub fn allowance(&self, owner: &AccountId, spender: &AccountId) -> u128 {
+        self.state().allowance(owner, spender)
+    }
+
+    /// Returns the current total supply.
+    pub fn total_supply(&self) -> u128 {
+        self.state().total_supply
+    }
+
+    /// Creates a `ContractMock` backed by the state of this token, to be deployed with
+    /// `MockingApi::deploy`.
+    pub fn mock(&self) -> ContractMock {
+        let total_supply = self.clone();
+        let balance_of = self.clone();
+        let allowance = self.clone();
+        let transfer = self.clone();
+        let transfer_from = self.clone();
+        let approve = self.clone();
+        let increase_allowance = self.clone();
+        let decrease_allowance = self.clone();
+
+        ContractMock::new()
+            .with_message_label(
+                "PSP22::total_supply",
+                mock_message(move |()| total_supply.total_supply()),
+            )
+            .with_message_label(
+                "PSP22::balance_of",
+                mock_message(move |who: AccountId| balance_of.balance_of(&who)),
+            )
+            .with_message_label(
+                "PSP22::allowance",
+                mock_message(move |(owner, spender): (AccountId, AccountId)| {
+                    allowance.allowance(&owner, &spender)
+                }),
+            )
+            .with_message_label(
+                "PSP22::transfer",
+                mock_message(move |(to, value, _data): (AccountId, u128, Vec<u8>)| {
+                    let mut state = transfer.state();
+                    let from = state.caller()?;
+                    state.transfer(from, to, value)
+                }),
+            )
+            .with_message_label(
+                "PSP22::transfer_from",
+                mock_message(
+                    move |(from, to, value, _data): (AccountId, AccountId, u128, Vec<u8>)| {
+                        let mut state = transfer_from.state();
+                        let spender = state.caller()?;
+                        let allowance = state.allowance(&from, &spender);
+                        if allowance < value {
+                            return Err(Psp22Error::InsufficientAllowance);
+                        }
+                        state.transfer(from.clone(), to, value)?;
+                        state.allowances.insert((from, spender), allowance - value);
+                        Ok(())
+                    },
+                ),
+            )
+            .with_message_label(
+                "PSP22::approve",
+                mock_message(move |(spender, value): (AccountId, u128)| {
+                    let mut state = approve.state();
+                    let owner = state.caller()?;
+                    state.allowances.insert((owner, spender), value);
+                    Ok::<(), Psp22Error>(())
+                }),
+            )
+            .with_message_label(
+                "PSP22::increase_allowance",
+                mock_message(move |(spender, delta): (AccountId, u128)| {
+                    let mut state = increase_allowance.state();
+                    let owner = state.caller()?;
+                    let allowance = state.allowance(&owner, &spender).saturating_add(delta);
+                    state.allowances.insert((owner, spender), allowance);
+                    Ok::<(), Psp22Error>(())
+                }),
+            )
+            .with_message_label(
+                "PSP22::decrease_allowance",
+                mock_message(move |(spender, delta): (AccountId, u128)| {
+                    let mut state = decrease_allowance.state();
+                    let owner = state.caller()?;
+                    let allowance = state
+                        .allowance(&owner, &spender)
+                        .checked_sub(delta)
+                        .ok_or(Psp22Error::InsufficientAllowance)?;
+                    state.allowances.insert((owner, spender), allowance);
+                    Ok::<(), Psp22Error>(())
+                }),
+            )
+    }
+
+    fn state(&self) -> MutexGuard<'_, Psp22State<AccountId>> {
+        self.state
+            .lock()
+            .expect("Should be able to acquire lock on the token state")
+    }
+}
+
+/// Asserts that the PSP22 token at `token` (a real contract or a `Psp22Mock`) reports `amount` as
+/// the balance of `who`. The balance is queried with a dry run, so nothing is recorded.
+#[track_caller]
+pub fn assert_psp22_balance<T: Sandbox>(
+    session: &mut Session<T>,
+    token: &AccountIdFor<T::Runtime>,
+    who: &AccountIdFor<T::Runtime>,
+    amount: u128,
+) where
+    T::Runtime: Config,
+{
+    let mut input = selector_of("PSP22::balance_of").to_vec();
+    who.encode_to(&mut input);
+
+    let result = session.dry_run_call_raw(token.clone(), input, None);
+    let output = match result.result {
+        Ok(output) if !output.did_revert() => output,
+        other => panic!("PSP22::balance_of of {token:?} failed: {other:?}"),
+    };
+    let balance = MessageResult::<u128>::decode(&mut output.data.as_slice())
+        .unwrap_or_else(|err| panic!("Cannot decode the balance returned by {token:?}: {err:?}"))
+        .unwrap_or_else(|err| panic!("PSP22::balance_of of {token:?} failed: {err:?}"));
+    assert_eq!(
+        balance, amount,
+        "Unexpected PSP22 balance of {who:?} in token {token:?}"
+    );
+}
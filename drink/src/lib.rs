@@ -2,22 +2,96 @@
 //! a running node.
 
 #![warn(missing_docs)]
+#![cfg_attr(docsrs, feature(doc_cfg))]
 
+pub mod environment;
 pub mod errors;
+#[cfg(feature = "session")]
+#[cfg_attr(docsrs, doc(cfg(feature = "session")))]
+pub mod estimate;
+pub mod fixtures;
+#[cfg(feature = "session")]
+#[cfg_attr(docsrs, doc(cfg(feature = "session")))]
+pub mod helpers;
 pub mod pallet_contracts_debugging;
+pub mod prelude;
+pub mod record;
+pub mod recorder;
+pub mod results;
 #[cfg(feature = "session")]
+#[cfg_attr(docsrs, doc(cfg(feature = "session")))]
+pub mod scenario;
+#[cfg(feature = "session")]
+#[cfg_attr(docsrs, doc(cfg(feature = "session")))]
 pub mod session;
+pub mod weights;
 
 #[cfg(feature = "macros")]
+#[cfg_attr(docsrs, doc(cfg(feature = "macros")))]
 pub use drink_test_macro::{contract_bundle_provider, test};
+pub use environment::{environment_report, EnvironmentReport};
 pub use errors::Error;
 pub use frame_support;
+pub use frame_system;
 pub use ink_sandbox::{
     api as sandbox_api, create_sandbox, pallet_balances, pallet_contracts, pallet_timestamp,
     sp_externalities, AccountId32, DispatchError, Sandbox, Ss58Codec, Weight,
 };
+pub use recorder::{Recordable, Recorder};
+#[cfg(feature = "chaos")]
+#[cfg_attr(docsrs, doc(cfg(feature = "chaos")))]
+pub use session::chaos;
+#[cfg(feature = "session")]
+#[cfg_attr(docsrs, doc(cfg(feature = "session")))]
+pub use session::mock::{
+    mock_message, ContractMock, MessageMock, MockGasStrategy, MockedCall, MockedCallResult,
+    Selector,
+};
+
+/// The variant of a test generated by `#[drink::test(variants(real, mocked))]`: whether the
+/// dependencies of the tested contract should be the real contracts or mocks.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TestVariant {
+    /// Real dependency contracts.
+    Real,
+    /// Mocked dependencies.
+    Mocked,
+}
+
+impl TestVariant {
+    /// Whether the dependencies should be the real contracts.
+    pub fn is_real(self) -> bool {
+        self == TestVariant::Real
+    }
+
+    /// Whether the dependencies should be mocked.
+    pub fn is_mocked(self) -> bool {
+        self == TestVariant::Mocked
+    }
+}
+
+/// Used by the code generated by `#[drink::test]` and `#[drink::contract_bundle_provider]`, which
+/// relies on the `session` module.
 #[cfg(feature = "session")]
-pub use session::mock::{mock_message, ContractMock, MessageMock, MockedCallResult, Selector};
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __require_session_feature {
+    () => {};
+}
+
+/// Used by the code generated by `#[drink::test]` and `#[drink::contract_bundle_provider]`: without
+/// the `session` feature, explains what is missing (instead of a bunch of unresolved imports).
+#[cfg(not(feature = "session"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __require_session_feature {
+    () => {
+        compile_error!(
+            "drink! test macros need the `session` feature of `drink` (enabled by default). Enable \
+             it, e.g. `drink = { version = \"...\", features = [\"session\", \"macros\"] }`"
+        );
+    };
+}
 
 /// Main result type for the drink crate.
 pub type DrinkResult<T> = std::result::Result<T, Error>;
@@ -20,10 +20,12 @@
 //! simple argument types, and those that implement some specific traits. This means that usually,
 //! complex objects will be passed in their encoded form (`Vec<u8>` obtained with scale encoding).
 
+mod composite;
 mod intercepting;
 mod runtime;
 mod tracing;
 
+pub use composite::{CompositeInterceptor, CompositeTracer};
 pub use runtime::{InterceptingExt, InterceptingExtT, NoopExt, TracingExt, TracingExtT};
 
 /// Main configuration parameter for the contracts pallet debugging. Provides all the necessary
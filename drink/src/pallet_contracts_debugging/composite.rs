@@ -0,0 +1,193 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use parity_scale_codec::{Decode, Encode};
+
+use crate::{
+    environment::internal_error,
+    pallet_contracts::debug::ExecResult,
+    pallet_contracts_debugging::{InterceptingExtT, TracingExtT},
+};
+
+/// A tracer that passes every callback to a list of tracers, in the order they were added.
+///
+/// If a tracer panics, the remaining ones are still called, and then the (first) panic is
+/// propagated.
+///
+/// Clones share the list of tracers, so tracers can be added after the composite has been
+/// registered as the tracing extension.
+#[derive(Clone, Default)]
+pub struct CompositeTracer {
+    tracers: Arc<Mutex<Vec<Box<dyn TracingExtT + Send>>>>,
+}
+
+impl CompositeTracer {
+    /// Creates a composite with no tracers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `tracer` at the end of the list.
+    pub fn push(&self, tracer: impl TracingExtT + Send + 'static) {
+        self.tracers
+            .lock()
+            .expect("Should be able to acquire lock on tracers")
+            .push(Box::new(tracer));
+    }
+
+    /// Returns the number of tracers.
+    pub fn len(&self) -> usize {
+        self.tracers
+            .lock()
+            .expect("Should be able to acquire lock on tracers")
+            .len()
+    }
+
+    /// Returns `true` if there are no tracers.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl TracingExtT for CompositeTracer {
+    fn after_call(
+        &self,
+        contract_address: Vec<u8>,
+        is_call: bool,
+        input_data: Vec<u8>,
+        result: Vec<u8>,
+    ) {
+        let tracers = self
+            .tracers
+            .lock()
+            .expect("Should be able to acquire lock on tracers");
+
+        let mut first_panic = None;
+        for tracer in tracers.iter() {
+            let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+                tracer.after_call(
+                    contract_address.clone(),
+                    is_call,
+                    input_data.clone(),
+                    result.clone(),
+                )
+            }));
+            if let Err(payload) = outcome {
+                first_panic.get_or_insert(payload);
+            }
+        }
+
+        // Release the lock first, so that the tracers survive the panic.
+        drop(tracers);
+        if let Some(payload) = first_panic {
+            panic::resume_unwind(payload);
+        }
+    }
+}
+
+/// An interceptor that passes every call to a list of interceptors, in order, until one of them
+/// intercepts it.
+///
+/// The interceptors before the one that intercepted a call get its `after_call` (with the output
+/// of the intercepted call), as if the call had been executed normally. The ones after it don't
+/// hear about the call at all. If no interceptor intercepts the call, all of them get `after_call`.
+///
+/// Clones share the list of interceptors, so interceptors can be added after the composite has
+/// been registered as the intercepting extension.
+#[derive(Clone, Default)]
+pub struct CompositeInterceptor {
+    interceptors: Arc<Mutex<Vec<Box<dyn InterceptingExtT + Send>>>>,
+}
+
+impl CompositeInterceptor {
+    /// Creates a composite with no interceptors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts `interceptor` at `index` of the list. Panics if `index > len()`.
+    pub fn insert(&self, index: usize, interceptor: impl InterceptingExtT + Send + 'static) {
+        self.lock().insert(index, Box::new(interceptor));
+    }
+
+    /// Adds `interceptor` at the end of the list.
+    pub fn push(&self, interceptor: impl InterceptingExtT + Send + 'static) {
+        self.lock().push(Box::new(interceptor));
+    }
+
+    /// Returns the number of interceptors.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if there are no interceptors.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if `self` is the only handle to the list of interceptors, e.g. because the
+    /// clone registered as the intercepting extension has been replaced (and dropped).
+    pub fn is_detached(&self) -> bool {
+        Arc::strong_count(&self.interceptors) == 1
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Box<dyn InterceptingExtT + Send>>> {
+        self.interceptors
+            .lock()
+            .expect("Should be able to acquire lock on interceptors")
+    }
+}
+
+impl InterceptingExtT for CompositeInterceptor {
+    fn intercept_call(
+        &self,
+        contract_address: Vec<u8>,
+        is_call: bool,
+        input_data: Vec<u8>,
+    ) -> Vec<u8> {
+        let interceptors = self.lock();
+        for (idx, interceptor) in interceptors.iter().enumerate() {
+            let result =
+                interceptor.intercept_call(contract_address.clone(), is_call, input_data.clone());
+            let intercepted =
+                Option::<ExecResult>::decode(&mut result.as_slice()).unwrap_or_else(|err| {
+                    internal_error(format!("Intercepting result should be decodable: {err}"))
+                });
+            let Some(intercepted) = intercepted else {
+                continue;
+            };
+            // Like the pallet, report only the output of the calls that haven't failed.
+            if let Ok(output) = intercepted {
+                for earlier in &interceptors[..idx] {
+                    earlier.after_call(
+                        contract_address.clone(),
+                        is_call,
+                        input_data.clone(),
+                        output.data.clone(),
+                    );
+                }
+            }
+            return result;
+        }
+        None::<()>.encode()
+    }
+
+    fn after_call(
+        &self,
+        contract_address: Vec<u8>,
+        is_call: bool,
+        input_data: Vec<u8>,
+        result: Vec<u8>,
+    ) {
+        for interceptor in self.lock().iter() {
+            interceptor.after_call(
+                contract_address.clone(),
+                is_call,
+                input_data.clone(),
+                result.clone(),
+            );
+        }
+    }
+}
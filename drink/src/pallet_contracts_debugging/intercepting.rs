@@ -2,6 +2,7 @@ use ink_sandbox::AccountIdFor;
 use parity_scale_codec::{Decode, Encode};
 
 use crate::{
+    environment::internal_error,
     pallet_contracts::{
         debug::{CallInterceptor, ExecResult, ExportedFunction},
         Config,
@@ -23,7 +24,9 @@ impl<R: Config> CallInterceptor<R> for DrinkDebug {
             input_data.to_vec(),
         )
         .and_then(|intercepting_result| {
-            Decode::decode(&mut intercepting_result.as_slice()).expect("Decoding should succeed")
+            Decode::decode(&mut intercepting_result.as_slice()).unwrap_or_else(|err| {
+                internal_error(format!("Intercepting result should be decodable: {err}"))
+            })
         })
     }
 }
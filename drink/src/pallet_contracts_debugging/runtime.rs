@@ -18,6 +18,14 @@ pub trait ContractCallDebugger {
         input_data: Vec<u8>,
         result: Vec<u8>,
     ) {
+        if let Some(ext) = self.extension::<InterceptingExt>() {
+            ext.after_call(
+                contract_address.clone(),
+                is_call,
+                input_data.clone(),
+                result.clone(),
+            );
+        }
         if let Some(ext) = self.extension::<TracingExt>() {
             ext.after_call(contract_address, is_call, input_data, result);
         }
@@ -52,6 +60,19 @@ decl_extension! {
     pub struct TracingExt(Box<dyn TracingExtT + Send>);
 }
 
+impl TracingExtT for TracingExt {
+    fn after_call(
+        &self,
+        contract_address: Vec<u8>,
+        is_call: bool,
+        input_data: Vec<u8>,
+        result: Vec<u8>,
+    ) {
+        self.0
+            .after_call(contract_address, is_call, input_data, result)
+    }
+}
+
 /// This trait describes a runtime extension that can be used to intercept contract calls.
 pub trait InterceptingExtT {
     /// Called when a contract call is made.
@@ -66,6 +87,16 @@ pub trait InterceptingExtT {
         // By default, do not intercept, continue with the standard procedure.
         None::<()>.encode()
     }
+
+    /// Called after a contract call is made (that has not been intercepted), with its output.
+    fn after_call(
+        &self,
+        _contract_address: Vec<u8>,
+        _is_call: bool,
+        _input_data: Vec<u8>,
+        _result: Vec<u8>,
+    ) {
+    }
 }
 
 decl_extension! {
@@ -73,6 +104,28 @@ decl_extension! {
     pub struct InterceptingExt(Box<dyn InterceptingExtT + Send>);
 }
 
+impl InterceptingExtT for InterceptingExt {
+    fn intercept_call(
+        &self,
+        contract_address: Vec<u8>,
+        is_call: bool,
+        input_data: Vec<u8>,
+    ) -> Vec<u8> {
+        self.0.intercept_call(contract_address, is_call, input_data)
+    }
+
+    fn after_call(
+        &self,
+        contract_address: Vec<u8>,
+        is_call: bool,
+        input_data: Vec<u8>,
+        result: Vec<u8>,
+    ) {
+        self.0
+            .after_call(contract_address, is_call, input_data, result)
+    }
+}
+
 /// The simplest extension - uses default implementation.
 pub struct NoopExt;
 impl TracingExtT for NoopExt {}
@@ -0,0 +1,49 @@
+//! The most commonly used items, importable at once with `use drink::prelude::*`.
+//!
+//! ```rust, ignore
+//! use drink::prelude::*;
+//!
+//! #[drink::contract_bundle_provider]
+//! enum BundleProvider {}
+//!
+//! #[drink::test]
+//! fn testcase(mut session: Session) -> Result<(), Box<dyn std::error::Error>> {
+//!     session.deploy_bundle(BundleProvider::local()?, "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)?;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! The prelude brings in:
+//! - the session API: [`Session`], [`SessionBuilder`], [`ContractBundle`], [`SessionError`] and
+//!   the [`NO_ARGS`], [`NO_SALT`] and [`NO_ENDOWMENT`] constants,
+//! - the mocking API: the [`MockingApi`] trait, [`ContractMock`] and [`mock_message`],
+//! - the [`local_contract_file!`] and [`contract_bundle_provider`] macros,
+//! - the sandbox: [`MinimalSandbox`], the [`Sandbox`] trait, the sandbox API traits (balances,
+//!   contracts, system and timestamp) and [`decode_debug_buffer`],
+//! - common types: [`AccountId32`], [`Weight`], [`DrinkResult`], [`MessageResult`] and
+//!   [`TestVariant`].
+//!
+//! It deliberately leaves out items whose names are likely to collide with `ink::prelude::*` or
+//! the standard prelude, like `Error` or `Selector`. The `#[drink::test]` attribute is not
+//! included either, since it would shadow the built-in `#[test]`: always use it with the full
+//! path.
+
+#[cfg(feature = "macros")]
+#[cfg_attr(docsrs, doc(cfg(feature = "macros")))]
+pub use drink_test_macro::contract_bundle_provider;
+pub use ink_sandbox::{
+    api::{contracts_api::decode_debug_buffer, prelude::*},
+    AccountId32, Sandbox, Weight,
+};
+
+pub use crate::{errors::MessageResult, minimal::MinimalSandbox, DrinkResult, TestVariant};
+#[cfg(feature = "session")]
+#[cfg_attr(docsrs, doc(cfg(feature = "session")))]
+pub use crate::{
+    local_contract_file, mock_message,
+    session::{
+        error::SessionError, mocking_api::MockingApi, ContractBundle, Session, SessionBuilder,
+        NO_ARGS, NO_ENDOWMENT, NO_SALT,
+    },
+    ContractMock,
+};
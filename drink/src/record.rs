@@ -0,0 +1,1084 @@
+//! Recording of the results and events of contract interactions.
+
+#[cfg(feature = "session")]
+use std::sync::Arc;
+
+#[cfg(feature = "session")]
+use contract_transcode::{ContractMessageTranscoder, Value};
+use frame_support::{
+    sp_runtime::traits::UniqueSaturatedInto, traits::fungible::Inspect, weights::Weight,
+};
+use frame_system::Config as SysConfig;
+use ink_sandbox::{
+    pallet_contracts::{self, StorageDeposit},
+    AccountIdFor, EventRecordOf,
+};
+#[cfg(feature = "session")]
+use parity_scale_codec::Decode;
+use parity_scale_codec::Encode;
+#[cfg(feature = "session")]
+use serde_json::json;
+
+#[cfg(feature = "chaos")]
+use crate::session::chaos::{InjectedFailure, InjectedFailureKind};
+#[cfg(feature = "session")]
+use crate::{
+    errors::MessageResult,
+    session::{
+        error::SessionError,
+        replay::{InteractionKind, RecordedInteraction, RecordedScenario},
+    },
+};
+use crate::{
+    minimal::{MinimalSandboxRuntime, RuntimeEvent},
+    results::InteractionResult,
+    weights::HumanWeight,
+};
+
+pub(crate) type BalanceOf<R> =
+    <<R as pallet_contracts::Config>::Currency as Inspect<AccountIdFor<R>>>::Balance;
+type ContractInstantiateResult<R> =
+    pallet_contracts::ContractInstantiateResult<AccountIdFor<R>, BalanceOf<R>, EventRecordOf<R>>;
+type ContractExecResult<R> = pallet_contracts::ContractExecResult<BalanceOf<R>, EventRecordOf<R>>;
+
+/// Data structure storing the results of contract interaction during a session.
+///
+/// # Naming convention
+///
+/// By `result` we mean the full result (enriched with some context information) of the contract
+/// interaction, like `ContractExecResult`. By `return` we mean the return value of the contract
+/// execution, like a value returned from a message or the address of a newly instantiated contract.
+#[derive(frame_support::CloneNoBound, frame_support::DefaultNoBound)]
+pub struct Record<Config: pallet_contracts::Config> {
+    /// The results of contract instantiation.
+    deploy_results: Vec<ContractInstantiateResult<Config>>,
+    /// The actors that performed contract instantiations (aligned with `deploy_results`).
+    deployers: Vec<AccountIdFor<Config>>,
+    /// The return values of contract instantiation (i.e. the addresses of the newly instantiated
+    /// contracts).
+    deploy_returns: Vec<AccountIdFor<Config>>,
+    /// The code hashes of the newly instantiated contracts, right after the instantiation (aligned
+    /// with `deploy_returns`).
+    deploy_code_hashes: Vec<<Config as SysConfig>::Hash>,
+
+    /// The results of contract calls.
+    call_results: Vec<ContractExecResult<Config>>,
+    /// The actors that performed contract calls (aligned with `call_results`).
+    callers: Vec<AccountIdFor<Config>>,
+    /// The return values of contract calls (in the SCALE-encoded form).
+    call_returns: Vec<Vec<u8>>,
+
+    /// Whether every contract interaction was an instantiation (`true`) or a call, in the order
+    /// they happened.
+    deploy_order: Vec<bool>,
+
+    /// The storage deposits of all contract interactions (both instantiations and calls), in the
+    /// order they happened.
+    storage_deposits: Vec<StorageDeposit<BalanceOf<Config>>>,
+
+    /// All contract interactions (both instantiations and calls), with their input, in order.
+    #[cfg(feature = "session")]
+    interactions: Vec<RecordedInteraction<Config>>,
+    /// The indices of the interactions that were scheduled calls (see `Session::schedule_call`).
+    #[cfg(feature = "session")]
+    scheduled_interactions: Vec<usize>,
+
+    /// The blocks of all contract interactions (both instantiations and calls), in the order they
+    /// happened.
+    blocks: Vec<BlockInfo>,
+
+    /// The events emitted by the contracts.
+    event_batches: Vec<EventBatch<Config>>,
+
+    /// The events emitted by the block initialization and finalization hooks (see
+    /// `Recorder::build_block`).
+    block_hook_batches: Vec<EventBatch<Config>>,
+
+    /// The contracts re-entered within an interaction (see `Session::with_reentrancy_detection`).
+    reentrancies: Vec<Reentrancy<AccountIdFor<Config>>>,
+
+    /// The suspicious (but successful) interactions.
+    warnings: Vec<Warning>,
+
+    /// The failures injected into the contract calls (see `Session::inject_failure`).
+    #[cfg(feature = "chaos")]
+    injected_failures: Vec<InjectedFailure<AccountIdFor<Config>>>,
+}
+
+// API for `Recorder` and `Session` to record results and events related to contract interaction.
+impl<Config: pallet_contracts::Config> Record<Config> {
+    pub(crate) fn push_deploy_result(
+        &mut self,
+        result: ContractInstantiateResult<Config>,
+        deployer: AccountIdFor<Config>,
+    ) {
+        self.storage_deposits.push(result.storage_deposit.clone());
+        self.set_last_outcome(InteractionOutcome {
+            success: result.result.as_ref().is_ok_and(|r| !r.result.did_revert()),
+            weight: result.gas_consumed,
+            revert: result.result.as_ref().is_ok_and(|r| r.result.did_revert()),
+        });
+        self.deploy_results.push(result);
+        self.deployers.push(deployer);
+        self.deploy_order.push(true);
+    }
+
+    pub(crate) fn push_deploy_return(&mut self, return_value: AccountIdFor<Config>) {
+        self.deploy_returns.push(return_value);
+    }
+
+    pub(crate) fn push_deploy_code_hash(&mut self, code_hash: <Config as SysConfig>::Hash) {
+        self.deploy_code_hashes.push(code_hash);
+    }
+
+    pub(crate) fn push_call_result(
+        &mut self,
+        result: ContractExecResult<Config>,
+        caller: AccountIdFor<Config>,
+    ) {
+        self.storage_deposits.push(result.storage_deposit.clone());
+        self.set_last_outcome(InteractionOutcome {
+            success: result.result.as_ref().is_ok_and(|r| !r.did_revert()),
+            weight: result.gas_consumed,
+            revert: result.result.as_ref().is_ok_and(|r| r.did_revert()),
+        });
+        self.call_results.push(result);
+        self.callers.push(caller);
+        self.deploy_order.push(false);
+    }
+
+    pub(crate) fn push_call_return(&mut self, return_value: Vec<u8>) {
+        self.call_returns.push(return_value);
+    }
+
+    #[cfg(feature = "session")]
+    pub(crate) fn push_interaction(&mut self, interaction: RecordedInteraction<Config>) {
+        self.interactions.push(interaction);
+    }
+
+    /// Marks the interaction `idx` as a scheduled call.
+    #[cfg(feature = "session")]
+    pub(crate) fn push_scheduled(&mut self, idx: usize) {
+        self.scheduled_interactions.push(idx);
+    }
+
+    pub(crate) fn push_event_batches(
+        &mut self,
+        events: Vec<EventRecordOf<Config>>,
+        block: BlockInfo,
+    ) {
+        self.blocks.push(block);
+        self.event_batches.push(EventBatch {
+            events,
+            outcome: None,
+            block,
+            source: EventSource::Interaction,
+        });
+    }
+
+    /// Records the events emitted by the `phase` hooks of `block`, unless there are none.
+    pub(crate) fn push_block_hook_batch(
+        &mut self,
+        events: Vec<EventRecordOf<Config>>,
+        block: BlockInfo,
+        phase: HookPhase,
+    ) {
+        if events.is_empty() {
+            return;
+        }
+        self.block_hook_batches.push(EventBatch {
+            events,
+            outcome: None,
+            block,
+            source: EventSource::BlockHook {
+                block: block.number,
+                phase,
+            },
+        });
+    }
+
+    /// Records a re-entrancy detected during the interaction that has just finished.
+    #[cfg(feature = "session")]
+    pub(crate) fn push_reentrancy(&mut self, chain: Vec<AccountIdFor<Config>>) {
+        self.reentrancies.push(Reentrancy {
+            interaction: self.deploy_order.len().saturating_sub(1),
+            chain,
+        });
+    }
+
+    /// Records a failure injected during the interaction that has just finished.
+    #[cfg(feature = "chaos")]
+    pub(crate) fn push_injected_failure(
+        &mut self,
+        callee: AccountIdFor<Config>,
+        kind: InjectedFailureKind,
+    ) {
+        self.injected_failures.push(InjectedFailure {
+            interaction: self.deploy_order.len().saturating_sub(1),
+            callee,
+            kind,
+        });
+    }
+
+    /// Records a warning about the interaction that has just finished.
+    #[cfg(feature = "session")]
+    pub(crate) fn push_warning(&mut self, kind: WarningKind) {
+        self.warnings.push(Warning {
+            interaction: self.deploy_order.len().saturating_sub(1),
+            kind,
+        });
+    }
+
+    /// Attaches `outcome` to the batch of the interaction that has just finished. Events are always
+    /// recorded before the interaction result, so this is the last batch.
+    fn set_last_outcome(&mut self, outcome: InteractionOutcome) {
+        if let Some(batch) = self.event_batches.last_mut() {
+            batch.outcome = Some(outcome);
+        }
+    }
+}
+
+// API for the end user.
+impl<Config: pallet_contracts::Config> Record<Config> {
+    /// Returns all the results of contract instantiations that happened during the session.
+    pub fn deploy_results(&self) -> &[ContractInstantiateResult<Config>] {
+        &self.deploy_results
+    }
+
+    /// Returns the last result of contract instantiation that happened during the session. Panics
+    /// if there were no contract instantiations.
+    #[track_caller]
+    pub fn last_deploy_result(&self) -> &ContractInstantiateResult<Config> {
+        self.try_last_deploy_result()
+            .unwrap_or_else(|| panic!("{}", self.missing("deploy results")))
+    }
+
+    /// Like `last_deploy_result`, but returns `None` if there were no contract instantiations.
+    pub fn try_last_deploy_result(&self) -> Option<&ContractInstantiateResult<Config>> {
+        self.deploy_results.last()
+    }
+
+    /// Returns the actors that performed contract instantiations during the session. The `i`-th
+    /// element corresponds to the `i`-th element of `deploy_results`.
+    pub fn deployers(&self) -> &[AccountIdFor<Config>] {
+        &self.deployers
+    }
+
+    /// Returns all the return values of contract instantiations that happened during the session.
+    pub fn deploy_returns(&self) -> &[AccountIdFor<Config>] {
+        &self.deploy_returns
+    }
+
+    /// Returns the last return value of contract instantiation that happened during the session.
+    /// Panics if there were no successful contract instantiations.
+    #[track_caller]
+    pub fn last_deploy_return(&self) -> &AccountIdFor<Config> {
+        self.try_last_deploy_return()
+            .unwrap_or_else(|| panic!("{}", self.missing("deploy returns")))
+    }
+
+    /// Like `last_deploy_return`, but returns `None` if there were no successful contract
+    /// instantiations.
+    pub fn try_last_deploy_return(&self) -> Option<&AccountIdFor<Config>> {
+        self.deploy_returns.last()
+    }
+
+    /// Returns the code hashes that the newly instantiated contracts had right after the
+    /// instantiation. The `i`-th element corresponds to the `i`-th element of `deploy_returns`.
+    ///
+    /// Unlike `Session::code_hash_of`, these are not affected by later code upgrades.
+    pub fn deploy_code_hashes(&self) -> &[<Config as SysConfig>::Hash] {
+        &self.deploy_code_hashes
+    }
+
+    /// Returns the results of all the contract interactions (both instantiations and calls), in the
+    /// order they happened. Useful for checks that apply to all of them, e.g. that no interaction
+    /// consumed too much gas.
+    pub fn interaction_results(&self) -> Vec<&dyn InteractionResult<AccountIdFor<Config>>> {
+        let (mut deploys, mut calls) = (self.deploy_results.iter(), self.call_results.iter());
+        self.deploy_order
+            .iter()
+            .map(|&deploy| -> &dyn InteractionResult<AccountIdFor<Config>> {
+                match deploy {
+                    true => deploys
+                        .next()
+                        .expect("Every deployment should have its result"),
+                    false => calls.next().expect("Every call should have its result"),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns all the results of contract calls that happened during the session.
+    pub fn call_results(&self) -> &[ContractExecResult<Config>] {
+        &self.call_results
+    }
+
+    /// Returns the last result of contract call that happened during the session. Panics if there
+    /// were no contract calls.
+    #[track_caller]
+    pub fn last_call_result(&self) -> &ContractExecResult<Config> {
+        self.try_last_call_result()
+            .unwrap_or_else(|| panic!("{}", self.missing("call results")))
+    }
+
+    /// Like `last_call_result`, but returns `None` if there were no contract calls.
+    pub fn try_last_call_result(&self) -> Option<&ContractExecResult<Config>> {
+        self.call_results.last()
+    }
+
+    /// Returns the actors that performed contract calls during the session. The `i`-th element
+    /// corresponds to the `i`-th element of `call_results`.
+    pub fn callers(&self) -> &[AccountIdFor<Config>] {
+        &self.callers
+    }
+
+    /// Returns the results of all contract calls performed by `actor` during the session.
+    pub fn calls_by<'a>(
+        &'a self,
+        actor: &'a AccountIdFor<Config>,
+    ) -> impl Iterator<Item = &'a ContractExecResult<Config>> + 'a {
+        self.call_results
+            .iter()
+            .zip(&self.callers)
+            .filter(move |(_, caller)| *caller == actor)
+            .map(|(result, _)| result)
+    }
+
+    /// Returns all the (encoded) return values of contract calls that happened during the session.
+    pub fn call_returns(&self) -> &[Vec<u8>] {
+        &self.call_returns
+    }
+
+    /// Returns the last (encoded) return value of contract call that happened during the session.
+    /// Panics if there were no successful contract calls.
+    #[track_caller]
+    pub fn last_call_return(&self) -> &[u8] {
+        self.try_last_call_return()
+            .unwrap_or_else(|| panic!("{}", self.missing("call returns")))
+    }
+
+    /// Like `last_call_return`, but returns `None` if there were no successful contract calls.
+    pub fn try_last_call_return(&self) -> Option<&[u8]> {
+        self.call_returns.last().map(Vec::as_slice)
+    }
+
+    /// Returns the last (decoded) return value of contract call that happened during the session.
+    /// Panics if there were no contract calls.
+    #[cfg(feature = "session")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "session")))]
+    #[track_caller]
+    pub fn last_call_return_decoded<T: Decode>(&self) -> Result<MessageResult<T>, SessionError> {
+        let mut raw = self.last_call_return();
+        MessageResult::decode(&mut raw).map_err(|err| {
+            SessionError::Decoding(format!(
+                "Failed to decode the result of calling a contract: {err:?}"
+            ))
+        })
+    }
+
+    /// Returns the storage deposits of all contract interactions (both instantiations and calls)
+    /// that happened during the session, in order.
+    pub fn storage_deposits(&self) -> &[StorageDeposit<BalanceOf<Config>>] {
+        &self.storage_deposits
+    }
+
+    /// Returns the storage deposit of the last contract interaction (instantiation or call) as a
+    /// signed amount: positive for a charge, negative for a refund. Panics if there were no
+    /// contract interactions.
+    #[track_caller]
+    pub fn last_storage_deposit(&self) -> i128 {
+        self.try_last_storage_deposit()
+            .unwrap_or_else(|| panic!("{}", self.missing("storage deposits")))
+    }
+
+    /// Like `last_storage_deposit`, but returns `None` if there were no contract interactions.
+    pub fn try_last_storage_deposit(&self) -> Option<i128> {
+        self.storage_deposits.last().map(signed_storage_deposit)
+    }
+
+    /// Returns the storage deposit of the last contract call, normalized to `u128` (whatever the
+    /// balance type of the runtime is). Panics if there were no contract calls.
+    #[track_caller]
+    pub fn last_call_deposit(&self) -> DepositChange<u128> {
+        DepositChange::from_storage_deposit(&self.last_call_result().storage_deposit)
+    }
+
+    /// Like `last_call_deposit`, but returns `None` if there were no contract calls.
+    pub fn try_last_call_deposit(&self) -> Option<DepositChange<u128>> {
+        self.try_last_call_result()
+            .map(|result| DepositChange::from_storage_deposit(&result.storage_deposit))
+    }
+
+    /// Returns the storage deposit of the last contract instantiation, normalized to `u128`
+    /// (whatever the balance type of the runtime is). Panics if there were no contract
+    /// instantiations.
+    #[track_caller]
+    pub fn last_deploy_deposit(&self) -> DepositChange<u128> {
+        DepositChange::from_storage_deposit(&self.last_deploy_result().storage_deposit)
+    }
+
+    /// Like `last_deploy_deposit`, but returns `None` if there were no contract instantiations.
+    pub fn try_last_deploy_deposit(&self) -> Option<DepositChange<u128>> {
+        self.try_last_deploy_result()
+            .map(|result| DepositChange::from_storage_deposit(&result.storage_deposit))
+    }
+
+    /// Returns all the contract interactions (both instantiations and calls) that happened during
+    /// the session, together with their input, in order.
+    #[cfg(feature = "session")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "session")))]
+    pub fn interactions(&self) -> &[RecordedInteraction<Config>] {
+        &self.interactions
+    }
+
+    /// Returns the indices (in `interactions`) of the interactions that were scheduled calls (see
+    /// `Session::schedule_call`), in order.
+    #[cfg(feature = "session")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "session")))]
+    pub fn scheduled_interactions(&self) -> &[usize] {
+        &self.scheduled_interactions
+    }
+
+    /// Returns the storage deposit limits that the contract interactions (both instantiations and
+    /// calls) ran with, in order. `None` means that the deposit was not checked.
+    #[cfg(feature = "session")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "session")))]
+    pub fn deposit_limits(&self) -> Vec<Option<BalanceOf<Config>>> {
+        self.interactions
+            .iter()
+            .map(|interaction| interaction.storage_deposit_limit)
+            .collect()
+    }
+
+    /// Returns all the contract interactions of the session as a scenario that can be replayed
+    /// with `Session::replay`.
+    #[cfg(feature = "session")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "session")))]
+    pub fn scenario(&self) -> RecordedScenario<Config> {
+        RecordedScenario {
+            interactions: self.interactions.clone(),
+        }
+    }
+
+    /// Returns the blocks in which the contract interactions happened. The `i`-th element
+    /// corresponds to the `i`-th interaction (the same order as in `storage_deposits` and
+    /// `event_batches`).
+    pub fn blocks(&self) -> &[BlockInfo] {
+        &self.blocks
+    }
+
+    /// Returns all the event batches that were recorded for contract interactions during the
+    /// session.
+    pub fn event_batches(&self) -> &[EventBatch<Config>] {
+        &self.event_batches
+    }
+
+    /// Returns the batches of the events emitted by the block hooks (`on_initialize` and
+    /// `on_finalize` of the pallets), in the order they were emitted. Only non-empty batches of
+    /// the blocks built by the session (e.g. `Session::advance_blocks`) or with
+    /// `Recorder::build_block` are recorded.
+    ///
+    /// Like the interaction batches, they are copied out of the runtime, so they stay available
+    /// after the runtime resets its events.
+    pub fn block_hook_batches(&self) -> &[EventBatch<Config>] {
+        &self.block_hook_batches
+    }
+
+    /// Returns the event batches of the contract interactions that happened in block `number`.
+    ///
+    /// Batches are copied out of the runtime when they are emitted, so they stay available after
+    /// the runtime resets its events at the start of a new block.
+    pub fn event_batches_in_block(&self, number: u64) -> Vec<&EventBatch<Config>> {
+        self.event_batches
+            .iter()
+            .filter(|batch| batch.block_number() == number)
+            .collect()
+    }
+
+    /// Returns the last event batch that was recorded for contract interactions during the session.
+    /// Panics if there were no event batches.
+    #[track_caller]
+    pub fn last_event_batch(&self) -> &EventBatch<Config> {
+        self.try_last_event_batch()
+            .unwrap_or_else(|| panic!("{}", self.missing("event batches")))
+    }
+
+    /// Like `last_event_batch`, but returns `None` if there were no contract interactions.
+    pub fn try_last_event_batch(&self) -> Option<&EventBatch<Config>> {
+        self.event_batches.last()
+    }
+
+    /// Returns the contracts re-entered within an interaction, in the order they were detected.
+    /// Empty unless re-entrancy detection is enabled (see `Session::with_reentrancy_detection`).
+    pub fn reentrancies(&self) -> &[Reentrancy<AccountIdFor<Config>>] {
+        &self.reentrancies
+    }
+
+    /// Returns the failures injected into the contract calls (see `Session::inject_failure`), in
+    /// the order they happened.
+    #[cfg(feature = "chaos")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chaos")))]
+    pub fn injected_failures(&self) -> &[InjectedFailure<AccountIdFor<Config>>] {
+        &self.injected_failures
+    }
+
+    /// Returns the warnings about the interactions that succeeded, but look suspicious (see
+    /// `WarningKind`), in the order they happened.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Describes what the record holds, for the panic messages of the `last_*` accessors.
+    fn missing(&self, what: &str) -> String {
+        format!(
+            "No {what} in the record: it holds {} deploys ({} successful) and {} calls ({} \
+             successful). Make sure the interaction happens before its outcome is read (or use the \
+             `try_last_*` accessors)",
+            self.deploy_results.len(),
+            self.deploy_returns.len(),
+            self.call_results.len(),
+            self.call_returns.len(),
+        )
+    }
+}
+
+impl<Config: pallet_contracts::Config> Record<Config>
+where
+    Config::RuntimeEvent: TryInto<pallet_contracts::Event<Config>>,
+{
+    /// Returns the number and the size of the contract events emitted during every recorded
+    /// interaction, in the order of `event_batches`.
+    pub fn event_stats(&self) -> Vec<EventStats> {
+        self.event_batches
+            .iter()
+            .map(EventBatch::event_stats)
+            .collect()
+    }
+}
+
+/// A summary of the record: every contract interaction with its actor, block and outcome (the full
+/// results are too verbose to be read).
+impl<Config: pallet_contracts::Config> std::fmt::Debug for Record<Config> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Record of {} deploys and {} calls:",
+            self.deploy_results.len(),
+            self.call_results.len()
+        )?;
+
+        let (mut deploys, mut calls) = (self.deployers.iter(), self.callers.iter());
+        for (idx, &is_deploy) in self.deploy_order.iter().enumerate() {
+            let (kind, actor) = match is_deploy {
+                true => ("deploy", deploys.next()),
+                false => ("call", calls.next()),
+            };
+            let outcome = match self.event_batches.get(idx).and_then(|batch| batch.outcome) {
+                Some(outcome) if outcome.success => "succeeded",
+                Some(outcome) if outcome.revert => "reverted",
+                Some(_) => "failed",
+                None => "pending",
+            };
+            let actor = actor.map_or_else(|| "unknown".to_string(), |actor| format!("{actor:?}"));
+            let block = self.blocks.get(idx).copied().unwrap_or_default();
+            writeln!(
+                f,
+                "  #{idx} {kind} by {actor} in block {} (timestamp {}): {outcome}",
+                block.number, block.timestamp,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "session")]
+impl<Config: pallet_contracts::Config> Record<Config>
+where
+    AccountIdFor<Config>: std::fmt::Display,
+    Config::RuntimeEvent: TryInto<pallet_contracts::Event<Config>>,
+{
+    /// Returns the record as JSON, e.g. to review the trace of a test outside of it: an object with
+    /// the `interactions` array, in order.
+    ///
+    /// Every interaction has its `kind` (`deploy` or `call`), `actor`, `storage_deposit` (signed,
+    /// as a decimal string) and `block` (`number` and `timestamp`), and, once its result is known,
+    /// `success`, `reverted`, `gas_consumed` and the `emitted_event_count` and
+    /// `emitted_event_bytes` (see `event_stats`). Interactions performed by a `Session` also have
+    /// the `constructor` or the `message`, the `args` and the contract `address` (`null` for a
+    /// failed deployment).
+    #[cfg_attr(docsrs, doc(cfg(feature = "session")))]
+    pub fn to_json(&self) -> serde_json::Value {
+        let (mut deploys, mut calls) = (self.deployers.iter(), self.callers.iter());
+        let interactions = self
+            .deploy_order
+            .iter()
+            .enumerate()
+            .map(|(idx, &is_deploy)| {
+                let (kind, actor) = match is_deploy {
+                    true => ("deploy", deploys.next()),
+                    false => ("call", calls.next()),
+                };
+                let mut entry = json!({
+                    "kind": kind,
+                    "actor": actor.map(ToString::to_string),
+                    "storage_deposit": self
+                        .storage_deposits
+                        .get(idx)
+                        .map(|deposit| signed_storage_deposit(deposit).to_string()),
+                    "block": self.blocks.get(idx).map(|block| json!({
+                        "number": block.number,
+                        "timestamp": block.timestamp,
+                    })),
+                });
+                let batch = self.event_batches.get(idx);
+                if let Some(outcome) = batch.and_then(|batch| batch.outcome) {
+                    entry["success"] = outcome.success.into();
+                    entry["reverted"] = outcome.revert.into();
+                    entry["gas_consumed"] = json!({
+                        "ref_time": outcome.weight.ref_time(),
+                        "proof_size": outcome.weight.proof_size(),
+                    });
+                    let stats = batch.map(EventBatch::event_stats).unwrap_or_default();
+                    entry["emitted_event_count"] = stats.emitted_event_count.into();
+                    entry["emitted_event_bytes"] = stats.emitted_event_bytes.into();
+                }
+                if let Some(interaction) = self.interactions.get(idx) {
+                    add_interaction_details(&mut entry, &interaction.kind);
+                }
+                entry
+            })
+            .collect::<Vec<_>>();
+
+        json!({ "interactions": interactions })
+    }
+}
+
+/// Adds what the interaction was (as recorded by the session) to its JSON `entry`.
+#[cfg(feature = "session")]
+fn add_interaction_details<R: pallet_contracts::Config>(
+    entry: &mut serde_json::Value,
+    kind: &InteractionKind<R>,
+) where
+    AccountIdFor<R>: std::fmt::Display,
+{
+    match kind {
+        InteractionKind::Deploy {
+            constructor,
+            args,
+            deployed,
+            ..
+        } => {
+            entry["constructor"] = constructor.as_str().into();
+            entry["args"] = args.clone().into();
+            entry["address"] = deployed.as_ref().map(ToString::to_string).into();
+        }
+        InteractionKind::Call {
+            address,
+            message,
+            args,
+        } => {
+            entry["message"] = message.as_str().into();
+            entry["args"] = args.clone().into();
+            entry["address"] = address.to_string().into();
+        }
+    }
+}
+
+/// Normalizes `deposit` to a signed amount: positive for a charge, negative for a refund.
+pub(crate) fn signed_storage_deposit<Balance: UniqueSaturatedInto<u128> + Clone>(
+    deposit: &StorageDeposit<Balance>,
+) -> i128 {
+    DepositChange::from_storage_deposit(deposit).signed()
+}
+
+/// A storage deposit with the amount in a runtime-independent type (see
+/// `Record::last_call_deposit`), so that helpers can be written once for all sandboxes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DepositChange<Balance> {
+    /// The amount charged from the origin.
+    Charge(Balance),
+    /// The amount refunded to the origin.
+    Refund(Balance),
+}
+
+impl DepositChange<u128> {
+    /// Converts `deposit` of any runtime (saturating the amount, if it doesn't fit into `u128`).
+    pub fn from_storage_deposit<Balance: UniqueSaturatedInto<u128> + Clone>(
+        deposit: &StorageDeposit<Balance>,
+    ) -> Self {
+        match deposit {
+            StorageDeposit::Charge(amount) => Self::Charge(amount.clone().unique_saturated_into()),
+            StorageDeposit::Refund(amount) => Self::Refund(amount.clone().unique_saturated_into()),
+        }
+    }
+
+    /// Returns the deposit as a signed amount: positive for a charge, negative for a refund.
+    pub fn signed(&self) -> i128 {
+        match *self {
+            Self::Charge(amount) => i128::try_from(amount).unwrap_or(i128::MAX),
+            Self::Refund(amount) => -i128::try_from(amount).unwrap_or(i128::MAX),
+        }
+    }
+}
+
+/// Everything about a successful contract deployment (see `Session::deploy_full`).
+pub struct DeployOutcome<R: pallet_contracts::Config> {
+    /// The address of the deployed contract.
+    pub address: AccountIdFor<R>,
+    /// The actor that deployed the contract.
+    pub deployer: AccountIdFor<R>,
+    /// The hash of the contract code.
+    pub code_hash: <R as SysConfig>::Hash,
+    /// The gas consumed by the deployment.
+    pub gas_consumed: Weight,
+    /// The events emitted during the deployment (only).
+    pub events: EventBatch<R>,
+}
+
+/// Outcome of the contract interaction during which an `EventBatch` was emitted.
+///
+/// Contract interactions in drink are not real extrinsics, so there are no
+/// `ExtrinsicSuccess`/`ExtrinsicFailed` system events. When porting assertions from node-based
+/// tests, use the outcome instead:
+///  - `ExtrinsicSuccess` corresponds to `success`,
+///  - `ExtrinsicFailed` corresponds to `!success`. On a node, a reverted contract fails the
+///    extrinsic with `ContractReverted`, hence `revert` implies `!success`. Otherwise, the dispatch
+///    error can be found in the interaction result,
+///  - the weight in the dispatch info corresponds to `weight`, i.e. the gas consumed by the
+///    interaction (without the extrinsic base weight).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct InteractionOutcome {
+    /// Whether the interaction succeeded (was neither reverted nor failed).
+    pub success: bool,
+    /// The gas consumed by the interaction.
+    pub weight: Weight,
+    /// Whether the contract reverted.
+    pub revert: bool,
+}
+
+/// The block in which a contract interaction happened.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct BlockInfo {
+    /// The block number.
+    pub number: u64,
+    /// The timestamp of the block (as set in the runtime, e.g. in milliseconds).
+    pub timestamp: u64,
+}
+
+/// The size of the contract events emitted during a single contract interaction, by all the
+/// contracts involved (including the nested calls).
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct EventStats {
+    /// The number of contract events.
+    pub emitted_event_count: usize,
+    /// The total size of the contract events: their data and topics.
+    pub emitted_event_bytes: usize,
+}
+
+/// A contract re-entered within a single contract interaction.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Reentrancy<AccountId> {
+    /// The index of the interaction, among all the contract interactions (as in
+    /// `Record::interaction_results`).
+    pub interaction: usize,
+    /// The active call chain, from the outermost callee to the re-entered contract (which
+    /// therefore appears in it twice).
+    pub chain: Vec<AccountId>,
+}
+
+/// A contract interaction that looks suspicious.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Warning {
+    /// The index of the interaction, among all the contract interactions (as in
+    /// `Record::interaction_results`).
+    pub interaction: usize,
+    /// What is suspicious about the interaction.
+    pub kind: WarningKind,
+}
+
+/// The kinds of `Warning`.
+///
+/// Gas of a successful interaction is compared with its gas limit component-wise: it is "close to
+/// the limit" if either its ref time or proof size is within 5% of the limit.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WarningKind {
+    /// The interaction consumed almost all of its gas limit, so a slightly more expensive run
+    /// (e.g. with more data in storage) would fail.
+    GasConsumedNearLimit {
+        /// The gas consumed by the interaction.
+        consumed: Weight,
+        /// The gas limit of the interaction.
+        limit: Weight,
+    },
+    /// The interaction consumed much less than its gas limit, but it required almost all of it:
+    /// a limit based on the consumed gas would make it fail. Use `gas_required` for setting
+    /// limits.
+    GasRequiredNearLimit {
+        /// The gas required by the interaction.
+        required: Weight,
+        /// The gas limit of the interaction.
+        limit: Weight,
+    },
+    /// The interactions in the current block have consumed more weight than the block budget
+    /// (see `Session::with_block_budget`).
+    BlockBudgetExceeded {
+        /// The weight consumed in the current block, including the interaction.
+        used: Weight,
+        /// The block budget.
+        budget: Weight,
+    },
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "interaction #{}: ", self.interaction)?;
+        match self.kind {
+            WarningKind::GasConsumedNearLimit { consumed, limit } => {
+                write!(
+                    f,
+                    "consumed {}, close to the gas limit {}",
+                    HumanWeight(consumed),
+                    HumanWeight(limit)
+                )
+            }
+            WarningKind::GasRequiredNearLimit { required, limit } => {
+                write!(
+                    f,
+                    "required {}, close to the gas limit {}",
+                    HumanWeight(required),
+                    HumanWeight(limit)
+                )
+            }
+            WarningKind::BlockBudgetExceeded { used, budget } => {
+                write!(
+                    f,
+                    "used {} in the block, over the block budget {}",
+                    HumanWeight(used),
+                    HumanWeight(budget)
+                )
+            }
+        }
+    }
+}
+
+/// A batch of runtime events that were emitted during a single contract interaction.
+#[derive(frame_support::CloneNoBound)]
+pub struct EventBatch<R: SysConfig> {
+    events: Vec<EventRecordOf<R>>,
+    outcome: Option<InteractionOutcome>,
+    block: BlockInfo,
+    source: EventSource,
+}
+
+/// What emitted the events of an `EventBatch`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EventSource {
+    /// A contract interaction.
+    Interaction,
+    /// The hooks run when a block is built.
+    BlockHook {
+        /// The number of the initialized or finalized block.
+        block: u64,
+        /// Which hooks emitted the events.
+        phase: HookPhase,
+    },
+}
+
+/// The block hooks that emitted the events of an `EventBatch`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HookPhase {
+    /// `on_initialize`, run when the block is started.
+    Initialize,
+    /// `on_finalize`, run when the block is finished.
+    Finalize,
+}
+
+impl<R: SysConfig> EventBatch<R> {
+    /// Returns all the events that were emitted during the contract interaction.
+    pub fn all_events(&self) -> &[EventRecordOf<R>] {
+        &self.events
+    }
+
+    /// Returns what emitted the events of the batch.
+    pub fn source(&self) -> EventSource {
+        self.source
+    }
+
+    /// Returns the outcome of the contract interaction. See `InteractionOutcome` for how it maps to
+    /// the extrinsic events of a node. `None` for the batches of block hooks, which have no
+    /// outcome.
+    pub fn outcome(&self) -> Option<InteractionOutcome> {
+        self.outcome
+    }
+
+    /// Returns the number of the block in which the contract interaction happened.
+    pub fn block_number(&self) -> u64 {
+        self.block.number
+    }
+
+    /// Returns the timestamp of the block in which the contract interaction happened.
+    pub fn timestamp(&self) -> u64 {
+        self.block.timestamp
+    }
+
+    /// Returns the SCALE encoding of every event record of the batch (`frame_system::EventRecord`).
+    ///
+    /// This lets you decode the events with types generated elsewhere, e.g. by `subxt` (together
+    /// with `Session::runtime_metadata`). Note that `subxt::events::Events::decode_from` expects
+    /// the encoding of the whole list, like in the `System::Events` storage - that is
+    /// `batch.all_events().encode()`.
+    pub fn encoded_events(&self) -> Vec<Vec<u8>> {
+        self.events.iter().map(Encode::encode).collect()
+    }
+
+    /// Decodes every event record of the batch with `decoder`, which gets the encoded record (see
+    /// `encoded_events`). Stops at the first error.
+    pub fn decode_with<F, E, Err>(&self, mut decoder: F) -> Result<Vec<E>, Err>
+    where
+        F: FnMut(&[u8]) -> Result<E, Err>,
+    {
+        self.encoded_events()
+            .iter()
+            .map(|encoded| decoder(encoded))
+            .collect()
+    }
+}
+
+impl<R: SysConfig> EventBatch<R>
+where
+    R::RuntimeEvent: TryInto<frame_system::Event<R>>,
+{
+    /// Returns the accounts that were created during the contract interaction (e.g. an endowed
+    /// contract or a first-time transfer recipient).
+    pub fn new_accounts(&self) -> Vec<AccountIdFor<R>> {
+        self.system_events()
+            .filter_map(|event| match event {
+                frame_system::Event::NewAccount { account } => Some(account),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the accounts that were reaped during the contract interaction, i.e. whose balance
+    /// dropped below the existential deposit (or that were terminated).
+    pub fn reaped_accounts(&self) -> Vec<AccountIdFor<R>> {
+        self.system_events()
+            .filter_map(|event| match event {
+                frame_system::Event::KilledAccount { account } => Some(account),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn system_events(&self) -> impl Iterator<Item = frame_system::Event<R>> + '_ {
+        self.events
+            .iter()
+            .filter_map(|record| record.event.clone().try_into().ok())
+    }
+}
+
+impl<R: pallet_contracts::Config> EventBatch<R>
+where
+    R::RuntimeEvent: TryInto<pallet_contracts::Event<R>>,
+{
+    /// Returns the number and the size of the contract events emitted during the interaction.
+    pub fn event_stats(&self) -> EventStats {
+        let mut stats = EventStats::default();
+        for record in &self.events {
+            if let Ok(pallet_contracts::Event::<R>::ContractEmitted { data, .. }) =
+                record.event.clone().try_into()
+            {
+                stats.emitted_event_count += 1;
+                stats.emitted_event_bytes += data.len()
+                    + record
+                        .topics
+                        .iter()
+                        .map(|topic| topic.as_ref().len())
+                        .sum::<usize>();
+            }
+        }
+        stats
+    }
+}
+
+impl EventBatch<MinimalSandboxRuntime> {
+    /// Returns all the contract events that were emitted during the contract interaction.
+    ///
+    /// **WARNING**: This method will return all the events that were emitted by ANY contract. If your
+    /// call triggered multiple contracts, you will have to filter the events yourself.
+    ///
+    /// We have to match against static enum variant, and thus (at least for now) we support only
+    /// `MinimalSandbox`.
+    pub fn contract_events(&self) -> Vec<&[u8]> {
+        self.events
+            .iter()
+            .filter_map(|event| match &event.event {
+                RuntimeEvent::Contracts(
+                    pallet_contracts::Event::<MinimalSandboxRuntime>::ContractEmitted {
+                        data, ..
+                    },
+                ) => Some(data.as_slice()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the contract events that were emitted by the contract at `address` during the
+    /// contract interaction.
+    pub fn contract_events_by(&self, address: &AccountIdFor<MinimalSandboxRuntime>) -> Vec<&[u8]> {
+        self.events
+            .iter()
+            .filter_map(|event| match &event.event {
+                RuntimeEvent::Contracts(
+                    pallet_contracts::Event::<MinimalSandboxRuntime>::ContractEmitted {
+                        contract,
+                        data,
+                    },
+                ) if contract == address => Some(data.as_slice()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The same as `contract_events`, but decodes the events using the given transcoder.
+    ///
+    /// **WARNING**: This method will try to decode all the events that were emitted by ANY
+    /// contract. This means that some contract events might either fail to decode or be decoded
+    /// incorrectly (to some rubbish). In the former case, they will be skipped, but with the latter
+    /// case, you will have to filter the events yourself.
+    ///
+    /// **WARNING 2**: This method will ignore anonymous events.
+    #[cfg(feature = "session")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "session")))]
+    pub fn contract_events_decoded(
+        &self,
+        transcoder: &Arc<ContractMessageTranscoder>,
+    ) -> Vec<Value> {
+        let signature_topics = transcoder
+            .metadata()
+            .spec()
+            .events()
+            .iter()
+            .filter_map(|event| event.signature_topic())
+            .map(|sig| sig.as_bytes().try_into().unwrap())
+            .collect::<Vec<[u8; 32]>>();
+
+        self.contract_events()
+            .into_iter()
+            .filter_map(|data| {
+                for signature_topic in &signature_topics {
+                    if let Ok(decoded) = transcoder
+                        // We have to `encode` the data because `decode_contract_event` is targeted
+                        // at decoding the data from the runtime, and not directly from the contract
+                        // events.
+                        .decode_contract_event(&signature_topic, &mut &*data.encode())
+                    {
+                        return Some(decoded);
+                    }
+                }
+                None
+            })
+            .collect()
+    }
+}
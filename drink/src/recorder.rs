@@ -0,0 +1,206 @@
+//! Recording contract interactions performed directly on a `Sandbox` (without a `Session`).
+//!
+//! ```rust, ignore
+//! let mut recorder = Recorder::new(MinimalSandbox::default());
+//! let result = recorder.record(|sandbox| {
+//!     sandbox.call_contract(address, 0, data, origin, gas_limit, None, Determinism::Enforced)
+//! });
+//! assert!(recorder.last_call_result().result.is_ok());
+//! let events = recorder.last_event_batch().all_events();
+//! ```
+
+use std::ops::Deref;
+
+use frame_support::{
+    sp_runtime::traits::{One, UniqueSaturatedInto},
+    traits::Time,
+};
+use frame_system::pallet_prelude::BlockNumberFor;
+use ink_sandbox::{
+    api::prelude::*, pallet_contracts, AccountIdFor, ContractExecResultFor,
+    ContractInstantiateResultFor, Sandbox,
+};
+
+use crate::{
+    environment::internal_error,
+    record::{BlockInfo, HookPhase, Record},
+};
+
+/// Result of a contract interaction that can be stored in a `Record`.
+pub trait Recordable<R: pallet_contracts::Config> {
+    /// Stores the result (and the return value, if the interaction succeeded) in `record`, as
+    /// performed by `actor`.
+    fn record_into(&self, record: &mut Record<R>, actor: AccountIdFor<R>);
+}
+
+impl<R: pallet_contracts::Config> Recordable<R> for ContractInstantiateResultFor<R> {
+    fn record_into(&self, record: &mut Record<R>, actor: AccountIdFor<R>) {
+        if let Ok(exec_result) = &self.result {
+            if !exec_result.result.did_revert() {
+                record.push_deploy_return(exec_result.account_id.clone());
+            }
+        }
+        record.push_deploy_result(self.clone(), actor);
+    }
+}
+
+impl<R: pallet_contracts::Config> Recordable<R> for ContractExecResultFor<R> {
+    fn record_into(&self, record: &mut Record<R>, actor: AccountIdFor<R>) {
+        if let Ok(exec_result) = &self.result {
+            if !exec_result.did_revert() {
+                record.push_call_return(exec_result.data.clone());
+            }
+        }
+        record.push_call_result(self.clone(), actor);
+    }
+}
+
+/// A `Sandbox` together with the `Record` of the contract interactions performed on it.
+///
+/// Every interaction executed with `record` is stored, along with the events it emitted. All the
+/// `Record` accessors are available directly on the recorder.
+pub struct Recorder<T: Sandbox>
+where
+    T::Runtime: pallet_contracts::Config,
+{
+    pub(crate) sandbox: T,
+    pub(crate) record: Record<T::Runtime>,
+    actor: AccountIdFor<T::Runtime>,
+}
+
+impl<T: Sandbox> Recorder<T>
+where
+    T::Runtime: pallet_contracts::Config,
+{
+    /// Creates a recorder with an empty record. Interactions are attributed to the default actor
+    /// of the sandbox.
+    pub fn new(sandbox: T) -> Self {
+        Self {
+            sandbox,
+            record: Default::default(),
+            actor: T::default_actor(),
+        }
+    }
+
+    /// Sets the actor that the recorded interactions are attributed to and returns the old one.
+    ///
+    /// Notice that it doesn't affect the interactions themselves: the origin is whatever you pass
+    /// to the sandbox.
+    pub fn set_actor(&mut self, actor: AccountIdFor<T::Runtime>) -> AccountIdFor<T::Runtime> {
+        std::mem::replace(&mut self.actor, actor)
+    }
+
+    /// The underlying `Sandbox` instance.
+    pub fn sandbox(&mut self) -> &mut T {
+        &mut self.sandbox
+    }
+
+    /// Returns the sandbox and the record, consuming the recorder.
+    pub fn into_parts(self) -> (T, Record<T::Runtime>) {
+        (self.sandbox, self.record)
+    }
+
+    /// Executes `interaction` (a contract deployment or call) and records its result, together
+    /// with the events emitted in the meantime.
+    pub fn record<V: Recordable<T::Runtime>>(
+        &mut self,
+        interaction: impl FnOnce(&mut T) -> V,
+    ) -> V {
+        self.record_as(self.actor.clone(), interaction)
+    }
+
+    /// Like `record`, but attributes the interaction to `actor`.
+    pub fn record_as<V: Recordable<T::Runtime>>(
+        &mut self,
+        actor: AccountIdFor<T::Runtime>,
+        interaction: impl FnOnce(&mut T) -> V,
+    ) -> V {
+        let block = self.block_info();
+        let start = self.sandbox.events().len();
+        let result = interaction(&mut self.sandbox);
+        let events = self.sandbox.events()[start..].to_vec();
+
+        // Events go first, so that the result can attach the outcome to their batch.
+        self.record.push_event_batches(events, block);
+        result.record_into(&mut self.record, actor);
+
+        // Remember which code the newly instantiated contracts run.
+        let new_contracts =
+            self.record.deploy_returns()[self.record.deploy_code_hashes().len()..].to_vec();
+        for address in new_contracts {
+            let code_hash = self
+                .sandbox
+                .execute_with(|| pallet_contracts::Pallet::<T::Runtime>::code_hash(&address))
+                .unwrap_or_else(|| {
+                    internal_error("Freshly instantiated contract should have code")
+                });
+            self.record.push_deploy_code_hash(code_hash);
+        }
+
+        result
+    }
+
+    /// Builds a new block (like `BlockAPI::build_block`) and records the events emitted by the
+    /// `on_finalize` hooks of the current block and the `on_initialize` hooks of the new one (see
+    /// `Record::block_hook_batches`). Returns the number of the new block.
+    pub fn build_block(&mut self) -> BlockNumberFor<T::Runtime> {
+        let current = self.sandbox.block_number();
+        let finalized = self.block_info();
+        let start = self.sandbox.events().len();
+        let parent_hash = self.sandbox.execute_with(|| T::finalize_block(current));
+        let finalize_events = self.sandbox.events()[start..].to_vec();
+        self.record
+            .push_block_hook_batch(finalize_events, finalized, HookPhase::Finalize);
+
+        // Initializing a block resets the events, so all of them come from the hooks.
+        let next = current + One::one();
+        self.sandbox
+            .execute_with(|| T::initialize_block(next, parent_hash));
+        let initialize_events = self.sandbox.events();
+        let initialized = self.block_info();
+        self.record
+            .push_block_hook_batch(initialize_events, initialized, HookPhase::Initialize);
+        next
+    }
+
+    fn block_info(&mut self) -> BlockInfo {
+        self.sandbox.execute_with(|| BlockInfo {
+            number: frame_system::Pallet::<T::Runtime>::block_number().unique_saturated_into(),
+            timestamp: <T::Runtime as pallet_contracts::Config>::Time::now()
+                .unique_saturated_into(),
+        })
+    }
+
+    /// Executes `action` (e.g. a contract call) without recording it, reverting all its changes
+    /// afterwards (see `Sandbox::dry_run`).
+    ///
+    /// Dry runs never contribute to the record: the events they emit are reverted together with
+    /// the rest of the state, so they cannot get into the batch of any interaction recorded later.
+    /// In debug builds, this is checked after every dry run.
+    pub fn dry_run<V>(&mut self, action: impl FnOnce(&mut T) -> V) -> V {
+        let events_before = cfg!(debug_assertions).then(|| self.sandbox.events().len());
+        let result = self.sandbox.dry_run(action);
+        if let Some(events_before) = events_before {
+            let events_after = self.sandbox.events().len();
+            assert_eq!(
+                events_before,
+                events_after,
+                "A dry run has left {} events behind, which would be recorded as emitted by the \
+                 next interaction",
+                events_after.saturating_sub(events_before)
+            );
+        }
+        result
+    }
+}
+
+impl<T: Sandbox> Deref for Recorder<T>
+where
+    T::Runtime: pallet_contracts::Config,
+{
+    type Target = Record<T::Runtime>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.record
+    }
+}
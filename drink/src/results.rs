@@ -0,0 +1,109 @@
+//! A common interface of the results of contract instantiations and calls, for writing helpers that
+//! work with both.
+//!
+//! ```rust, ignore
+//! for result in session.record().interaction_results() {
+//!     assert!(result.gas_consumed().all_lte(max_gas));
+//! }
+//! ```
+
+use frame_support::{sp_runtime::traits::UniqueSaturatedInto, weights::Weight};
+use ink_sandbox::{
+    pallet_contracts::{ContractResult, ExecReturnValue, InstantiateReturnValue},
+    DispatchError,
+};
+
+use crate::record::DepositChange;
+
+/// What a contract interaction has produced.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum InteractionOutput<'a, AccountId> {
+    /// A contract has been instantiated at the address.
+    Deployed(&'a AccountId),
+    /// The contract has returned the (encoded) data. See `InteractionResult::did_revert` for
+    /// whether it reverted.
+    Returned(&'a [u8]),
+    /// The interaction has been aborted by the pallet.
+    Failed(DispatchError),
+}
+
+/// The result of a contract interaction, either an instantiation or a call.
+pub trait InteractionResult<AccountId> {
+    /// The gas consumed by the interaction.
+    fn gas_consumed(&self) -> Weight;
+    /// The gas required by the interaction (might be more than consumed, see `ContractResult`).
+    fn gas_required(&self) -> Weight;
+    /// The storage deposit charged (or refunded) by the interaction.
+    fn storage_deposit(&self) -> DepositChange<u128>;
+    /// Whether the contract reverted. `false` if the interaction has been aborted by the pallet.
+    fn did_revert(&self) -> bool;
+    /// What the interaction has produced. A reverted instantiation gives the returned data.
+    fn output(&self) -> InteractionOutput<'_, AccountId>;
+}
+
+impl<AccountId, Balance, EventRecord> InteractionResult<AccountId>
+    for ContractResult<
+        Result<InstantiateReturnValue<AccountId>, DispatchError>,
+        Balance,
+        EventRecord,
+    >
+where
+    Balance: UniqueSaturatedInto<u128> + Clone,
+{
+    fn gas_consumed(&self) -> Weight {
+        self.gas_consumed
+    }
+
+    fn gas_required(&self) -> Weight {
+        self.gas_required
+    }
+
+    fn storage_deposit(&self) -> DepositChange<u128> {
+        DepositChange::from_storage_deposit(&self.storage_deposit)
+    }
+
+    fn did_revert(&self) -> bool {
+        self.result
+            .as_ref()
+            .is_ok_and(|result| result.result.did_revert())
+    }
+
+    fn output(&self) -> InteractionOutput<'_, AccountId> {
+        match &self.result {
+            Ok(result) if result.result.did_revert() => {
+                InteractionOutput::Returned(&result.result.data)
+            }
+            Ok(result) => InteractionOutput::Deployed(&result.account_id),
+            Err(err) => InteractionOutput::Failed(*err),
+        }
+    }
+}
+
+impl<AccountId, Balance, EventRecord> InteractionResult<AccountId>
+    for ContractResult<Result<ExecReturnValue, DispatchError>, Balance, EventRecord>
+where
+    Balance: UniqueSaturatedInto<u128> + Clone,
+{
+    fn gas_consumed(&self) -> Weight {
+        self.gas_consumed
+    }
+
+    fn gas_required(&self) -> Weight {
+        self.gas_required
+    }
+
+    fn storage_deposit(&self) -> DepositChange<u128> {
+        DepositChange::from_storage_deposit(&self.storage_deposit)
+    }
+
+    fn did_revert(&self) -> bool {
+        self.result.as_ref().is_ok_and(ExecReturnValue::did_revert)
+    }
+
+    fn output(&self) -> InteractionOutput<'_, AccountId> {
+        match &self.result {
+            Ok(result) => InteractionOutput::Returned(&result.data),
+            Err(err) => InteractionOutput::Failed(*err),
+        }
+    }
+}
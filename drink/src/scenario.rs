@@ -0,0 +1,251 @@
+//! Load-style tests: executing a weighted mix of contract calls from a pool of actors.
+//!
+//! ```rust, ignore
+//! let stats = LoadRunner::new(token_address)
+//!     .message("PSP22::transfer", 7, move |rng| vec![rng.pick(&users).to_string(), "1".into(), "[]".into()])
+//!     .message("PSP22::approve", 2, move |rng| vec![rng.pick(&spenders).to_string(), "5".into()])
+//!     .actors(users.clone())
+//!     .calls(1_000)
+//!     .advance_block_every(10)
+//!     .invariant(|session| check_total_supply(session))
+//!     .run(&mut session);
+//! ```
+
+use std::collections::BTreeMap;
+
+use frame_support::weights::Weight;
+use ink_sandbox::{api::prelude::*, AccountIdFor, Sandbox};
+
+use crate::{
+    pallet_contracts::Config,
+    session::{error::SessionError, Session},
+};
+
+/// Generator of message arguments.
+pub type ArgsGenerator = Box<dyn FnMut(&mut ScenarioRng) -> Vec<String>>;
+
+/// Check run after every call. Returns a description of the violation, if any.
+pub type Invariant<T> = Box<dyn FnMut(&mut Session<T>) -> Result<(), String>>;
+
+/// Small, deterministic pseudo-random number generator (SplitMix64), so that every scenario can be
+/// reproduced from its seed.
+pub struct ScenarioRng(u64);
+
+impl ScenarioRng {
+    /// Creates a generator from `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Returns the next pseudo-random number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a pseudo-random number from `0..bound`. Panics if `bound` is zero.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "Bound must be positive");
+        self.next_u64() % bound
+    }
+
+    /// Returns a pseudo-random element of `items`. Panics if `items` is empty.
+    pub fn pick<'a, I>(&mut self, items: &'a [I]) -> &'a I {
+        &items[self.below(items.len() as u64) as usize]
+    }
+}
+
+/// A message that the runner calls, with its relative weight in the mix.
+struct MessageTemplate {
+    label: String,
+    weight: u32,
+    args: ArgsGenerator,
+}
+
+/// Aggregate statistics of a `LoadRunner` run.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct LoadStats {
+    /// Number of executed calls, per message label.
+    pub calls: BTreeMap<String, usize>,
+    /// Number of failed calls, per failure kind (e.g. `reverted` or `trapped`).
+    pub failures: BTreeMap<String, usize>,
+    /// Total gas consumed by all the calls.
+    pub total_gas: Weight,
+    /// The biggest storage deposit charged by a single call.
+    pub max_storage_deposit: i128,
+}
+
+/// Executes a weighted mix of calls to a single contract, from a pool of actors.
+///
+/// Every call picks a message template (with probability proportional to its weight), generates
+/// its arguments and picks an actor from the pool. After every call, all the invariants are
+/// checked - a violation panics with the seed that reproduces the run.
+pub struct LoadRunner<T: Sandbox>
+where
+    T::Runtime: Config,
+{
+    contract: AccountIdFor<T::Runtime>,
+    messages: Vec<MessageTemplate>,
+    actors: Vec<AccountIdFor<T::Runtime>>,
+    calls: usize,
+    block_every: Option<usize>,
+    seed: u64,
+    invariants: Vec<Invariant<T>>,
+}
+
+impl<T: Sandbox> LoadRunner<T>
+where
+    T::Runtime: Config,
+{
+    /// Creates a runner calling `contract`. By default, it executes 100 calls from the session
+    /// actor, within a single block, with seed `0`.
+    pub fn new(contract: AccountIdFor<T::Runtime>) -> Self {
+        Self {
+            contract,
+            messages: vec![],
+            actors: vec![],
+            calls: 100,
+            block_every: None,
+            seed: 0,
+            invariants: vec![],
+        }
+    }
+
+    /// Adds `label` message to the mix, with relative `weight` and generator of its arguments.
+    pub fn message(
+        mut self,
+        label: &str,
+        weight: u32,
+        args: impl FnMut(&mut ScenarioRng) -> Vec<String> + 'static,
+    ) -> Self {
+        self.messages.push(MessageTemplate {
+            label: label.to_string(),
+            weight,
+            args: Box::new(args),
+        });
+        self
+    }
+
+    /// Sets the pool of actors that perform the calls.
+    pub fn actors(self, actors: Vec<AccountIdFor<T::Runtime>>) -> Self {
+        Self { actors, ..self }
+    }
+
+    /// Sets the number of calls to execute.
+    pub fn calls(self, calls: usize) -> Self {
+        Self { calls, ..self }
+    }
+
+    /// Builds a new block every `calls` calls. Panics if `calls` is zero.
+    pub fn advance_block_every(self, calls: usize) -> Self {
+        assert!(calls > 0, "Block interval must be positive");
+        Self {
+            block_every: Some(calls),
+            ..self
+        }
+    }
+
+    /// Sets the seed of the pseudo-random choices.
+    pub fn seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+
+    /// Adds an invariant checked after every call.
+    pub fn invariant(
+        mut self,
+        invariant: impl FnMut(&mut Session<T>) -> Result<(), String> + 'static,
+    ) -> Self {
+        self.invariants.push(Box::new(invariant));
+        self
+    }
+
+    /// Executes the scenario within `session`. The session actor is restored afterwards.
+    pub fn run(mut self, session: &mut Session<T>) -> LoadStats {
+        let total_weight: u64 = self.messages.iter().map(|m| m.weight as u64).sum();
+        assert!(
+            total_weight > 0,
+            "At least one message with positive weight is needed"
+        );
+
+        let mut rng = ScenarioRng::new(self.seed);
+        let mut stats = LoadStats::default();
+        let original_actor = session.get_actor();
+
+        for call_idx in 0..self.calls {
+            if let Some(block_every) = self.block_every {
+                if call_idx > 0 && call_idx % block_every == 0 {
+                    session.sandbox().build_block();
+                }
+            }
+
+            let mut point = rng.below(total_weight);
+            let template = self
+                .messages
+                .iter_mut()
+                .find(|m| {
+                    let hit = point < m.weight as u64;
+                    point = point.saturating_sub(m.weight as u64);
+                    hit
+                })
+                .expect("Point is below the total weight");
+            let args = (template.args)(&mut rng);
+            let actor = if self.actors.is_empty() {
+                original_actor.clone()
+            } else {
+                rng.pick(&self.actors).clone()
+            };
+
+            session.set_actor(actor.clone());
+            let calls_before = session.record().call_results().len();
+            let result = session.call_with_address::<_, ()>(
+                self.contract.clone(),
+                &template.label,
+                args.as_slice(),
+                None,
+            );
+
+            *stats.calls.entry(template.label.clone()).or_default() += 1;
+            let failure = match result {
+                Ok(Ok(_)) => None,
+                Ok(Err(lang_err)) => Some(format!("language error: {lang_err:?}")),
+                Err(err) => Some(failure_kind(&err)),
+            };
+            if let Some(failure) = failure {
+                *stats.failures.entry(failure).or_default() += 1;
+            }
+            if session.record().call_results().len() > calls_before {
+                stats
+                    .total_gas
+                    .saturating_accrue(session.record().last_call_result().gas_consumed);
+                stats.max_storage_deposit = stats
+                    .max_storage_deposit
+                    .max(session.record().last_storage_deposit());
+            }
+
+            for invariant in &mut self.invariants {
+                if let Err(violation) = invariant(session) {
+                    panic!(
+                        "Invariant violated after call #{call_idx} (`{}` by {actor:?}): \
+                         {violation}. Reproduce with seed {}",
+                        template.label, self.seed
+                    );
+                }
+            }
+        }
+
+        session.set_actor(original_actor);
+        stats
+    }
+}
+
+fn failure_kind(err: &SessionError) -> String {
+    match err {
+        SessionError::CallReverted(_) => "reverted".to_string(),
+        SessionError::CallFailed(err) => format!("failed: {err:?}"),
+        SessionError::ContractTrapped { .. } => "trapped".to_string(),
+        other => other.to_string(),
+    }
+}
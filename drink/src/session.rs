@@ -1,37 +1,106 @@
 //! This module provides a context-aware interface for interacting with contracts.
 
 use std::{
+    collections::BTreeMap,
     fmt::Debug,
     mem,
-    sync::{Arc, Mutex},
+    path::Path,
+    sync::{Arc, RwLock},
+    time::Instant,
 };
 
 pub use contract_transcode;
 use contract_transcode::ContractMessageTranscoder;
 use error::SessionError;
-use frame_support::{traits::fungible::Inspect, weights::Weight};
+use frame_support::{
+    sp_runtime::traits::StaticLookup,
+    traits::{fungible::Inspect, Get},
+    weights::Weight,
+};
 use ink_sandbox::{
     api::prelude::*, AccountIdFor, ContractExecResultFor, ContractInstantiateResultFor, Sandbox,
 };
-use parity_scale_codec::Decode;
-pub use record::{EventBatch, Record};
+use parity_scale_codec::{Decode, Encode};
+use replay::{InteractionKind, RecordedInteraction, RecordedResult};
+use scheduled_calls::Scheduler;
+pub use scheduled_calls::{ScheduledCall, ScheduledCallFailure};
+use state_diff::StateSnapshot;
+pub use state_diff::{SessionSnapshot, StateDiff};
+use state_dump::StateDump;
+pub use system::{ManifestDeployment, SystemManifest};
+use transcoding::encoding_error;
+pub use transcoding::{ContractMessageTranscoderExt, DecodedInput};
+pub use value_flow::{ValueFlow, ValueFlowKind};
 
+pub use crate::record::{
+    BlockInfo, DeployOutcome, DepositChange, EventBatch, EventSource, EventStats, HookPhase,
+    InteractionOutcome, Record, Reentrancy, Warning, WarningKind,
+};
 use crate::{
+    environment::EnvironmentReport,
     minimal::MinimalSandboxRuntime,
-    pallet_contracts::{Config, Determinism},
-    pallet_contracts_debugging::{InterceptingExt, TracingExt},
+    pallet_contracts::{
+        Config, Determinism, Error as ContractsError, Pallet as Contracts, StorageDeposit,
+    },
+    pallet_contracts_debugging::{
+        CompositeInterceptor, CompositeTracer, InterceptingExt, InterceptingExtT, TracingExt,
+        TracingExtT,
+    },
+    record::{self, BalanceOf},
+    recorder::Recorder,
     session::mock::MockRegistry,
 };
 
-pub mod mock;
-use mock::MockingExtension;
+pub mod abi;
+pub mod arg_encoders;
+pub mod block_author;
+mod block_budget;
+pub mod builder;
 pub mod bundle;
+pub mod call_template;
+#[cfg(feature = "chaos")]
+#[cfg_attr(docsrs, doc(cfg(feature = "chaos")))]
+pub mod chaos;
+mod clock;
+pub mod coverage;
+mod diagnostics;
 pub mod error;
+pub mod event_observers;
+mod event_order;
+mod forwarder;
+pub mod hooks;
+mod instances;
+pub mod metrics;
+pub mod mock;
 pub mod mocking_api;
-mod record;
+mod reentrancy;
+pub mod replay;
+pub mod scheduled_calls;
+pub mod state_diff;
+mod state_dump;
+mod storage_layout;
+pub mod system;
 mod transcoding;
+pub mod value_flow;
+pub mod view;
+mod warnings;
 
+pub use abi::AbiKind;
+use arg_encoders::{apply_arg_encoders, ArgEncoder};
+use block_budget::BlockBudget;
+pub use builder::SessionBuilder;
 pub use bundle::ContractBundle;
+pub use call_template::{CallTarget, CallTemplate};
+use clock::FrozenTime;
+pub use coverage::CallStats;
+use event_observers::EventObservers;
+pub use event_observers::ObserverHandle;
+pub use event_order::ExpectedEvent;
+use hooks::Hook;
+pub use hooks::InteractionInfo;
+use metrics::MetricsCollector;
+pub use metrics::{InteractionMetrics, SessionMetrics};
+pub use view::SessionView;
 
 use self::mocking_api::MockingApi;
 use crate::{
@@ -40,7 +109,6 @@ use crate::{
     session::transcoding::TranscoderRegistry,
 };
 
-type BalanceOf<R> = <<R as Config>::Currency as Inspect<AccountIdFor<R>>>::Balance;
 type HashFor<R> = <R as frame_system::Config>::Hash;
 
 /// Convenient value for an empty sequence of call/instantiation arguments.
@@ -137,38 +205,71 @@ pub struct Session<T: Sandbox>
 where
     T::Runtime: Config,
 {
-    sandbox: T,
+    recorder: Recorder<T>,
 
     actor: AccountIdFor<T::Runtime>,
     gas_limit: Weight,
+    /// Gas limits of deployments and calls, overriding `gas_limit` (see `with_deploy_gas_limit`
+    /// and `with_call_gas_limit`).
+    deploy_gas_limit: Option<Weight>,
+    call_gas_limit: Option<Weight>,
+    /// Factor (numerator, denominator) applied to the gas limit of every interaction.
+    gas_scale: (u64, u64),
+    storage_deposit_limit: Option<BalanceOf<T::Runtime>>,
     determinism: Determinism,
+    diagnostics: bool,
+    /// Whether a new block is built before every call (see `with_unique_context`).
+    unique_context: bool,
+    /// Whether the transcoders of terminated contracts are removed (see
+    /// `with_transcoder_eviction`).
+    evict_terminated_transcoders: bool,
 
     transcoders: TranscoderRegistry<AccountIdFor<T::Runtime>>,
-    record: Record<T::Runtime>,
-    mocks: Arc<Mutex<MockRegistry<AccountIdFor<T::Runtime>>>>,
+    /// Contracts that are not called with the default (SCALE) encoding.
+    abis: BTreeMap<AccountIdFor<T::Runtime>, AbiKind>,
+    names: BTreeMap<String, AccountIdFor<T::Runtime>>,
+    /// Call templates (see `define_call_template`).
+    call_templates: BTreeMap<String, CallTemplate<AccountIdFor<T::Runtime>>>,
+    /// Custom argument encoders, by type path (see `register_arg_encoder`).
+    arg_encoders: BTreeMap<String, ArgEncoder>,
+    /// Contract event observers (see `on_contract_event`).
+    event_observers: EventObservers<T::Runtime>,
+    /// Mock registry shared with the mocking extension. `None` if mocking is disabled.
+    mocks: Option<Arc<RwLock<MockRegistry<AccountIdFor<T::Runtime>>>>>,
+    /// Whether the mocks are consulted after the other interceptors (see
+    /// `SessionBuilder::mocks_last`).
+    mocks_last: bool,
+    /// Interceptors registered as the intercepting extension, including the mocking extension.
+    /// `None` if there are none (mocking is disabled and `add_interceptor` hasn't been called).
+    interceptors: Option<CompositeInterceptor>,
+    /// Checks run after every successful contract interaction (see `check_invariant`).
+    invariants: Vec<Invariant<T>>,
+    /// Callbacks run before and after every contract interaction (see `add_hook`).
+    hooks: Vec<Hook<T::Runtime>>,
+    /// Weight consumed in the current block (see `with_block_budget`). `None` if disabled.
+    block_budget: Option<BlockBudget>,
+    /// Timestamp that the chain is kept at (see `freeze_time`). `None` if time flows normally.
+    frozen_time: Option<FrozenTime<T>>,
+    /// Interaction metrics (see `with_metrics`). `None` if disabled.
+    metrics: Option<MetricsCollector>,
+    /// Tracers registered as the tracing extension (see `add_tracer`). `None` until the first one.
+    tracers: Option<CompositeTracer>,
+    /// Calls waiting for their block (see `schedule_call`).
+    scheduler: Scheduler<AccountIdFor<T::Runtime>, BalanceOf<T::Runtime>>,
+    /// The forwarder fixture (see `forwarder`). `None` until the first use.
+    forwarder: Option<AccountIdFor<T::Runtime>>,
 }
 
+/// An invariant checked by the session after every contract interaction.
+type Invariant<T> = Box<dyn Fn(&mut Session<T>) -> Result<(), String>>;
+
 impl<T: Sandbox> Default for Session<T>
 where
     T::Runtime: Config,
     T: Default,
 {
     fn default() -> Self {
-        let mocks = Arc::new(Mutex::new(MockRegistry::new()));
-        let mut sandbox = T::default();
-        sandbox.register_extension(InterceptingExt(Box::new(MockingExtension {
-            mock_registry: Arc::clone(&mocks),
-        })));
-
-        Self {
-            sandbox,
-            mocks,
-            actor: T::default_actor(),
-            gas_limit: T::default_gas_limit(),
-            determinism: Determinism::Enforced,
-            transcoders: TranscoderRegistry::new(),
-            record: Default::default(),
-        }
+        SessionBuilder::new().build()
     }
 }
 
@@ -187,6 +288,9 @@ where
     }
 
     /// Sets a new actor and returns the old one.
+    ///
+    /// `pallet-contracts` addresses accounts by their `AccountId` directly, so any account can act
+    /// (and be called back) without being registered or mapped first.
     pub fn set_actor(&mut self, actor: AccountIdFor<T::Runtime>) -> AccountIdFor<T::Runtime> {
         mem::replace(&mut self.actor, actor)
     }
@@ -206,6 +310,178 @@ where
         self.gas_limit
     }
 
+    /// Sets a gas limit for deployments (including dry runs), overriding `gas_limit`, and returns
+    /// updated `self`. Use it when constructors need far more gas than messages.
+    pub fn with_deploy_gas_limit(mut self, gas_limit: Weight) -> Self {
+        self.deploy_gas_limit = Some(gas_limit);
+        self
+    }
+
+    /// Sets (or, with `None`, removes) the gas limit for deployments and returns the old one (see
+    /// `with_deploy_gas_limit`).
+    pub fn set_deploy_gas_limit(&mut self, gas_limit: Option<Weight>) -> Option<Weight> {
+        mem::replace(&mut self.deploy_gas_limit, gas_limit)
+    }
+
+    /// Returns the gas limit in effect for deployments: the one set with `with_deploy_gas_limit`,
+    /// or `gas_limit` if there is none.
+    pub fn get_deploy_gas_limit(&self) -> Weight {
+        self.deploy_gas_limit.unwrap_or(self.gas_limit)
+    }
+
+    /// Sets a gas limit for calls (including dry runs), overriding `gas_limit`, and returns updated
+    /// `self`.
+    pub fn with_call_gas_limit(mut self, gas_limit: Weight) -> Self {
+        self.call_gas_limit = Some(gas_limit);
+        self
+    }
+
+    /// Sets (or, with `None`, removes) the gas limit for calls and returns the old one (see
+    /// `with_call_gas_limit`).
+    pub fn set_call_gas_limit(&mut self, gas_limit: Option<Weight>) -> Option<Weight> {
+        mem::replace(&mut self.call_gas_limit, gas_limit)
+    }
+
+    /// Returns the gas limit in effect for calls: the one set with `with_call_gas_limit`, or
+    /// `gas_limit` if there is none.
+    pub fn get_call_gas_limit(&self) -> Weight {
+        self.call_gas_limit.unwrap_or(self.gas_limit)
+    }
+
+    /// Sets a factor applied to the gas limit of every interaction (including dry runs) and
+    /// returns updated `self`. The gas limit itself stays as it is.
+    ///
+    /// Together with `max_block_weight`, this lets you check how contracts behave close to (or
+    /// above) the block weight limit.
+    pub fn with_gas_scale(mut self, numerator: u64, denominator: u64) -> Self {
+        self.set_gas_scale(numerator, denominator);
+        self
+    }
+
+    /// Sets a factor applied to the gas limit of every interaction and returns the old one.
+    pub fn set_gas_scale(&mut self, numerator: u64, denominator: u64) -> (u64, u64) {
+        assert_ne!(denominator, 0, "Gas scale denominator must not be zero");
+        mem::replace(&mut self.gas_scale, (numerator, denominator))
+    }
+
+    /// Returns the gas limit actually passed to the interactions: the gas limit scaled by the gas
+    /// scale. Deployments and calls with their own gas limits use `effective_deploy_gas_limit` and
+    /// `effective_call_gas_limit` instead.
+    pub fn effective_gas_limit(&self) -> Weight {
+        self.scale_gas(self.gas_limit)
+    }
+
+    /// Returns the gas limit actually passed to deployments (scaled by the gas scale).
+    pub fn effective_deploy_gas_limit(&self) -> Weight {
+        self.scale_gas(self.get_deploy_gas_limit())
+    }
+
+    /// Returns the gas limit actually passed to calls (scaled by the gas scale).
+    pub fn effective_call_gas_limit(&self) -> Weight {
+        self.scale_gas(self.get_call_gas_limit())
+    }
+
+    fn scale_gas(&self, gas_limit: Weight) -> Weight {
+        let (numerator, denominator) = self.gas_scale;
+        let scale = |value: u64| {
+            (value as u128 * numerator as u128 / denominator as u128).min(u64::MAX as u128) as u64
+        };
+        Weight::from_parts(scale(gas_limit.ref_time()), scale(gas_limit.proof_size()))
+    }
+
+    /// Returns the maximum weight of a block in the runtime.
+    pub fn max_block_weight(&self) -> Weight {
+        <T::Runtime as frame_system::Config>::BlockWeights::get().max_block
+    }
+
+    /// Sets a new storage deposit limit and returns updated `self`.
+    ///
+    /// `None` means that there is no limit.
+    pub fn with_storage_deposit_limit(
+        self,
+        storage_deposit_limit: Option<BalanceOf<T::Runtime>>,
+    ) -> Self {
+        Self {
+            storage_deposit_limit,
+            ..self
+        }
+    }
+
+    /// Sets a new storage deposit limit and returns the old one.
+    pub fn set_storage_deposit_limit(
+        &mut self,
+        storage_deposit_limit: Option<BalanceOf<T::Runtime>>,
+    ) -> Option<BalanceOf<T::Runtime>> {
+        mem::replace(&mut self.storage_deposit_limit, storage_deposit_limit)
+    }
+
+    /// Returns currently set storage deposit limit.
+    pub fn get_storage_deposit_limit(&self) -> Option<BalanceOf<T::Runtime>> {
+        self.storage_deposit_limit
+    }
+
+    /// Panics if any recorded interaction (deployment or call) ran without a storage deposit
+    /// limit, i.e. with its deposit unchecked. Meant as a guard at the end of a test suite, so
+    /// that deposit regressions are not missed because the limit was accidentally left unset.
+    #[track_caller]
+    pub fn assert_all_calls_deposit_checked(&self) {
+        let unchecked = self
+            .recorder
+            .record
+            .deposit_limits()
+            .iter()
+            .enumerate()
+            .filter(|(_, limit)| limit.is_none())
+            .map(|(idx, _)| format!("#{idx}"))
+            .collect::<Vec<_>>();
+        assert!(
+            unchecked.is_empty(),
+            "Interactions {} ran without a storage deposit limit",
+            unchecked.join(", ")
+        );
+    }
+
+    /// Enables or disables failure diagnostics and returns updated `self`.
+    ///
+    /// When enabled (default), an interaction that failed because of too low a limit is followed
+    /// by a dry run without that limit, so that the error can report the required amount.
+    pub fn with_diagnostics(self, diagnostics: bool) -> Self {
+        Self {
+            diagnostics,
+            ..self
+        }
+    }
+
+    /// Enables or disables failure diagnostics and returns the previous setting.
+    pub fn set_diagnostics(&mut self, diagnostics: bool) -> bool {
+        mem::replace(&mut self.diagnostics, diagnostics)
+    }
+
+    /// Enables or disables the unique context mode and returns updated `self`.
+    ///
+    /// When enabled (disabled by default), a new block is built before every contract call (not
+    /// before deployments or dry runs), so that repeated identical calls don't run in the same
+    /// context: anything the contract derives from the block number or the timestamp differs
+    /// between them. This is useful for property tests calling the same message with the same
+    /// arguments over and over.
+    ///
+    /// Contract addresses depend only on the deployer, the code hash, the constructor input and
+    /// the salt, so a message instantiating a contract with a constant salt and input still fails
+    /// when called twice. Tests that rely on the block number (or on all calls being in the same
+    /// block) should keep this mode disabled.
+    pub fn with_unique_context(self, unique_context: bool) -> Self {
+        Self {
+            unique_context,
+            ..self
+        }
+    }
+
+    /// Enables or disables the unique context mode (see `with_unique_context`) and returns the
+    /// previous setting.
+    pub fn set_unique_context(&mut self, unique_context: bool) -> bool {
+        mem::replace(&mut self.unique_context, unique_context)
+    }
+
     /// Sets a new determinism policy and returns updated `self`.
     pub fn with_determinism(self, determinism: Determinism) -> Self {
         Self {
@@ -230,29 +506,323 @@ where
     }
 
     /// Registers a transcoder for a particular contract.
+    ///
+    /// In debug builds, panics if the contract metadata contains colliding selectors.
     pub fn set_transcoder(
         &mut self,
         contract_address: AccountIdFor<T::Runtime>,
         transcoder: &Arc<ContractMessageTranscoder>,
     ) {
+        if cfg!(debug_assertions) {
+            transcoder.assert_no_selector_collisions();
+        }
+
+        #[cfg(feature = "tracing-logs")]
+        tracing::debug!(contract = ?contract_address, "transcoder registered");
+
         self.transcoders.register(contract_address, transcoder);
     }
 
+    /// Removes the transcoder registered for a particular contract and returns it, if any.
+    pub fn remove_transcoder(
+        &mut self,
+        contract_address: &AccountIdFor<T::Runtime>,
+    ) -> Option<Arc<ContractMessageTranscoder>> {
+        self.transcoders.remove(contract_address)
+    }
+
+    /// Makes the contract at `from` use the transcoder registered for the contract at `to`, e.g.
+    /// for a proxy delegating to a logic contract (see `Fixture::DelegateProxy`), whose metadata
+    /// is the logic's, while its events are emitted by the proxy. Returns the previous alias of
+    /// `from`, if any.
+    ///
+    /// The alias takes precedence over a transcoder registered for `from` and follows the
+    /// transcoder registered for `to` at the time of use. It is respected wherever the session
+    /// looks up transcoders: encoding calls, `decode_input`, event observers and
+    /// `assert_events_in_order`. Aliases are not chained.
+    pub fn alias_transcoder(
+        &mut self,
+        from: AccountIdFor<T::Runtime>,
+        to: AccountIdFor<T::Runtime>,
+    ) -> Option<AccountIdFor<T::Runtime>> {
+        #[cfg(feature = "tracing-logs")]
+        tracing::debug!(contract = ?from, alias = ?to, "transcoder aliased");
+
+        self.transcoders.alias(from, to)
+    }
+
+    /// Removes the alias set with `alias_transcoder` for the contract at `from` and returns the
+    /// aliased contract, if any.
+    pub fn remove_transcoder_alias(
+        &mut self,
+        from: &AccountIdFor<T::Runtime>,
+    ) -> Option<AccountIdFor<T::Runtime>> {
+        self.transcoders.remove_alias(from)
+    }
+
+    /// Removes all the registered transcoders. Aliases (see `alias_transcoder`) are kept.
+    pub fn clear_transcoders(&mut self) {
+        self.transcoders.clear();
+    }
+
+    /// Whether a transcoder is registered for a particular contract (directly or through an alias).
+    pub fn has_transcoder(&self, contract_address: &AccountIdFor<T::Runtime>) -> bool {
+        self.transcoders.contains(contract_address)
+    }
+
+    /// Returns the contracts that have a transcoder registered, in the order of their addresses.
+    pub fn transcoded_addresses(&self) -> Vec<AccountIdFor<T::Runtime>> {
+        self.transcoders.registered_addresses().cloned().collect()
+    }
+
+    /// Enables or disables evicting the transcoders of terminated contracts and returns updated
+    /// `self`.
+    ///
+    /// When enabled, after every contract interaction (and in `terminate_all_instances_of`), the
+    /// transcoders of the contracts that no longer exist are removed, so that a contract
+    /// redeployed at the same address does not use a stale one.
+    pub fn with_transcoder_eviction(self, evict: bool) -> Self {
+        Self {
+            evict_terminated_transcoders: evict,
+            ..self
+        }
+    }
+
+    /// Removes the transcoders of the contracts that no longer exist, if enabled with
+    /// `with_transcoder_eviction`.
+    pub(crate) fn evict_terminated_transcoders(&mut self) {
+        if !self.evict_terminated_transcoders {
+            return;
+        }
+        for address in self.transcoded_addresses() {
+            if self.code_hash_of(&address).is_none() {
+                #[cfg(feature = "tracing-logs")]
+                tracing::debug!(contract = ?address, "transcoder evicted");
+
+                self.transcoders.remove(&address);
+            }
+        }
+    }
+
+    /// The error for a missing transcoder of the contract at `address`.
+    pub(crate) fn missing_transcoder(&self, address: &AccountIdFor<T::Runtime>) -> SessionError {
+        SessionError::NoTranscoder {
+            address: format!("{address:?}"),
+            registered: self
+                .transcoders
+                .registered_addresses()
+                .map(|address| format!("{address:?}"))
+                .collect(),
+        }
+    }
+
+    /// Sets the ABI used to encode calls to a particular contract.
+    ///
+    /// With `AbiKind::Solidity`, the message passed to `call`-like methods is either a full
+    /// signature (e.g. `"transfer(address,uint256)"`) or a label, whose signature is derived from
+    /// the registered transcoder (see `abi` module). The contract responds with ABI-encoded data,
+    /// which is not a `MessageResult`: the typed return value is decoded directly from it, so use
+    /// `()` and inspect `record().last_call_return()` (e.g. with `abi::decode_uint`).
+    pub fn set_abi(&mut self, contract_address: AccountIdFor<T::Runtime>, abi: AbiKind) {
+        match abi {
+            AbiKind::Scale => self.abis.remove(&contract_address),
+            _ => self.abis.insert(contract_address, abi),
+        };
+    }
+
+    /// Returns the ABI used to encode calls to a particular contract.
+    pub fn abi_of(&self, contract_address: &AccountIdFor<T::Runtime>) -> AbiKind {
+        self.abis.get(contract_address).copied().unwrap_or_default()
+    }
+
     /// The underlying `Sandbox` instance.
     pub fn sandbox(&mut self) -> &mut T {
-        &mut self.sandbox
+        &mut self.recorder.sandbox
+    }
+
+    /// Runs `f` within the externalities of the sandbox, like `TestExternalities::execute_with`.
+    ///
+    /// This gives you direct access to any runtime storage, e.g.:
+    /// ```rust, ignore
+    /// let issuance = session.execute_with(pallet_balances::TotalIssuance::<MinimalSandboxRuntime>::get);
+    /// ```
+    ///
+    /// Keep in mind that:
+    ///  - `f` cannot use the session itself (it is borrowed for the whole call), so any session
+    ///    method has to be called before or after `execute_with`,
+    ///  - whatever `f` writes to the storage is persisted, bypassing all the pallet logic (no
+    ///    events, no weight, no deposits) and the session record.
+    pub fn execute_with<V>(&mut self, f: impl FnOnce() -> V) -> V {
+        self.recorder.sandbox.execute_with(f)
+    }
+
+    /// Returns the versions of drink and of the crates it has been built with, together with the
+    /// runtime of the sandbox and the enabled features - worth attaching to bug reports.
+    pub fn environment(&self) -> EnvironmentReport {
+        EnvironmentReport::for_runtime::<T::Runtime>()
     }
 
     /// Returns a reference to the record of the session.
     pub fn record(&self) -> &Record<T::Runtime> {
-        &self.record
+        &self.recorder
+    }
+
+    /// Returns the record of the session as JSON (see `Record::to_json`), extended with what the
+    /// session knows about the contracts: `names`, mapping the contract names (see
+    /// `deploy_bundle_as`) to their addresses, and `call_templates` (see `CallTemplate::to_json`).
+    pub fn record_json(&self) -> serde_json::Value
+    where
+        AccountIdFor<T::Runtime>: std::fmt::Display,
+        <T::Runtime as frame_system::Config>::RuntimeEvent:
+            TryInto<crate::pallet_contracts::Event<T::Runtime>>,
+    {
+        let mut json = self.record().to_json();
+        json["names"] = self
+            .names
+            .iter()
+            .map(|(name, address)| (name.clone(), address.to_string().into()))
+            .collect::<serde_json::Map<_, _>>()
+            .into();
+        json["call_templates"] = self
+            .call_templates
+            .iter()
+            .map(|(name, template)| (name.clone(), template.to_json()))
+            .collect::<serde_json::Map<_, _>>()
+            .into();
+        json
+    }
+
+    /// Runs `action` and asserts that the contract interactions it performed were charged (or
+    /// refunded) exactly `expected` storage deposit in total. Returns whatever `action` returned.
+    ///
+    /// ```rust, ignore
+    /// session.expect_storage_deposit(StorageDeposit::Refund(expected_refund), |session| {
+    ///     session.call::<_, ()>("remove", &["1"], NO_ENDOWMENT)
+    /// })??;
+    /// ```
+    pub fn expect_storage_deposit<V>(
+        &mut self,
+        expected: StorageDeposit<BalanceOf<T::Runtime>>,
+        action: impl FnOnce(&mut Self) -> V,
+    ) -> V {
+        let start = self.recorder.storage_deposits().len();
+        let result = action(self);
+        let actual: i128 = self.recorder.storage_deposits()[start..]
+            .iter()
+            .map(record::signed_storage_deposit)
+            .sum();
+
+        assert_eq!(
+            actual,
+            record::signed_storage_deposit(&expected),
+            "Unexpected storage deposit (positive values are charges, negative are refunds)"
+        );
+        result
+    }
+
+    /// Writes the whole sandbox state (all top-level and contract child trie entries) to `path`.
+    ///
+    /// The dump is deterministic, i.e. the same state always gives the same file. The session
+    /// record, transcoders, names and mocks are not included.
+    pub fn export_state(&mut self, path: impl AsRef<Path>) -> Result<(), SessionError> {
+        let dump = self
+            .recorder
+            .sandbox
+            .execute_with(StateDump::take::<T::Runtime>);
+        std::fs::write(path, dump.encode())
+            .map_err(|err| SessionError::StateDump(format!("Failed to write the state: {err}")))
+    }
+
+    /// Replaces the whole sandbox state with the one written by `export_state` to `path`.
+    ///
+    /// The session record, transcoders, names and mocks are left untouched, so you will most
+    /// likely need to register transcoders for the contracts you want to call.
+    pub fn import_state(&mut self, path: impl AsRef<Path>) -> Result<(), SessionError> {
+        let bytes = std::fs::read(path)
+            .map_err(|err| SessionError::StateDump(format!("Failed to read the state: {err}")))?;
+        let dump = StateDump::decode(&mut &bytes[..])
+            .map_err(|err| SessionError::StateDump(format!("Failed to decode the state: {err}")))?;
+        self.recorder
+            .sandbox
+            .execute_with(|| dump.restore::<T::Runtime>());
+        Ok(())
+    }
+
+    /// Creates an independent copy of the session (a branch), e.g. to explore two alternative
+    /// continuations of the same scenario without repeating the common part.
+    ///
+    /// The branch gets a copy of the whole sandbox state (see `export_state`), the record,
+    /// transcoders, ABIs, names, call templates, mocks and the settings (actor, gas limit and
+    /// scale, storage deposit limit, determinism, diagnostics). Invariants, argument encoders,
+    /// event observers and extensions registered on the sandbox are not copied. Afterwards, the
+    /// two sessions evolve independently.
+    ///
+    /// The cost is that of copying the full storage of the sandbox, so it is cheap for typical
+    /// tests, but not something to do after every interaction.
+    pub fn branch(&mut self) -> Session<T>
+    where
+        T: Default,
+    {
+        let dump = self
+            .recorder
+            .sandbox
+            .execute_with(StateDump::take::<T::Runtime>);
+        let mut sandbox = T::default();
+        sandbox.execute_with(|| dump.restore::<T::Runtime>());
+
+        let mut builder = SessionBuilder::new()
+            .sandbox(sandbox)
+            .actor(self.actor.clone())
+            .default_gas_limit(self.gas_limit)
+            .determinism(self.determinism)
+            .diagnostics(self.diagnostics);
+        if let Some(limit) = self.storage_deposit_limit {
+            builder = builder.storage_deposit_limit(limit);
+        }
+        if self.mocks.is_none() {
+            builder = builder.without_mocking();
+        }
+        builder = builder.mocks_last(self.mocks_last);
+        let mut branch = builder.build();
+
+        branch.recorder.record = self.recorder.record.clone();
+        branch.gas_scale = self.gas_scale;
+        branch.deploy_gas_limit = self.deploy_gas_limit;
+        branch.call_gas_limit = self.call_gas_limit;
+        branch.transcoders = self.transcoders.clone();
+        branch.evict_terminated_transcoders = self.evict_terminated_transcoders;
+        branch.abis = self.abis.clone();
+        branch.names = self.names.clone();
+        branch.call_templates = self.call_templates.clone();
+        branch.scheduler = self.scheduler.clone();
+        branch.forwarder = self.forwarder.clone();
+        if let (Some(original), Some(copy)) = (&self.mocks, &branch.mocks) {
+            *copy
+                .write()
+                .expect("Should be able to acquire lock on registry") = original
+                .read()
+                .expect("Should be able to acquire lock on registry")
+                .clone();
+        }
+        branch
     }
 
     /// Returns a reference for mocking API.
+    ///
+    /// Panics if the session was built with `SessionBuilder::without_mocking`.
     pub fn mocking_api(&mut self) -> &mut impl MockingApi<T::Runtime> {
+        assert!(
+            self.mocks.is_some(),
+            "Mocking is disabled for this session (it was built with `SessionBuilder::without_mocking`)"
+        );
         self
     }
 
+    /// Returns `true` if the session supports mocking contracts.
+    pub fn is_mocking_enabled(&self) -> bool {
+        self.mocks.is_some()
+    }
+
     /// Deploys a contract with a given constructor, arguments, salt and endowment. In case of
     /// success, returns `self`.
     pub fn deploy_and<S: AsRef<str> + Debug>(
@@ -274,16 +844,16 @@ where
         )
         .map(|_| self)
     }
-    fn record_events<V>(&mut self, recording: impl FnOnce(&mut Self) -> V) -> V {
-        let start = self.sandbox.events().len();
-        let result = recording(self);
-        let events = self.sandbox.events()[start..].to_vec();
-        self.record.push_event_batches(events);
-        result
-    }
 
     /// Deploys a contract with a given constructor, arguments, salt and endowment. In case of
     /// success, returns the address of the deployed contract.
+    ///
+    /// The address is the contract's `AccountId` (`pallet-contracts` has no separate H160 form),
+    /// so it can be used directly in balance- and event-related assertions.
+    #[cfg_attr(
+        feature = "tracing-logs",
+        tracing::instrument(skip_all, fields(constructor = constructor, actor = ?self.actor))
+    )]
     pub fn deploy<S: AsRef<str> + Debug>(
         &mut self,
         contract_bytes: Vec<u8>,
@@ -293,19 +863,66 @@ where
         endowment: Option<BalanceOf<T::Runtime>>,
         transcoder: &Arc<ContractMessageTranscoder>,
     ) -> Result<AccountIdFor<T::Runtime>, SessionError> {
-        let data = transcoder
-            .encode(constructor, args)
-            .map_err(|err| SessionError::Encoding(err.to_string()))?;
+        self.deploy_with_abi(
+            contract_bytes,
+            constructor,
+            args,
+            salt,
+            endowment,
+            transcoder,
+            AbiKind::Scale,
+        )
+    }
+
+    /// Like `deploy`, but encodes the constructor input with `abi` and registers it for the
+    /// deployed contract.
+    fn deploy_with_abi<S: AsRef<str> + Debug>(
+        &mut self,
+        contract_bytes: Vec<u8>,
+        constructor: &str,
+        args: &[S],
+        salt: Vec<u8>,
+        endowment: Option<BalanceOf<T::Runtime>>,
+        transcoder: &Arc<ContractMessageTranscoder>,
+        abi: AbiKind,
+    ) -> Result<AccountIdFor<T::Runtime>, SessionError> {
+        let started = Instant::now();
+        let data = encode_constructor(&self.arg_encoders, transcoder, abi, constructor, args)?;
+
+        // Keep the input around in case we need to diagnose a failure.
+        let diagnostic_input = self
+            .diagnostics
+            .then(|| (contract_bytes.clone(), data.clone(), salt.clone()));
+        let recorded_salt = salt.clone();
+
+        let info = InteractionInfo {
+            callee: None,
+            label: constructor.to_string(),
+            actor: self.actor.clone(),
+            input: data.clone(),
+        };
+        self.run_before_hooks(true, &info)?;
 
-        let result = self.record_events(|session| {
-            session.sandbox.deploy_contract(
+        let (actor, gas_limit, storage_deposit_limit) = (
+            self.actor.clone(),
+            self.effective_deploy_gas_limit(),
+            self.storage_deposit_limit,
+        );
+        self.pin_frozen_time();
+        #[cfg(feature = "tracing-logs")]
+        self.warn_if_interceptors_replaced();
+        self.start_reentrancy_detection();
+        #[cfg(feature = "chaos")]
+        self.start_failure_injection();
+        let result = self.recorder.record_as(actor.clone(), |sandbox| {
+            sandbox.deploy_contract(
                 contract_bytes,
                 endowment.unwrap_or_default(),
                 data,
                 salt,
-                session.actor.clone(),
-                session.gas_limit,
-                None,
+                actor,
+                gas_limit,
+                storage_deposit_limit,
             )
         });
 
@@ -315,16 +932,109 @@ where
             }
             Ok(exec_result) => {
                 let address = exec_result.account_id.clone();
-                self.record.push_deploy_return(address.clone());
-                self.transcoders.register(address.clone(), transcoder);
+                self.set_transcoder(address.clone(), transcoder);
+                self.set_abi(address.clone(), abi);
 
                 Ok(address)
             }
-            Err(err) => Err(SessionError::DeploymentFailed(*err)),
+            Err(err) => Err(self.diagnose_deployment_failure(
+                *err,
+                endowment.unwrap_or_default(),
+                diagnostic_input,
+            )),
         };
 
-        self.record.push_deploy_result(result);
-        ret
+        #[cfg(feature = "tracing-logs")]
+        match &ret {
+            Ok(address) => tracing::debug!(
+                address = ?address,
+                gas_consumed = ?result.gas_consumed,
+                gas_required = ?result.gas_required,
+                "contract deployed"
+            ),
+            Err(err) => tracing::warn!(
+                error = %err,
+                gas_consumed = ?result.gas_consumed,
+                "contract deployment failed"
+            ),
+        }
+
+        let events = self.recorder.last_event_batch().all_events().len();
+        self.recorder.record.push_interaction(RecordedInteraction {
+            actor: self.actor.clone(),
+            endowment: endowment.unwrap_or_default(),
+            kind: InteractionKind::Deploy {
+                constructor: constructor.to_string(),
+                args: args.iter().map(|arg| arg.as_ref().to_string()).collect(),
+                salt: recorded_salt,
+                deployed: ret.as_ref().ok().cloned(),
+            },
+            gas_limit,
+            storage_deposit_limit,
+            result: RecordedResult::of_deployment::<T::Runtime>(&result, events),
+        });
+        self.measure_interaction(true, constructor, started, result.gas_consumed);
+        if ret.is_ok() {
+            self.check_gas_usage(gas_limit, result.gas_consumed, result.gas_required);
+        }
+        let reentrancy = self.record_reentrancies();
+        self.evict_terminated_transcoders();
+        #[cfg(feature = "chaos")]
+        self.record_injected_failures();
+        let hooks = self.run_after_deploy_hooks(&info, &result);
+        let budget = self.charge_block_budget(result.gas_consumed);
+        let address = ret?;
+        reentrancy?;
+        hooks?;
+        budget?;
+        self.notify_event_observers(|| format!("deployment with `{constructor}`"));
+        self.check_invariants(|| format!("deployment with `{constructor}`"))?;
+        Ok(address)
+    }
+
+    /// Similar to `deploy_bundle`, but additionally registers the deployed contract under `name`,
+    /// so that it can be later referred to with `call_to` or `address_of`.
+    ///
+    /// Fails (before deploying) if `name` is already taken. Use `name_contract` to overwrite.
+    pub fn deploy_bundle_as<S: AsRef<str> + Debug>(
+        &mut self,
+        name: &str,
+        contract_file: ContractBundle,
+        constructor: &str,
+        args: &[S],
+        salt: Vec<u8>,
+        endowment: Option<BalanceOf<T::Runtime>>,
+    ) -> Result<AccountIdFor<T::Runtime>, SessionError> {
+        if self.names.contains_key(name) {
+            return Err(SessionError::NameTaken(name.to_string()));
+        }
+        let address = self.deploy_bundle(contract_file, constructor, args, salt, endowment)?;
+        self.names.insert(name.to_string(), address.clone());
+        Ok(address)
+    }
+
+    /// Registers `address` under `name`. If the name is already taken, fails unless `overwrite` is
+    /// set, in which case returns the previously registered address.
+    pub fn name_contract(
+        &mut self,
+        name: &str,
+        address: AccountIdFor<T::Runtime>,
+        overwrite: bool,
+    ) -> Result<Option<AccountIdFor<T::Runtime>>, SessionError> {
+        if !overwrite && self.names.contains_key(name) {
+            return Err(SessionError::NameTaken(name.to_string()));
+        }
+        Ok(self.names.insert(name.to_string(), address))
+    }
+
+    /// Returns the address of the contract registered under `name`.
+    pub fn address_of(&self, name: &str) -> Option<AccountIdFor<T::Runtime>> {
+        self.names.get(name).cloned()
+    }
+
+    /// Returns all the names registered in the session, together with the contract addresses.
+    pub fn contract_names(&self) -> &BTreeMap<String, AccountIdFor<T::Runtime>> {
+        &self.names
     }
 
     /// Similar to `deploy` but takes the parsed contract file (`ContractBundle`) as a first argument.
@@ -338,17 +1048,83 @@ where
         salt: Vec<u8>,
         endowment: Option<BalanceOf<T::Runtime>>,
     ) -> Result<AccountIdFor<T::Runtime>, SessionError> {
-        self.deploy(
+        self.deploy_with_abi(
             contract_file.wasm,
             constructor,
             args,
             salt,
             endowment,
             &contract_file.transcoder,
+            contract_file.abi,
         )
     }
 
+    /// Deploys a contract with a fallible constructor (returning `Result<Self, E>`). Expect the
+    /// deployment to be reverted and returns the error returned by the constructor.
+    ///
+    /// Panics if the deployment succeeds. A deployment reverted by ink! itself (before the
+    /// constructor ran) fails with `SessionError::DeploymentLangError`, other failures (e.g. the
+    /// deployment being aborted by the pallet) are returned as they are.
+    pub fn deploy_and_expect_error<S: AsRef<str> + Debug, E: Debug + Decode>(
+        &mut self,
+        contract_file: ContractBundle,
+        constructor: &str,
+        args: &[S],
+        salt: Vec<u8>,
+        endowment: Option<BalanceOf<T::Runtime>>,
+    ) -> Result<E, SessionError> {
+        match self.deploy_bundle(contract_file, constructor, args, salt, endowment) {
+            Ok(address) => panic!("Deployment should fail, but the contract is at {address:?}"),
+            Err(SessionError::DeploymentReverted) => {}
+            Err(err) => return Err(err),
+        }
+
+        let payload = match &self.recorder.last_deploy_result().result {
+            Ok(exec_result) => exec_result.result.data.clone(),
+            Err(_) => unreachable!("Reverted deployment should have been executed"),
+        };
+        // Like messages, constructors return `Result<_, LangError>`.
+        let result = MessageResult::<Result<(), E>>::decode(&mut &payload[..])
+            .map_err(|err| SessionError::Decoding(err.to_string()))?;
+        match result {
+            Ok(Err(error)) => Ok(error),
+            Ok(Ok(())) => Err(SessionError::Decoding(
+                "Reverted constructor has returned `Ok`".to_string(),
+            )),
+            Err(lang_error) => Err(SessionError::DeploymentLangError(lang_error)),
+        }
+    }
+
+    /// Similar to `deploy_bundle`, but returns everything about the deployment, including the
+    /// events emitted by the constructor.
+    pub fn deploy_full<S: AsRef<str> + Debug>(
+        &mut self,
+        contract_file: ContractBundle,
+        constructor: &str,
+        args: &[S],
+        salt: Vec<u8>,
+        endowment: Option<BalanceOf<T::Runtime>>,
+    ) -> Result<DeployOutcome<T::Runtime>, SessionError> {
+        let address = self.deploy_bundle(contract_file, constructor, args, salt, endowment)?;
+
+        Ok(DeployOutcome {
+            address,
+            deployer: self.actor.clone(),
+            code_hash: *self
+                .recorder
+                .deploy_code_hashes()
+                .last()
+                .expect("Deployment should have been recorded"),
+            gas_consumed: self.recorder.last_deploy_result().gas_consumed,
+            events: self.recorder.last_event_batch().clone(),
+        })
+    }
+
     /// Performs a dry run of the deployment of a contract.
+    ///
+    /// Unlike real deployments, dry runs don't enforce the session storage deposit limit, so that
+    /// they always report how much deposit is really needed. To simulate the limited behavior, use
+    /// `dry_run_deployment_with_limit`.
     pub fn dry_run_deployment<S: AsRef<str> + Debug>(
         &mut self,
         contract_file: ContractBundle,
@@ -357,22 +1133,112 @@ where
         salt: Vec<u8>,
         endowment: Option<BalanceOf<T::Runtime>>,
     ) -> Result<ContractInstantiateResultFor<T::Runtime>, SessionError> {
-        let data = contract_file
-            .transcoder
-            .encode(constructor, args)
-            .map_err(|err| SessionError::Encoding(err.to_string()))?;
+        let actor = self.actor.clone();
+        self.dry_run_deployment_as(actor, contract_file, constructor, args, salt, endowment)
+    }
+
+    /// Like `dry_run_deployment`, but performed by `actor` instead of the session actor (which
+    /// stays as it is).
+    pub fn dry_run_deployment_as<S: AsRef<str> + Debug>(
+        &mut self,
+        actor: AccountIdFor<T::Runtime>,
+        contract_file: ContractBundle,
+        constructor: &str,
+        args: &[S],
+        salt: Vec<u8>,
+        endowment: Option<BalanceOf<T::Runtime>>,
+    ) -> Result<ContractInstantiateResultFor<T::Runtime>, SessionError> {
+        self.dry_run_deployment_internal(
+            actor,
+            contract_file,
+            constructor,
+            args,
+            salt,
+            endowment,
+            None,
+        )
+    }
+
+    /// Like `dry_run_deployment`, but with `storage_deposit_limit` enforced, as it would be in a
+    /// real deployment with such a limit.
+    pub fn dry_run_deployment_with_limit<S: AsRef<str> + Debug>(
+        &mut self,
+        contract_file: ContractBundle,
+        constructor: &str,
+        args: &[S],
+        salt: Vec<u8>,
+        endowment: Option<BalanceOf<T::Runtime>>,
+        storage_deposit_limit: BalanceOf<T::Runtime>,
+    ) -> Result<ContractInstantiateResultFor<T::Runtime>, SessionError> {
+        let actor = self.actor.clone();
+        self.dry_run_deployment_internal(
+            actor,
+            contract_file,
+            constructor,
+            args,
+            salt,
+            endowment,
+            Some(storage_deposit_limit),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn dry_run_deployment_internal<S: AsRef<str> + Debug>(
+        &mut self,
+        actor: AccountIdFor<T::Runtime>,
+        contract_file: ContractBundle,
+        constructor: &str,
+        args: &[S],
+        salt: Vec<u8>,
+        endowment: Option<BalanceOf<T::Runtime>>,
+        storage_deposit_limit: Option<BalanceOf<T::Runtime>>,
+    ) -> Result<ContractInstantiateResultFor<T::Runtime>, SessionError> {
+        let data = encode_constructor(
+            &self.arg_encoders,
+            &contract_file.transcoder,
+            contract_file.abi,
+            constructor,
+            args,
+        )?;
 
-        Ok(self.sandbox.dry_run(|sandbox| {
+        let gas_limit = self.effective_deploy_gas_limit();
+        self.pin_frozen_time();
+        Ok(self.recorder.dry_run(|sandbox| {
             sandbox.deploy_contract(
                 contract_file.wasm,
                 endowment.unwrap_or_default(),
                 data,
                 salt,
+                actor,
+                gas_limit,
+                storage_deposit_limit,
+            )
+        }))
+    }
+
+    /// Performs a dry run of the deployment of `contract_bytes` with already encoded constructor
+    /// input (`data`). Neither transcoders nor the record are involved. As with all dry runs, the
+    /// storage deposit limit is not enforced.
+    pub fn dry_run_deploy_raw(
+        &mut self,
+        contract_bytes: Vec<u8>,
+        data: Vec<u8>,
+        salt: Vec<u8>,
+        endowment: Option<BalanceOf<T::Runtime>>,
+    ) -> ContractInstantiateResultFor<T::Runtime> {
+        let gas_limit = self.effective_deploy_gas_limit();
+        self.pin_frozen_time();
+        self.recorder.dry_run(|sandbox| {
+            sandbox.deploy_contract(
+                contract_bytes,
+                endowment.unwrap_or_default(),
+                data,
+                salt,
                 self.actor.clone(),
-                self.gas_limit,
+                gas_limit,
                 None,
             )
-        }))
+        })
     }
 
     /// Similar to `deploy_and` but takes the parsed contract file (`ContractBundle`) as a first argument.
@@ -397,7 +1263,7 @@ where
 
     /// Uploads a raw contract code. In case of success returns the code hash.
     pub fn upload(&mut self, contract_bytes: Vec<u8>) -> Result<HashFor<T::Runtime>, SessionError> {
-        let result = self.sandbox.upload_contract(
+        let result = self.recorder.sandbox.upload_contract(
             contract_bytes,
             self.actor.clone(),
             None,
@@ -406,7 +1272,15 @@ where
 
         result
             .map(|upload_result| upload_result.code_hash)
-            .map_err(SessionError::UploadFailed)
+            .map_err(|err| {
+                if err == ContractsError::<T::Runtime>::CodeTooLarge.into() {
+                    SessionError::CodeTooLarge
+                } else if err == ContractsError::<T::Runtime>::CodeRejected.into() {
+                    SessionError::CodeRejected
+                } else {
+                    SessionError::UploadFailed(err)
+                }
+            })
     }
 
     /// Similar to `upload_and` but takes the contract bundle as the first argument.
@@ -426,6 +1300,42 @@ where
         self.upload(contract_file.wasm)
     }
 
+    /// Replaces the code of the contract registered under `name` (see `deploy_bundle_as`) with the
+    /// code from `contract_file`, keeping its address and storage. The transcoder of the contract
+    /// is swapped for the new one.
+    ///
+    /// Returns `true` if the storage layout in the new metadata differs from the old one. The
+    /// storage is kept as it is anyway - migrating it is up to you. Note that the comparison is
+    /// structural, so it may also flag changes that only renumbered the types in the metadata.
+    pub fn redeploy_bundle(
+        &mut self,
+        name: &str,
+        contract_file: ContractBundle,
+    ) -> Result<bool, SessionError> {
+        let address = self
+            .address_of(name)
+            .ok_or_else(|| SessionError::UnknownName(name.to_string()))?;
+        let code_hash = self.upload(contract_file.wasm)?;
+
+        self.recorder
+            .sandbox
+            .execute_with(|| {
+                Contracts::<T::Runtime>::set_code(
+                    frame_system::RawOrigin::Root.into(),
+                    <T::Runtime as frame_system::Config>::Lookup::unlookup(address.clone()),
+                    code_hash,
+                )
+            })
+            .map_err(SessionError::RedeploymentFailed)?;
+
+        let layout_changed = self.transcoders.get(&address).is_some_and(|old| {
+            old.metadata().layout() != contract_file.transcoder.metadata().layout()
+        });
+        self.set_transcoder(address, &contract_file.transcoder);
+
+        Ok(layout_changed)
+    }
+
     /// Calls a contract with a given address. In case of a successful call, returns `self`.
     pub fn call_and<S: AsRef<str> + Debug>(
         mut self,
@@ -435,7 +1345,7 @@ where
     ) -> Result<Self, SessionError> {
         // We ignore result, so we can pass `()` as the message result type, which will never fail
         // at decoding.
-        self.call_internal::<_, ()>(None, message, args, endowment)
+        self.call_internal::<_, ()>(None, message, args, endowment, true)
             .map(|_| self)
     }
 
@@ -449,7 +1359,7 @@ where
     ) -> Result<Self, SessionError> {
         // We ignore result, so we can pass `()` as the message result type, which will never fail
         // at decoding.
-        self.call_internal::<_, ()>(Some(address), message, args, endowment)
+        self.call_internal::<_, ()>(Some(address), message, args, endowment, true)
             .map(|_| self)
     }
 
@@ -460,7 +1370,7 @@ where
         args: &[S],
         endowment: Option<BalanceOf<T::Runtime>>,
     ) -> Result<MessageResult<V>, SessionError> {
-        self.call_internal::<_, V>(None, message, args, endowment)
+        self.call_internal::<_, V>(None, message, args, endowment, true)
     }
 
     /// Calls the last deployed contract. Expect it to be reverted and the message result to be of
@@ -472,13 +1382,106 @@ where
         endowment: Option<BalanceOf<T::Runtime>>,
     ) -> Result<E, SessionError> {
         Ok(self
-            .call_internal::<_, Result<(), E>>(None, message, args, endowment)
+            .call_internal::<_, Result<(), E>>(None, message, args, endowment, true)
             .expect_err("Call should fail")
             .decode_revert::<Result<(), E>>()?
             .expect("Call should return an error")
             .expect_err("Call should return an error"))
     }
 
+    /// Checks that `message` of the last deployed contract is denied to every of `actors`: called
+    /// by the actor, it must be reverted with `expected_err` (the message returning
+    /// `Result<_, E>`).
+    ///
+    /// Every attempt starts from a snapshot of the state, which is restored afterwards, so the
+    /// attempts don't affect each other nor the rest of the test. The attempts stay in the record.
+    /// Panics, naming the actor, if a call is not reverted with the expected error.
+    #[track_caller]
+    pub fn assert_denied_for<S: AsRef<str> + Debug, E: Debug + Decode + PartialEq>(
+        &mut self,
+        actors: &[AccountIdFor<T::Runtime>],
+        message: &str,
+        args: &[S],
+        expected_err: E,
+    ) {
+        for actor in actors {
+            let state = self
+                .recorder
+                .sandbox
+                .execute_with(StateDump::take::<T::Runtime>);
+            let previous_actor = self.set_actor(actor.clone());
+            // The return value is not needed, and `()` can be decoded from any successful result.
+            let result = self.call_internal::<_, ()>(None, message, args, None, true);
+            self.set_actor(previous_actor);
+            self.recorder
+                .sandbox
+                .execute_with(|| state.restore::<T::Runtime>());
+
+            match result {
+                Ok(_) => panic!(
+                    "Call to `{message}` by {actor:?} should have been denied, but it succeeded"
+                ),
+                Err(SessionError::CallReverted(data)) => {
+                    match MessageResult::<Result<(), E>>::decode(&mut &data[..]) {
+                        Ok(Ok(Err(err))) if err == expected_err => {}
+                        other => panic!(
+                            "Call to `{message}` by {actor:?} should have been reverted with \
+                             {expected_err:?}, but it was reverted with {other:?}"
+                        ),
+                    }
+                }
+                Err(err) => panic!(
+                    "Call to `{message}` by {actor:?} should have been reverted with \
+                     {expected_err:?}, but it failed: {err}"
+                ),
+            }
+        }
+    }
+
+    /// Calls the last deployed contract up to `attempts` times, until `post` holds for the message
+    /// result. Returns the number of the successful attempt (starting from 1) and its result.
+    ///
+    /// Before every attempt, the state is saved. If the call is reverted (or fails) or `post`
+    /// doesn't hold, the state is restored and a new block is built, so that the next attempt can
+    /// end differently (e.g. when the contract relies on block-local randomness). All the attempts
+    /// stay in the record. Other errors (e.g. encoding errors) are returned immediately.
+    pub fn call_until<S: AsRef<str> + Debug, V: Decode>(
+        &mut self,
+        attempts: u32,
+        message: &str,
+        args: &[S],
+        endowment: Option<BalanceOf<T::Runtime>>,
+        post: impl Fn(&MessageResult<V>) -> bool,
+    ) -> Result<(u32, MessageResult<V>), SessionError> {
+        for attempt in 1..=attempts {
+            let state = self
+                .recorder
+                .sandbox
+                .execute_with(StateDump::take::<T::Runtime>);
+
+            match self.call_internal::<_, V>(None, message, args, endowment, true) {
+                Ok(result) if post(&result) => return Ok((attempt, result)),
+                Ok(_)
+                | Err(
+                    SessionError::CallReverted(_)
+                    | SessionError::CallFailed(_)
+                    | SessionError::ContractTrapped { .. },
+                ) => {}
+                Err(err) => return Err(err),
+            }
+
+            self.recorder
+                .sandbox
+                .execute_with(|| state.restore::<T::Runtime>());
+            self.recorder.build_block();
+        }
+
+        Err(SessionError::AttemptsExhausted {
+            message: message.to_string(),
+            attempts,
+        })
+    }
+
     /// Calls a contract with a given address. In case of a successful call, returns the encoded
     /// result.
     pub fn call_with_address<S: AsRef<str> + Debug, V: Decode>(
@@ -488,10 +1491,41 @@ where
         args: &[S],
         endowment: Option<BalanceOf<T::Runtime>>,
     ) -> Result<MessageResult<V>, SessionError> {
-        self.call_internal(Some(address), message, args, endowment)
+        self.call_internal(Some(address), message, args, endowment, true)
+    }
+
+    /// Similar to `call_with_address`, but does not check whether there is a contract at `address`
+    /// before dispatching the call. Useful for testing how the pallet itself handles such calls.
+    pub fn call_raw<S: AsRef<str> + Debug, V: Decode>(
+        &mut self,
+        address: AccountIdFor<T::Runtime>,
+        message: &str,
+        args: &[S],
+        endowment: Option<BalanceOf<T::Runtime>>,
+    ) -> Result<MessageResult<V>, SessionError> {
+        self.call_internal(Some(address), message, args, endowment, false)
+    }
+
+    /// Calls the contract registered under `name` (see `deploy_bundle_as`). In case of a
+    /// successful call, returns the encoded result.
+    pub fn call_to<S: AsRef<str> + Debug, V: Decode>(
+        &mut self,
+        name: &str,
+        message: &str,
+        args: &[S],
+        endowment: Option<BalanceOf<T::Runtime>>,
+    ) -> Result<MessageResult<V>, SessionError> {
+        let address = self
+            .address_of(name)
+            .ok_or_else(|| SessionError::UnknownName(name.to_string()))?;
+        self.call_internal(Some(address), message, args, endowment, true)
     }
 
     /// Performs a dry run of a contract call.
+    ///
+    /// Unlike real calls, dry runs don't enforce the session storage deposit limit, so that they
+    /// always report how much deposit is really needed. To simulate the limited behavior, use
+    /// `dry_run_call_with_limit`.
     pub fn dry_run_call<S: AsRef<str> + Debug>(
         &mut self,
         address: AccountIdFor<T::Runtime>,
@@ -499,61 +1533,197 @@ where
         args: &[S],
         endowment: Option<BalanceOf<T::Runtime>>,
     ) -> Result<ContractExecResultFor<T::Runtime>, SessionError> {
-        let data = self
-            .transcoders
-            .get(&address)
-            .as_ref()
-            .ok_or(SessionError::NoTranscoder)?
-            .encode(message, args)
-            .map_err(|err| SessionError::Encoding(err.to_string()))?;
+        let actor = self.actor.clone();
+        self.dry_run_call_as(actor, address, message, args, endowment)
+    }
+
+    /// Like `dry_run_call`, but performed by `actor` instead of the session actor (which stays as
+    /// it is). Useful to check what another account would see or pay.
+    pub fn dry_run_call_as<S: AsRef<str> + Debug>(
+        &mut self,
+        actor: AccountIdFor<T::Runtime>,
+        address: AccountIdFor<T::Runtime>,
+        message: &str,
+        args: &[S],
+        endowment: Option<BalanceOf<T::Runtime>>,
+    ) -> Result<ContractExecResultFor<T::Runtime>, SessionError> {
+        self.dry_run_call_internal(actor, address, message, args, endowment, None)
+    }
+
+    /// Like `dry_run_call`, but with `storage_deposit_limit` enforced, as it would be in a real
+    /// call with such a limit.
+    pub fn dry_run_call_with_limit<S: AsRef<str> + Debug>(
+        &mut self,
+        address: AccountIdFor<T::Runtime>,
+        message: &str,
+        args: &[S],
+        endowment: Option<BalanceOf<T::Runtime>>,
+        storage_deposit_limit: BalanceOf<T::Runtime>,
+    ) -> Result<ContractExecResultFor<T::Runtime>, SessionError> {
+        let actor = self.actor.clone();
+        self.dry_run_call_internal(
+            actor,
+            address,
+            message,
+            args,
+            endowment,
+            Some(storage_deposit_limit),
+        )
+    }
 
-        Ok(self.sandbox.dry_run(|sandbox| {
+    fn dry_run_call_internal<S: AsRef<str> + Debug>(
+        &mut self,
+        actor: AccountIdFor<T::Runtime>,
+        address: AccountIdFor<T::Runtime>,
+        message: &str,
+        args: &[S],
+        endowment: Option<BalanceOf<T::Runtime>>,
+        storage_deposit_limit: Option<BalanceOf<T::Runtime>>,
+    ) -> Result<ContractExecResultFor<T::Runtime>, SessionError> {
+        self.ensure_contract_exists(&address)?;
+        let data = self.encode_message(&address, message, args)?;
+
+        let gas_limit = self.effective_call_gas_limit();
+        self.pin_frozen_time();
+        Ok(self.recorder.dry_run(|sandbox| {
             sandbox.call_contract(
+                address,
+                endowment.unwrap_or_default(),
+                data,
+                actor,
+                gas_limit,
+                storage_deposit_limit,
+                self.determinism,
+            )
+        }))
+    }
+
+    /// Performs a dry run of a contract call and reports, besides the result, the changes that
+    /// the call would have made to contract storage and account balances.
+    pub fn dry_run_call_with_diff<S: AsRef<str> + Debug>(
+        &mut self,
+        address: AccountIdFor<T::Runtime>,
+        message: &str,
+        args: &[S],
+        endowment: Option<BalanceOf<T::Runtime>>,
+    ) -> Result<(ContractExecResultFor<T::Runtime>, StateDiff<T::Runtime>), SessionError> {
+        self.ensure_contract_exists(&address)?;
+        let data = self.encode_message(&address, message, args)?;
+
+        let gas_limit = self.effective_call_gas_limit();
+        self.pin_frozen_time();
+        Ok(self.recorder.dry_run(|sandbox| {
+            let before = sandbox.execute_with(StateSnapshot::<T::Runtime>::take);
+            let result = sandbox.call_contract(
                 address,
                 endowment.unwrap_or_default(),
                 data,
                 self.actor.clone(),
-                self.gas_limit,
+                gas_limit,
                 None,
                 self.determinism,
-            )
+            );
+            let after = sandbox.execute_with(StateSnapshot::<T::Runtime>::take);
+            (result, before.diff(after))
         }))
     }
 
+    /// Performs a dry run of a contract call with already encoded input (`data`, including the
+    /// selector). Neither transcoders nor the record are involved, so the contract at `address`
+    /// doesn't have to be known to the session. As with all dry runs, the storage deposit limit is
+    /// not enforced.
+    pub fn dry_run_call_raw(
+        &mut self,
+        address: AccountIdFor<T::Runtime>,
+        data: Vec<u8>,
+        endowment: Option<BalanceOf<T::Runtime>>,
+    ) -> ContractExecResultFor<T::Runtime> {
+        let gas_limit = self.effective_call_gas_limit();
+        self.pin_frozen_time();
+        self.recorder.dry_run(|sandbox| {
+            sandbox.call_contract(
+                address,
+                endowment.unwrap_or_default(),
+                data,
+                self.actor.clone(),
+                gas_limit,
+                None,
+                self.determinism,
+            )
+        })
+    }
+
+    #[cfg_attr(
+        feature = "tracing-logs",
+        tracing::instrument(
+            skip_all,
+            fields(message = message, callee = tracing::field::Empty, actor = ?self.actor)
+        )
+    )]
     fn call_internal<S: AsRef<str> + Debug, V: Decode>(
         &mut self,
         address: Option<AccountIdFor<T::Runtime>>,
         message: &str,
         args: &[S],
         endowment: Option<BalanceOf<T::Runtime>>,
+        check_contract: bool,
     ) -> Result<MessageResult<V>, SessionError> {
+        let started = Instant::now();
         let address = match address {
             Some(address) => address,
             None => self
-                .record
+                .recorder
                 .deploy_returns()
                 .last()
                 .ok_or(SessionError::NoContract)?
                 .clone(),
         };
+        if check_contract {
+            self.ensure_contract_exists(&address)?;
+        }
 
-        let data = self
-            .transcoders
-            .get(&address)
-            .as_ref()
-            .ok_or(SessionError::NoTranscoder)?
-            .encode(message, args)
-            .map_err(|err| SessionError::Encoding(err.to_string()))?;
+        #[cfg(feature = "tracing-logs")]
+        tracing::Span::current().record("callee", tracing::field::debug(&address));
 
-        let result = self.record_events(|session| {
-            session.sandbox.call_contract(
+        let abi = self.abi_of(&address);
+        let data = self.encode_message(&address, message, args)?;
+
+        // Keep the input around in case we need to diagnose a failure.
+        let diagnostic_input = self.diagnostics.then(|| (address.clone(), data.clone()));
+        let callee = address.clone();
+
+        let info = InteractionInfo {
+            callee: Some(address.clone()),
+            label: message.to_string(),
+            actor: self.actor.clone(),
+            input: data.clone(),
+        };
+        self.run_before_hooks(false, &info)?;
+        if self.unique_context {
+            self.recorder.build_block();
+        }
+        self.pin_frozen_time();
+
+        let (actor, gas_limit, storage_deposit_limit, determinism) = (
+            self.actor.clone(),
+            self.effective_call_gas_limit(),
+            self.storage_deposit_limit,
+            self.determinism,
+        );
+        #[cfg(feature = "tracing-logs")]
+        self.warn_if_interceptors_replaced();
+        self.start_reentrancy_detection();
+        #[cfg(feature = "chaos")]
+        self.start_failure_injection();
+        let result = self.recorder.record_as(actor.clone(), |sandbox| {
+            sandbox.call_contract(
                 address,
                 endowment.unwrap_or_default(),
                 data,
-                session.actor.clone(),
-                session.gas_limit,
-                None,
-                session.determinism,
+                actor,
+                gas_limit,
+                storage_deposit_limit,
+                determinism,
             )
         });
 
@@ -561,19 +1731,287 @@ where
             Ok(exec_result) if exec_result.did_revert() => {
                 Err(SessionError::CallReverted(exec_result.data.clone()))
             }
-            Ok(exec_result) => {
-                self.record.push_call_return(exec_result.data.clone());
-                self.record.last_call_return_decoded::<V>()
-            }
-            Err(err) => Err(SessionError::CallFailed(*err)),
+            Ok(exec_result) => match abi {
+                AbiKind::Scale => self.recorder.last_call_return_decoded::<V>(),
+                AbiKind::Solidity => V::decode(&mut exec_result.data.as_slice())
+                    .map(Ok)
+                    .map_err(|err| SessionError::Decoding(err.to_string())),
+            },
+            Err(err) => Err(self.diagnose_call_failure(
+                *err,
+                endowment.unwrap_or_default(),
+                diagnostic_input,
+                &result.debug_message,
+            )),
         };
 
-        self.record.push_call_result(result);
-        ret
+        #[cfg(feature = "tracing-logs")]
+        match &ret {
+            Ok(_) => tracing::debug!(
+                gas_consumed = ?result.gas_consumed,
+                gas_required = ?result.gas_required,
+                "contract called"
+            ),
+            Err(err) => tracing::warn!(
+                error = %err,
+                gas_consumed = ?result.gas_consumed,
+                "contract call failed"
+            ),
+        }
+
+        self.annotate_recorded_gas(&callee, &info.input, result.gas_consumed);
+
+        let events = self.recorder.last_event_batch().all_events().len();
+        self.recorder.record.push_interaction(RecordedInteraction {
+            actor: self.actor.clone(),
+            endowment: endowment.unwrap_or_default(),
+            kind: InteractionKind::Call {
+                address: callee,
+                message: message.to_string(),
+                args: args.iter().map(|arg| arg.as_ref().to_string()).collect(),
+            },
+            gas_limit,
+            storage_deposit_limit,
+            result: RecordedResult::of_call::<T::Runtime>(&result, events),
+        });
+        self.measure_interaction(false, message, started, result.gas_consumed);
+        if ret.is_ok() {
+            self.check_gas_usage(gas_limit, result.gas_consumed, result.gas_required);
+        }
+        let reentrancy = self.record_reentrancies();
+        self.evict_terminated_transcoders();
+        #[cfg(feature = "chaos")]
+        self.record_injected_failures();
+        let hooks = self.run_after_call_hooks(&info, &result);
+        let budget = self.charge_block_budget(result.gas_consumed);
+        let ret = ret?;
+        reentrancy?;
+        hooks?;
+        budget?;
+        self.notify_event_observers(|| format!("call to `{message}`"));
+        self.check_invariants(|| format!("call to `{message}`"))?;
+        Ok(ret)
+    }
+
+    /// Encodes a call to `message` of the contract at `address`, according to its ABI.
+    fn encode_message<S: AsRef<str> + Debug>(
+        &self,
+        address: &AccountIdFor<T::Runtime>,
+        message: &str,
+        args: &[S],
+    ) -> Result<Vec<u8>, SessionError> {
+        let transcoder = self.transcoders.get(address);
+        match self.abi_of(address) {
+            AbiKind::Scale => {
+                let transcoder = transcoder
+                    .as_ref()
+                    .ok_or_else(|| self.missing_transcoder(address))?;
+                let args =
+                    apply_arg_encoders(&self.arg_encoders, transcoder, message, false, args)?;
+                transcoder.encode(message, args).map_err(|err| {
+                    encoding_error(
+                        transcoder,
+                        Some(format!("{address:?}")),
+                        message,
+                        false,
+                        err,
+                    )
+                })
+            }
+            AbiKind::Solidity => abi::encode_call(transcoder.as_deref(), message, args)
+                .map_err(SessionError::Encoding),
+        }
+    }
+
+    /// Decodes the input of a call to the contract at `address` (`data`, including the selector),
+    /// e.g. captured by a tracer or a mock, into the message label and its arguments. Uses the
+    /// transcoder registered for `address` (or for its alias, see `alias_transcoder`).
+    pub fn decode_input(
+        &self,
+        address: &AccountIdFor<T::Runtime>,
+        data: &[u8],
+    ) -> Result<DecodedInput, SessionError> {
+        self.transcoders
+            .get(address)
+            .ok_or_else(|| self.missing_transcoder(address))?
+            .decode_input(data)
+    }
+
+    /// Returns the hash of the code currently run by the contract at `address` (`None` if there is
+    /// no contract). It reflects code upgrades - for the code at instantiation time, see
+    /// `Record::deploy_code_hashes`.
+    pub fn code_hash_of(
+        &mut self,
+        address: &AccountIdFor<T::Runtime>,
+    ) -> Option<HashFor<T::Runtime>> {
+        self.view().code_hash_of(address)
     }
 
-    /// Set the tracing extension
+    /// Checks (without dispatching anything) that there is a contract at `address`.
+    pub(crate) fn ensure_contract_exists(
+        &mut self,
+        address: &AccountIdFor<T::Runtime>,
+    ) -> Result<(), SessionError> {
+        match self.code_hash_of(address) {
+            Some(_) => Ok(()),
+            None => Err(SessionError::ContractNotFound(format!("{address:?}"))),
+        }
+    }
+
+    /// Returns the existential deposit of the native currency, i.e. the minimum balance an account
+    /// must keep in order not to be reaped.
+    ///
+    /// It is a constant of the runtime: `MinimalSandbox` inherits it from `create_sandbox!`. Custom
+    /// runtimes can set it via `pallet_balances::Config::ExistentialDeposit`.
+    pub fn existential_deposit(&self) -> BalanceOf<T::Runtime> {
+        <T::Runtime as Config>::Currency::minimum_balance()
+    }
+
+    /// Returns the total issuance of the native currency.
+    pub fn total_issuance(&mut self) -> BalanceOf<T::Runtime> {
+        self.view().total_issuance()
+    }
+
+    /// Registers an invariant that is checked after every successful contract interaction
+    /// (deployment or call). If the check fails, the interaction returns
+    /// `SessionError::InvariantViolated`, pointing to the interaction that broke it. Its effects
+    /// are kept, though.
+    ///
+    /// ```rust, ignore
+    /// session.check_invariant(|session| {
+    ///     let held = session.sandbox().free_balance(&alice) + session.sandbox().free_balance(&bob);
+    ///     (held <= session.total_issuance())
+    ///         .then_some(())
+    ///         .ok_or_else(|| "users hold more than the total issuance".to_string())
+    /// });
+    /// ```
+    ///
+    /// Interactions performed by the invariant itself are not checked.
+    pub fn check_invariant(
+        &mut self,
+        invariant: impl Fn(&mut Self) -> Result<(), String> + 'static,
+    ) {
+        self.invariants.push(Box::new(invariant));
+    }
+
+    /// Runs all the registered invariants after `interaction`.
+    fn check_invariants(
+        &mut self,
+        interaction: impl FnOnce() -> String,
+    ) -> Result<(), SessionError> {
+        // Invariants may interact with the session themselves, so we take them out for the time of
+        // checking to avoid recursion.
+        let invariants = mem::take(&mut self.invariants);
+        let outcome = invariants.iter().try_for_each(|invariant| invariant(self));
+        self.invariants = invariants;
+
+        outcome.map_err(|violation| {
+            SessionError::InvariantViolated(format!("{violation} (after {})", interaction()))
+        })
+    }
+
+    /// Set the tracing extension, replacing all the tracers registered so far (including the ones
+    /// added with `add_tracer`).
     pub fn set_tracing_extension(&mut self, d: TracingExt) {
-        self.sandbox.register_extension(d);
+        self.tracers = None;
+        self.add_tracer(d);
+    }
+
+    /// Adds `tracer` to the tracers of the session. All of them are called (in the order they were
+    /// added) after every contract call, including the nested ones.
+    ///
+    /// If a tracer panics, the other tracers are still called, and then the panic is propagated.
+    pub fn add_tracer(&mut self, tracer: impl TracingExtT + Send + 'static) {
+        let sandbox = &mut self.recorder.sandbox;
+        self.tracers
+            .get_or_insert_with(|| {
+                let tracers = CompositeTracer::new();
+                sandbox.register_extension(TracingExt(Box::new(tracers.clone())));
+                tracers
+            })
+            .push(tracer);
+    }
+
+    /// Adds the intercepting extension `ext` to the interceptors of the session (see
+    /// `add_interceptor`). Unlike registering it directly on the sandbox, this keeps the mocks
+    /// working.
+    pub fn set_intercepting_extension(&mut self, ext: InterceptingExt) {
+        self.add_interceptor(ext);
+    }
+
+    /// Adds `interceptor` to the interceptors of the session. They are consulted (in the order
+    /// they were added) on every contract call, including the nested ones, until one of them
+    /// intercepts the call. The mocks are consulted before them, unless the session was built with
+    /// `SessionBuilder::mocks_last`.
+    ///
+    /// The interceptors are registered on the sandbox as a single intercepting extension.
+    /// Registering another `InterceptingExt` directly on the sandbox replaces all of them
+    /// (including the mocks), which is reported with a warning (with the `tracing-logs` feature).
+    pub fn add_interceptor(&mut self, interceptor: impl InterceptingExtT + Send + 'static) {
+        let before_mocks = self.mocks_last && self.mocks.is_some();
+        let sandbox = &mut self.recorder.sandbox;
+        let interceptors = self.interceptors.get_or_insert_with(|| {
+            let interceptors = CompositeInterceptor::new();
+            sandbox.register_extension(InterceptingExt(Box::new(interceptors.clone())));
+            interceptors
+        });
+        match before_mocks {
+            true => interceptors.insert(interceptors.len() - 1, interceptor),
+            false => interceptors.push(interceptor),
+        }
+    }
+
+    /// Warns if the intercepting extension of the session has been replaced directly on the
+    /// sandbox, which silently disables the mocks and the interceptors.
+    #[cfg(feature = "tracing-logs")]
+    fn warn_if_interceptors_replaced(&self) {
+        if self
+            .interceptors
+            .as_ref()
+            .is_some_and(CompositeInterceptor::is_detached)
+        {
+            tracing::warn!(
+                "the intercepting extension of the session has been replaced on the sandbox: \
+                 mocks and interceptors are disabled (use `Session::add_interceptor` instead)"
+            );
+        }
+    }
+}
+
+impl<T: Sandbox<Runtime = MinimalSandboxRuntime>> Session<T> {
+    /// Returns the SCALE-encoded metadata of the runtime (`RuntimeMetadataPrefixed`), e.g. for
+    /// decoding events with `subxt` (see `EventBatch::encoded_events`).
+    pub fn runtime_metadata(&self) -> Vec<u8> {
+        MinimalSandboxRuntime::metadata().encode()
+    }
+
+    /// Returns the data of all the contract events emitted during the session (by any contract),
+    /// across all the blocks, in emission order. See `EventBatch::contract_events`.
+    pub fn all_contract_events(&self) -> Vec<&[u8]> {
+        self.record()
+            .event_batches()
+            .iter()
+            .flat_map(EventBatch::contract_events)
+            .collect()
+    }
+}
+
+/// Encodes the input of `constructor`, according to `abi` (applying `arg_encoders` for SCALE).
+fn encode_constructor<S: AsRef<str> + Debug>(
+    arg_encoders: &BTreeMap<String, ArgEncoder>,
+    transcoder: &ContractMessageTranscoder,
+    abi: AbiKind,
+    constructor: &str,
+    args: &[S],
+) -> Result<Vec<u8>, SessionError> {
+    match abi {
+        AbiKind::Scale => transcoder
+            .encode(
+                constructor,
+                apply_arg_encoders(arg_encoders, transcoder, constructor, true, args)?,
+            )
+            .map_err(|err| encoding_error(transcoder, None, constructor, true, err)),
+        AbiKind::Solidity => abi::encode_constructor(Some(transcoder), constructor, args)
+            .map_err(SessionError::Encoding),
     }
 }
@@ -0,0 +1,407 @@
+//! Support for contracts exposing a Solidity-compatible ABI (ink! `abi = "sol"`).
+//!
+//! Such contracts cannot be called through the SCALE path of `ContractMessageTranscoder`. Instead,
+//! the call data is built with the Ethereum ABI encoding, from the same string arguments. The
+//! function signature is either given explicitly (`"transfer(address,uint256)"`) or derived from
+//! the contract metadata, by message label.
+//!
+//! Supported types: `uint<N>`, `int<N>` (values up to 128 bits), `bool`, `address`, `bytes<N>`,
+//! `bytes` and `string`. Arrays and tuples are not supported.
+
+use contract_transcode::ContractMessageTranscoder;
+use frame_support::sp_runtime::traits::{Hash, Keccak256};
+use scale_info::{form::PortableForm, PortableRegistry, TypeDef, TypeDefPrimitive};
+
+/// Size of a single ABI word.
+const WORD: usize = 32;
+
+/// The encoding used to talk to a contract.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum AbiKind {
+    /// Standard ink! encoding (SCALE), handled by `ContractMessageTranscoder`.
+    #[default]
+    Scale,
+    /// Solidity-compatible (Ethereum ABI) encoding.
+    Solidity,
+}
+
+/// Builds the call data of a message: the 4-byte selector of the signature, followed by the
+/// encoded arguments.
+///
+/// `message` is either a full signature (e.g. `"transfer(address,uint256)"`) or a message label,
+/// in which case the signature is derived from the metadata in `transcoder`.
+pub fn encode_call<S: AsRef<str>>(
+    transcoder: Option<&ContractMessageTranscoder>,
+    message: &str,
+    args: &[S],
+) -> Result<Vec<u8>, String> {
+    let signature = match message.contains('(') {
+        true => message.replace(' ', ""),
+        false => signature_from_metadata(transcoder, message, false)?,
+    };
+    let (_, types) = parse_signature(&signature)?;
+
+    let mut data = selector(&signature).to_vec();
+    data.extend(encode_args(&types, args)?);
+    Ok(data)
+}
+
+/// Builds the input of a constructor: just the encoded arguments (Solidity constructors are not
+/// dispatched by selector).
+///
+/// `constructor` is either a full signature (e.g. `"new(uint256)"`) or a constructor label, in
+/// which case the argument types are derived from the metadata in `transcoder`.
+pub fn encode_constructor<S: AsRef<str>>(
+    transcoder: Option<&ContractMessageTranscoder>,
+    constructor: &str,
+    args: &[S],
+) -> Result<Vec<u8>, String> {
+    let signature = match constructor.contains('(') {
+        true => constructor.replace(' ', ""),
+        false => signature_from_metadata(transcoder, constructor, true)?,
+    };
+    let (_, types) = parse_signature(&signature)?;
+    encode_args(&types, args)
+}
+
+/// Returns the 4-byte selector of `signature` (first four bytes of its Keccak-256 hash).
+pub fn selector(signature: &str) -> [u8; 4] {
+    Keccak256::hash(signature.as_bytes()).as_bytes()[..4]
+        .try_into()
+        .expect("Hash should have at least 4 bytes")
+}
+
+/// Decodes a single `uint<N>` word (e.g. a return value). Returns `None` if `data` is not a word or
+/// the value does not fit into `u128`.
+pub fn decode_uint(data: &[u8]) -> Option<u128> {
+    let word: &[u8; WORD] = data.get(..WORD)?.try_into().ok()?;
+    if word[..16].iter().any(|b| *b != 0) {
+        return None;
+    }
+    Some(u128::from_be_bytes(word[16..].try_into().ok()?))
+}
+
+/// Decodes a single `bool` word (e.g. a return value).
+pub fn decode_bool(data: &[u8]) -> Option<bool> {
+    decode_uint(data).and_then(|value| match value {
+        0 => Some(false),
+        1 => Some(true),
+        _ => None,
+    })
+}
+
+fn signature_from_metadata(
+    transcoder: Option<&ContractMessageTranscoder>,
+    label: &str,
+    constructor: bool,
+) -> Result<String, String> {
+    let transcoder = transcoder.ok_or_else(|| {
+        format!("No metadata to derive the signature of `{label}` from - pass a full signature")
+    })?;
+    let spec = transcoder.metadata().spec();
+    let registry = transcoder.metadata().registry();
+
+    let arg_types = match constructor {
+        true => spec
+            .constructors()
+            .iter()
+            .find(|c| c.label() == label)
+            .map(|c| c.args().iter().map(|a| a.ty().ty().id).collect::<Vec<_>>()),
+        false => spec
+            .messages()
+            .iter()
+            .find(|m| m.label() == label)
+            .map(|m| m.args().iter().map(|a| a.ty().ty().id).collect::<Vec<_>>()),
+    }
+    .ok_or_else(|| format!("No `{label}` in the contract metadata"))?;
+
+    let types = arg_types
+        .into_iter()
+        .map(|id| sol_type(registry, id))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(format!("{label}({})", types.join(",")))
+}
+
+/// Maps a type from the metadata registry to its Solidity counterpart.
+fn sol_type(registry: &PortableRegistry, id: u32) -> Result<String, String> {
+    let ty = registry
+        .resolve(id)
+        .ok_or_else(|| format!("Unknown type id {id}"))?;
+    let is_u8 = |id: u32| {
+        matches!(
+            registry.resolve(id).map(|t| &t.type_def),
+            Some(TypeDef::<PortableForm>::Primitive(TypeDefPrimitive::U8))
+        )
+    };
+
+    match &ty.type_def {
+        TypeDef::Primitive(primitive) => Ok(match primitive {
+            TypeDefPrimitive::Bool => "bool",
+            TypeDefPrimitive::Str => "string",
+            TypeDefPrimitive::U8 => "uint8",
+            TypeDefPrimitive::U16 => "uint16",
+            TypeDefPrimitive::U32 => "uint32",
+            TypeDefPrimitive::U64 => "uint64",
+            TypeDefPrimitive::U128 => "uint128",
+            TypeDefPrimitive::U256 => "uint256",
+            TypeDefPrimitive::I8 => "int8",
+            TypeDefPrimitive::I16 => "int16",
+            TypeDefPrimitive::I32 => "int32",
+            TypeDefPrimitive::I64 => "int64",
+            TypeDefPrimitive::I128 => "int128",
+            TypeDefPrimitive::I256 => "int256",
+            TypeDefPrimitive::Char => return Err("`char` has no Solidity counterpart".into()),
+        }
+        .to_string()),
+        TypeDef::Sequence(seq) if is_u8(seq.type_param.id) => Ok("bytes".to_string()),
+        TypeDef::Array(array) if is_u8(array.type_param.id) && array.len == 20 => {
+            Ok("address".to_string())
+        }
+        TypeDef::Array(array) if is_u8(array.type_param.id) && array.len as usize <= WORD => {
+            Ok(format!("bytes{}", array.len))
+        }
+        TypeDef::Composite(composite) if composite.fields.len() == 1 => {
+            sol_type(registry, composite.fields[0].ty.id)
+        }
+        _ => Err(format!(
+            "Type {:?} has no supported Solidity counterpart",
+            ty.path
+        )),
+    }
+}
+
+/// Splits `name(type1,type2)` into the name and the argument types.
+fn parse_signature(signature: &str) -> Result<(&str, Vec<&str>), String> {
+    let (name, rest) = signature
+        .split_once('(')
+        .ok_or_else(|| format!("Invalid signature `{signature}`"))?;
+    let types = rest
+        .strip_suffix(')')
+        .ok_or_else(|| format!("Invalid signature `{signature}`"))?;
+    if types.contains(['(', ')', '[', ']']) {
+        return Err(format!(
+            "Arrays and tuples are not supported: `{signature}`"
+        ));
+    }
+    let types = match types.is_empty() {
+        true => vec![],
+        false => types.split(',').collect(),
+    };
+    Ok((name, types))
+}
+
+/// Encodes `args` as a tuple of `types` (head-tail encoding).
+fn encode_args<S: AsRef<str>>(types: &[&str], args: &[S]) -> Result<Vec<u8>, String> {
+    if types.len() != args.len() {
+        return Err(format!(
+            "Expected {} arguments, got {}",
+            types.len(),
+            args.len()
+        ));
+    }
+
+    let mut heads = vec![];
+    let mut tail = vec![];
+    for (ty, arg) in types.iter().zip(args) {
+        match encode_dynamic(ty, arg.as_ref())? {
+            Some(encoded) => {
+                heads.push(None);
+                tail.push(encoded);
+            }
+            None => heads.push(Some(encode_static(ty, arg.as_ref())?)),
+        }
+    }
+
+    let mut offset = heads.len() * WORD;
+    let mut tails = tail.iter();
+    let mut data = vec![];
+    for head in heads {
+        match head {
+            Some(word) => data.extend(word),
+            None => {
+                data.extend(uint_word(offset as u128));
+                offset += tails.next().expect("Every dynamic head has a tail").len();
+            }
+        }
+    }
+    data.extend(tail.into_iter().flatten());
+    Ok(data)
+}
+
+/// Encodes a dynamic value (length-prefixed and padded). Returns `None` for static types.
+fn encode_dynamic(ty: &str, arg: &str) -> Result<Option<Vec<u8>>, String> {
+    let bytes = match ty {
+        "bytes" => parse_hex(arg)?,
+        "string" => arg.trim_matches('"').as_bytes().to_vec(),
+        _ => return Ok(None),
+    };
+    let mut encoded = uint_word(bytes.len() as u128).to_vec();
+    encoded.extend(&bytes);
+    encoded.resize(WORD + bytes.len().div_ceil(WORD) * WORD, 0);
+    Ok(Some(encoded))
+}
+
+/// Encodes a static value as a single word.
+fn encode_static(ty: &str, arg: &str) -> Result<[u8; WORD], String> {
+    let invalid = || format!("Invalid `{ty}` value: `{arg}`");
+
+    if ty == "bool" {
+        return match arg {
+            "true" => Ok(uint_word(1)),
+            "false" => Ok(uint_word(0)),
+            _ => Err(invalid()),
+        };
+    }
+    if ty == "address" {
+        let bytes = parse_hex(arg)?;
+        if bytes.len() != 20 {
+            return Err(invalid());
+        }
+        let mut word = [0; WORD];
+        word[12..].copy_from_slice(&bytes);
+        return Ok(word);
+    }
+    if let Some(bits) = ty.strip_prefix("uint") {
+        let bits = bit_width(ty, bits)?;
+        let value = match arg.strip_prefix("0x") {
+            Some(hex) => u128::from_str_radix(hex, 16),
+            None => arg.replace('_', "").parse(),
+        }
+        .map_err(|_| invalid())?;
+        if bits < 128 && value >> bits != 0 {
+            return Err(format!("`{arg}` is out of range for `{ty}`"));
+        }
+        return Ok(uint_word(value));
+    }
+    if let Some(bits) = ty.strip_prefix("int") {
+        let bits = bit_width(ty, bits)?;
+        if arg.trim_start_matches('-').starts_with("0x") {
+            return Err(format!(
+                "Hex values are supported only for unsigned integers, not `{ty}`: `{arg}`"
+            ));
+        }
+        let value: i128 = arg.replace('_', "").parse().map_err(|_| invalid())?;
+        if bits < 128 && !(-(1 << (bits - 1))..1 << (bits - 1)).contains(&value) {
+            return Err(format!("`{arg}` is out of range for `{ty}`"));
+        }
+        let fill = if value < 0 { 0xff } else { 0 };
+        let mut word = [fill; WORD];
+        word[16..].copy_from_slice(&value.to_be_bytes());
+        return Ok(word);
+    }
+    if let Some(len) = ty.strip_prefix("bytes") {
+        let len: usize = len
+            .parse()
+            .map_err(|_| format!("Unsupported type `{ty}`"))?;
+        let bytes = parse_hex(arg)?;
+        if bytes.len() != len || len > WORD {
+            return Err(invalid());
+        }
+        let mut word = [0; WORD];
+        word[..len].copy_from_slice(&bytes);
+        return Ok(word);
+    }
+    Err(format!("Unsupported type `{ty}`"))
+}
+
+/// Parses the `N` of `uint<N>`/`int<N>` (256 if omitted). Values wider than 128 bits are not
+/// supported anyway, so only the widths up to 128 bits narrow the accepted range.
+fn bit_width(ty: &str, bits: &str) -> Result<u32, String> {
+    if bits.is_empty() {
+        return Ok(256);
+    }
+    match bits.parse() {
+        Ok(bits) if bits % 8 == 0 && (8..=256).contains(&bits) => Ok(bits),
+        _ => Err(format!("Unsupported type `{ty}`")),
+    }
+}
+
+fn uint_word(value: u128) -> [u8; WORD] {
+    let mut word = [0; WORD];
+    word[16..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+fn parse_hex(arg: &str) -> Result<Vec<u8>, String> {
+    let hex = arg.strip_prefix("0x").unwrap_or(arg);
+    if hex.len() % 2 != 0 {
+        return Err(format!("Invalid hex value: `{arg}`"));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("Invalid hex value: `{arg}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(value: u128) -> Vec<u8> {
+        uint_word(value).to_vec()
+    }
+
+    #[test]
+    fn dynamic_values_are_placed_after_the_heads() {
+        let data = encode_args(&["string", "uint8", "bytes"], &["ab", "7", "0x0102"]).unwrap();
+
+        let expected = [
+            // Heads: the offset of the string, the number and the offset of the bytes.
+            word(3 * 32),
+            word(7),
+            word(5 * 32),
+            // Tails: length-prefixed and padded to whole words.
+            word(2),
+            [b"ab".as_slice(), &[0; 30]].concat(),
+            word(2),
+            [[1, 2].as_slice(), &[0; 30]].concat(),
+        ]
+        .concat();
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn fixed_bytes_are_left_aligned() {
+        let encoded = encode_static("bytes4", "0xdeadbeef").unwrap();
+        assert_eq!(encoded[..4], [0xde, 0xad, 0xbe, 0xef]);
+        assert!(encoded[4..].iter().all(|b| *b == 0));
+
+        assert!(encode_static("bytes4", "0xdead").is_err());
+        assert!(encode_static("bytes33", &format!("0x{}", "00".repeat(33))).is_err());
+    }
+
+    #[test]
+    fn negative_ints_are_sign_extended() {
+        assert_eq!(encode_static("int8", "-1").unwrap(), [0xff; WORD]);
+
+        let encoded = encode_static("int256", "-2").unwrap();
+        assert!(encoded[..WORD - 1].iter().all(|b| *b == 0xff));
+        assert_eq!(encoded[WORD - 1], 0xfe);
+
+        assert_eq!(encode_static("int16", "2").unwrap(), uint_word(2));
+    }
+
+    #[test]
+    fn integers_are_checked_against_their_width() {
+        assert_eq!(encode_static("uint8", "255").unwrap(), uint_word(255));
+        assert!(encode_static("uint8", "256").is_err());
+        assert!(encode_static("uint16", "0x10000").is_err());
+        assert!(encode_static("uint", "0x10000").is_ok());
+
+        assert!(encode_static("int8", "-128").is_ok());
+        assert!(encode_static("int8", "127").is_ok());
+        assert!(encode_static("int8", "128").is_err());
+        assert!(encode_static("int8", "-129").is_err());
+
+        assert!(encode_static("uint7", "1").is_err());
+        assert!(encode_static("int264", "1").is_err());
+    }
+
+    #[test]
+    fn hex_is_rejected_for_signed_integers() {
+        let error = encode_static("int32", "0x10").unwrap_err();
+        assert!(error.contains("only for unsigned integers"), "{error}");
+        assert!(encode_static("int32", "-0x10").is_err());
+    }
+}
@@ -0,0 +1,96 @@
+//! Custom parsing of string arguments of particular types.
+//!
+//! `ContractMessageTranscoder` parses every argument according to its type in the contract
+//! metadata. For types with a non-obvious representation (e.g. a fixed-point newtype), you can
+//! register an encoder, which turns the string argument into a `Value` instead:
+//!
+//! ```rust, ignore
+//! session.register_arg_encoder("my_contract::FixedPoint", |arg| {
+//!     let scaled = (arg.parse::<f64>().map_err(|e| e.to_string())? * 1e6) as u128;
+//!     Ok(Value::UInt(scaled))
+//! });
+//! session.call::<_, ()>("set_price", &["1.5"], NO_ENDOWMENT)??;
+//! ```
+//!
+//! Encoders apply to the top-level arguments of messages and constructors (not to the fields of
+//! nested types), and only to contracts using the SCALE encoding.
+
+use std::collections::BTreeMap;
+
+use contract_transcode::{ContractMessageTranscoder, Value};
+use ink_sandbox::Sandbox;
+
+use crate::{
+    pallet_contracts::Config,
+    session::{error::SessionError, Session},
+};
+
+/// Turns a string argument into the `Value` to be encoded.
+pub type ArgEncoder = Box<dyn Fn(&str) -> Result<Value, String>>;
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config,
+{
+    /// Registers `encoder` for all the arguments whose type has the path `type_path` (segments
+    /// joined with `::`, as in the contract metadata, e.g. `my_contract::FixedPoint`). Replaces the
+    /// encoder registered for the same path before (if any).
+    pub fn register_arg_encoder(
+        &mut self,
+        type_path: &str,
+        encoder: impl Fn(&str) -> Result<Value, String> + 'static,
+    ) {
+        self.arg_encoders
+            .insert(type_path.to_string(), Box::new(encoder));
+    }
+}
+
+/// Returns `args` of the message (or the constructor) `label`, with the arguments of types that
+/// have an encoder in `encoders` substituted by the rendered `Value`s.
+pub(crate) fn apply_arg_encoders<S: AsRef<str>>(
+    encoders: &BTreeMap<String, ArgEncoder>,
+    transcoder: &ContractMessageTranscoder,
+    label: &str,
+    constructor: bool,
+    args: &[S],
+) -> Result<Vec<String>, SessionError> {
+    let mut args = args
+        .iter()
+        .map(|arg| arg.as_ref().to_string())
+        .collect::<Vec<_>>();
+    if encoders.is_empty() {
+        return Ok(args);
+    }
+
+    let spec = transcoder.metadata().spec();
+    let arg_types = match constructor {
+        true => spec
+            .constructors()
+            .iter()
+            .find(|c| c.label() == label)
+            .map(|c| c.args().iter().map(|a| a.ty().ty().id).collect::<Vec<_>>()),
+        false => spec
+            .messages()
+            .iter()
+            .find(|m| m.label() == label)
+            .map(|m| m.args().iter().map(|a| a.ty().ty().id).collect::<Vec<_>>()),
+    };
+    // Unknown labels are reported by the transcoder.
+    let Some(arg_types) = arg_types else {
+        return Ok(args);
+    };
+
+    let registry = transcoder.metadata().registry();
+    for (arg, type_id) in args.iter_mut().zip(arg_types) {
+        let Some(ty) = registry.resolve(type_id) else {
+            continue;
+        };
+        let path = ty.path.segments.join("::");
+        if let Some(encoder) = encoders.get(&path) {
+            *arg = encoder(arg)
+                .map_err(|err| SessionError::Encoding(format!("`{path}` argument: {err}")))?
+                .to_string();
+        }
+    }
+    Ok(args)
+}
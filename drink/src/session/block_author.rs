@@ -0,0 +1,72 @@
+//! Setting the author of the current block.
+//!
+//! `pallet-contracts` has no host function exposing the block author, so contracts can observe it
+//! only through a chain extension or runtime code reading the digest (e.g. a `FindAuthor`
+//! implementation, see `DigestAuthor`).
+//!
+//! ```rust, ignore
+//! session.set_block_author(bob.clone());
+//! assert_eq!(session.block_author(), Some(bob));
+//! session.sandbox().build_block();
+//! assert_eq!(session.block_author(), None);
+//! ```
+
+use frame_support::{
+    sp_runtime::{ConsensusEngineId, DigestItem},
+    traits::FindAuthor,
+};
+use ink_sandbox::{AccountIdFor, Sandbox};
+use parity_scale_codec::{Decode, Encode};
+
+use crate::{pallet_contracts::Config, session::Session};
+
+/// The engine id of the pre-runtime digest items carrying the block author set with
+/// `Session::set_block_author`.
+pub const BLOCK_AUTHOR_ENGINE_ID: ConsensusEngineId = *b"drnk";
+
+/// Finds the block author set with `Session::set_block_author` (the last one, if it has been set
+/// multiple times in the same block).
+///
+/// Use it as the `FindAuthor` of the runtime pallets that need the block author (e.g.
+/// `pallet_authorship`).
+pub struct DigestAuthor;
+
+impl<AccountId: Decode> FindAuthor<AccountId> for DigestAuthor {
+    fn find_author<'a, I>(digests: I) -> Option<AccountId>
+    where
+        I: 'a + IntoIterator<Item = (ConsensusEngineId, &'a [u8])>,
+    {
+        digests
+            .into_iter()
+            .filter(|(engine_id, _)| *engine_id == BLOCK_AUTHOR_ENGINE_ID)
+            .last()
+            .and_then(|(_, mut data)| AccountId::decode(&mut data).ok())
+    }
+}
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config,
+{
+    /// Sets the author of the current block, by depositing a pre-runtime digest item (see
+    /// `BLOCK_AUTHOR_ENGINE_ID`). The digest is reset with every new block, so the author has to
+    /// be set again after `build_block`.
+    pub fn set_block_author(&mut self, author: AccountIdFor<T::Runtime>) {
+        self.execute_with(|| {
+            frame_system::Pallet::<T::Runtime>::deposit_log(DigestItem::PreRuntime(
+                BLOCK_AUTHOR_ENGINE_ID,
+                author.encode(),
+            ))
+        });
+    }
+
+    /// Returns the author of the current block, if it has been set with `set_block_author`.
+    pub fn block_author(&mut self) -> Option<AccountIdFor<T::Runtime>> {
+        self.execute_with(|| {
+            let digest = frame_system::Pallet::<T::Runtime>::digest();
+            <DigestAuthor as FindAuthor<_>>::find_author(
+                digest.logs().iter().filter_map(DigestItem::as_pre_runtime),
+            )
+        })
+    }
+}
@@ -0,0 +1,127 @@
+//! Checking that a sequence of contract interactions fits in a single block.
+//!
+//! ```rust, ignore
+//! let mut session = Session::<MinimalSandbox>::default().with_block_budget(max_block_weight);
+//! session.call::<_, ()>("deposit", NO_ARGS, NO_ENDOWMENT)??;
+//! session.call::<_, ()>("withdraw", NO_ARGS, NO_ENDOWMENT)??;
+//! if let Some(left) = session.remaining_block_budget() {
+//!     println!("{} left", HumanWeight(left));
+//! }
+//! ```
+
+use frame_support::{sp_runtime::traits::UniqueSaturatedInto, weights::Weight};
+use ink_sandbox::Sandbox;
+
+use crate::{
+    pallet_contracts::Config,
+    record::WarningKind,
+    session::{error::SessionError, Session},
+};
+
+/// Weight consumed by the interactions in the current block.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct BlockBudget {
+    budget: Weight,
+    fail: bool,
+    /// The block that `used` refers to.
+    block: u64,
+    used: Weight,
+}
+
+impl BlockBudget {
+    /// Starts counting from scratch if `block` is not the one being accounted.
+    fn enter(&mut self, block: u64) {
+        if block != self.block {
+            self.block = block;
+            self.used = Weight::zero();
+        }
+    }
+}
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config,
+{
+    /// Enables block budget accounting and returns updated `self`.
+    ///
+    /// The weight consumed by the contract interactions (both the successful and the failed ones,
+    /// but not the dry runs) is summed up per block: the sum is reset whenever the session notices
+    /// that a new block has been built. An interaction making the sum exceed `max_weight` (in
+    /// either component) fails with `SessionError::BlockBudgetExceeded` - after it has been
+    /// executed and recorded, so its effects are not reverted. Use `set_fail_on_block_budget` to
+    /// record a warning instead.
+    pub fn with_block_budget(mut self, max_weight: Weight) -> Self {
+        let block = self.current_block();
+        self.block_budget = Some(BlockBudget {
+            budget: max_weight,
+            fail: true,
+            block,
+            used: Weight::zero(),
+        });
+        self
+    }
+
+    /// Sets whether exceeding the block budget fails the interaction (default) or just records a
+    /// `WarningKind::BlockBudgetExceeded`. Returns the previous setting.
+    ///
+    /// Panics if block budget accounting is not enabled (see `with_block_budget`).
+    pub fn set_fail_on_block_budget(&mut self, fail: bool) -> bool {
+        let budget = self
+            .block_budget
+            .as_mut()
+            .expect("Block budget accounting is not enabled");
+        std::mem::replace(&mut budget.fail, fail)
+    }
+
+    /// Returns the weight that the interactions can still consume in the current block, or `None`
+    /// if block budget accounting is not enabled (see `with_block_budget`).
+    pub fn remaining_block_budget(&mut self) -> Option<Weight> {
+        if self.block_budget.is_none() {
+            return None;
+        }
+        let block = self.current_block();
+        let budget = self.block_budget.as_mut()?;
+        budget.enter(block);
+        Some(budget.budget.saturating_sub(budget.used))
+    }
+
+    /// Adds the weight consumed by the last (just recorded) interaction to the block budget.
+    pub(crate) fn charge_block_budget(&mut self, consumed: Weight) -> Result<(), SessionError> {
+        let Some(budget) = &mut self.block_budget else {
+            return Ok(());
+        };
+        let block = self
+            .recorder
+            .record
+            .blocks()
+            .last()
+            .expect("The interaction should have been recorded")
+            .number;
+        budget.enter(block);
+        budget.used = budget.used.saturating_add(consumed);
+        if !budget.used.any_gt(budget.budget) {
+            return Ok(());
+        }
+
+        let (used, budget, fail) = (budget.used, budget.budget, budget.fail);
+        #[cfg(feature = "tracing-logs")]
+        tracing::warn!(?used, ?budget, "block budget exceeded");
+
+        match fail {
+            true => Err(SessionError::BlockBudgetExceeded { used, budget }),
+            false => {
+                self.recorder
+                    .record
+                    .push_warning(WarningKind::BlockBudgetExceeded { used, budget });
+                Ok(())
+            }
+        }
+    }
+
+    pub(crate) fn current_block(&mut self) -> u64 {
+        self.recorder
+            .sandbox
+            .execute_with(frame_system::Pallet::<T::Runtime>::block_number)
+            .unique_saturated_into()
+    }
+}
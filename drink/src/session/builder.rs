@@ -0,0 +1,350 @@
+//! Fluent configuration of a `Session`.
+
+use std::{
+    env::VarError,
+    marker::PhantomData,
+    sync::{Arc, RwLock},
+};
+
+use contract_transcode::ContractMessageTranscoder;
+use frame_support::weights::Weight;
+use ink_sandbox::{AccountIdFor, Sandbox};
+
+use crate::{
+    pallet_contracts::{Config, Determinism},
+    pallet_contracts_debugging::{CompositeInterceptor, InterceptingExt},
+    recorder::Recorder,
+    session::{
+        mock::{MockRegistry, MockingExtension},
+        transcoding::TranscoderRegistry,
+        BalanceOf, Session,
+    },
+};
+
+/// Environment variable overriding the default gas limit of the sessions, as `ref_time` or
+/// `ref_time,proof_size` (the proof size defaults to the one of `Sandbox::default_gas_limit`).
+pub const DEFAULT_GAS_LIMIT_ENV: &str = "DRINK_DEFAULT_GAS_LIMIT";
+
+/// Source of the sandbox for a `SessionBuilder`.
+pub trait SandboxSource<T> {
+    /// Returns the sandbox to be used by the session.
+    fn into_sandbox(self) -> T;
+}
+
+/// The sandbox will be created with `Default::default()`.
+pub struct SandboxFromDefault;
+
+impl<T: Default> SandboxSource<T> for SandboxFromDefault {
+    fn into_sandbox(self) -> T {
+        T::default()
+    }
+}
+
+/// An already constructed sandbox, passed to `SessionBuilder::sandbox`.
+pub struct ProvidedSandbox<T>(T);
+
+impl<T> SandboxSource<T> for ProvidedSandbox<T> {
+    fn into_sandbox(self) -> T {
+        self.0
+    }
+}
+
+/// Builder for a `Session`.
+///
+/// Every setting that is not explicitly configured falls back to the same default as in
+/// `Session::default()`:
+/// ```rust, no_run
+/// # use drink::{minimal::MinimalSandbox, session::SessionBuilder, AccountId32, Weight};
+/// let session = SessionBuilder::<MinimalSandbox>::new()
+///     .actor(AccountId32::new([2; 32]))
+///     .default_gas_limit(Weight::from_parts(100_000_000_000, 1024 * 1024))
+///     .storage_deposit_limit(1_000_000)
+///     .build();
+/// ```
+///
+/// The gas limit is resolved in the following order: the value passed to `default_gas_limit`, the
+/// `DRINK_DEFAULT_GAS_LIMIT` environment variable (see `DEFAULT_GAS_LIMIT_ENV`) and
+/// `Sandbox::default_gas_limit`.
+///
+/// If your sandbox needs some custom setup (or does not implement `Default` at all), you can pass
+/// it already constructed:
+/// ```rust, no_run
+/// # use drink::{minimal::MinimalSandbox, session::SessionBuilder};
+/// # fn my_sandbox() -> MinimalSandbox { MinimalSandbox::default() }
+/// let session = SessionBuilder::new().sandbox(my_sandbox()).build();
+/// ```
+pub struct SessionBuilder<T: Sandbox, S = SandboxFromDefault>
+where
+    T::Runtime: Config,
+{
+    sandbox: S,
+    actor: Option<AccountIdFor<T::Runtime>>,
+    gas_limit: Option<Weight>,
+    storage_deposit_limit: Option<BalanceOf<T::Runtime>>,
+    determinism: Determinism,
+    diagnostics: bool,
+    mocking: bool,
+    mocks_last: bool,
+    transcoders: Vec<(AccountIdFor<T::Runtime>, Arc<ContractMessageTranscoder>)>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Sandbox> SessionBuilder<T>
+where
+    T::Runtime: Config,
+{
+    /// Creates a new builder with the default configuration.
+    pub fn new() -> Self {
+        Self {
+            sandbox: SandboxFromDefault,
+            actor: None,
+            gas_limit: None,
+            storage_deposit_limit: None,
+            determinism: Determinism::Enforced,
+            diagnostics: true,
+            mocking: true,
+            mocks_last: false,
+            transcoders: vec![],
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: Sandbox> Default for SessionBuilder<T>
+where
+    T::Runtime: Config,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Sandbox, S> SessionBuilder<T, S>
+where
+    T::Runtime: Config,
+{
+    /// Uses an already constructed `sandbox` instead of creating a default one.
+    pub fn sandbox(self, sandbox: T) -> SessionBuilder<T, ProvidedSandbox<T>> {
+        SessionBuilder {
+            sandbox: ProvidedSandbox(sandbox),
+            actor: self.actor,
+            gas_limit: self.gas_limit,
+            storage_deposit_limit: self.storage_deposit_limit,
+            determinism: self.determinism,
+            diagnostics: self.diagnostics,
+            mocking: self.mocking,
+            mocks_last: self.mocks_last,
+            transcoders: self.transcoders,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Sets the actor (origin of all interactions).
+    pub fn actor(self, actor: AccountIdFor<T::Runtime>) -> Self {
+        Self {
+            actor: Some(actor),
+            ..self
+        }
+    }
+
+    /// Sets the default gas limit of the session's interactions. Takes precedence over the
+    /// `DRINK_DEFAULT_GAS_LIMIT` environment variable.
+    pub fn default_gas_limit(self, gas_limit: Weight) -> Self {
+        Self {
+            gas_limit: Some(gas_limit),
+            ..self
+        }
+    }
+
+    /// Sets the storage deposit limit. By default, there is no limit.
+    pub fn storage_deposit_limit(self, storage_deposit_limit: BalanceOf<T::Runtime>) -> Self {
+        Self {
+            storage_deposit_limit: Some(storage_deposit_limit),
+            ..self
+        }
+    }
+
+    /// Sets the determinism policy.
+    pub fn determinism(self, determinism: Determinism) -> Self {
+        Self {
+            determinism,
+            ..self
+        }
+    }
+
+    /// Enables or disables failure diagnostics (see `Session::with_diagnostics`).
+    pub fn diagnostics(self, diagnostics: bool) -> Self {
+        Self {
+            diagnostics,
+            ..self
+        }
+    }
+
+    /// Disables contract mocking.
+    ///
+    /// By default, the session registers an `InterceptingExt` on the sandbox, which is consulted
+    /// on every contract call (one extra runtime interface round trip per call). If you don't use
+    /// mocks, you can skip it. `Session::mocking_api` will panic for such a session, while your
+    /// own interceptors (`Session::add_interceptor`) still work.
+    pub fn without_mocking(self) -> Self {
+        Self {
+            mocking: false,
+            ..self
+        }
+    }
+
+    /// Sets whether the mocks are consulted after the interceptors added with
+    /// `Session::add_interceptor` (by default, they are consulted first). Either way, the first
+    /// one to intercept a call answers it.
+    pub fn mocks_last(self, mocks_last: bool) -> Self {
+        Self { mocks_last, ..self }
+    }
+
+    /// Registers a transcoder for a particular contract.
+    pub fn transcoder(
+        mut self,
+        contract_address: AccountIdFor<T::Runtime>,
+        transcoder: &Arc<ContractMessageTranscoder>,
+    ) -> Self {
+        self.transcoders
+            .push((contract_address, Arc::clone(transcoder)));
+        self
+    }
+
+    /// Builds the session.
+    ///
+    /// Panics if the gas limit is taken from `DRINK_DEFAULT_GAS_LIMIT` and the variable has an
+    /// invalid value.
+    pub fn build(self) -> Session<T>
+    where
+        S: SandboxSource<T>,
+    {
+        let mut sandbox = self.sandbox.into_sandbox();
+        let mut interceptors = None;
+        let mocks = self.mocking.then(|| {
+            let mocks = Arc::new(RwLock::new(MockRegistry::new()));
+            let composite = CompositeInterceptor::new();
+            composite.push(MockingExtension {
+                mock_registry: Arc::clone(&mocks),
+            });
+            sandbox.register_extension(InterceptingExt(Box::new(composite.clone())));
+            interceptors = Some(composite);
+            mocks
+        });
+
+        let mut session = Session {
+            recorder: Recorder::new(sandbox),
+            mocks,
+            mocks_last: self.mocks_last,
+            interceptors,
+            actor: self.actor.unwrap_or_else(T::default_actor),
+            gas_limit: self
+                .gas_limit
+                .unwrap_or_else(resolve_default_gas_limit::<T>),
+            deploy_gas_limit: None,
+            call_gas_limit: None,
+            gas_scale: (1, 1),
+            storage_deposit_limit: self.storage_deposit_limit,
+            determinism: self.determinism,
+            diagnostics: self.diagnostics,
+            unique_context: false,
+            evict_terminated_transcoders: false,
+            transcoders: TranscoderRegistry::new(),
+            abis: Default::default(),
+            names: Default::default(),
+            call_templates: Default::default(),
+            arg_encoders: Default::default(),
+            event_observers: Default::default(),
+            invariants: vec![],
+            hooks: vec![],
+            block_budget: None,
+            frozen_time: None,
+            metrics: None,
+            tracers: None,
+            scheduler: Default::default(),
+            forwarder: None,
+        };
+
+        #[cfg(feature = "tracing-logs")]
+        tracing::debug!(
+            actor = ?session.actor,
+            gas_limit = ?session.gas_limit,
+            determinism = ?session.determinism,
+            "session created"
+        );
+
+        for (contract_address, transcoder) in self.transcoders {
+            session.set_transcoder(contract_address, &transcoder);
+        }
+
+        session
+    }
+}
+
+/// Returns the gas limit from `DRINK_DEFAULT_GAS_LIMIT`, or `Sandbox::default_gas_limit` if the
+/// variable is not set. Panics if the variable is set, but is not valid.
+fn resolve_default_gas_limit<T: Sandbox>() -> Weight {
+    let default = T::default_gas_limit();
+    match std::env::var(DEFAULT_GAS_LIMIT_ENV) {
+        Ok(value) => parse_gas_limit(&value, default),
+        Err(VarError::NotPresent) => default,
+        Err(VarError::NotUnicode(value)) => {
+            panic!("Invalid value of `{DEFAULT_GAS_LIMIT_ENV}`: {value:?} is not valid unicode")
+        }
+    }
+}
+
+/// Parses `value` of `DRINK_DEFAULT_GAS_LIMIT`: `ref_time` (with the proof size of `default`) or
+/// `ref_time,proof_size`. Panics if `value` has any other form.
+fn parse_gas_limit(value: &str, default: Weight) -> Weight {
+    let parse = |part: &str| {
+        part.trim()
+            .replace('_', "")
+            .parse::<u64>()
+            .unwrap_or_else(|_| {
+                panic!(
+                    "Invalid value of `{DEFAULT_GAS_LIMIT_ENV}`: `{value}` (expected \
+                     `ref_time[,proof_size]`)"
+                )
+            })
+    };
+    match value.split_once(',') {
+        Some((ref_time, proof_size)) => Weight::from_parts(parse(ref_time), parse(proof_size)),
+        None => Weight::from_parts(parse(value), default.proof_size()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULT: Weight = Weight::from_parts(1_000, 2_000);
+
+    #[test]
+    fn ref_time_alone_keeps_the_default_proof_size() {
+        assert_eq!(
+            parse_gas_limit("500_000", DEFAULT),
+            Weight::from_parts(500_000, 2_000)
+        );
+    }
+
+    #[test]
+    fn ref_time_and_proof_size_are_both_parsed() {
+        assert_eq!(
+            parse_gas_limit(" 500_000 , 3_000 ", DEFAULT),
+            Weight::from_parts(500_000, 3_000)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid value of `DRINK_DEFAULT_GAS_LIMIT`: `500k`")]
+    fn invalid_values_are_rejected() {
+        parse_gas_limit("500k", DEFAULT);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid value of `DRINK_DEFAULT_GAS_LIMIT`")]
+    fn extra_parts_are_rejected() {
+        parse_gas_limit("1,2,3", DEFAULT);
+    }
+}
@@ -1,11 +1,20 @@
 //! This module provides simple utilities for loading and parsing `.contract` files in context of `drink` tests.
 
-use std::{path::PathBuf, sync::Arc};
+use std::{ops::RangeInclusive, path::PathBuf, sync::Arc};
 
 use contract_metadata::ContractMetadata;
 use contract_transcode::ContractMessageTranscoder;
+use serde_json::{Map, Value};
 
-use crate::{DrinkResult, Error};
+use crate::{
+    session::{abi::AbiKind, error::SessionError},
+    Error,
+};
+
+/// The ink! metadata versions that `ContractBundle` can parse. The bundles built by the test macros
+/// are checked against the same range.
+pub const SUPPORTED_METADATA_VERSIONS: RangeInclusive<u64> =
+    drink_test_macro::__supported_metadata_versions!();
 
 /// A struct representing the result of parsing a `.contract` bundle file.
 ///
@@ -20,28 +29,32 @@ pub struct ContractBundle {
     pub wasm: Vec<u8>,
     /// Transcoder derived from the ABI/metadata
     pub transcoder: Arc<ContractMessageTranscoder>,
+    /// ABI of the contract messages (SCALE, unless set otherwise with `with_abi`)
+    pub abi: AbiKind,
 }
 
 impl ContractBundle {
     /// Load and parse the information in a `.contract` bundle under `path`, producing a
     /// `ContractBundle` struct.
-    pub fn load<P>(path: P) -> DrinkResult<Self>
+    pub fn load<P>(path: P) -> Result<Self, SessionError>
     where
         P: AsRef<std::path::Path>,
     {
         let metadata: ContractMetadata = ContractMetadata::load(&path).map_err(|e| {
             Error::BundleLoadFailed(format!("Failed to load the contract file:\n{e:?}"))
         })?;
+        Self::from_metadata(metadata)
+    }
 
-        let ink_metadata = serde_json::from_value(serde_json::Value::Object(metadata.abi))
-            .map_err(|e| {
-                Error::BundleLoadFailed(format!(
-                    "Failed to parse metadata from the contract file:\n{e:?}"
-                ))
-            })?;
-
-        let transcoder = Arc::new(ContractMessageTranscoder::new(ink_metadata));
+    /// Parse the content of a `.contract` bundle, producing a `ContractBundle` struct.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SessionError> {
+        let metadata: ContractMetadata = serde_json::from_slice(bytes).map_err(|e| {
+            Error::BundleLoadFailed(format!("Failed to parse the contract file:\n{e:?}"))
+        })?;
+        Self::from_metadata(metadata)
+    }
 
+    fn from_metadata(metadata: ContractMetadata) -> Result<Self, SessionError> {
         let wasm = metadata
             .source
             .wasm
@@ -49,8 +62,32 @@ impl ContractBundle {
                 "Failed to get the WASM blob from the contract file".to_string(),
             ))?
             .0;
+        Self::from_parts(wasm, metadata.abi)
+    }
+
+    /// Creates a bundle out of the contract code and its ink! metadata (`abi`, i.e. the part of a
+    /// `.contract` file describing the contract interface).
+    pub(crate) fn from_parts(wasm: Vec<u8>, abi: Map<String, Value>) -> Result<Self, SessionError> {
+        // Check the version first: parsing metadata of another version fails with obscure errors.
+        check_metadata_version(&abi)?;
+
+        let ink_metadata = serde_json::from_value(Value::Object(abi)).map_err(|e| {
+            Error::BundleLoadFailed(format!(
+                "Failed to parse metadata from the contract file:\n{e:?}"
+            ))
+        })?;
 
-        Ok(Self { wasm, transcoder })
+        Ok(Self {
+            wasm,
+            transcoder: Arc::new(ContractMessageTranscoder::new(ink_metadata)),
+            abi: AbiKind::Scale,
+        })
+    }
+
+    /// Marks the contract as using `abi`, so that deploying it and calling it uses the right
+    /// encoding.
+    pub fn with_abi(self, abi: AbiKind) -> Self {
+        Self { abi, ..self }
     }
 
     /// Load the `.contract` bundle (`contract_file_name`) located in the `project_dir`` working directory.
@@ -65,6 +102,31 @@ impl ContractBundle {
     }
 }
 
+/// Fails with `SessionError::UnsupportedMetadataVersion` unless the ink! metadata `abi` has one of
+/// the `SUPPORTED_METADATA_VERSIONS`.
+fn check_metadata_version(abi: &Map<String, Value>) -> Result<(), SessionError> {
+    let found = match abi.get("version") {
+        Some(Value::Number(version)) => version.as_u64(),
+        Some(Value::String(version)) => version.parse().ok(),
+        // Before ink! 4, the metadata was nested under a version key, like `{"V3": {...}}`.
+        _ => abi
+            .keys()
+            .find_map(|key| key.strip_prefix('V')?.parse().ok()),
+    };
+
+    match found {
+        Some(version) if SUPPORTED_METADATA_VERSIONS.contains(&version) => Ok(()),
+        Some(version) => Err(SessionError::UnsupportedMetadataVersion {
+            found: version,
+            supported: SUPPORTED_METADATA_VERSIONS,
+        }),
+        None => Err(Error::BundleLoadFailed(
+            "Failed to determine the ink! metadata version of the contract file".to_string(),
+        )
+        .into()),
+    }
+}
+
 /// A convenience macro that allows you to load a bundle found in the target directory
 /// of the current project.
 #[macro_export]
@@ -0,0 +1,143 @@
+//! Calls that are repeated many times with mostly the same arguments.
+//!
+//! ```rust, ignore
+//! session.define_call_template(
+//!     "send",
+//!     CallTarget::Name("token".to_string()),
+//!     "PSP22::transfer",
+//!     vec![bob.to_string(), "1".to_string(), "[]".to_string()],
+//! )?;
+//! // Same as calling `PSP22::transfer` with `[bob, "5", "[]"]`.
+//! session.call_template::<Result<(), PSP22Error>>("send", &[(1, "5".to_string())])??;
+//! ```
+
+use std::collections::BTreeMap;
+
+use ink_sandbox::{AccountIdFor, Sandbox};
+use parity_scale_codec::Decode;
+use serde_json::json;
+
+use crate::{
+    errors::MessageResult,
+    pallet_contracts::Config,
+    session::{error::SessionError, Session},
+};
+
+/// The contract that a call template targets.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum CallTarget<AccountId> {
+    /// Contract under a fixed address.
+    Address(AccountId),
+    /// Contract registered under a name (see `Session::name_contract`), resolved on every call.
+    Name(String),
+}
+
+/// A message call with default arguments (see `Session::define_call_template`).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CallTemplate<AccountId> {
+    /// The called contract.
+    pub target: CallTarget<AccountId>,
+    /// Message label.
+    pub message: String,
+    /// Arguments used unless overridden.
+    pub default_args: Vec<String>,
+}
+
+impl<AccountId> CallTemplate<AccountId> {
+    /// Returns the default arguments with the positional `overrides` applied.
+    pub fn args_with(&self, overrides: &[(usize, String)]) -> Result<Vec<String>, SessionError> {
+        let mut args = self.default_args.clone();
+        for (position, value) in overrides {
+            let arg = args.get_mut(*position).ok_or_else(|| {
+                SessionError::InvalidTemplate(format!(
+                    "`{}` has {} arguments, cannot override argument {position}",
+                    self.message,
+                    self.default_args.len()
+                ))
+            })?;
+            *arg = value.clone();
+        }
+        Ok(args)
+    }
+}
+
+impl<AccountId: std::fmt::Display> CallTemplate<AccountId> {
+    /// Returns the template as JSON: the `target` (either an `address` or a `name`), the `message`
+    /// and the `default_args`.
+    pub fn to_json(&self) -> serde_json::Value {
+        let target = match &self.target {
+            CallTarget::Address(address) => json!({ "address": address.to_string() }),
+            CallTarget::Name(name) => json!({ "name": name }),
+        };
+        json!({
+            "target": target,
+            "message": self.message,
+            "default_args": self.default_args,
+        })
+    }
+}
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config,
+{
+    /// Defines a call template `name`: a call of `message` on `target` with `default_args`.
+    ///
+    /// The call is encoded right away, so that a misspelled message or invalid arguments are
+    /// reported here, rather than on the first use of the template. Redefining a template
+    /// replaces it.
+    pub fn define_call_template(
+        &mut self,
+        name: &str,
+        target: CallTarget<AccountIdFor<T::Runtime>>,
+        message: &str,
+        default_args: Vec<String>,
+    ) -> Result<(), SessionError> {
+        let template = CallTemplate {
+            target,
+            message: message.to_string(),
+            default_args,
+        };
+        let address = self.resolve_call_target(&template.target)?;
+        self.encode_message(&address, message, &template.default_args)
+            .map_err(|err| SessionError::InvalidTemplate(format!("`{name}`: {err}")))?;
+
+        self.call_templates.insert(name.to_string(), template);
+        Ok(())
+    }
+
+    /// Calls the template `name` (see `define_call_template`), with the positional `overrides`
+    /// merged into its default arguments.
+    pub fn call_template<V: Decode>(
+        &mut self,
+        name: &str,
+        overrides: &[(usize, String)],
+    ) -> Result<MessageResult<V>, SessionError> {
+        let template = self
+            .call_templates
+            .get(name)
+            .ok_or_else(|| SessionError::UnknownTemplate(name.to_string()))?;
+        let args = template.args_with(overrides)?;
+        let message = template.message.clone();
+        let address = self.resolve_call_target(&template.target)?;
+
+        self.call_internal(Some(address), &message, &args, None, true)
+    }
+
+    /// Returns all the call templates defined in the session.
+    pub fn call_templates(&self) -> &BTreeMap<String, CallTemplate<AccountIdFor<T::Runtime>>> {
+        &self.call_templates
+    }
+
+    pub(crate) fn resolve_call_target(
+        &self,
+        target: &CallTarget<AccountIdFor<T::Runtime>>,
+    ) -> Result<AccountIdFor<T::Runtime>, SessionError> {
+        match target {
+            CallTarget::Address(address) => Ok(address.clone()),
+            CallTarget::Name(name) => self
+                .address_of(name)
+                .ok_or_else(|| SessionError::UnknownName(name.to_string())),
+        }
+    }
+}
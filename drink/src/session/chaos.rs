@@ -0,0 +1,174 @@
+//! Failure injection: making chosen contract calls fail on purpose, to test how a system of
+//! contracts copes with them.
+//!
+//! ```rust, ignore
+//! // The third contract call (counting the nested ones) runs out of gas.
+//! session.inject_failure(FailurePolicy::OutOfGasAt(3));
+//! // The next call to the oracle traps.
+//! session.inject_failure(FailurePolicy::TrapCallee(oracle.clone()));
+//!
+//! let result = session.call::<_, ()>("rebalance", NO_ARGS, NO_ENDOWMENT);
+//! assert!(!session.record().injected_failures().is_empty());
+//! ```
+//!
+//! Available with the `chaos` feature.
+
+use ink_sandbox::{AccountIdFor, Sandbox};
+use parity_scale_codec::Encode;
+
+use crate::{
+    pallet_contracts::{self, debug::ExecResult, Config},
+    session::Session,
+};
+
+/// When and how an intercepted contract call is made to fail.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum FailurePolicy<AccountId> {
+    /// The `n`-th contract call (counted from 1, since the policy was injected) runs out of gas.
+    /// Both top-level and nested calls count; constructors don't.
+    OutOfGasAt(usize),
+    /// The next call to the contract at the given address traps.
+    TrapCallee(AccountId),
+}
+
+/// The kind of an injected failure.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InjectedFailureKind {
+    /// The call failed with `OutOfGas`.
+    OutOfGas,
+    /// The call failed with `ContractTrapped`.
+    Trap,
+}
+
+/// A failure injected into a contract call (see `Session::inject_failure`).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct InjectedFailure<AccountId> {
+    /// The index of the interaction, among all the contract interactions (as in
+    /// `Record::interactions`).
+    pub interaction: usize,
+    /// The contract whose call failed.
+    pub callee: AccountId,
+    /// How the call failed.
+    pub kind: InjectedFailureKind,
+}
+
+/// A policy waiting to be triggered, together with the (encoded) result of the failed call.
+#[derive(Clone)]
+struct ArmedPolicy<AccountId> {
+    policy: FailurePolicy<AccountId>,
+    /// The calls seen since the policy was injected.
+    calls: usize,
+    /// Encoded `Option<ExecResult>`, as returned by the intercepting extension.
+    result: Vec<u8>,
+}
+
+/// The failure policies of a session, consulted by the intercepting extension.
+#[derive(Clone)]
+pub(crate) struct FailureInjector<AccountId> {
+    policies: Vec<ArmedPolicy<AccountId>>,
+    /// The failures injected since the last interaction started.
+    injected: Vec<(AccountId, InjectedFailureKind)>,
+}
+
+impl<AccountId> Default for FailureInjector<AccountId> {
+    fn default() -> Self {
+        Self {
+            policies: vec![],
+            injected: vec![],
+        }
+    }
+}
+
+impl<AccountId: PartialEq + Clone> FailureInjector<AccountId> {
+    /// Checks the call to `callee` against the policies. Returns the encoded result of the failed
+    /// call, if a policy has been triggered (it is removed then).
+    pub fn intercept(&mut self, callee: &AccountId) -> Option<Vec<u8>> {
+        let mut triggered = None;
+        for (idx, armed) in self.policies.iter_mut().enumerate() {
+            armed.calls += 1;
+            let kind = match &armed.policy {
+                FailurePolicy::OutOfGasAt(n) if armed.calls >= *n => InjectedFailureKind::OutOfGas,
+                FailurePolicy::TrapCallee(address) if address == callee => {
+                    InjectedFailureKind::Trap
+                }
+                _ => continue,
+            };
+            if triggered.is_none() {
+                triggered = Some((idx, kind));
+            }
+        }
+
+        let (idx, kind) = triggered?;
+        self.injected.push((callee.clone(), kind));
+        Some(self.policies.remove(idx).result)
+    }
+}
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config,
+{
+    /// Registers `policy`: once its condition is met, the intercepted contract call fails and the
+    /// policy is removed. The injected failures are noted in the record (see
+    /// `Record::injected_failures`), so that they can be told apart from the organic ones.
+    ///
+    /// The failure is reported to the calling contract as its own, so it aborts the whole
+    /// interaction, just like a failure of the top-level call would.
+    ///
+    /// Failures are injected with the intercepting extension, so this panics if the session was
+    /// built with `SessionBuilder::without_mocking`. Dry runs trigger the policies as well.
+    pub fn inject_failure(&mut self, policy: FailurePolicy<AccountIdFor<T::Runtime>>) {
+        let error = match policy {
+            FailurePolicy::OutOfGasAt(_) => pallet_contracts::Error::<T::Runtime>::OutOfGas,
+            FailurePolicy::TrapCallee(_) => pallet_contracts::Error::<T::Runtime>::ContractTrapped,
+        };
+        let result: ExecResult = Err(error.into());
+        let armed = ArmedPolicy {
+            policy,
+            calls: 0,
+            result: Some(result).encode(),
+        };
+        self.update_failure_injector(|injector| injector.policies.push(armed));
+    }
+
+    /// Removes the policies that have not been triggered yet.
+    pub fn clear_failure_policies(&mut self) {
+        self.update_failure_injector(|injector| injector.policies.clear());
+    }
+
+    /// Forgets the failures injected in the previous dry runs.
+    pub(crate) fn start_failure_injection(&mut self) {
+        if self.mocks.is_some() {
+            self.update_failure_injector(|injector| injector.injected.clear());
+        }
+    }
+
+    /// Notes the failures injected during the last interaction in the record.
+    pub(crate) fn record_injected_failures(&mut self) {
+        if self.mocks.is_none() {
+            return;
+        }
+        let injected =
+            self.update_failure_injector(|injector| std::mem::take(&mut injector.injected));
+        for (callee, kind) in injected {
+            #[cfg(feature = "tracing-logs")]
+            tracing::warn!(callee = ?callee, kind = ?kind, "failure injected");
+
+            self.recorder.record.push_injected_failure(callee, kind);
+        }
+    }
+
+    fn update_failure_injector<V>(
+        &self,
+        f: impl FnOnce(&mut FailureInjector<AccountIdFor<T::Runtime>>) -> V,
+    ) -> V {
+        let mocks = self.mocks.as_ref().expect(
+            "Failure injection is not available for this session (it was built with \
+             `SessionBuilder::without_mocking`)",
+        );
+        f(mocks
+            .write()
+            .expect("Should be able to acquire lock on registry")
+            .failure_injector())
+    }
+}
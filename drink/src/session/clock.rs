@@ -0,0 +1,85 @@
+//! Freezing the chain timestamp, so that block-number logic can be tested in isolation from time
+//! logic.
+//!
+//! ```rust, ignore
+//! session.freeze_time();
+//! let frozen = session.now();
+//! session.advance_blocks(100);
+//! assert_eq!(session.now(), frozen);
+//! ```
+
+use frame_support::{sp_runtime::traits::UniqueSaturatedInto, traits::Time};
+use ink_sandbox::{api::prelude::*, Sandbox};
+
+use crate::{pallet_contracts::Config, pallet_timestamp, session::Session};
+
+/// The timestamp that the chain is kept at.
+pub(crate) struct FrozenTime<T> {
+    moment: u64,
+    /// Sets the timestamp in the sandbox. Stored here, since only `freeze_time` knows that the
+    /// runtime includes `pallet_timestamp`.
+    pin: fn(&mut T, u64),
+}
+
+fn pin_timestamp<T: Sandbox>(sandbox: &mut T, moment: u64)
+where
+    T::Runtime: pallet_timestamp::Config,
+{
+    sandbox.set_timestamp(moment.unique_saturated_into());
+}
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config + pallet_timestamp::Config,
+{
+    /// Freezes the chain timestamp at its current value: building new blocks (be it with
+    /// `advance_blocks`, the sandbox API or in the unique context mode) no longer moves it, and
+    /// contracts observe the frozen value.
+    pub fn freeze_time(&mut self) {
+        let moment = self.now();
+        self.frozen_time = Some(FrozenTime {
+            moment,
+            pin: pin_timestamp::<T>,
+        });
+    }
+}
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config,
+{
+    /// Lets the timestamp move with new blocks again (see `freeze_time`). The current timestamp
+    /// stays frozen until the next block is built.
+    pub fn unfreeze_time(&mut self) {
+        self.frozen_time = None;
+    }
+
+    /// Whether the timestamp is frozen (see `freeze_time`).
+    pub fn is_time_frozen(&self) -> bool {
+        self.frozen_time.is_some()
+    }
+
+    /// Returns the timestamp of the current block (as set in the runtime, e.g. in milliseconds).
+    pub fn now(&mut self) -> u64 {
+        self.pin_frozen_time();
+        self.execute_with(|| <T::Runtime as Config>::Time::now().unique_saturated_into())
+    }
+
+    /// Builds `count` new blocks, keeping the timestamp if it is frozen (see `freeze_time`). The
+    /// calls scheduled for every block are executed right after it is built (see
+    /// `schedule_call`).
+    pub fn advance_blocks(&mut self, count: u32) {
+        for _ in 0..count {
+            self.recorder.build_block();
+            self.pin_frozen_time();
+            self.run_scheduled_calls();
+        }
+    }
+
+    /// Brings the timestamp back to the frozen value, in case a block has been built since.
+    pub(crate) fn pin_frozen_time(&mut self) {
+        if let Some(FrozenTime { moment, pin }) = &self.frozen_time {
+            pin(&mut self.recorder.sandbox, *moment);
+        }
+    }
+}
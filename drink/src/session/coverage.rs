@@ -0,0 +1,78 @@
+//! Which contract messages were called during the session, and with what outcome.
+//!
+//! ```rust, ignore
+//! let uncovered = session.record().report_uncovered(&address, &bundle.transcoder);
+//! assert!(uncovered.is_empty(), "Messages never called: {uncovered:?}");
+//! ```
+
+use std::collections::BTreeMap;
+
+use contract_transcode::ContractMessageTranscoder;
+use ink_sandbox::AccountIdFor;
+
+use crate::{
+    pallet_contracts::Config,
+    record::Record,
+    session::replay::{InteractionKind, RecordedInteraction},
+};
+
+/// How many times a message was called, and how the calls ended.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct CallStats {
+    /// All the calls.
+    pub calls: usize,
+    /// The calls that neither reverted nor failed.
+    pub successes: usize,
+    /// The calls in which the contract reverted.
+    pub reverts: usize,
+    /// The calls that were aborted by the pallet (e.g. ran out of gas or trapped).
+    pub failures: usize,
+}
+
+impl<R: Config> Record<R> {
+    /// Returns the statistics of the contract calls performed by the session, per callee and
+    /// message label.
+    ///
+    /// Only calls made through the session are counted (neither nested calls between contracts,
+    /// nor raw calls with already encoded input).
+    pub fn call_coverage(&self) -> BTreeMap<(AccountIdFor<R>, String), CallStats> {
+        let mut coverage = BTreeMap::<_, CallStats>::new();
+        for RecordedInteraction { kind, result, .. } in self.interactions() {
+            let InteractionKind::Call {
+                address, message, ..
+            } = kind
+            else {
+                continue;
+            };
+
+            let stats = coverage
+                .entry((address.clone(), message.clone()))
+                .or_default();
+            stats.calls += 1;
+            match (result.failed, result.reverted) {
+                (true, _) => stats.failures += 1,
+                (false, true) => stats.reverts += 1,
+                (false, false) => stats.successes += 1,
+            }
+        }
+        coverage
+    }
+
+    /// Returns the labels of the messages from the metadata in `transcoder` that were never called
+    /// on the contract at `address`.
+    pub fn report_uncovered(
+        &self,
+        address: &AccountIdFor<R>,
+        transcoder: &ContractMessageTranscoder,
+    ) -> Vec<String> {
+        let coverage = self.call_coverage();
+        transcoder
+            .metadata()
+            .spec()
+            .messages()
+            .iter()
+            .map(|message| message.label().to_string())
+            .filter(|label| !coverage.contains_key(&(address.clone(), label.clone())))
+            .collect()
+    }
+}
@@ -0,0 +1,158 @@
+//! Diagnosing failed contract interactions.
+//!
+//! When an interaction fails because of too low a limit (gas or storage deposit), we repeat it as a
+//! dry run without limits, with the same origin and input, so that the error can tell the user how
+//! much was actually needed. When a call traps, the error carries the panic message and the debug
+//! output of the contract instead.
+
+use frame_support::{
+    sp_runtime::{traits::UniqueSaturatedInto, DispatchError},
+    weights::Weight,
+};
+use ink_sandbox::{
+    api::{contracts_api::decode_debug_buffer, prelude::*},
+    AccountIdFor, Sandbox,
+};
+
+use crate::{
+    pallet_contracts::{Config, Error as ContractsError, StorageDeposit},
+    session::{error::SessionError, BalanceOf, Session},
+};
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config,
+{
+    /// Translates a failed deployment into a `SessionError`, enriching it with diagnostics if
+    /// `input` (contract code, constructor data and salt) is available.
+    pub(super) fn diagnose_deployment_failure(
+        &mut self,
+        err: DispatchError,
+        endowment: BalanceOf<T::Runtime>,
+        input: Option<(Vec<u8>, Vec<u8>, Vec<u8>)>,
+    ) -> SessionError {
+        let Some(exhausted) = self.exhausted_limit(&err) else {
+            return SessionError::DeploymentFailed(err);
+        };
+        let Some((contract_bytes, data, salt)) = input else {
+            return SessionError::DeploymentFailed(err);
+        };
+
+        let gas_limit = self.max_block_weight();
+        let result = self.recorder.dry_run(|sandbox| {
+            sandbox.deploy_contract(
+                contract_bytes,
+                endowment,
+                data,
+                salt,
+                self.actor.clone(),
+                gas_limit,
+                None,
+            )
+        });
+
+        let limit = self.effective_deploy_gas_limit();
+        self.limit_error(
+            exhausted,
+            limit,
+            result.gas_required,
+            result.storage_deposit,
+        )
+    }
+
+    /// Translates a failed call into a `SessionError`, enriching it with diagnostics if `input`
+    /// (callee and call data) is available. Traps are explained with `debug_message` (the debug
+    /// buffer of the call).
+    pub(super) fn diagnose_call_failure(
+        &mut self,
+        err: DispatchError,
+        endowment: BalanceOf<T::Runtime>,
+        input: Option<(AccountIdFor<T::Runtime>, Vec<u8>)>,
+        debug_message: &[u8],
+    ) -> SessionError {
+        if err == ContractsError::<T::Runtime>::ContractTrapped.into() {
+            return trap_error(debug_message);
+        }
+        let Some(exhausted) = self.exhausted_limit(&err) else {
+            return SessionError::CallFailed(err);
+        };
+        let Some((address, data)) = input else {
+            return SessionError::CallFailed(err);
+        };
+
+        let gas_limit = self.max_block_weight();
+        let result = self.recorder.dry_run(|sandbox| {
+            sandbox.call_contract(
+                address,
+                endowment,
+                data,
+                self.actor.clone(),
+                gas_limit,
+                None,
+                self.determinism,
+            )
+        });
+
+        let limit = self.effective_call_gas_limit();
+        self.limit_error(
+            exhausted,
+            limit,
+            result.gas_required,
+            result.storage_deposit,
+        )
+    }
+
+    /// Checks whether `err` was caused by one of the session limits.
+    fn exhausted_limit(&self, err: &DispatchError) -> Option<ExhaustedLimit<T::Runtime>> {
+        if *err == ContractsError::<T::Runtime>::OutOfGas.into() {
+            Some(ExhaustedLimit::Gas)
+        } else if *err == ContractsError::<T::Runtime>::StorageDepositLimitExhausted.into() {
+            self.storage_deposit_limit
+                .map(ExhaustedLimit::StorageDeposit)
+        } else {
+            None
+        }
+    }
+
+    /// Builds the error for `exhausted` limit, given the gas limit of the failed interaction and
+    /// the requirements measured by a dry run.
+    fn limit_error(
+        &self,
+        exhausted: ExhaustedLimit<T::Runtime>,
+        gas_limit: Weight,
+        gas_required: Weight,
+        storage_deposit: StorageDeposit<BalanceOf<T::Runtime>>,
+    ) -> SessionError {
+        match exhausted {
+            ExhaustedLimit::Gas => SessionError::GasLimitTooLow {
+                limit: gas_limit,
+                required: gas_required,
+            },
+            ExhaustedLimit::StorageDeposit(limit) => SessionError::StorageDepositTooLow {
+                limit: limit.unique_saturated_into(),
+                required: storage_deposit.charge_or_zero().unique_saturated_into(),
+            },
+        }
+    }
+}
+
+/// Builds `SessionError::ContractTrapped` from the debug buffer of the trapped call. The panic
+/// message is everything from the `panicked at` line on (it may span multiple lines).
+fn trap_error(debug_message: &[u8]) -> SessionError {
+    let last_debug_output = decode_debug_buffer(debug_message);
+    let message = last_debug_output
+        .iter()
+        .rposition(|line| line.starts_with("panicked at"))
+        .map(|start| last_debug_output[start..].join("\n"));
+
+    SessionError::ContractTrapped {
+        message,
+        last_debug_output,
+    }
+}
+
+/// The session limit that caused an interaction to fail.
+enum ExhaustedLimit<R: Config> {
+    Gas,
+    StorageDeposit(BalanceOf<R>),
+}
@@ -1,10 +1,15 @@
 //! Module exposing errors and result types for the session API.
 
-use frame_support::sp_runtime::DispatchError;
+use std::ops::RangeInclusive;
+
+use frame_support::{sp_runtime::DispatchError, weights::Weight};
 use parity_scale_codec::Decode;
 use thiserror::Error;
 
-use crate::errors::MessageResult;
+use crate::{
+    errors::{LangError, MessageResult},
+    weights::HumanWeight,
+};
 
 /// Session specific errors.
 #[derive(Clone, Error, Debug)]
@@ -21,24 +26,163 @@ pub enum SessionError {
     /// Deployment has been reverted by the contract.
     #[error("Contract deployment has been reverted")]
     DeploymentReverted,
+    /// Deployment has been reverted by ink! before the constructor ran (e.g. because the input
+    /// could not be decoded).
+    #[error("Contract deployment has been reverted by ink!: {0}")]
+    DeploymentLangError(LangError),
     /// Deployment failed (aborted by the pallet).
     #[error("Contract deployment failed before execution: {0:?}")]
     DeploymentFailed(DispatchError),
+    /// The contract bundle has been produced for an ink! metadata version that cannot be parsed
+    /// (see `SUPPORTED_METADATA_VERSIONS`).
+    #[error(
+        "The contract bundle has ink! metadata version {found}, but only versions {}-{} are \
+         supported. Rebuild the contract with a compatible `cargo-contract`",
+        .supported.start(),
+        .supported.end()
+    )]
+    UnsupportedMetadataVersion {
+        /// The version found in the bundle.
+        found: u64,
+        /// The supported versions.
+        supported: RangeInclusive<u64>,
+    },
     /// Code upload failed (aborted by the pallet).
     #[error("Code upload failed: {0:?}")]
     UploadFailed(DispatchError),
+    /// Replacing the code of a contract failed (aborted by the pallet).
+    #[error("Contract redeployment failed: {0:?}")]
+    RedeploymentFailed(DispatchError),
+    /// Code upload failed, because the code exceeds the maximum code size of the runtime.
+    #[error("Code upload failed: the code is too large")]
+    CodeTooLarge,
+    /// Code upload failed, because the code did not pass the validation of the runtime. Most
+    /// often, it declares more memory pages than the runtime allows - in that case rebuild the
+    /// contract with a lower `max_memory_pages` (see `#[drink::test]`).
+    #[error("Code upload failed: the code has been rejected (e.g. it uses too many memory pages)")]
+    CodeRejected,
     /// Call has been reverted by the contract.
     #[error("Contract call has been reverted. Encoded error: {0:?}")]
     CallReverted(Vec<u8>),
     /// Contract call failed (aborted by the pallet).
     #[error("Contract call failed before execution: {0:?}")]
     CallFailed(DispatchError),
+    /// Contract call trapped (e.g. the contract panicked or hit `unreachable`). Built from the
+    /// debug buffer of the call, so the details are available only for contracts built in debug
+    /// mode.
+    #[error(
+        "Contract trapped: {}",
+        .message.as_deref().unwrap_or("no panic message (is the contract built in debug mode?)")
+    )]
+    ContractTrapped {
+        /// The panic message of the contract, if it has printed one.
+        message: Option<String>,
+        /// The lines of the debug buffer of the failed call.
+        last_debug_output: Vec<String>,
+    },
+    /// Interaction failed because the storage deposit limit was too low. `required` is the
+    /// deposit that a dry run without the limit has charged.
+    #[error("Storage deposit limit too low: limit is {limit}, but {required} is required")]
+    StorageDepositTooLow {
+        /// The storage deposit limit that was set for the interaction.
+        limit: u128,
+        /// The storage deposit that the interaction requires.
+        required: u128,
+    },
+    /// Interaction ran out of gas. `required` is the weight that a dry run with the whole block
+    /// weight available has required.
+    #[error(
+        "Gas limit too low: limit is {}, but {} is required",
+        HumanWeight(*.limit),
+        HumanWeight(*.required)
+    )]
+    GasLimitTooLow {
+        /// The gas limit that was set for the interaction.
+        limit: Weight,
+        /// The weight that the interaction requires.
+        required: Weight,
+    },
     /// There is no deployed contract to call.
     #[error("No deployed contract")]
     NoContract,
+    /// There is no contract at the given address.
+    #[error("No contract at address {0}")]
+    ContractNotFound(String),
     /// There is no registered transcoder to encode/decode messages for the called contract.
-    #[error("Missing transcoder")]
-    NoTranscoder,
+    #[error(
+        "Missing transcoder for {address} (transcoders are registered for: [{}])",
+        .registered.join(", ")
+    )]
+    NoTranscoder {
+        /// The contract without a transcoder.
+        address: String,
+        /// The contracts that have a transcoder registered.
+        registered: Vec<String>,
+    },
+    /// The contract name is already registered in the session.
+    #[error("Contract name `{0}` is already taken")]
+    NameTaken(String),
+    /// There is no contract registered under the name.
+    #[error("No contract registered under the name `{0}`")]
+    UnknownName(String),
+    /// The deployments of a system manifest refer to each other in a cycle (see
+    /// `Session::deploy_system`).
+    #[error("Cyclic references between deployments: {}", .0.join(" -> "))]
+    CyclicDeployments(Vec<String>),
+    /// A deployment of a system manifest failed (see `Session::deploy_system`).
+    #[error("Deployment of `{name}` failed: {error}")]
+    SystemDeploymentFailed {
+        /// The name of the failed deployment.
+        name: String,
+        /// The reason of the failure.
+        error: Box<SessionError>,
+    },
+    /// There is no call template defined under the name.
+    #[error("No call template defined under the name `{0}`")]
+    UnknownTemplate(String),
+    /// A call template cannot be defined or used as requested.
+    #[error("Invalid call template: {0}")]
+    InvalidTemplate(String),
+    /// A registered invariant does not hold after a contract interaction.
+    #[error("Invariant violated: {0}")]
+    InvariantViolated(String),
+    /// None of the attempts of `Session::call_until` has met the postcondition.
+    #[error("Call to `{message}` has not met the postcondition in {attempts} attempts")]
+    AttemptsExhausted {
+        /// The called message.
+        message: String,
+        /// The number of attempts.
+        attempts: u32,
+    },
+    /// A contract has been re-entered within a single interaction (see
+    /// `Session::set_fail_on_reentrancy`).
+    #[error("Contract re-entered within a single interaction: {}", .chain.join(" -> "))]
+    ReentrancyDetected {
+        /// The active call chain, from the outermost callee to the re-entered contract.
+        chain: Vec<String>,
+    },
+    /// Terminating a contract from outside (see `Session::terminate_all_instances_of`) failed.
+    #[error("Contract termination failed: {0}")]
+    TerminationFailed(String),
+    /// A hook registered with `Session::add_hook` has rejected the interaction.
+    #[error("Interaction rejected by a hook: {0}")]
+    HookRejected(String),
+    /// The contract interactions have consumed more weight than the block budget (see
+    /// `Session::with_block_budget`).
+    #[error(
+        "Block budget exceeded: used {}, but the budget is {}",
+        HumanWeight(*.used),
+        HumanWeight(*.budget)
+    )]
+    BlockBudgetExceeded {
+        /// The weight consumed in the current block, including the last interaction.
+        used: Weight,
+        /// The block budget.
+        budget: Weight,
+    },
+    /// Exporting or importing the sandbox state failed.
+    #[error("State export/import failed: {0}")]
+    StateDump(String),
 }
 
 impl SessionError {
@@ -0,0 +1,162 @@
+//! Callbacks invoked for the contract events emitted during the session.
+//!
+//! ```rust, ignore
+//! let flips = Rc::new(RefCell::new(vec![]));
+//! let collected = Rc::clone(&flips);
+//! let handle = session.on_contract_event(Some(address), move |event| {
+//!     collected.borrow_mut().push(event)
+//! });
+//! session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+//! assert_eq!(flips.borrow().len(), 1);
+//! session.remove_event_observer(handle);
+//! ```
+
+use std::panic::{self, AssertUnwindSafe};
+
+use contract_transcode::Value;
+use frame_system::Config as SysConfig;
+use ink_sandbox::{pallet_contracts, AccountIdFor, Sandbox};
+use parity_scale_codec::Encode;
+
+use crate::{
+    record::EventBatch,
+    session::{transcoding::TranscoderRegistry, Session},
+};
+
+/// Identifies an observer registered with `Session::on_contract_event`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ObserverHandle(u64);
+
+/// Calls the observer for every matching event of the batch. Events are decoded with the
+/// transcoders of the emitting contracts.
+type EventObserver<R> = Box<dyn FnMut(&EventBatch<R>, &TranscoderRegistry<AccountIdFor<R>>, &str)>;
+
+/// The observers registered in a session.
+#[derive(frame_support::DefaultNoBound)]
+pub(crate) struct EventObservers<R: pallet_contracts::Config> {
+    next_id: u64,
+    observers: Vec<(ObserverHandle, EventObserver<R>)>,
+}
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: pallet_contracts::Config,
+    <T::Runtime as SysConfig>::RuntimeEvent: TryInto<pallet_contracts::Event<T::Runtime>>,
+{
+    /// Registers `observer`, called synchronously after every contract interaction, once for each
+    /// decoded contract event emitted by the contract at `address` (or by any contract, if
+    /// `address` is `None`). Events that cannot be decoded with the transcoder of the emitting
+    /// contract (or that have no signature topic) are skipped.
+    ///
+    /// If the observer panics, the panic is propagated with a message saying which interaction
+    /// and event triggered it.
+    pub fn on_contract_event(
+        &mut self,
+        address: Option<AccountIdFor<T::Runtime>>,
+        observer: impl FnMut(Value) + 'static,
+    ) -> ObserverHandle {
+        let handle = ObserverHandle(self.event_observers.next_id);
+        self.event_observers.next_id += 1;
+        self.event_observers
+            .observers
+            .push((handle, event_observer(address, observer)));
+        handle
+    }
+}
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: pallet_contracts::Config,
+{
+    /// Unregisters the observer registered with `on_contract_event`. Returns `false` if there was
+    /// no such observer (e.g. it had already been removed).
+    pub fn remove_event_observer(&mut self, handle: ObserverHandle) -> bool {
+        let observers = &mut self.event_observers.observers;
+        let before = observers.len();
+        observers.retain(|(other, _)| *other != handle);
+        observers.len() != before
+    }
+
+    /// Passes the events of the last interaction (`interaction`) to all the registered observers.
+    pub(crate) fn notify_event_observers(&mut self, interaction: impl FnOnce() -> String) {
+        if self.event_observers.observers.is_empty() {
+            return;
+        }
+        let interaction = interaction();
+        let batch = self.recorder.record.last_event_batch();
+        for (_, observer) in &mut self.event_observers.observers {
+            observer(batch, &self.transcoders, &interaction);
+        }
+    }
+}
+
+/// Wraps `observer` into a callback going through the contract events of a batch.
+fn event_observer<R: pallet_contracts::Config>(
+    address: Option<AccountIdFor<R>>,
+    mut observer: impl FnMut(Value) + 'static,
+) -> EventObserver<R>
+where
+    <R as SysConfig>::RuntimeEvent: TryInto<pallet_contracts::Event<R>>,
+{
+    Box::new(
+        move |batch: &EventBatch<R>,
+              transcoders: &TranscoderRegistry<AccountIdFor<R>>,
+              interaction: &str| {
+            for (idx, decoded) in decode_contract_events(batch, transcoders, address.as_ref()) {
+                let description = format!("{decoded}");
+                if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| observer(decoded))) {
+                    let reason = payload
+                        .downcast_ref::<&str>()
+                        .map(|reason| reason.to_string())
+                        .or_else(|| payload.downcast_ref::<String>().cloned())
+                        .unwrap_or_default();
+                    panic!(
+                        "Contract event observer panicked on event #{idx} ({description}) of \
+                         {interaction}: {reason}"
+                    );
+                }
+            }
+        },
+    )
+}
+
+/// Decodes the contract events of `batch` emitted by the contract at `address` (or by any
+/// contract, if `address` is `None`), using the transcoders of the emitting contracts. Returns
+/// every decoded event together with its index in the batch. Events that cannot be decoded (or
+/// that have no signature topic) are skipped.
+pub(crate) fn decode_contract_events<R: pallet_contracts::Config>(
+    batch: &EventBatch<R>,
+    transcoders: &TranscoderRegistry<AccountIdFor<R>>,
+    address: Option<&AccountIdFor<R>>,
+) -> Vec<(usize, Value)>
+where
+    <R as SysConfig>::RuntimeEvent: TryInto<pallet_contracts::Event<R>>,
+{
+    let mut decoded_events = vec![];
+    for (idx, record) in batch.all_events().iter().enumerate() {
+        let Ok(pallet_contracts::Event::<R>::ContractEmitted { contract, data }) =
+            record.event.clone().try_into()
+        else {
+            continue;
+        };
+        if address.is_some_and(|address| *address != contract) {
+            continue;
+        }
+        let Some(transcoder) = transcoders.get(&contract) else {
+            continue;
+        };
+        // The first topic of a (non-anonymous) ink! event is its signature.
+        let Some(Ok(topic)) = record
+            .topics
+            .first()
+            .map(|topic| <[u8; 32]>::try_from(topic.as_ref()))
+        else {
+            continue;
+        };
+        // The transcoder expects the data as stored by the runtime (length-prefixed).
+        if let Ok(decoded) = transcoder.decode_contract_event(&topic, &mut &*data.encode()) {
+            decoded_events.push((idx, decoded));
+        }
+    }
+    decoded_events
+}
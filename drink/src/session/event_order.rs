@@ -0,0 +1,101 @@
+//! Checking that a contract has emitted events in the expected order.
+//!
+//! ```rust, ignore
+//! session.call::<_, ()>("transfer_from", &args, NO_ENDOWMENT)??;
+//! session.assert_events_in_order(&token, &[
+//!     ("Approval", None),
+//!     ("Transfer", Some(vec![("value", Value::UInt(10))])),
+//! ]);
+//! ```
+
+use contract_transcode::Value;
+use frame_system::Config as SysConfig;
+use ink_sandbox::{pallet_contracts, AccountIdFor, Sandbox};
+
+use crate::session::{event_observers::decode_contract_events, Session};
+
+/// An expected event: its name and, optionally, the values of (some of) its fields.
+pub type ExpectedEvent<'a> = (&'a str, Option<Vec<(&'a str, Value)>>);
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: pallet_contracts::Config,
+    <T::Runtime as SysConfig>::RuntimeEvent: TryInto<pallet_contracts::Event<T::Runtime>>,
+{
+    /// Panics unless the events emitted by the contract at `address` in the last interaction
+    /// contain `expected` in order - not necessarily one right after another. An expected event
+    /// matches an emitted one with the same name and, unless its fields are `None`, the same
+    /// values of the listed fields (other fields are not compared).
+    ///
+    /// Events are decoded with the transcoder of the contract; the ones that cannot be decoded
+    /// are ignored. The failure message lists both the expected and the actual sequence.
+    #[track_caller]
+    pub fn assert_events_in_order(
+        &self,
+        address: &AccountIdFor<T::Runtime>,
+        expected: &[ExpectedEvent],
+    ) {
+        let actual = decode_contract_events(
+            self.recorder.record.last_event_batch(),
+            &self.transcoders,
+            Some(address),
+        )
+        .into_iter()
+        .map(|(_, event)| event)
+        .collect::<Vec<_>>();
+
+        let mut remaining = actual.iter();
+        let matched = expected
+            .iter()
+            .take_while(|expected| remaining.any(|event| matches_event(expected, event)))
+            .count();
+        if matched == expected.len() {
+            return;
+        }
+
+        let expected = expected
+            .iter()
+            .enumerate()
+            .map(|(idx, event)| {
+                let marker = if idx < matched { " " } else { "-" };
+                format!("  {marker} {}", describe(event))
+            })
+            .collect::<Vec<_>>();
+        let actual = actual
+            .iter()
+            .map(|event| format!("    {event}"))
+            .collect::<Vec<_>>();
+        panic!(
+            "Events of {address:?} do not match the expected order (`-` marks the events that were \
+             not found in order)\nexpected:\n{}\nactual:\n{}",
+            expected.join("\n"),
+            actual.join("\n")
+        );
+    }
+}
+
+fn matches_event((name, fields): &ExpectedEvent, event: &Value) -> bool {
+    let Value::Map(map) = event else {
+        return false;
+    };
+    if map.ident().as_deref() != Some(*name) {
+        return false;
+    }
+    fields
+        .iter()
+        .flatten()
+        .all(|(field, value)| map.get_by_str(field) == Some(value))
+}
+
+fn describe((name, fields): &ExpectedEvent) -> String {
+    match fields {
+        None => name.to_string(),
+        Some(fields) => {
+            let fields = fields
+                .iter()
+                .map(|(field, value)| format!("{field}: {value}"))
+                .collect::<Vec<_>>();
+            format!("{name} {{ {} }}", fields.join(", "))
+        }
+    }
+}
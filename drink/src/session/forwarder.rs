@@ -0,0 +1,87 @@
+//! Calling contracts through the `Forwarder` fixture, i.e. with a contract as the caller.
+//!
+//! ```rust, ignore
+//! // `vault` sees the forwarder (and not the session actor) as its caller.
+//! let result = session.call_via_forwarder::<_, ()>(vault.clone(), "deposit", NO_ARGS, Some(100));
+//! assert_eq!(result??, ());
+//! ```
+
+use std::fmt::{Debug, Display};
+
+use ink_sandbox::{api::prelude::*, AccountIdFor, Sandbox};
+use parity_scale_codec::Decode;
+
+use crate::{
+    errors::MessageResult,
+    fixtures::Fixture,
+    pallet_contracts::Config,
+    session::{error::SessionError, BalanceOf, Session},
+};
+
+/// Salt of the forwarder deployed by the session.
+const FORWARDER_SALT: &[u8] = b"drink::fixtures::Forwarder";
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config,
+    AccountIdFor<T::Runtime>: Display,
+{
+    /// Returns the address of the session forwarder (`Fixture::Forwarder`), deploying it (and
+    /// registering its transcoder) on first use.
+    ///
+    /// Like mocks, the forwarder is deployed by the default actor, outside the record: it doesn't
+    /// become the default callee of `call`.
+    pub fn forwarder(&mut self) -> Result<AccountIdFor<T::Runtime>, SessionError> {
+        if let Some(forwarder) = &self.forwarder {
+            return Ok(forwarder.clone());
+        }
+
+        let bundle = Fixture::Forwarder.bundle();
+        let forwarder = self
+            .sandbox()
+            .deploy_contract(
+                bundle.wasm,
+                0u32.into(),
+                // The selector of the `new` constructor.
+                vec![0x9b, 0xae, 0x9d, 0x5e],
+                FORWARDER_SALT.to_vec(),
+                T::default_actor(),
+                T::default_gas_limit(),
+                None,
+            )
+            .result
+            .map_err(SessionError::DeploymentFailed)?
+            .account_id;
+
+        self.set_transcoder(forwarder.clone(), &bundle.transcoder);
+        self.forwarder = Some(forwarder.clone());
+        Ok(forwarder)
+    }
+
+    /// Calls `message` of the contract at `callee` through the session forwarder (see
+    /// `forwarder`), so that the callee sees a contract as its caller. The call is encoded with
+    /// the transcoder of `callee`. `value` is transferred to the forwarder and then, by the
+    /// forwarder, to the callee.
+    ///
+    /// The output of the callee is returned as if it were called directly: a revert of the callee
+    /// is returned as `SessionError::CallReverted`, while a trap fails the whole call. The
+    /// interaction is recorded as a call to `forward`.
+    pub fn call_via_forwarder<S: AsRef<str> + Debug, V: Decode>(
+        &mut self,
+        callee: AccountIdFor<T::Runtime>,
+        message: &str,
+        args: &[S],
+        value: Option<BalanceOf<T::Runtime>>,
+    ) -> Result<MessageResult<V>, SessionError> {
+        let data = self.encode_message(&callee, message, args)?;
+        let forwarder = self.forwarder()?;
+
+        let hex = data.iter().map(|b| format!("{b:02x}")).collect::<String>();
+        let forward_args = [
+            callee.to_string(),
+            format!("0x{hex}"),
+            format!("{:?}", value.unwrap_or_default()),
+        ];
+        self.call_internal(Some(forwarder), "forward", &forward_args, value, true)
+    }
+}
@@ -0,0 +1,160 @@
+//! Callbacks invoked before and after every contract call or deployment, for frameworks built on
+//! top of the session.
+//!
+//! ```rust, ignore
+//! session.add_hook(Hook::before_call(|info| match info.label.as_str() {
+//!     "withdraw" => Err("withdrawals are not allowed in this scenario".to_string()),
+//!     _ => Ok(()),
+//! }));
+//! assert!(matches!(
+//!     session.call::<_, ()>("withdraw", NO_ARGS, NO_ENDOWMENT),
+//!     Err(SessionError::HookRejected(_))
+//! ));
+//! ```
+
+use ink_sandbox::{AccountIdFor, ContractExecResultFor, ContractInstantiateResultFor, Sandbox};
+
+use crate::{
+    pallet_contracts::Config,
+    session::{error::SessionError, Session},
+};
+
+/// Describes the interaction passed to the hooks.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct InteractionInfo<AccountId> {
+    /// The called contract (`None` for deployments).
+    pub callee: Option<AccountId>,
+    /// The label of the called message or constructor.
+    pub label: String,
+    /// The origin of the interaction.
+    pub actor: AccountId,
+    /// The encoded input (including the selector).
+    pub input: Vec<u8>,
+}
+
+/// Hook run before an interaction. Returning an error vetoes the interaction.
+pub type BeforeHook<R> = Box<dyn FnMut(&InteractionInfo<AccountIdFor<R>>) -> Result<(), String>>;
+/// Hook run after a call, with its result.
+pub type AfterCallHook<R> = Box<
+    dyn FnMut(&InteractionInfo<AccountIdFor<R>>, &ContractExecResultFor<R>) -> Result<(), String>,
+>;
+/// Hook run after a deployment, with its result.
+pub type AfterDeployHook<R> = Box<
+    dyn FnMut(
+        &InteractionInfo<AccountIdFor<R>>,
+        &ContractInstantiateResultFor<R>,
+    ) -> Result<(), String>,
+>;
+
+/// A callback registered with `Session::add_hook`.
+///
+/// An error returned by any hook is reported as `SessionError::HookRejected`. Before-hooks veto
+/// the interaction (nothing is executed nor recorded). After-hooks run once the interaction has
+/// been recorded, so their error doesn't revert its effects.
+pub enum Hook<R: Config> {
+    /// Run before every call.
+    BeforeCall(BeforeHook<R>),
+    /// Run after every call (successful or not).
+    AfterCall(AfterCallHook<R>),
+    /// Run before every deployment.
+    BeforeDeploy(BeforeHook<R>),
+    /// Run after every deployment (successful or not).
+    AfterDeploy(AfterDeployHook<R>),
+}
+
+impl<R: Config> Hook<R> {
+    /// Creates a `Hook::BeforeCall`.
+    pub fn before_call(
+        hook: impl FnMut(&InteractionInfo<AccountIdFor<R>>) -> Result<(), String> + 'static,
+    ) -> Self {
+        Self::BeforeCall(Box::new(hook))
+    }
+
+    /// Creates a `Hook::AfterCall`.
+    pub fn after_call(
+        hook: impl FnMut(
+                &InteractionInfo<AccountIdFor<R>>,
+                &ContractExecResultFor<R>,
+            ) -> Result<(), String>
+            + 'static,
+    ) -> Self {
+        Self::AfterCall(Box::new(hook))
+    }
+
+    /// Creates a `Hook::BeforeDeploy`.
+    pub fn before_deploy(
+        hook: impl FnMut(&InteractionInfo<AccountIdFor<R>>) -> Result<(), String> + 'static,
+    ) -> Self {
+        Self::BeforeDeploy(Box::new(hook))
+    }
+
+    /// Creates a `Hook::AfterDeploy`.
+    pub fn after_deploy(
+        hook: impl FnMut(
+                &InteractionInfo<AccountIdFor<R>>,
+                &ContractInstantiateResultFor<R>,
+            ) -> Result<(), String>
+            + 'static,
+    ) -> Self {
+        Self::AfterDeploy(Box::new(hook))
+    }
+}
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config,
+{
+    /// Registers `hook`. Hooks run in the order they were registered, until one of them fails.
+    ///
+    /// Dry runs are not hooked. The hooks don't have access to the session, so they cannot
+    /// interact with the contracts themselves.
+    pub fn add_hook(&mut self, hook: Hook<T::Runtime>) {
+        self.hooks.push(hook);
+    }
+
+    /// Runs the before-hooks of a call (or a deployment, if `deploy` is set).
+    pub(crate) fn run_before_hooks(
+        &mut self,
+        deploy: bool,
+        info: &InteractionInfo<AccountIdFor<T::Runtime>>,
+    ) -> Result<(), SessionError> {
+        self.hooks
+            .iter_mut()
+            .try_for_each(|hook| match hook {
+                Hook::BeforeCall(hook) if !deploy => hook(info),
+                Hook::BeforeDeploy(hook) if deploy => hook(info),
+                _ => Ok(()),
+            })
+            .map_err(SessionError::HookRejected)
+    }
+
+    /// Runs the after-hooks of a call.
+    pub(crate) fn run_after_call_hooks(
+        &mut self,
+        info: &InteractionInfo<AccountIdFor<T::Runtime>>,
+        result: &ContractExecResultFor<T::Runtime>,
+    ) -> Result<(), SessionError> {
+        self.hooks
+            .iter_mut()
+            .try_for_each(|hook| match hook {
+                Hook::AfterCall(hook) => hook(info, result),
+                _ => Ok(()),
+            })
+            .map_err(SessionError::HookRejected)
+    }
+
+    /// Runs the after-hooks of a deployment.
+    pub(crate) fn run_after_deploy_hooks(
+        &mut self,
+        info: &InteractionInfo<AccountIdFor<T::Runtime>>,
+        result: &ContractInstantiateResultFor<T::Runtime>,
+    ) -> Result<(), SessionError> {
+        self.hooks
+            .iter_mut()
+            .try_for_each(|hook| match hook {
+                Hook::AfterDeploy(hook) => hook(info, result),
+                _ => Ok(()),
+            })
+            .map_err(SessionError::HookRejected)
+    }
+}
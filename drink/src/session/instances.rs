@@ -0,0 +1,106 @@
+//! Finding (and terminating) all the contracts running the same code, e.g. the ones created by a
+//! factory contract.
+
+use frame_support::sp_runtime::traits::StaticLookup;
+use ink_sandbox::{api::prelude::*, AccountIdFor, Sandbox};
+use parity_scale_codec::Encode;
+
+use crate::{
+    pallet_contracts::{Config, Pallet as Contracts},
+    session::{error::SessionError, state_diff::contract_tries, HashFor, Session},
+};
+
+/// A contract that terminates itself on every call, sending its balance to the account passed as
+/// the input.
+const TERMINATOR_CONTRACT: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal1" "seal_terminate" (func $seal_terminate (param i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; Memory layout:
+	;; [0, 4)     buffer length
+	;; [32, 128)  encoded beneficiary
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(i32.store (i32.const 0) (i32.const 96))
+		(call $seal_input (i32.const 32) (i32.const 0))
+		(call $seal_terminate (i32.const 32))
+	)
+)
+"#;
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config,
+{
+    /// Returns the addresses of all the contracts currently running the code with `code_hash`.
+    ///
+    /// It goes through all the contracts in the sandbox, i.e. it takes O(number of contracts),
+    /// which is fine for tests.
+    pub fn instances_of(
+        &mut self,
+        code_hash: HashFor<T::Runtime>,
+    ) -> Vec<AccountIdFor<T::Runtime>> {
+        self.recorder.sandbox.execute_with(|| {
+            contract_tries::<T::Runtime>()
+                .into_iter()
+                .map(|(address, _)| address)
+                .filter(|address| Contracts::<T::Runtime>::code_hash(address) == Some(code_hash))
+                .collect()
+        })
+    }
+
+    /// Terminates all the contracts running the code with `code_hash` (see `instances_of`), sending
+    /// their balances (and released storage deposits) to `beneficiary`. Returns the number of the
+    /// terminated contracts.
+    ///
+    /// The contracts don't have to support termination: their code is replaced with a contract
+    /// that terminates itself, which is then called by the session actor. None of this is
+    /// recorded.
+    pub fn terminate_all_instances_of(
+        &mut self,
+        code_hash: HashFor<T::Runtime>,
+        beneficiary: AccountIdFor<T::Runtime>,
+    ) -> Result<usize, SessionError> {
+        let instances = self.instances_of(code_hash);
+        if instances.is_empty() {
+            return Ok(0);
+        }
+
+        let terminator = self.upload(
+            wat::parse_str(TERMINATOR_CONTRACT).expect("Terminator contract should be valid"),
+        )?;
+        for address in &instances {
+            self.recorder
+                .sandbox
+                .execute_with(|| {
+                    Contracts::<T::Runtime>::set_code(
+                        frame_system::RawOrigin::Root.into(),
+                        <T::Runtime as frame_system::Config>::Lookup::unlookup(address.clone()),
+                        terminator,
+                    )
+                })
+                .map_err(|err| SessionError::TerminationFailed(format!("{address:?}: {err:?}")))?;
+
+            let result = self.recorder.sandbox.call_contract(
+                address.clone(),
+                Default::default(),
+                beneficiary.encode(),
+                self.actor.clone(),
+                self.effective_call_gas_limit(),
+                None,
+                self.determinism,
+            );
+            if let Err(err) = result.result {
+                return Err(SessionError::TerminationFailed(format!(
+                    "{address:?}: {err:?}"
+                )));
+            }
+        }
+        self.evict_terminated_transcoders();
+        Ok(instances.len())
+    }
+}
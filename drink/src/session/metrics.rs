@@ -0,0 +1,161 @@
+//! Per-interaction metrics (wall-clock duration, gas, events), for finding out why a test is slow.
+//!
+//! ```rust, ignore
+//! let mut session = Session::<MinimalSandbox>::default().with_metrics();
+//! session.deploy_bundle(bundle, "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)?;
+//! session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+//! eprintln!("{}", session.metrics().unwrap());
+//! ```
+//!
+//! With `DRINK_METRICS=print`, the summary is also printed to stderr when the session is dropped.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    time::{Duration, Instant},
+};
+
+use frame_support::weights::Weight;
+use ink_sandbox::Sandbox;
+
+use crate::{pallet_contracts::Config, session::Session, weights::HumanWeight};
+
+/// Environment variable controlling what happens with the metrics when a session is dropped.
+/// The only supported value is `print`: the summary is printed to stderr.
+pub const METRICS_ENV: &str = "DRINK_METRICS";
+
+/// Aggregates of all the interactions with the same constructor or message.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct InteractionMetrics {
+    /// The number of interactions.
+    pub count: usize,
+    /// The wall-clock time of all the interactions, including encoding and recording.
+    pub total_duration: Duration,
+    /// The wall-clock time of the slowest interaction.
+    pub max_duration: Duration,
+    /// The gas consumed by all the interactions.
+    pub gas_consumed: Weight,
+    /// The number of runtime events emitted by all the interactions.
+    pub events: usize,
+}
+
+impl InteractionMetrics {
+    fn add(&mut self, duration: Duration, gas_consumed: Weight, events: usize) {
+        self.count += 1;
+        self.total_duration += duration;
+        self.max_duration = self.max_duration.max(duration);
+        self.gas_consumed = self.gas_consumed.saturating_add(gas_consumed);
+        self.events += events;
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.count += other.count;
+        self.total_duration += other.total_duration;
+        self.max_duration = self.max_duration.max(other.max_duration);
+        self.gas_consumed = self.gas_consumed.saturating_add(other.gas_consumed);
+        self.events += other.events;
+    }
+}
+
+/// Metrics of the contract interactions performed by a session, per constructor and message label.
+///
+/// Only interactions with transcoded input are measured (not the raw ones, nor dry runs).
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct SessionMetrics {
+    /// Deployments, by constructor label.
+    pub constructors: BTreeMap<String, InteractionMetrics>,
+    /// Calls, by message label.
+    pub messages: BTreeMap<String, InteractionMetrics>,
+}
+
+impl SessionMetrics {
+    /// Aggregates of all the measured interactions.
+    pub fn total(&self) -> InteractionMetrics {
+        let mut total = InteractionMetrics::default();
+        for metrics in self.constructors.values().chain(self.messages.values()) {
+            total.merge(metrics);
+        }
+        total
+    }
+}
+
+impl fmt::Display for SessionMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let row = |f: &mut fmt::Formatter<'_>, label: &str, metrics: &InteractionMetrics| {
+            writeln!(
+                f,
+                "{label:<32} {:>6} {:>12.3} {:>12.3} {:>24} {:>8}",
+                metrics.count,
+                metrics.total_duration.as_secs_f64() * 1000.,
+                metrics.max_duration.as_secs_f64() * 1000.,
+                HumanWeight(metrics.gas_consumed),
+                metrics.events,
+            )
+        };
+
+        writeln!(
+            f,
+            "{:<32} {:>6} {:>12} {:>12} {:>24} {:>8}",
+            "interaction", "count", "total [ms]", "max [ms]", "gas", "events"
+        )?;
+        for (label, metrics) in &self.constructors {
+            row(f, &format!("{label} (constructor)"), metrics)?;
+        }
+        for (label, metrics) in &self.messages {
+            row(f, label, metrics)?;
+        }
+        row(f, "total", &self.total())
+    }
+}
+
+/// Collects the metrics of a session and prints them on drop, if requested with `DRINK_METRICS`.
+#[derive(Default)]
+pub(crate) struct MetricsCollector(SessionMetrics);
+
+impl Drop for MetricsCollector {
+    fn drop(&mut self) {
+        if std::env::var(METRICS_ENV).is_ok_and(|value| value == "print") {
+            eprintln!("Session metrics:\n{}", self.0);
+        }
+    }
+}
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config,
+{
+    /// Enables collecting metrics of the contract interactions and returns updated `self`.
+    pub fn with_metrics(mut self) -> Self {
+        self.metrics.get_or_insert_with(Default::default);
+        self
+    }
+
+    /// Returns the metrics collected so far, or `None` if they are not enabled (see
+    /// `with_metrics`).
+    pub fn metrics(&self) -> Option<&SessionMetrics> {
+        self.metrics.as_ref().map(|collector| &collector.0)
+    }
+
+    /// Adds the last (just recorded) interaction, started at `started`, to the metrics of the
+    /// constructor or the message `label`.
+    pub(crate) fn measure_interaction(
+        &mut self,
+        constructor: bool,
+        label: &str,
+        started: Instant,
+        gas_consumed: Weight,
+    ) {
+        let Some(collector) = &mut self.metrics else {
+            return;
+        };
+        let events = self.recorder.last_event_batch().all_events().len();
+        let metrics = match constructor {
+            true => &mut collector.0.constructors,
+            false => &mut collector.0.messages,
+        };
+        metrics
+            .entry(label.to_string())
+            .or_default()
+            .add(started.elapsed(), gas_consumed, events);
+    }
+}
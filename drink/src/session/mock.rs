@@ -3,19 +3,69 @@
 mod contract;
 mod error;
 mod extension;
+mod recording;
 use std::collections::BTreeMap;
 
-pub use contract::{mock_message, ContractMock, MessageMock, Selector};
+pub use contract::{mock_message, selector_of, ContractMock, MessageMock, MockedCall, Selector};
 use error::MockingError;
 pub(crate) use extension::MockingExtension;
+use frame_support::weights::Weight;
+pub use recording::{MockRecording, RecordedResponse};
+
+#[cfg(feature = "chaos")]
+use crate::session::chaos::FailureInjector;
+use crate::session::reentrancy::ReentrancyDetector;
 
 /// Untyped result of a mocked call.
 pub type MockedCallResult = Result<Vec<u8>, MockingError>;
 
+/// How much gas a call answered by a mock charges the caller, besides the regular call overhead
+/// (see `Session::set_mock_gas_strategy`).
+///
+/// Only the ref time is charged: the proof size of the weights is ignored, since it cannot be
+/// consumed on demand.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum MockGasStrategy {
+    /// Nothing is charged, so a mocked call is much cheaper than a real one.
+    #[default]
+    Zero,
+    /// The same weight is charged for every call.
+    Fixed(Weight),
+    /// The weight is charged per byte of the call input (including the selector).
+    ProportionalToInput(Weight),
+}
+
+impl MockGasStrategy {
+    /// Returns the weight charged for a call with `input`.
+    pub fn charge(&self, input: &[u8]) -> Weight {
+        match *self {
+            MockGasStrategy::Zero => Weight::zero(),
+            MockGasStrategy::Fixed(weight) => weight,
+            MockGasStrategy::ProportionalToInput(per_byte) => {
+                per_byte.saturating_mul(input.len() as u64)
+            }
+        }
+    }
+}
+
 /// A registry of mocked contracts.
+#[derive(Clone)]
 pub(crate) struct MockRegistry<AccountId: Ord> {
     mocked_contracts: BTreeMap<AccountId, ContractMock>,
+    /// The contracts answering on behalf of the mocks that charge gas, with the addresses of their
+    /// mocks (see `MockingApi::deploy`).
+    twins: BTreeMap<AccountId, AccountId>,
+    /// The gas charged for the mocked calls.
+    gas_strategy: MockGasStrategy,
     nonce: u8,
+    /// Kept here, since the calls are tracked by the same extension (see
+    /// `Session::with_reentrancy_detection`).
+    reentrancy_detector: ReentrancyDetector<AccountId>,
+    /// Responses of the real contracts being recorded (see `MockingApi::start_recording`).
+    recordings: BTreeMap<AccountId, MockRecording>,
+    /// Failures to inject into the calls (see `Session::inject_failure`).
+    #[cfg(feature = "chaos")]
+    failure_injector: FailureInjector<AccountId>,
 }
 
 impl<AccountId: Ord> MockRegistry<AccountId> {
@@ -23,7 +73,13 @@ impl<AccountId: Ord> MockRegistry<AccountId> {
     pub fn new() -> Self {
         Self {
             mocked_contracts: BTreeMap::new(),
+            twins: BTreeMap::new(),
+            gas_strategy: MockGasStrategy::Zero,
             nonce: 0u8,
+            reentrancy_detector: ReentrancyDetector::default(),
+            recordings: BTreeMap::new(),
+            #[cfg(feature = "chaos")]
+            failure_injector: FailureInjector::default(),
         }
     }
 
@@ -38,8 +94,76 @@ impl<AccountId: Ord> MockRegistry<AccountId> {
         self.mocked_contracts.insert(address, mock)
     }
 
+    /// Registers `twin` as the contract answering on behalf of the mock at `address`, so that the
+    /// mock can charge gas for the calls.
+    pub fn register_twin(&mut self, twin: AccountId, address: AccountId) {
+        self.twins.insert(twin, address);
+    }
+
+    /// Returns the address of the mock that `twin` answers for, if it is a twin.
+    pub fn mock_behind(&self, twin: &AccountId) -> Option<&AccountId> {
+        self.twins.get(twin)
+    }
+
+    /// Whether the mock at `address` has a twin, i.e. can charge gas for the calls.
+    pub fn has_twin(&self, address: &AccountId) -> bool {
+        self.twins.values().any(|mock| mock == address)
+    }
+
+    /// Returns the gas charged for the mocked calls.
+    pub fn gas_strategy(&self) -> MockGasStrategy {
+        self.gas_strategy
+    }
+
+    /// Sets the gas charged for the mocked calls.
+    pub fn set_gas_strategy(&mut self, gas_strategy: MockGasStrategy) {
+        self.gas_strategy = gas_strategy;
+    }
+
     /// Returns the mock for `address`, if any.
     pub fn get(&self, address: &AccountId) -> Option<&ContractMock> {
         self.mocked_contracts.get(address)
     }
+
+    /// Returns the mock for `address` for modification, if any.
+    pub fn get_mut(&mut self, address: &AccountId) -> Option<&mut ContractMock> {
+        self.mocked_contracts.get_mut(address)
+    }
+
+    /// Returns the re-entrancy detector fed with the calls going through the registry.
+    pub fn reentrancy_detector(&mut self) -> &mut ReentrancyDetector<AccountId> {
+        &mut self.reentrancy_detector
+    }
+
+    /// Returns the failure policies consulted for the calls going through the registry.
+    #[cfg(feature = "chaos")]
+    pub fn failure_injector(&mut self) -> &mut FailureInjector<AccountId> {
+        &mut self.failure_injector
+    }
+
+    /// Starts recording the responses of the contract at `address`, dropping the previous
+    /// recording, if any.
+    pub fn start_recording(&mut self, address: AccountId) {
+        self.recordings.insert(address, MockRecording::new());
+    }
+
+    /// Stops recording the responses of the contract at `address` and returns the recording.
+    pub fn finish_recording(&mut self, address: &AccountId) -> Option<MockRecording> {
+        self.recordings.remove(address)
+    }
+
+    /// Records the response of the contract at `address`, if it is being recorded.
+    pub fn record_response(&mut self, address: &AccountId, input: Vec<u8>, output: Vec<u8>) {
+        if let Some(recording) = self.recordings.get_mut(address) {
+            recording.record(input, output);
+        }
+    }
+
+    /// Attaches the consumed gas to the recorded response of the contract at `address`, if it is
+    /// being recorded.
+    pub fn annotate_recorded_gas(&mut self, address: &AccountId, input: &[u8], gas: Weight) {
+        if let Some(recording) = self.recordings.get_mut(address) {
+            recording.annotate_gas(input, gas);
+        }
+    }
 }
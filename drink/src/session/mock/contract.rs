@@ -1,10 +1,18 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
 
+use contract_transcode::ContractMessageTranscoder;
+use frame_support::{
+    sp_runtime::traits::{BlakeTwo256, Hash},
+    weights::Weight,
+};
 use parity_scale_codec::{Decode, Encode};
 
 use crate::{
     errors::LangError,
-    session::mock::{error::MockingError, MockedCallResult},
+    session::mock::{error::MockingError, MockRecording, MockedCallResult},
 };
 
 /// Alias for a 4-byte selector.
@@ -15,10 +23,47 @@ pub type Selector = [u8; 4];
 /// have a collection of differently typed closures. Fortunately, we can assume that all types are
 /// en/decodable, so we can use `Vec<u8>` as a common denominator.
 pub type MessageMock = Box<dyn Fn(Vec<u8>) -> MockedCallResult + Send + Sync>;
+/// A message mock shared between the copies of a contract mock, cheap to take out of the mock
+/// registry.
+pub(crate) type SharedMessageMock = Arc<dyn Fn(Vec<u8>) -> MockedCallResult + Send + Sync>;
+
+/// A call received by a mock.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MockedCall {
+    /// The selector of the called message.
+    pub selector: Selector,
+    /// The encoded arguments (without the selector).
+    pub input: Vec<u8>,
+    /// The encoded output, either from the message mock or from the proxied contract. `None` if
+    /// the call did not complete.
+    pub output: Option<Vec<u8>>,
+    /// Whether the call has been forwarded to the proxied contract (see `ContractMock::proxy_to`).
+    pub proxied: bool,
+}
+
+impl MockedCall {
+    /// Returns the whole input of the call (the selector followed by the arguments), e.g. for
+    /// `Session::decode_input`.
+    pub fn data(&self) -> Vec<u8> {
+        [self.selector.as_slice(), &self.input].concat()
+    }
+}
 
 /// A contract mock.
+#[derive(Clone)]
 pub struct ContractMock {
-    messages: BTreeMap<Selector, MessageMock>,
+    /// Message mocks (shared, so that the mock can be copied to a session branch).
+    messages: BTreeMap<Selector, SharedMessageMock>,
+    /// Labels of the registered messages (or hex-encoded selectors, if registered by selector).
+    labels: BTreeMap<Selector, String>,
+    /// Selectors registered by `from_metadata`, which can be overridden without a collision.
+    placeholders: BTreeSet<Selector>,
+    /// The encoded address of the contract that unmatched calls are forwarded to.
+    proxy: Option<Vec<u8>>,
+    /// All the calls received by the mock, in order.
+    calls: Vec<MockedCall>,
+    /// Recorded responses of a real contract, used for the messages that are not mocked.
+    recording: Option<MockRecording>,
 }
 
 impl ContractMock {
@@ -26,20 +71,172 @@ impl ContractMock {
     pub fn new() -> Self {
         Self {
             messages: BTreeMap::new(),
+            labels: BTreeMap::new(),
+            placeholders: BTreeSet::new(),
+            proxy: None,
+            calls: vec![],
+            recording: None,
+        }
+    }
+
+    /// Creates a mock replaying the recorded responses of a real contract (see
+    /// `MockingApi::start_recording`).
+    ///
+    /// A call is answered with the response recorded for exactly the same input (selector and
+    /// arguments). A call with an input that has not been recorded fails, unless the mock is a spy
+    /// (see `proxy_to`), in which case it is forwarded to the proxied contract. Messages added with
+    /// `with_message` take precedence over the recording.
+    ///
+    /// A replayed call charges the caller the recorded gas (`RecordedResponse::gas_consumed`), on
+    /// top of the `MockGasStrategy` of the session. Like any gas charged by mocks, it is not
+    /// charged by spies.
+    pub fn from_recording(recording: MockRecording) -> Self {
+        Self {
+            recording: Some(recording),
+            ..Self::new()
+        }
+    }
+
+    /// Makes the mock a spy of the contract at `target`: calls to messages that are not mocked are
+    /// forwarded to `target` (with the same input and value) and its output is returned.
+    ///
+    /// The forwarding is a regular nested call made by the mock contract, so `target` sees the mock
+    /// as the caller - `pallet-contracts` doesn't allow to preserve the original one. Every call,
+    /// together with its output, is available in `MockingApi::mock_calls`.
+    pub fn proxy_to<AccountId: Encode>(mut self, target: AccountId) -> Self {
+        self.proxy = Some(target.encode());
+        self
+    }
+
+    /// The encoded address of the proxied contract, if any.
+    pub(crate) fn proxy_target(&self) -> Option<&[u8]> {
+        self.proxy.as_deref()
+    }
+
+    /// Whether calls to `selector` with `input` should be forwarded to the proxied contract.
+    pub(crate) fn forwards(&self, selector: &Selector, input: &[u8]) -> bool {
+        let answered = self.messages.contains_key(selector) || self.replays(*selector, input);
+        self.proxy.is_some() && (!answered || self.placeholders.contains(selector))
+    }
+
+    /// Whether there is a recorded response to `selector` with `input`.
+    fn replays(&self, selector: Selector, input: &[u8]) -> bool {
+        self.recording
+            .as_ref()
+            .is_some_and(|recording| recording.response(selector, input).is_some())
+    }
+
+    pub(crate) fn log_call(&mut self, call: MockedCall) {
+        self.calls.push(call);
+    }
+
+    /// Completes the last forwarded call that has no output yet.
+    pub(crate) fn log_proxied_output(&mut self, output: Vec<u8>) {
+        if let Some(call) = self
+            .calls
+            .iter_mut()
+            .rev()
+            .find(|call| call.proxied && call.output.is_none())
+        {
+            call.output = Some(output);
         }
     }
 
+    /// Returns all the calls received by the mock, in order.
+    pub fn calls(&self) -> &[MockedCall] {
+        &self.calls
+    }
+
+    /// Creates a mock covering the whole interface described by the contract metadata.
+    ///
+    /// Every message is pre-registered with a handler that fails with the message label, so that
+    /// calling a message you forgot to mock aborts the test with `Unmocked selector called:
+    /// <label>`. Override the messages you need with `with_message`.
+    pub fn from_metadata(transcoder: &ContractMessageTranscoder) -> Self {
+        let mut mock = Self::new();
+        for message in transcoder.metadata().spec().messages() {
+            let selector: Selector = message
+                .selector()
+                .to_bytes()
+                .try_into()
+                .expect("Selector should have 4 bytes");
+            let label = message.label().clone();
+            mock = mock.with_labeled_message(
+                selector,
+                label.clone(),
+                Box::new(move |_| Err(MockingError::NotMocked(label.clone()))),
+            );
+            mock.placeholders.insert(selector);
+        }
+        mock
+    }
+
     /// Adds a message mock.
-    pub fn with_message(mut self, selector: Selector, message: MessageMock) -> Self {
-        self.messages.insert(selector, message);
+    ///
+    /// In debug builds, panics if another message has already been registered under `selector`
+    /// (except for the placeholders registered by `from_metadata`).
+    pub fn with_message(self, selector: Selector, message: MessageMock) -> Self {
+        let label = format!("0x{}", hex(&selector));
+        self.with_labeled_message(selector, label, message)
+    }
+
+    /// Adds a message mock for the message with `label`, computing its selector the same way as
+    /// ink! does.
+    ///
+    /// In debug builds, panics if another message has already been registered under the same
+    /// selector (except for the placeholders registered by `from_metadata`).
+    pub fn with_message_label(self, label: &str, message: MessageMock) -> Self {
+        self.with_labeled_message(selector_of(label), label.to_string(), message)
+    }
+
+    fn with_labeled_message(
+        mut self,
+        selector: Selector,
+        label: String,
+        message: MessageMock,
+    ) -> Self {
+        let overrides_placeholder = self.placeholders.remove(&selector);
+        if cfg!(debug_assertions) && !overrides_placeholder {
+            if let Some(registered) = self.labels.get(&selector) {
+                panic!(
+                    "Selector collision: `{label}` has the same selector (0x{}) as already \
+                     registered `{registered}`",
+                    hex(&selector)
+                );
+            }
+        }
+        self.labels.insert(selector, label);
+        self.messages.insert(selector, Arc::from(message));
         self
     }
 
+    /// Returns the message mock registered under `selector`, if any.
+    pub(crate) fn message(&self, selector: &Selector) -> Option<SharedMessageMock> {
+        self.messages.get(selector).cloned()
+    }
+
+    /// The gas recorded for the response to `selector` with `input`, if the call is answered from
+    /// the recording. Zero otherwise.
+    pub(crate) fn recorded_gas(&self, selector: Selector, input: &[u8]) -> Weight {
+        if self.messages.contains_key(&selector) {
+            return Weight::zero();
+        }
+        self.recording
+            .as_ref()
+            .and_then(|recording| recording.response(selector, input))
+            .and_then(|response| response.gas_consumed)
+            .unwrap_or_else(Weight::zero)
+    }
+
     /// Try to call a message mock. Returns an error if there is no message mock for `selector`.
     pub fn call(&self, selector: Selector, input: Vec<u8>) -> MockedCallResult {
-        match self.messages.get(&selector) {
-            None => Err(MockingError::MessageNotFound(selector)),
-            Some(message) => message(input),
+        match (self.messages.get(&selector), &self.recording) {
+            (Some(message), _) => message(input),
+            (None, Some(recording)) => recording
+                .response(selector, &input)
+                .map(|response| response.output.clone())
+                .ok_or(MockingError::NotRecorded(selector)),
+            (None, None) => Err(MockingError::MessageNotFound(selector)),
         }
     }
 }
@@ -50,6 +247,18 @@ impl Default for ContractMock {
     }
 }
 
+/// Computes the selector of a message with `label`, the same way as ink! does (first four bytes of
+/// the BLAKE2b-256 hash of the label).
+pub fn selector_of(label: &str) -> Selector {
+    BlakeTwo256::hash(label.as_bytes()).as_bytes()[..4]
+        .try_into()
+        .expect("Hash should have at least 4 bytes")
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
 /// A helper function to create a message mock out of a typed closure.
 ///
 /// In particular, it takes care of decoding the input and encoding the output. Also, wraps the
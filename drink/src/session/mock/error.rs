@@ -9,4 +9,8 @@ pub enum MockingError {
     MessageNotFound(Selector),
     #[error("Decoding message arguments failed: {0:?}")]
     ArgumentDecoding(parity_scale_codec::Error),
+    #[error("Unmocked selector called: {0}")]
+    NotMocked(String),
+    #[error("No recorded response for the input (selector: {0:?})")]
+    NotRecorded(Selector),
 }
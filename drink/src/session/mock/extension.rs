@@ -1,12 +1,16 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    fmt::Debug,
+    sync::{Arc, RwLock, RwLockWriteGuard},
+};
 
 use parity_scale_codec::{Decode, Encode};
 
 use crate::{
+    environment::internal_error,
     errors::MessageResult,
     pallet_contracts::{chain_extension::ReturnFlags, debug::ExecResult, ExecReturnValue},
     pallet_contracts_debugging::InterceptingExtT,
-    session::mock::{MockRegistry, Selector},
+    session::mock::{MockRegistry, MockedCall, Selector},
 };
 
 /// Runtime extension enabling contract call interception.
@@ -14,57 +18,260 @@ pub(crate) struct MockingExtension<AccountId: Ord> {
     /// Mock registry, shared with the sandbox.
     ///
     /// Potentially the runtime is executed in parallel and thus we need to wrap the registry in
-    /// `Arc<Mutex>` instead of `Rc<RefCell>`.
-    pub mock_registry: Arc<Mutex<MockRegistry<AccountId>>>,
+    /// `Arc<RwLock>` instead of `Rc<RefCell>`. The lock is never held while a message mock runs.
+    pub mock_registry: Arc<RwLock<MockRegistry<AccountId>>>,
 }
 
-impl<AccountId: Ord + Decode> InterceptingExtT for MockingExtension<AccountId> {
+impl<AccountId: Ord> MockingExtension<AccountId> {
+    fn registry(&self) -> RwLockWriteGuard<'_, MockRegistry<AccountId>> {
+        self.mock_registry
+            .write()
+            .expect("Should be able to acquire registry")
+    }
+}
+
+impl<AccountId: Ord + Clone + Decode + Debug> InterceptingExtT for MockingExtension<AccountId> {
     fn intercept_call(
         &self,
         contract_address: Vec<u8>,
-        _is_call: bool,
+        is_call: bool,
         input_data: Vec<u8>,
     ) -> Vec<u8> {
-        let contract_address = Decode::decode(&mut &contract_address[..])
-            .expect("Contract address should be decodable");
+        let contract_address: AccountId = Decode::decode(&mut &contract_address[..])
+            .unwrap_or_else(|err| {
+                internal_error(format!("Contract address should be decodable: {err}"))
+            });
 
-        match self
-            .mock_registry
-            .lock()
-            .expect("Should be able to acquire registry")
-            .get(&contract_address)
-        {
-            // There is no mock registered for this address, so we return `None` to indicate that
-            // the call should be executed normally.
-            None => None::<()>.encode(),
-            // We intercept the call and return the result of the mock.
-            Some(mock) => {
-                let (selector, call_data) = input_data.split_at(4);
-                let selector: Selector = selector
-                    .try_into()
-                    .expect("Input data should contain at least selector bytes");
-
-                let result = mock
-                    .call(selector, call_data.to_vec())
-                    .expect("TODO: let the user define the fallback mechanism");
-
-                // Although we don't know the exact type, thanks to the SCALE encoding we know
-                // that `()` will always succeed (we only care about the `Ok`/`Err` distinction).
-                let decoded_result: MessageResult<()> =
-                    Decode::decode(&mut &result[..]).expect("Mock result should be decodable");
-
-                let flags = match decoded_result {
-                    Ok(_) => ReturnFlags::empty(),
-                    Err(_) => ReturnFlags::REVERT,
-                };
-
-                let result: ExecResult = Ok(ExecReturnValue {
-                    flags,
-                    data: result,
-                });
-
-                Some(result).encode()
+        let mut registry = self.registry();
+        registry.reentrancy_detector().enter(&contract_address);
+
+        #[cfg(feature = "chaos")]
+        if is_call {
+            if let Some(result) = registry.failure_injector().intercept(&contract_address) {
+                #[cfg(feature = "tracing-logs")]
+                tracing::debug!(contract = ?contract_address, "failure injected");
+
+                registry.reentrancy_detector().exit(&contract_address);
+                return result;
+            }
+        }
+
+        // The twin of a mock that charges gas answers the calls on behalf of the mock (see
+        // `MockingApi::deploy`).
+        let answered_for = registry.mock_behind(&contract_address).cloned();
+        let twin = answered_for.is_some();
+        let mock_address = answered_for.unwrap_or_else(|| contract_address.clone());
+        let charge = registry.gas_strategy().charge(&input_data);
+        let has_twin = registry.has_twin(&mock_address);
+
+        // There is no mock registered for this address, so we return `None` to indicate that the
+        // call should be executed normally.
+        let Some(mock) = registry.get_mut(&mock_address) else {
+            #[cfg(feature = "tracing-logs")]
+            tracing::trace!(contract = ?contract_address, "no mock registered");
+
+            return None::<()>.encode();
+        };
+
+        let (selector, call_data) = input_data.split_at(4);
+        let selector: Selector = selector
+            .try_into()
+            .expect("Input data should contain at least selector bytes");
+
+        // Unmatched calls to a spy are executed normally: the mock contract forwards them to the
+        // proxied contract.
+        if mock.forwards(&selector, call_data) {
+            #[cfg(feature = "tracing-logs")]
+            tracing::debug!(contract = ?contract_address, selector = ?selector, "mock proxied");
+
+            mock.log_call(MockedCall {
+                selector,
+                input: call_data.to_vec(),
+                output: None,
+                proxied: true,
+            });
+            return None::<()>.encode();
+        }
+
+        // The code of the mock contract burns the gas to charge and asks the twin for the output.
+        let charge = charge.saturating_add(mock.recorded_gas(selector, call_data));
+        if !twin && has_twin && !charge.is_zero() {
+            #[cfg(feature = "tracing-logs")]
+            tracing::debug!(contract = ?contract_address, charge = ?charge, "mock charging gas");
+
+            return None::<()>.encode();
+        }
+
+        // We intercept the call and return the result of the mock. Message mocks are user code,
+        // so only their handles are taken out of the registry and the lock is released while
+        // they run.
+        let result = match mock.message(&selector) {
+            Some(message) => {
+                drop(registry);
+                let result = message(call_data.to_vec());
+                registry = self.registry();
+                result
+            }
+            None => mock.call(selector, call_data.to_vec()),
+        };
+
+        #[cfg(feature = "tracing-logs")]
+        match &result {
+            Ok(_) => tracing::debug!(
+                contract = ?contract_address,
+                selector = ?selector,
+                "mock hit"
+            ),
+            Err(err) => tracing::error!(
+                contract = ?contract_address,
+                selector = ?selector,
+                error = %err,
+                "mock miss"
+            ),
+        }
+
+        // A mock that cannot answer (e.g. an unmocked message of `ContractMock::from_metadata`) is
+        // a mistake in the test setup, so it is reported right away (without poisoning the lock).
+        let result = match result {
+            Ok(result) => result,
+            Err(err) => {
+                drop(registry);
+                panic!("Mocked call to {contract_address:?} failed: {err}")
             }
+        };
+        // The registry may have been unlocked in the meantime, so the mock is looked up again.
+        if let Some(mock) = registry.get_mut(&mock_address) {
+            mock.log_call(MockedCall {
+                selector,
+                input: call_data.to_vec(),
+                output: Some(result.clone()),
+                proxied: false,
+            });
         }
+
+        // Although we don't know the exact type, thanks to the SCALE encoding we know that `()`
+        // will always succeed (we only care about the `Ok`/`Err` distinction).
+        let decoded_result: MessageResult<()> =
+            Decode::decode(&mut &result[..]).expect("Mock result should be decodable");
+
+        let flags = match decoded_result {
+            Ok(_) => ReturnFlags::empty(),
+            Err(_) => ReturnFlags::REVERT,
+        };
+
+        // The mock contract expects the ref time to charge in front of the output of its twin.
+        let data = match twin {
+            true => [charge.ref_time().encode(), result].concat(),
+            false => result,
+        };
+        let result: ExecResult = Ok(ExecReturnValue { flags, data });
+
+        // The call is answered right away, so `after_call` won't be called for it.
+        registry.reentrancy_detector().exit(&contract_address);
+
+        Some(result).encode()
+    }
+
+    fn after_call(
+        &self,
+        contract_address: Vec<u8>,
+        is_call: bool,
+        input_data: Vec<u8>,
+        result: Vec<u8>,
+    ) {
+        let contract_address: AccountId = Decode::decode(&mut &contract_address[..])
+            .unwrap_or_else(|err| {
+                internal_error(format!("Contract address should be decodable: {err}"))
+            });
+
+        let mut registry = self.registry();
+        registry.reentrancy_detector().exit(&contract_address);
+        if is_call {
+            registry.record_response(&contract_address, input_data, result.clone());
+        }
+
+        // Only the calls forwarded by a spy are still waiting for their output.
+        if let Some(mock) = registry.get_mut(&contract_address) {
+            mock.log_proxied_output(result);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::Barrier,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+    use crate::session::mock::{mock_message, ContractMock};
+
+    const ADDRESS: u32 = 7;
+    const SELECTOR: Selector = [1, 2, 3, 4];
+
+    /// How long the registry lock is held while a slow message mock runs: before the change, the
+    /// mock ran under the lock, now only its handle is taken out of the registry. The lock must be
+    /// held at least 10 times shorter.
+    ///
+    /// Run with `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore]
+    fn registry_lock_hold_time() {
+        const CALLS: u32 = 20;
+        const MOCK_DURATION: Duration = Duration::from_millis(5);
+
+        let started = Arc::new(Barrier::new(2));
+        let mut registry = MockRegistry::new();
+        registry.register(
+            ADDRESS,
+            ContractMock::new().with_message(SELECTOR, {
+                let started = started.clone();
+                mock_message(move |()| {
+                    started.wait();
+                    thread::sleep(MOCK_DURATION);
+                })
+            }),
+        );
+        let extension = MockingExtension {
+            mock_registry: Arc::new(RwLock::new(registry)),
+        };
+
+        // Runs `call` for `CALLS` times, measuring how long another thread waits for the registry
+        // while the mock runs.
+        let measure = |call: &dyn Fn()| {
+            let registry = extension.mock_registry.clone();
+            let started = started.clone();
+            let waiting = thread::spawn(move || {
+                (0..CALLS)
+                    .map(|_| {
+                        started.wait();
+                        let start = Instant::now();
+                        drop(registry.read().expect("Should be able to acquire registry"));
+                        start.elapsed()
+                    })
+                    .sum::<Duration>()
+            });
+            (0..CALLS).for_each(|_| call());
+            waiting.join().expect("Waiting thread should not panic")
+        };
+
+        let under_lock = measure(&|| {
+            let mut registry = extension.registry();
+            let mock = registry
+                .get_mut(&ADDRESS)
+                .expect("Mock should be registered");
+            mock.call(SELECTOR, vec![])
+                .expect("Mocked call should succeed");
+        });
+        let intercepted = measure(&|| {
+            extension.intercept_call(ADDRESS.encode(), true, SELECTOR.to_vec());
+        });
+
+        assert!(
+            intercepted * 10 < under_lock,
+            "{CALLS} calls held the lock for {under_lock:?} before and for {intercepted:?} now"
+        );
     }
 }
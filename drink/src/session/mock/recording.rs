@@ -0,0 +1,70 @@
+use std::collections::BTreeMap;
+
+use frame_support::weights::Weight;
+
+use crate::session::mock::Selector;
+
+/// A response of a real contract, recorded to be replayed by a mock.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RecordedResponse {
+    /// The encoded output of the message.
+    pub output: Vec<u8>,
+    /// The gas consumed by the call. Known only for the calls made by the session directly (not
+    /// for the nested ones).
+    pub gas_consumed: Option<Weight>,
+}
+
+/// Responses of a real contract, by the exact input (selector and arguments).
+///
+/// Produced with `MockingApi::start_recording` and `MockingApi::finish_recording`, and replayed
+/// with `ContractMock::from_recording`.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct MockRecording {
+    responses: BTreeMap<Vec<u8>, RecordedResponse>,
+}
+
+impl MockRecording {
+    /// Creates an empty recording.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `output` as the response to `input`. A later response to the same input replaces
+    /// the earlier one.
+    pub fn record(&mut self, input: Vec<u8>, output: Vec<u8>) {
+        self.responses.insert(
+            input,
+            RecordedResponse {
+                output,
+                gas_consumed: None,
+            },
+        );
+    }
+
+    /// Attaches the consumed gas to the response to `input`, if it has been recorded.
+    pub(crate) fn annotate_gas(&mut self, input: &[u8], gas_consumed: Weight) {
+        if let Some(response) = self.responses.get_mut(input) {
+            response.gas_consumed = Some(gas_consumed);
+        }
+    }
+
+    /// Returns the response to the message `selector` called with (encoded) `args`, if recorded.
+    pub fn response(&self, selector: Selector, args: &[u8]) -> Option<&RecordedResponse> {
+        self.responses.get(&[selector.as_slice(), args].concat())
+    }
+
+    /// Returns all the recorded responses, by the whole input.
+    pub fn responses(&self) -> &BTreeMap<Vec<u8>, RecordedResponse> {
+        &self.responses
+    }
+
+    /// Returns the number of the recorded responses.
+    pub fn len(&self) -> usize {
+        self.responses.len()
+    }
+
+    /// Returns `true` if nothing has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.responses.is_empty()
+    }
+}
@@ -1,21 +1,58 @@
 //! Mocking API for the sandbox.
+use std::sync::{Arc, RwLock};
+
+use frame_support::weights::Weight;
 use ink_sandbox::{api::prelude::*, AccountIdFor, Sandbox};
+use parity_scale_codec::Encode;
 
 use super::Session;
 use crate::{
     pallet_contracts::Config,
-    session::mock::ContractMock,
+    session::{
+        error::SessionError,
+        mock::{ContractMock, MockGasStrategy, MockRecording, MockRegistry, MockedCall},
+    },
     // DEFAULT_GAS_LIMIT,
 };
 
 /// Interface for basic mocking operations.
+///
+/// Note on gas: calls to a mocked contract are answered before the callee code is executed, so by
+/// default they are not charged anything beyond the regular call overhead, and `gas_consumed` of
+/// the outer call is lower than with the real callee. Use `Session::set_mock_gas_strategy` to
+/// charge the calls more realistically.
 pub trait MockingApi<R: Config> {
     /// Deploy `mock` as a standard contract. Returns the address of the deployed contract.
+    ///
+    /// Unless the mock is a spy (see `ContractMock::proxy_to`), a second contract is deployed
+    /// along with it: the mock contract burns the gas to charge (see `MockGasStrategy`) and gets
+    /// its output from that twin.
     fn deploy(&mut self, mock: ContractMock) -> AccountIdFor<R>;
 
     /// Mock part of an existing contract. In particular, allows to override real behavior of
     /// deployed contract's messages.
-    fn mock_existing_contract(&mut self, _mock: ContractMock, _address: AccountIdFor<R>);
+    ///
+    /// The calls that `mock` does not answer are executed by the contract itself (any proxy target
+    /// set on `mock` is overridden). The mocked calls are not charged gas (see `MockGasStrategy`).
+    /// Returns `SessionError::ContractNotFound` if there is no contract at `address`.
+    fn mock_existing_contract(
+        &mut self,
+        mock: ContractMock,
+        address: AccountIdFor<R>,
+    ) -> Result<(), SessionError>;
+
+    /// Returns all the calls received by the mock deployed at `address`, in order. Panics if there
+    /// is no mock there.
+    fn mock_calls(&self, address: &AccountIdFor<R>) -> Vec<MockedCall>;
+
+    /// Starts recording the responses of the (real) contract at `address` to the message calls,
+    /// including the nested ones, for `ContractMock::from_recording`. Restarts the recording if it
+    /// is already in progress.
+    fn start_recording(&mut self, address: AccountIdFor<R>);
+
+    /// Stops recording the responses of the contract at `address` and returns them. Panics if the
+    /// recording has not been started.
+    fn finish_recording(&mut self, address: &AccountIdFor<R>) -> MockRecording;
 }
 
 impl<T: Sandbox> MockingApi<T::Runtime> for Session<T>
@@ -25,39 +62,142 @@ where
     fn deploy(&mut self, mock: ContractMock) -> AccountIdFor<T::Runtime> {
         // We have to deploy some contract. We use a dummy contract for that. Thanks to that, we
         // ensure that the pallet will treat our mock just as a regular contract, until we actually
-        // call it.
-        let mock_bytes = wat::parse_str(DUMMY_CONTRACT).expect("Dummy contract should be valid");
+        // call it. A spy gets a contract that forwards the calls to the proxied one instead.
+        let (mock_address, twin) = match mock.proxy_target() {
+            None => {
+                let twin = self.deploy_mock_contract(DUMMY_CONTRACT, vec![]);
+                let charging = self.deploy_mock_contract(CHARGING_CONTRACT, twin.encode());
+                (charging, Some(twin))
+            }
+            Some(target) => {
+                let target = target.to_vec();
+                (self.deploy_mock_contract(FORWARDING_CONTRACT, target), None)
+            }
+        };
+
+        let mut mocks = self
+            .mocks()
+            .write()
+            .expect("Should be able to acquire lock on registry");
+        if let Some(twin) = twin {
+            mocks.register_twin(twin, mock_address.clone());
+        }
+        mocks.register(mock_address.clone(), mock);
+
+        mock_address
+    }
+
+    fn mock_existing_contract(
+        &mut self,
+        mock: ContractMock,
+        address: AccountIdFor<T::Runtime>,
+    ) -> Result<(), SessionError> {
+        self.ensure_contract_exists(&address)?;
+
+        // Like for a spy, the unanswered calls are executed normally - here by the contract itself.
+        let mock = mock.proxy_to(address.clone());
+        self.mocks()
+            .write()
+            .expect("Should be able to acquire lock on registry")
+            .register(address, mock);
+
+        Ok(())
+    }
+
+    fn mock_calls(&self, address: &AccountIdFor<T::Runtime>) -> Vec<MockedCall> {
+        self.mocks()
+            .read()
+            .expect("Should be able to acquire lock on registry")
+            .get(address)
+            .unwrap_or_else(|| panic!("No mock deployed at {address:?}"))
+            .calls()
+            .to_vec()
+    }
+
+    fn start_recording(&mut self, address: AccountIdFor<T::Runtime>) {
+        self.mocks()
+            .write()
+            .expect("Should be able to acquire lock on registry")
+            .start_recording(address);
+    }
+
+    fn finish_recording(&mut self, address: &AccountIdFor<T::Runtime>) -> MockRecording {
+        self.mocks()
+            .write()
+            .expect("Should be able to acquire lock on registry")
+            .finish_recording(address)
+            .unwrap_or_else(|| panic!("No recording in progress for {address:?}"))
+    }
+}
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config,
+{
+    /// Sets how much gas the calls answered by mocks charge the caller. Applies to the mocks that
+    /// are not spies (see `MockingApi::deploy`).
+    ///
+    /// ```rust, ignore
+    /// session.set_mock_gas_strategy(MockGasStrategy::Fixed(Weight::from_parts(1_000_000_000, 0)));
+    /// ```
+    pub fn set_mock_gas_strategy(&mut self, gas_strategy: MockGasStrategy) {
+        self.mocks()
+            .write()
+            .expect("Should be able to acquire lock on registry")
+            .set_gas_strategy(gas_strategy);
+    }
+
+    /// Returns how much gas the calls answered by mocks charge the caller.
+    pub fn mock_gas_strategy(&self) -> MockGasStrategy {
+        self.mocks()
+            .read()
+            .expect("Should be able to acquire lock on registry")
+            .gas_strategy()
+    }
+
+    fn mocks(&self) -> &Arc<RwLock<MockRegistry<AccountIdFor<T::Runtime>>>> {
+        self.mocks
+            .as_ref()
+            .expect("Mocking is disabled for this session")
+    }
+
+    /// Deploys the contract (in the WAT format) standing for a mock, with constructor `input`.
+    fn deploy_mock_contract(&mut self, wat: &str, input: Vec<u8>) -> AccountIdFor<T::Runtime> {
         let salt = self
-            .mocks
-            .lock()
+            .mocks()
+            .write()
             .expect("Should be able to acquire lock on registry")
             .salt();
 
-        let mock_address = self
-            .sandbox()
+        self.sandbox()
             .deploy_contract(
-                mock_bytes,
+                wat::parse_str(wat).expect("Mock contract should be valid"),
                 0u32.into(),
-                vec![],
+                input,
                 salt,
                 T::default_actor(),
                 T::default_gas_limit(),
                 None,
             )
             .result
-            .expect("Deployment of a dummy contract should succeed")
-            .account_id;
-
-        self.mocks
-            .lock()
-            .expect("Should be able to acquire lock on registry")
-            .register(mock_address.clone(), mock);
-
-        mock_address
+            .expect("Deployment of a mock contract should succeed")
+            .account_id
     }
 
-    fn mock_existing_contract(&mut self, _mock: ContractMock, _address: AccountIdFor<T::Runtime>) {
-        todo!("soon")
+    /// Attaches the gas consumed by the last call (to `callee`, with `input`) to its recorded
+    /// response, if the callee is being recorded.
+    pub(crate) fn annotate_recorded_gas(
+        &self,
+        callee: &AccountIdFor<T::Runtime>,
+        input: &[u8],
+        gas_consumed: Weight,
+    ) {
+        if let Some(mocks) = &self.mocks {
+            mocks
+                .write()
+                .expect("Should be able to acquire lock on registry")
+                .annotate_recorded_gas(callee, input, gas_consumed);
+        }
     }
 }
 
@@ -70,3 +210,135 @@ const DUMMY_CONTRACT: &str = r#"
 	(func (export "deploy"))
 	(func (export "call") (unreachable))
 )"#;
+
+/// A contract that is used to deploy a spy (see `ContractMock::proxy_to`).
+///
+/// The constructor stores its input (the encoded address of the proxied contract). Every message
+/// call is forwarded there, with the same input and value, as a tail call - the output (and the
+/// revert flag) of the proxied contract becomes the output of the spy.
+const FORWARDING_CONTRACT: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_set_storage" (func $seal_set_storage (param i32 i32 i32)))
+	(import "seal0" "seal_get_storage" (func $seal_get_storage (param i32 i32 i32) (result i32)))
+	(import "seal0" "seal_value_transferred" (func $seal_value_transferred (param i32 i32)))
+	(import "seal1" "seal_call" (func $seal_call (param i32 i32 i64 i32 i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; Memory layout:
+	;; [0, 32)    storage key (zeros)
+	;; [32, 36)   buffer length
+	;; [64, 96)   address of the proxied contract
+	;; [96, 112)  transferred value
+
+	(func (export "deploy")
+		(i32.store (i32.const 32) (i32.const 32))
+		(call $seal_input (i32.const 64) (i32.const 32))
+		(call $seal_set_storage (i32.const 0) (i32.const 64) (i32.load (i32.const 32)))
+	)
+
+	(func (export "call")
+		(i32.store (i32.const 32) (i32.const 32))
+		(drop (call $seal_get_storage (i32.const 0) (i32.const 64) (i32.const 32)))
+		(i32.store (i32.const 32) (i32.const 16))
+		(call $seal_value_transferred (i32.const 96) (i32.const 32))
+		;; Flags: FORWARD_INPUT | TAIL_CALL. Gas: 0 means all the remaining gas. No output buffer
+		;; (`u32::MAX`), since the output is returned directly.
+		(drop (call $seal_call
+			(i32.const 5)
+			(i32.const 64)
+			(i64.const 0)
+			(i32.const 96)
+			(i32.const 0)
+			(i32.const 0)
+			(i32.const -1)
+			(i32.const 0)
+		))
+		;; A tail call returns only if the proxied contract trapped.
+		(unreachable)
+	)
+)"#;
+
+/// A contract that is used to deploy a mock charging gas for the calls (see `MockGasStrategy`).
+///
+/// The constructor stores its input (the encoded address of the twin, answering on behalf of the
+/// mock). Every message call is passed to the twin, which returns the ref time to charge followed
+/// by the output of the mock. The contract burns the gas (checking the gas left after every
+/// iteration) and returns the output (and the revert flag) of the twin.
+const CHARGING_CONTRACT: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_set_storage" (func $seal_set_storage (param i32 i32 i32)))
+	(import "seal0" "seal_get_storage" (func $seal_get_storage (param i32 i32 i32) (result i32)))
+	(import "seal0" "seal_gas_left" (func $seal_gas_left (param i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "seal1" "seal_call" (func $seal_call (param i32 i32 i64 i32 i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; Memory layout:
+	;; [0, 32)      storage key (zeros)
+	;; [32, 36)     buffer length
+	;; [36, 40)     gas left length
+	;; [40, 48)     gas left (ref time)
+	;; [64, 96)     address of the twin
+	;; [96, 112)    value sent to the twin (zero)
+	;; [128, 136)   ref time to charge
+	;; [136, 65536) output of the mock
+
+	(func $gas_left (result i64)
+		(i32.store (i32.const 36) (i32.const 8))
+		(call $seal_gas_left (i32.const 40) (i32.const 36))
+		(i64.load (i32.const 40))
+	)
+
+	(func (export "deploy")
+		(i32.store (i32.const 32) (i32.const 32))
+		(call $seal_input (i32.const 64) (i32.const 32))
+		(call $seal_set_storage (i32.const 0) (i32.const 64) (i32.load (i32.const 32)))
+	)
+
+	(func (export "call")
+		(local $code i32)
+		(local $flags i32)
+		(local $target i64)
+
+		(i32.store (i32.const 32) (i32.const 32))
+		(drop (call $seal_get_storage (i32.const 0) (i32.const 64) (i32.const 32)))
+
+		;; Flags: CLONE_INPUT. Gas: 0 means all the remaining gas.
+		(i32.store (i32.const 32) (i32.const 65408))
+		(local.set $code (call $seal_call
+			(i32.const 2)
+			(i32.const 64)
+			(i64.const 0)
+			(i32.const 96)
+			(i32.const 0)
+			(i32.const 0)
+			(i32.const 128)
+			(i32.const 32)
+		))
+		;; A reverted twin (`CalleeReverted`) makes the mock revert, any other failure traps.
+		(if (i32.eq (local.get $code) (i32.const 2))
+			(then (local.set $flags (i32.const 1)))
+			(else (if (i32.ne (local.get $code) (i32.const 0)) (then (unreachable))))
+		)
+
+		;; Burn the gas down to the target. If there is not enough gas left, the target stays 0 and
+		;; the contract runs out of gas, like the real callee would.
+		(if (i64.lt_u (i64.load (i32.const 128)) (call $gas_left))
+			(then (local.set $target (i64.sub (call $gas_left) (i64.load (i32.const 128)))))
+		)
+		(block $charged
+			(loop $burn
+				(br_if $charged (i64.le_u (call $gas_left) (local.get $target)))
+				(br $burn)
+			)
+		)
+
+		(call $seal_return
+			(local.get $flags)
+			(i32.const 136)
+			(i32.sub (i32.load (i32.const 32)) (i32.const 8))
+		)
+	)
+)"#;
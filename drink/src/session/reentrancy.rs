@@ -0,0 +1,155 @@
+//! Detecting contracts re-entered within a single interaction.
+//!
+//! ```rust, ignore
+//! let mut session = Session::<MinimalSandbox>::default().with_reentrancy_detection();
+//! session.call::<_, ()>("withdraw", NO_ARGS, NO_ENDOWMENT)??;
+//! assert!(session.record().reentrancies().is_empty());
+//! ```
+
+use std::collections::BTreeSet;
+
+use ink_sandbox::{AccountIdFor, Sandbox};
+
+use crate::{
+    pallet_contracts::Config,
+    session::{error::SessionError, Session},
+};
+
+/// Tracks the active call chain, based on the callbacks of the intercepting extension (a contract
+/// enters the chain right before it is executed and leaves it after it returns).
+#[derive(Clone)]
+pub(crate) struct ReentrancyDetector<AccountId> {
+    enabled: bool,
+    fail: bool,
+    allowed: BTreeSet<AccountId>,
+    /// The contracts being executed, from the outermost one.
+    active: Vec<AccountId>,
+    /// The chains in which a contract has been re-entered, since the last `reset`.
+    detected: Vec<Vec<AccountId>>,
+}
+
+impl<AccountId> Default for ReentrancyDetector<AccountId> {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            fail: false,
+            allowed: BTreeSet::new(),
+            active: vec![],
+            detected: vec![],
+        }
+    }
+}
+
+impl<AccountId: Ord + Clone> ReentrancyDetector<AccountId> {
+    /// Called right before `address` is executed.
+    pub fn enter(&mut self, address: &AccountId) {
+        if !self.enabled {
+            return;
+        }
+        if self.active.contains(address) && !self.allowed.contains(address) {
+            let mut chain = self.active.clone();
+            chain.push(address.clone());
+            self.detected.push(chain);
+        }
+        self.active.push(address.clone());
+    }
+
+    /// Called after `address` has returned.
+    ///
+    /// A contract that traps is never reported as returned, so everything entered after the
+    /// last occurrence of `address` is dropped too.
+    pub fn exit(&mut self, address: &AccountId) {
+        if let Some(position) = self.active.iter().rposition(|active| active == address) {
+            self.active.truncate(position);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.active.clear();
+        self.detected.clear();
+    }
+}
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config,
+{
+    /// Enables re-entrancy detection and returns updated `self`.
+    ///
+    /// Whenever a contract is entered while it is already being executed (within the same
+    /// interaction), the active call chain is recorded (see `Record::reentrancies`). With
+    /// `set_fail_on_reentrancy`, such an interaction additionally fails with
+    /// `SessionError::ReentrancyDetected`. Contracts that are meant to be re-entered can be
+    /// excluded with `allow_reentrancy`.
+    ///
+    /// The detection relies on the intercepting extension, so it panics if the session was built
+    /// with `SessionBuilder::without_mocking`. Dry runs are not checked.
+    pub fn with_reentrancy_detection(self) -> Self {
+        self.update_reentrancy_detector(|detector| detector.enabled = true);
+        self
+    }
+
+    /// Sets whether an interaction in which a contract has been re-entered fails (with
+    /// `SessionError::ReentrancyDetected`, even if the contract call itself succeeded). Returns
+    /// the previous setting. Has no effect unless re-entrancy detection is enabled.
+    ///
+    /// The state changes of such an interaction are not reverted.
+    pub fn set_fail_on_reentrancy(&mut self, fail: bool) -> bool {
+        self.update_reentrancy_detector(|detector| std::mem::replace(&mut detector.fail, fail))
+    }
+
+    /// Allows the contract at `address` to be re-entered, i.e. it is no longer reported by the
+    /// re-entrancy detection.
+    pub fn allow_reentrancy(&mut self, address: AccountIdFor<T::Runtime>) {
+        self.update_reentrancy_detector(|detector| detector.allowed.insert(address));
+    }
+
+    /// Forgets the call chain of the previous interactions (or dry runs).
+    pub(crate) fn start_reentrancy_detection(&mut self) {
+        if self.mocks.is_some() {
+            self.update_reentrancy_detector(ReentrancyDetector::reset);
+        }
+    }
+
+    /// Records the chains in which a contract has been re-entered during the last interaction.
+    /// Fails with the first of them, if requested with `set_fail_on_reentrancy`.
+    pub(crate) fn record_reentrancies(&mut self) -> Result<(), SessionError> {
+        if self.mocks.is_none() {
+            return Ok(());
+        }
+        let (detected, fail) = self.update_reentrancy_detector(|detector| {
+            let detected = std::mem::take(&mut detector.detected);
+            detector.reset();
+            (detected, detector.fail)
+        });
+
+        let failure =
+            detected
+                .first()
+                .filter(|_| fail)
+                .map(|chain| SessionError::ReentrancyDetected {
+                    chain: chain.iter().map(|address| format!("{address:?}")).collect(),
+                });
+        for chain in detected {
+            #[cfg(feature = "tracing-logs")]
+            tracing::warn!(chain = ?chain, "contract re-entered");
+
+            self.recorder.record.push_reentrancy(chain);
+        }
+        failure.map_or(Ok(()), Err)
+    }
+
+    fn update_reentrancy_detector<V>(
+        &self,
+        f: impl FnOnce(&mut ReentrancyDetector<AccountIdFor<T::Runtime>>) -> V,
+    ) -> V {
+        let mocks = self.mocks.as_ref().expect(
+            "Re-entrancy detection is not available for this session (it was built with \
+             `SessionBuilder::without_mocking`)",
+        );
+        f(mocks
+            .write()
+            .expect("Should be able to acquire lock on registry")
+            .reentrancy_detector())
+    }
+}
@@ -0,0 +1,277 @@
+//! Repeating recorded contract interactions, e.g. against a new version of the contract.
+
+use std::collections::BTreeMap;
+
+use frame_support::{weights::Weight, CloneNoBound, DebugNoBound};
+use ink_sandbox::{AccountIdFor, ContractExecResultFor, ContractInstantiateResultFor, Sandbox};
+
+use crate::{
+    pallet_contracts::Config,
+    session::{bundle::ContractBundle, error::SessionError, BalanceOf, Session},
+};
+
+/// What a recorded interaction was.
+#[derive(CloneNoBound, DebugNoBound)]
+pub enum InteractionKind<R: Config> {
+    /// Contract deployment.
+    Deploy {
+        /// Constructor label.
+        constructor: String,
+        /// Constructor arguments.
+        args: Vec<String>,
+        /// Salt used for the deployment.
+        salt: Vec<u8>,
+        /// Address of the deployed contract (`None` if the deployment failed).
+        deployed: Option<AccountIdFor<R>>,
+    },
+    /// Contract call.
+    Call {
+        /// Callee address.
+        address: AccountIdFor<R>,
+        /// Message label.
+        message: String,
+        /// Message arguments.
+        args: Vec<String>,
+    },
+}
+
+/// The observable outcome of an interaction, compared during a replay.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RecordedResult {
+    /// Whether the interaction failed before or during execution (was aborted by the pallet).
+    pub failed: bool,
+    /// Whether the contract reverted.
+    pub reverted: bool,
+    /// The (encoded) data returned by the contract.
+    pub data: Vec<u8>,
+    /// The number of runtime events emitted during the interaction.
+    pub events: usize,
+}
+
+impl RecordedResult {
+    pub(super) fn of_deployment<R: Config>(
+        result: &ContractInstantiateResultFor<R>,
+        events: usize,
+    ) -> Self {
+        match &result.result {
+            Ok(exec_result) => Self {
+                failed: false,
+                reverted: exec_result.result.did_revert(),
+                data: exec_result.result.data.clone(),
+                events,
+            },
+            Err(_) => Self::failure(events),
+        }
+    }
+
+    pub(super) fn of_call<R: Config>(result: &ContractExecResultFor<R>, events: usize) -> Self {
+        match &result.result {
+            Ok(exec_result) => Self {
+                failed: false,
+                reverted: exec_result.did_revert(),
+                data: exec_result.data.clone(),
+                events,
+            },
+            Err(_) => Self::failure(events),
+        }
+    }
+
+    fn failure(events: usize) -> Self {
+        Self {
+            failed: true,
+            reverted: false,
+            data: vec![],
+            events,
+        }
+    }
+}
+
+/// A single contract interaction performed by the session, with everything needed to repeat it.
+#[derive(CloneNoBound, DebugNoBound)]
+pub struct RecordedInteraction<R: Config> {
+    /// The actor that performed the interaction.
+    pub actor: AccountIdFor<R>,
+    /// The value transferred to the contract.
+    pub endowment: BalanceOf<R>,
+    /// What the interaction was.
+    pub kind: InteractionKind<R>,
+    /// The gas limit in effect for the interaction (deployment or call gas limit, already scaled
+    /// by the gas scale).
+    pub gas_limit: Weight,
+    /// The storage deposit limit in effect for the interaction. `None` means that the deposit was
+    /// not checked against any limit.
+    pub storage_deposit_limit: Option<BalanceOf<R>>,
+    /// What came out of it.
+    pub result: RecordedResult,
+}
+
+/// A sequence of contract interactions, as recorded by the session (see `Record::scenario`).
+#[derive(CloneNoBound, DebugNoBound)]
+pub struct RecordedScenario<R: Config> {
+    /// The interactions, in order.
+    pub interactions: Vec<RecordedInteraction<R>>,
+}
+
+/// How a replayed interaction differs from the recorded one.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Divergence {
+    /// The interaction could not even be dispatched (e.g. the message does not exist anymore).
+    NotExecuted(String),
+    /// The interaction failed in one run, but not in the other.
+    FailureStatus {
+        /// Recorded status.
+        expected: bool,
+        /// Replayed status.
+        actual: bool,
+    },
+    /// The contract reverted in one run, but not in the other.
+    RevertStatus {
+        /// Recorded status.
+        expected: bool,
+        /// Replayed status.
+        actual: bool,
+    },
+    /// The contract returned different data.
+    ReturnValue {
+        /// Recorded (encoded) data.
+        expected: Vec<u8>,
+        /// Replayed (encoded) data.
+        actual: Vec<u8>,
+    },
+    /// A different number of events was emitted.
+    EventCount {
+        /// Recorded number of events.
+        expected: usize,
+        /// Replayed number of events.
+        actual: usize,
+    },
+}
+
+/// The outcome of `Session::replay`.
+#[derive(Clone, Debug, Default)]
+pub struct ReplayReport {
+    /// Divergences, as pairs of the interaction index (in the scenario) and the difference.
+    pub divergences: Vec<(usize, Divergence)>,
+}
+
+impl ReplayReport {
+    /// Returns `true` if the replay behaved exactly as recorded.
+    pub fn is_clean(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config,
+{
+    /// Repeats the interactions of `scenario`, with every deployment using `contract_file` (e.g.
+    /// a new version of the contract) and every call to a contract deployed in the scenario going
+    /// to its replayed counterpart. Returns the differences between the recorded and replayed
+    /// results.
+    ///
+    /// Deployment return data (addresses) is not compared. The session actor is restored
+    /// afterwards.
+    pub fn replay(
+        &mut self,
+        scenario: &RecordedScenario<T::Runtime>,
+        contract_file: ContractBundle,
+    ) -> ReplayReport {
+        let original_actor = self.get_actor();
+        let mut addresses = BTreeMap::new();
+        let mut report = ReplayReport::default();
+
+        for (idx, interaction) in scenario.interactions.iter().enumerate() {
+            self.set_actor(interaction.actor.clone());
+            let recorded_before = self.record().interactions().len();
+
+            let outcome = match &interaction.kind {
+                InteractionKind::Deploy {
+                    constructor,
+                    args,
+                    salt,
+                    deployed,
+                } => self
+                    .deploy(
+                        contract_file.wasm.clone(),
+                        constructor,
+                        args.as_slice(),
+                        salt.clone(),
+                        Some(interaction.endowment),
+                        &contract_file.transcoder,
+                    )
+                    .map(|address| {
+                        if let Some(deployed) = deployed {
+                            addresses.insert(deployed.clone(), address);
+                        }
+                    }),
+                InteractionKind::Call {
+                    address,
+                    message,
+                    args,
+                } => {
+                    let address = addresses.get(address).unwrap_or(address).clone();
+                    self.call_internal::<_, ()>(
+                        Some(address),
+                        message,
+                        args.as_slice(),
+                        Some(interaction.endowment),
+                        true,
+                    )
+                    .map(|_| ())
+                }
+            };
+
+            if self.record().interactions().len() == recorded_before {
+                let reason = outcome.err().map(|err: SessionError| err.to_string());
+                report
+                    .divergences
+                    .push((idx, Divergence::NotExecuted(reason.unwrap_or_default())));
+                continue;
+            }
+
+            let expected = &interaction.result;
+            let actual = &self.record().interactions()[recorded_before].result;
+            let deployment = matches!(interaction.kind, InteractionKind::Deploy { .. });
+            report
+                .divergences
+                .extend(compare(expected, actual, deployment).map(|d| (idx, d)));
+        }
+
+        self.set_actor(original_actor);
+        report
+    }
+}
+
+fn compare(
+    expected: &RecordedResult,
+    actual: &RecordedResult,
+    deployment: bool,
+) -> impl Iterator<Item = Divergence> {
+    let mut divergences = vec![];
+    if expected.failed != actual.failed {
+        divergences.push(Divergence::FailureStatus {
+            expected: expected.failed,
+            actual: actual.failed,
+        });
+    }
+    if expected.reverted != actual.reverted {
+        divergences.push(Divergence::RevertStatus {
+            expected: expected.reverted,
+            actual: actual.reverted,
+        });
+    }
+    if !deployment && expected.data != actual.data {
+        divergences.push(Divergence::ReturnValue {
+            expected: expected.data.clone(),
+            actual: actual.data.clone(),
+        });
+    }
+    if expected.events != actual.events {
+        divergences.push(Divergence::EventCount {
+            expected: expected.events,
+            actual: actual.events,
+        });
+    }
+    divergences.into_iter()
+}
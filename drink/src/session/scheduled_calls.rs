@@ -0,0 +1,176 @@
+//! Calls scheduled to be executed at a future block, e.g. by a keeper bot.
+//!
+//! ```rust, ignore
+//! let now = session.sandbox().block_number() as u64;
+//! let pool = CallTarget::Name("pool".to_string());
+//! session.schedule_call(now + 10, pool, "settle", NO_ARGS, keeper, None);
+//! session.advance_blocks(10);
+//! assert!(session.scheduled_call_failures().is_empty());
+//! ```
+
+use ink_sandbox::{AccountIdFor, Sandbox};
+
+use crate::{
+    pallet_contracts::Config,
+    session::{error::SessionError, BalanceOf, CallTarget, Session},
+};
+
+/// A contract call waiting for its block (see `Session::schedule_call`).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ScheduledCall<AccountId, Balance> {
+    /// Identifier of the call, e.g. for `Session::cancel_scheduled_call`.
+    pub id: u64,
+    /// The block at which the call is executed.
+    pub at_block: u64,
+    /// The called contract.
+    pub target: CallTarget<AccountId>,
+    /// Message label.
+    pub message: String,
+    /// Message arguments.
+    pub args: Vec<String>,
+    /// The account performing the call.
+    pub actor: AccountId,
+    /// Value transferred to the contract.
+    pub endowment: Option<Balance>,
+}
+
+/// A scheduled call that failed (see `Session::scheduled_call_failures`).
+#[derive(Clone, Debug)]
+pub struct ScheduledCallFailure<AccountId, Balance> {
+    /// The call.
+    pub call: ScheduledCall<AccountId, Balance>,
+    /// Why it failed.
+    pub error: SessionError,
+}
+
+/// Pending scheduled calls and the failures of the executed ones.
+#[derive(Clone)]
+pub(crate) struct Scheduler<AccountId, Balance> {
+    next_id: u64,
+    pending: Vec<ScheduledCall<AccountId, Balance>>,
+    failures: Vec<ScheduledCallFailure<AccountId, Balance>>,
+}
+
+impl<AccountId, Balance> Default for Scheduler<AccountId, Balance> {
+    fn default() -> Self {
+        Self {
+            next_id: 0,
+            pending: vec![],
+            failures: vec![],
+        }
+    }
+}
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config,
+{
+    /// Schedules a call of `message` on `target`, performed by `actor`, to be executed once the
+    /// session advances to block `at_block` (see `advance_blocks` and `run_scheduled_calls`).
+    /// Returns the identifier of the call.
+    ///
+    /// Calls due at the same block are executed in the order they were scheduled. A call
+    /// scheduled for the current (or a past) block is executed with the next block. The calls are
+    /// recorded like any other (see `Record::scheduled_interactions`); their errors don't
+    /// interrupt the block advancement, but are collected in `scheduled_call_failures`.
+    pub fn schedule_call<S: AsRef<str>>(
+        &mut self,
+        at_block: u64,
+        target: CallTarget<AccountIdFor<T::Runtime>>,
+        message: &str,
+        args: &[S],
+        actor: AccountIdFor<T::Runtime>,
+        endowment: Option<BalanceOf<T::Runtime>>,
+    ) -> u64 {
+        let id = self.scheduler.next_id;
+        self.scheduler.next_id += 1;
+        self.scheduler.pending.push(ScheduledCall {
+            id,
+            at_block,
+            target,
+            message: message.to_string(),
+            args: args.iter().map(|arg| arg.as_ref().to_string()).collect(),
+            actor,
+            endowment,
+        });
+        id
+    }
+
+    /// Returns the calls that have been scheduled, but not executed yet, in the order they were
+    /// scheduled.
+    pub fn pending_scheduled_calls(
+        &self,
+    ) -> &[ScheduledCall<AccountIdFor<T::Runtime>, BalanceOf<T::Runtime>>] {
+        &self.scheduler.pending
+    }
+
+    /// Cancels the pending scheduled call `id`. Returns the call, or `None` if there is no such
+    /// pending call.
+    pub fn cancel_scheduled_call(
+        &mut self,
+        id: u64,
+    ) -> Option<ScheduledCall<AccountIdFor<T::Runtime>, BalanceOf<T::Runtime>>> {
+        let idx = self
+            .scheduler
+            .pending
+            .iter()
+            .position(|call| call.id == id)?;
+        Some(self.scheduler.pending.remove(idx))
+    }
+
+    /// Returns the scheduled calls that have failed so far, in the order they were executed.
+    pub fn scheduled_call_failures(
+        &self,
+    ) -> &[ScheduledCallFailure<AccountIdFor<T::Runtime>, BalanceOf<T::Runtime>>] {
+        &self.scheduler.failures
+    }
+
+    /// Executes the scheduled calls that are due at the current block. Done automatically by
+    /// `advance_blocks`; call it after building blocks in another way (e.g. with the sandbox API).
+    pub fn run_scheduled_calls(&mut self) {
+        let block = self.current_block();
+        let (due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.scheduler.pending)
+            .into_iter()
+            .partition(|call| call.at_block <= block);
+        self.scheduler.pending = pending;
+
+        for call in due {
+            #[cfg(feature = "tracing-logs")]
+            tracing::debug!(
+                id = call.id,
+                message = %call.message,
+                "executing scheduled call"
+            );
+
+            if let Err(error) = self.execute_scheduled_call(&call) {
+                self.scheduler
+                    .failures
+                    .push(ScheduledCallFailure { call, error });
+            }
+        }
+    }
+
+    fn execute_scheduled_call(
+        &mut self,
+        call: &ScheduledCall<AccountIdFor<T::Runtime>, BalanceOf<T::Runtime>>,
+    ) -> Result<(), SessionError> {
+        let address = self.resolve_call_target(&call.target)?;
+        let interactions = self.recorder.record.interactions().len();
+
+        let previous_actor = self.set_actor(call.actor.clone());
+        // The return value is not needed, and `()` can be decoded from any successful result.
+        let result = self.call_internal::<_, ()>(
+            Some(address),
+            &call.message,
+            &call.args,
+            call.endowment,
+            true,
+        );
+        self.set_actor(previous_actor);
+
+        if self.recorder.record.interactions().len() > interactions {
+            self.recorder.record.push_scheduled(interactions);
+        }
+        result.map(|_| ())
+    }
+}
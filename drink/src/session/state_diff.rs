@@ -0,0 +1,339 @@
+//! Computing the changes that a contract interaction (or a sequence of them) has made to the
+//! state.
+//!
+//! ```rust, ignore
+//! let snapshot = session.snapshot();
+//! session.call::<_, ()>("increment", NO_ARGS, NO_ENDOWMENT)??;
+//! session.diff_since(&snapshot).assert_only(&[counter, session.get_actor()]);
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use frame_support::{
+    storage::{child::ChildInfo, storage_prefix, ChildTriePrefixIterator, PrefixIterator},
+    traits::{fungible::Inspect, PalletInfoAccess},
+};
+use ink_sandbox::{AccountIdFor, Sandbox};
+use parity_scale_codec::{Decode, Encode, Input};
+
+use crate::{
+    pallet_contracts::{Config, Pallet as Contracts},
+    session::{BalanceOf, HashFor, Session},
+};
+
+/// A single changed entry in the contract storage. Keys and values are raw (not decoded) bytes.
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct StorageChange {
+    /// The key of the entry.
+    pub key: Vec<u8>,
+    /// The value before the interaction (`None` if the entry did not exist).
+    pub before: Option<Vec<u8>>,
+    /// The value after the interaction (`None` if the entry has been removed).
+    pub after: Option<Vec<u8>>,
+}
+
+/// A change of the total balance of an account.
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct BalanceChange<Balance> {
+    /// The balance before the interaction.
+    pub before: Balance,
+    /// The balance after the interaction.
+    pub after: Balance,
+}
+
+/// The changes that a contract interaction has made (or would have made) to the state. It can be
+/// SCALE-encoded, e.g. to be stored next to the test as the expected outcome.
+#[derive(Encode, Decode)]
+pub struct StateDiff<R: Config> {
+    /// Changed contract storage entries, per contract.
+    pub storage: BTreeMap<AccountIdFor<R>, Vec<StorageChange>>,
+    /// Changed balances, per account.
+    pub balances: BTreeMap<AccountIdFor<R>, BalanceChange<BalanceOf<R>>>,
+    /// Newly instantiated contracts.
+    pub instantiated: Vec<AccountIdFor<R>>,
+    /// Hashes of the newly uploaded codes.
+    pub uploaded_codes: Vec<HashFor<R>>,
+}
+
+impl<R: Config> StateDiff<R> {
+    /// Returns `true` if the interaction has not changed anything.
+    pub fn is_empty(&self) -> bool {
+        self.storage.is_empty()
+            && self.balances.is_empty()
+            && self.instantiated.is_empty()
+            && self.uploaded_codes.is_empty()
+    }
+
+    /// Panics if the storage or the balance of any account outside `expected` has changed, or if
+    /// a contract outside `expected` has been instantiated. Uploaded codes are not checked (see
+    /// `uploaded_codes`).
+    ///
+    /// Use it to prove that a message has no unintended side effects. Remember that the caller's
+    /// balance changes whenever the interaction charges a storage deposit or transfers value.
+    #[track_caller]
+    pub fn assert_only(&self, expected: &[AccountIdFor<R>]) {
+        let unexpected = |account: &&AccountIdFor<R>| !expected.contains(account);
+
+        let mut changes = vec![];
+        for (contract, entries) in self.storage.iter().filter(|(a, _)| unexpected(a)) {
+            changes.push(format!(
+                "storage of {contract:?} ({} entries)",
+                entries.len()
+            ));
+        }
+        for (account, change) in self.balances.iter().filter(|(a, _)| unexpected(a)) {
+            changes.push(format!(
+                "balance of {account:?} ({:?} -> {:?})",
+                change.before, change.after
+            ));
+        }
+        for contract in self.instantiated.iter().filter(unexpected) {
+            changes.push(format!("instantiation of {contract:?}"));
+        }
+
+        if !changes.is_empty() {
+            panic!("Unexpected state changes: {}", changes.join(", "));
+        }
+    }
+}
+
+/// The state of a session at some point in time, to compare the later state with (see
+/// `Session::snapshot` and `Session::diff_since`).
+#[derive(Clone)]
+pub struct SessionSnapshot<R: Config>(StateSnapshot<R>);
+
+/// Contract storage, account balances and uploaded codes at some point in time.
+///
+/// Must be taken within externalities.
+#[derive(Clone)]
+pub(super) struct StateSnapshot<R: Config> {
+    storage: BTreeMap<AccountIdFor<R>, BTreeMap<Vec<u8>, Vec<u8>>>,
+    balances: BTreeMap<AccountIdFor<R>, BalanceOf<R>>,
+    codes: BTreeSet<HashFor<R>>,
+}
+
+impl<R: Config> StateSnapshot<R> {
+    pub(super) fn take() -> Self {
+        let storage = contract_tries::<R>()
+            .into_iter()
+            .map(|(contract, trie_id)| (contract, trie_entries(&trie_id)))
+            .collect();
+
+        let balances = frame_system::Account::<R>::iter_keys()
+            .map(|account| {
+                let balance = R::Currency::total_balance(&account);
+                (account, balance)
+            })
+            .collect();
+
+        let prefix = storage_prefix(Contracts::<R>::name().as_bytes(), b"PristineCode").to_vec();
+        let codes = PrefixIterator::<HashFor<R>>::new(prefix.clone(), prefix, |mut key, _| {
+            HashFor::<R>::decode(&mut key)
+        })
+        .collect();
+
+        Self {
+            storage,
+            balances,
+            codes,
+        }
+    }
+
+    /// Computes the changes between `self` and a later snapshot `after`.
+    pub(super) fn diff(self, mut after: Self) -> StateDiff<R> {
+        let instantiated = after
+            .storage
+            .keys()
+            .filter(|contract| !self.storage.contains_key(contract))
+            .cloned()
+            .collect();
+        let uploaded_codes = after.codes.difference(&self.codes).cloned().collect();
+
+        let mut storage = BTreeMap::new();
+        for (contract, mut before_entries) in self.storage {
+            let after_entries = after.storage.remove(&contract).unwrap_or_default();
+            let changes = entries_diff(&mut before_entries, after_entries);
+            if !changes.is_empty() {
+                storage.insert(contract, changes);
+            }
+        }
+        for (contract, after_entries) in after.storage {
+            let changes = entries_diff(&mut BTreeMap::new(), after_entries);
+            if !changes.is_empty() {
+                storage.insert(contract, changes);
+            }
+        }
+
+        let mut balances = BTreeMap::new();
+        for (account, before) in self.balances {
+            let after = after.balances.remove(&account).unwrap_or_default();
+            if before != after {
+                balances.insert(account, BalanceChange { before, after });
+            }
+        }
+        for (account, after) in after.balances {
+            balances.insert(
+                account,
+                BalanceChange {
+                    before: Default::default(),
+                    after,
+                },
+            );
+        }
+
+        StateDiff {
+            storage,
+            balances,
+            instantiated,
+            uploaded_codes,
+        }
+    }
+}
+
+fn entries_diff(
+    before: &mut BTreeMap<Vec<u8>, Vec<u8>>,
+    after: BTreeMap<Vec<u8>, Vec<u8>>,
+) -> Vec<StorageChange> {
+    let mut changes = vec![];
+    for (key, after_value) in after {
+        match before.remove(&key) {
+            Some(before_value) if before_value == after_value => {}
+            before_value => changes.push(StorageChange {
+                key,
+                before: before_value,
+                after: Some(after_value),
+            }),
+        }
+    }
+    for (key, before_value) in std::mem::take(before) {
+        changes.push(StorageChange {
+            key,
+            before: Some(before_value),
+            after: None,
+        });
+    }
+    changes.sort_by(|a, b| a.key.cmp(&b.key));
+    changes
+}
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config,
+{
+    /// Captures the current state, to be compared later with `diff_since`.
+    pub fn snapshot(&mut self) -> SessionSnapshot<T::Runtime> {
+        SessionSnapshot(
+            self.recorder
+                .sandbox
+                .execute_with(StateSnapshot::<T::Runtime>::take),
+        )
+    }
+
+    /// Summarizes what has changed since `snapshot` was taken: contract storage, balances,
+    /// instantiated contracts and uploaded codes.
+    pub fn diff_since(&mut self, snapshot: &SessionSnapshot<T::Runtime>) -> StateDiff<T::Runtime> {
+        let now = self
+            .recorder
+            .sandbox
+            .execute_with(StateSnapshot::<T::Runtime>::take);
+        snapshot.0.clone().diff(now)
+    }
+
+    /// Runs `action` and panics if it has grown the storage of the contract at `address`, i.e.
+    /// increased the number of its storage entries or their total size. Returns whatever `action`
+    /// returned.
+    ///
+    /// Use it to guard messages that must be storage-neutral (they only read or clean up after
+    /// themselves), so that they can't make their callers pay storage deposit.
+    #[track_caller]
+    pub fn assert_storage_neutral<V>(
+        &mut self,
+        address: &AccountIdFor<T::Runtime>,
+        action: impl FnOnce(&mut Self) -> V,
+    ) -> V {
+        let storage = |session: &mut Self| {
+            session
+                .recorder
+                .sandbox
+                .execute_with(|| contract_storage::<T::Runtime>(address))
+        };
+        let mut before = storage(self);
+        let ret = action(self);
+        let after = storage(self);
+
+        let size = |entries: &BTreeMap<Vec<u8>, Vec<u8>>| {
+            (entries.len(), entries.values().map(Vec::len).sum::<usize>())
+        };
+        let ((items_before, bytes_before), (items_after, bytes_after)) =
+            (size(&before), size(&after));
+        if items_after > items_before || bytes_after > bytes_before {
+            let grown = entries_diff(&mut before, after)
+                .into_iter()
+                .filter_map(|change| {
+                    let (old, new) = (
+                        change.before.map_or(0, |value| value.len()),
+                        change.after.map_or(0, |value| value.len()),
+                    );
+                    (new > old).then(|| format!("0x{} ({old} -> {new} bytes)", hex(&change.key)))
+                })
+                .collect::<Vec<_>>();
+            panic!(
+                "Storage of the contract {address:?} has grown from {items_before} entries \
+                 ({bytes_before} bytes) to {items_after} entries ({bytes_after} bytes). Grown \
+                 entries: {}",
+                grown.join(", ")
+            );
+        }
+        ret
+    }
+}
+
+/// Returns all the storage entries of the contract at `address` (none, if there is no such
+/// contract).
+fn contract_storage<R: Config>(address: &AccountIdFor<R>) -> BTreeMap<Vec<u8>, Vec<u8>> {
+    contract_tries::<R>()
+        .into_iter()
+        .find(|(contract, _)| contract == address)
+        .map(|(_, trie_id)| trie_entries(&trie_id))
+        .unwrap_or_default()
+}
+
+/// Returns all the entries of the contract child trie `trie_id`.
+fn trie_entries(trie_id: &[u8]) -> BTreeMap<Vec<u8>, Vec<u8>> {
+    let child_info = ChildInfo::new_default(trie_id);
+    ChildTriePrefixIterator::<(Vec<u8>, RawValue)>::with_prefix(&child_info, &[])
+        .map(|(key, value)| (key, value.0))
+        .collect()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Lists all contracts together with their child trie ids.
+///
+/// We read the raw `ContractInfoOf` storage, since it is not exposed by the pallet. The map is
+/// hashed with `Twox64Concat` and the first field of the `ContractInfo` is the trie id.
+pub(super) fn contract_tries<R: Config>() -> Vec<(AccountIdFor<R>, Vec<u8>)> {
+    let prefix = storage_prefix(Contracts::<R>::name().as_bytes(), b"ContractInfoOf").to_vec();
+    PrefixIterator::<(AccountIdFor<R>, Vec<u8>)>::new(prefix.clone(), prefix, |key, mut value| {
+        let contract = AccountIdFor::<R>::decode(&mut &key[8..])?;
+        let trie_id = Vec::<u8>::decode(&mut value)?;
+        Ok((contract, trie_id))
+    })
+    .collect()
+}
+
+/// Undecoded storage value.
+pub(super) struct RawValue(pub(super) Vec<u8>);
+
+impl Decode for RawValue {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, parity_scale_codec::Error> {
+        let len = input
+            .remaining_len()?
+            .ok_or("Unknown length of a raw storage value")?;
+        let mut value = vec![0u8; len];
+        input.read(&mut value)?;
+        Ok(RawValue(value))
+    }
+}
@@ -0,0 +1,70 @@
+//! Exporting and importing the full sandbox state.
+
+use frame_support::storage::{
+    child::{self, ChildInfo},
+    unhashed, ChildTriePrefixIterator, PrefixIterator,
+};
+use parity_scale_codec::{Decode, Encode};
+
+use crate::{
+    pallet_contracts::Config,
+    session::state_diff::{contract_tries, RawValue},
+};
+
+/// Prefix of the top-level keys under which the roots of child tries are kept.
+const CHILD_STORAGE_PREFIX: &[u8] = b":child_storage:";
+
+type Entries = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// All the key/value pairs of the sandbox storage: top-level ones and the ones in contract child
+/// tries. The entries are sorted by key, so the same state always gives the same dump.
+///
+/// Must be taken (and restored) within externalities.
+#[derive(Encode, Decode)]
+pub(super) struct StateDump {
+    top: Entries,
+    children: Vec<(Vec<u8>, Entries)>,
+}
+
+impl StateDump {
+    pub(super) fn take<R: Config>() -> Self {
+        let top = PrefixIterator::<(Vec<u8>, Vec<u8>)>::new(vec![], vec![], |key, value| {
+            Ok((key.to_vec(), value.to_vec()))
+        })
+        .filter(|(key, _)| !key.starts_with(CHILD_STORAGE_PREFIX))
+        .collect();
+
+        let children = contract_tries::<R>()
+            .into_iter()
+            .map(|(_, trie_id)| {
+                let entries = ChildTriePrefixIterator::<(Vec<u8>, RawValue)>::with_prefix(
+                    &ChildInfo::new_default(&trie_id),
+                    &[],
+                )
+                .map(|(key, value)| (key, value.0))
+                .collect();
+                (trie_id, entries)
+            })
+            .collect();
+
+        Self { top, children }
+    }
+
+    /// Replaces the whole current state with the dumped one.
+    pub(super) fn restore<R: Config>(self) {
+        for (_, trie_id) in contract_tries::<R>() {
+            let _ = child::clear_storage(&ChildInfo::new_default(&trie_id), None, None);
+        }
+        let _ = unhashed::clear_prefix(&[], None, None);
+
+        for (key, value) in self.top {
+            unhashed::put_raw(&key, &value);
+        }
+        for (trie_id, entries) in self.children {
+            let child_info = ChildInfo::new_default(&trie_id);
+            for (key, value) in entries {
+                child::put_raw(&child_info, &key, &value);
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! Decoding the storage of a contract with the layout from its metadata, for white-box assertions
+//! without getter messages.
+//!
+//! ```rust, ignore
+//! let storage = session.decode_contract_storage(&flipper)?;
+//! let Value::Map(fields) = storage else { unreachable!() };
+//! assert_eq!(fields.get_by_str("value"), Some(&Value::Bool(true)));
+//! ```
+
+use contract_transcode::{ContractMessageTranscoder, Map, Seq, Tuple, Value};
+use frame_support::{storage::child, Blake2_128Concat, StorageHasher};
+use ink_sandbox::{AccountIdFor, Sandbox};
+use serde_json::Value as Json;
+
+use crate::{
+    pallet_contracts::Config,
+    session::{error::SessionError, state_diff::contract_tries, Session},
+};
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config,
+{
+    /// Decodes the root storage struct of the contract at `address`, following the storage layout
+    /// from the metadata of its transcoder.
+    ///
+    /// Structs become `Value::Map`s named after the struct, enums are named after the variant.
+    /// `Lazy` fields are read from their own storage cells (`Value::Unit` if unset), while
+    /// `Mapping`s are not traversed: they are represented as placeholders, i.e.
+    /// `Value::Literal("Mapping(<root key>)")`.
+    pub fn decode_contract_storage(
+        &mut self,
+        address: &AccountIdFor<T::Runtime>,
+    ) -> Result<Value, SessionError> {
+        let transcoder = self
+            .transcoders
+            .get(address)
+            .ok_or_else(|| self.missing_transcoder(address))?;
+        let layout = serde_json::to_value(transcoder.metadata().layout())
+            .map_err(|err| SessionError::Decoding(err.to_string()))?;
+        let root = layout
+            .get("root")
+            .ok_or_else(|| SessionError::Decoding("Unsupported storage layout".to_string()))?;
+
+        self.recorder.sandbox.execute_with(|| {
+            let trie_id = contract_tries::<T::Runtime>()
+                .into_iter()
+                .find(|(contract, _)| contract == address)
+                .map(|(_, trie_id)| trie_id)
+                .ok_or_else(|| SessionError::ContractNotFound(format!("{address:?}")))?;
+            let decoder = LayoutDecoder {
+                transcoder: &transcoder,
+                trie: child::ChildInfo::new_default(&trie_id),
+            };
+            match decoder.decode_root(root) {
+                Ok(Value::Unit) => Err(SessionError::Decoding(
+                    "The root storage struct is not set".to_string(),
+                )),
+                result => result.map_err(SessionError::Decoding),
+            }
+        })
+    }
+}
+
+/// Walks the (JSON form of the) storage layout, reading the storage cells of a single contract.
+struct LayoutDecoder<'a> {
+    transcoder: &'a ContractMessageTranscoder,
+    trie: child::ChildInfo,
+}
+
+impl LayoutDecoder<'_> {
+    /// Decodes a layout stored in its own cell: the root struct, a `Lazy` field or a `Mapping`.
+    fn decode_root(&self, root: &Json) -> Result<Value, String> {
+        let root_key = field(root, "root_key")?
+            .as_str()
+            .ok_or("Invalid root key")?;
+        if self.is_mapping(root)? {
+            return Ok(Value::Literal(format!("Mapping({root_key})")));
+        }
+
+        // Contracts use variable-size keys, which the pallet hashes with `Blake2_128Concat`.
+        let key = Blake2_128Concat::hash(&hex_decode(root_key)?);
+        let Some(bytes) = child::get_raw(&self.trie, &key) else {
+            return Ok(Value::Unit);
+        };
+        let mut input = &bytes[..];
+        let value = self.decode(field(root, "layout")?, &mut input)?;
+        if !input.is_empty() {
+            return Err(format!(
+                "{} bytes left undecoded under {root_key}",
+                input.len()
+            ));
+        }
+        Ok(value)
+    }
+
+    /// Decodes `layout` from `input`, consuming the bytes of the value.
+    fn decode(&self, layout: &Json, input: &mut &[u8]) -> Result<Value, String> {
+        if let Some(leaf) = layout.get("leaf") {
+            let ty = field(leaf, "ty")?.as_u64().ok_or("Invalid type id")?;
+            return self
+                .transcoder
+                .decode(ty as u32, input)
+                .map_err(|err| err.to_string());
+        }
+        if let Some(root) = layout.get("root") {
+            return self.decode_root(root);
+        }
+        if let Some(layout) = layout.get("struct") {
+            return self.decode_struct(layout, input);
+        }
+        if let Some(layout) = layout.get("enum") {
+            let (discriminant, rest) = input.split_first().ok_or("Missing enum discriminant")?;
+            *input = rest;
+            let variant = field(layout, "variants")?
+                .get(discriminant.to_string())
+                .ok_or_else(|| format!("Unknown enum discriminant {discriminant}"))?;
+            return self.decode_struct(variant, input);
+        }
+        if let Some(array) = layout.get("array") {
+            let len = field(array, "len")?
+                .as_u64()
+                .ok_or("Invalid array length")?;
+            let elems = (0..len)
+                .map(|_| self.decode(field(array, "layout")?, input))
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(Value::Seq(Seq::new(elems)));
+        }
+        Err(format!("Unsupported storage layout: {layout}"))
+    }
+
+    /// Decodes a struct (or an enum variant) with its fields in order. Fields named with numbers
+    /// make a tuple.
+    fn decode_struct(&self, layout: &Json, input: &mut &[u8]) -> Result<Value, String> {
+        let name = field(layout, "name")?
+            .as_str()
+            .ok_or("Invalid struct name")?;
+        let fields = field(layout, "fields")?
+            .as_array()
+            .ok_or("Invalid struct fields")?
+            .iter()
+            .map(|field_layout| {
+                let field_name = field(field_layout, "name")?.as_str().unwrap_or_default();
+                let value = self.decode(field(field_layout, "layout")?, input)?;
+                Ok((field_name.to_string(), value))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        if fields.iter().all(|(name, _)| name.parse::<usize>().is_ok()) {
+            let values = fields.into_iter().map(|(_, value)| value).collect();
+            return Ok(Value::Tuple(Tuple::new(Some(name), values)));
+        }
+        let fields = fields
+            .into_iter()
+            .map(|(name, value)| (Value::String(name), value))
+            .collect();
+        Ok(Value::Map(Map::new(Some(name), fields)))
+    }
+
+    fn is_mapping(&self, root: &Json) -> Result<bool, String> {
+        let ty = field(root, "ty")?.as_u64().ok_or("Invalid type id")?;
+        Ok(self
+            .transcoder
+            .metadata()
+            .registry()
+            .resolve(ty as u32)
+            .and_then(|ty| ty.path.segments.last())
+            .is_some_and(|name| name == "Mapping"))
+    }
+}
+
+fn field<'a>(json: &'a Json, name: &str) -> Result<&'a Json, String> {
+    json.get(name)
+        .ok_or_else(|| format!("Missing `{name}` in the storage layout"))
+}
+
+fn hex_decode(hex: &str) -> Result<Vec<u8>, String> {
+    let hex = hex.trim_start_matches("0x");
+    (0..hex.len())
+        .step_by(2)
+        .map(|idx| {
+            hex.get(idx..idx + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| format!("Invalid storage key `{hex}`"))
+        })
+        .collect()
+}
@@ -0,0 +1,239 @@
+//! Deploying a whole system of contracts, referring to each other's addresses, in one go.
+//!
+//! ```rust, ignore
+//! let manifest = SystemManifest::new()
+//!     .deploy("checker", checker_bundle, "new", &["{{token}}"])
+//!     .deploy("token", token_bundle, "new", &["1000"]);
+//! // `token` is deployed first, since `checker` refers to it.
+//! let addresses = session.deploy_system(manifest)?;
+//! ```
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
+
+use ink_sandbox::{AccountIdFor, Sandbox};
+
+use crate::{
+    pallet_contracts::Config,
+    session::{bundle::ContractBundle, error::SessionError, BalanceOf, Session},
+};
+
+/// A single deployment of a `SystemManifest`.
+#[derive(Clone)]
+pub struct ManifestDeployment<Balance> {
+    /// The name that the contract is registered under (see `Session::name_contract`).
+    pub name: String,
+    /// The deployed contract.
+    pub bundle: ContractBundle,
+    /// Constructor label.
+    pub constructor: String,
+    /// Constructor arguments. Every `{{name}}` placeholder is replaced with the address of the
+    /// contract deployed (or already registered in the session) as `name`.
+    pub args: Vec<String>,
+    /// Salt of the deployment.
+    pub salt: Vec<u8>,
+    /// Value transferred to the contract.
+    pub endowment: Option<Balance>,
+}
+
+/// Contracts deployed together with `Session::deploy_system`.
+#[derive(Clone)]
+pub struct SystemManifest<Balance> {
+    /// The deployments, in the preferred order (a deployment is moved after the ones it refers
+    /// to).
+    pub deployments: Vec<ManifestDeployment<Balance>>,
+}
+
+impl<Balance> Default for SystemManifest<Balance> {
+    fn default() -> Self {
+        Self {
+            deployments: vec![],
+        }
+    }
+}
+
+impl<Balance> SystemManifest<Balance> {
+    /// Creates an empty manifest.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a deployment of `bundle` named `name`, with an empty salt and no endowment, and
+    /// returns updated `self`.
+    pub fn deploy<S: AsRef<str>>(
+        self,
+        name: &str,
+        bundle: ContractBundle,
+        constructor: &str,
+        args: &[S],
+    ) -> Self {
+        self.with(ManifestDeployment {
+            name: name.to_string(),
+            bundle,
+            constructor: constructor.to_string(),
+            args: args.iter().map(|arg| arg.as_ref().to_string()).collect(),
+            salt: vec![],
+            endowment: None,
+        })
+    }
+
+    /// Adds `deployment` and returns updated `self`.
+    pub fn with(mut self, deployment: ManifestDeployment<Balance>) -> Self {
+        self.deployments.push(deployment);
+        self
+    }
+}
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config,
+    AccountIdFor<T::Runtime>: Display,
+{
+    /// Deploys all the contracts of `manifest`, registers them under their names and returns
+    /// their addresses.
+    ///
+    /// A deployment referring to other ones (with `{{name}}` placeholders) is performed after
+    /// them; otherwise the manifest order is kept. Before anything is deployed, the manifest is
+    /// checked for taken or duplicate names (`SessionError::NameTaken`), placeholders that are
+    /// neither manifest nor session names (`SessionError::UnknownName`) and cyclic references
+    /// (`SessionError::CyclicDeployments`). If a deployment fails, the error says which one
+    /// (`SessionError::SystemDeploymentFailed`); the contracts deployed before stay registered.
+    pub fn deploy_system(
+        &mut self,
+        manifest: SystemManifest<BalanceOf<T::Runtime>>,
+    ) -> Result<BTreeMap<String, AccountIdFor<T::Runtime>>, SessionError> {
+        let order = self.deployment_order(&manifest.deployments)?;
+
+        let mut addresses = BTreeMap::new();
+        for idx in order {
+            let deployment = &manifest.deployments[idx];
+            let args = deployment
+                .args
+                .iter()
+                .map(|arg| self.resolve_placeholders(arg, &addresses))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let address = self
+                .deploy_bundle_as(
+                    &deployment.name,
+                    deployment.bundle.clone(),
+                    &deployment.constructor,
+                    &args,
+                    deployment.salt.clone(),
+                    deployment.endowment,
+                )
+                .map_err(|err| SessionError::SystemDeploymentFailed {
+                    name: deployment.name.clone(),
+                    error: Box::new(err),
+                })?;
+            addresses.insert(deployment.name.clone(), address);
+        }
+        Ok(addresses)
+    }
+
+    /// Validates `deployments` and orders them so that every deployment comes after the ones it
+    /// refers to. Returns the indices of the deployments.
+    fn deployment_order(
+        &self,
+        deployments: &[ManifestDeployment<BalanceOf<T::Runtime>>],
+    ) -> Result<Vec<usize>, SessionError> {
+        let mut indices = BTreeMap::new();
+        for (idx, deployment) in deployments.iter().enumerate() {
+            let name = &deployment.name;
+            if self.names.contains_key(name) || indices.insert(name.as_str(), idx).is_some() {
+                return Err(SessionError::NameTaken(name.clone()));
+            }
+        }
+
+        let mut references = vec![];
+        for deployment in deployments {
+            let mut referenced = vec![];
+            for name in deployment.args.iter().flat_map(|arg| placeholders(arg)) {
+                match indices.get(name) {
+                    Some(idx) => referenced.push(*idx),
+                    None if self.names.contains_key(name) => {}
+                    None => return Err(SessionError::UnknownName(name.to_string())),
+                }
+            }
+            references.push(referenced);
+        }
+
+        let mut order = vec![];
+        let mut done = BTreeSet::new();
+        for idx in 0..deployments.len() {
+            visit(idx, &references, &mut vec![], &mut done, &mut order).map_err(|cycle| {
+                SessionError::CyclicDeployments(
+                    cycle
+                        .into_iter()
+                        .map(|idx| deployments[idx].name.clone())
+                        .collect(),
+                )
+            })?;
+        }
+        Ok(order)
+    }
+
+    /// Replaces the placeholders in `arg` with the addresses of the contracts deployed so far
+    /// (`deployed`) or registered in the session.
+    fn resolve_placeholders(
+        &self,
+        arg: &str,
+        deployed: &BTreeMap<String, AccountIdFor<T::Runtime>>,
+    ) -> Result<String, SessionError> {
+        let mut resolved = arg.to_string();
+        for name in placeholders(arg) {
+            let address = deployed
+                .get(name)
+                .or_else(|| self.names.get(name))
+                .ok_or_else(|| SessionError::UnknownName(name.to_string()))?;
+            resolved = resolved.replace(&format!("{{{{{name}}}}}"), &address.to_string());
+        }
+        Ok(resolved)
+    }
+}
+
+/// Appends `idx` to `order` after all the deployments it (transitively) refers to. `path` holds
+/// the deployments being visited; on a cycle, returns the cycle (starting and ending with the
+/// same deployment).
+fn visit(
+    idx: usize,
+    references: &[Vec<usize>],
+    path: &mut Vec<usize>,
+    done: &mut BTreeSet<usize>,
+    order: &mut Vec<usize>,
+) -> Result<(), Vec<usize>> {
+    if done.contains(&idx) {
+        return Ok(());
+    }
+    if let Some(start) = path.iter().position(|visited| *visited == idx) {
+        let mut cycle = path[start..].to_vec();
+        cycle.push(idx);
+        return Err(cycle);
+    }
+
+    path.push(idx);
+    for referenced in &references[idx] {
+        visit(*referenced, references, path, done, order)?;
+    }
+    path.pop();
+
+    done.insert(idx);
+    order.push(idx);
+    Ok(())
+}
+
+/// Names referred to in `arg` with `{{name}}` placeholders.
+fn placeholders(arg: &str) -> Vec<&str> {
+    let mut names = vec![];
+    let mut rest = arg;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        names.push(&rest[start + 2..start + end]);
+        rest = &rest[start + end + 2..];
+    }
+    names
+}
@@ -1,15 +1,207 @@
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    sync::Arc,
+};
 
-use contract_transcode::ContractMessageTranscoder;
+use contract_transcode::{ContractMessageTranscoder, Value};
 
+use crate::session::error::SessionError;
+
+/// A message call decoded from its input data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedInput {
+    /// The label of the called message.
+    pub label: String,
+    /// The arguments, together with their names, in the order of declaration.
+    pub args: Vec<(String, Value)>,
+}
+
+impl Display for DecodedInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args = self
+            .args
+            .iter()
+            .map(|(name, value)| format!("{name}: {value}"))
+            .collect::<Vec<_>>();
+        write!(f, "{}({})", self.label, args.join(", "))
+    }
+}
+
+/// Sanity checks of contract metadata and decoding of the contract input.
+pub trait ContractMessageTranscoderExt {
+    /// Panics if two messages (or two constructors) of the contract share the same selector,
+    /// naming both of them. Useful when selectors are overridden manually.
+    fn assert_no_selector_collisions(&self);
+
+    /// Decodes the input of a message call (`data`, including the selector) into the message
+    /// label and its arguments. The message is resolved by its selector.
+    fn decode_input(&self, data: &[u8]) -> Result<DecodedInput, SessionError>;
+}
+
+impl ContractMessageTranscoderExt for ContractMessageTranscoder {
+    fn assert_no_selector_collisions(&self) {
+        let spec = self.metadata().spec();
+        assert_unique(
+            "message",
+            spec.messages()
+                .iter()
+                .map(|m| (m.selector().to_bytes().to_vec(), m.label())),
+        );
+        assert_unique(
+            "constructor",
+            spec.constructors()
+                .iter()
+                .map(|c| (c.selector().to_bytes().to_vec(), c.label())),
+        );
+    }
+
+    fn decode_input(&self, data: &[u8]) -> Result<DecodedInput, SessionError> {
+        if data.len() < 4 {
+            return Err(SessionError::Decoding(format!(
+                "Input 0x{} is shorter than a selector",
+                hex(data)
+            )));
+        }
+        let (selector, mut input) = data.split_at(4);
+
+        let contract = contract_name(self).unwrap_or_else(|| "<unknown>".to_string());
+        let message = self
+            .metadata()
+            .spec()
+            .messages()
+            .iter()
+            .find(|message| message.selector().to_bytes() == selector)
+            .ok_or_else(|| {
+                SessionError::Decoding(format!(
+                    "No message with selector 0x{} in the metadata of contract `{contract}`",
+                    hex(selector)
+                ))
+            })?;
+
+        let label = message.label().clone();
+        let args = message
+            .args()
+            .iter()
+            .map(|arg| {
+                self.decode(arg.ty().ty().id, &mut input)
+                    .map(|value| (arg.label().clone(), value))
+                    .map_err(|err| {
+                        SessionError::Decoding(format!(
+                            "Argument `{}` of message `{label}`: {err}",
+                            arg.label()
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if !input.is_empty() {
+            return Err(SessionError::Decoding(format!(
+                "{} trailing bytes in the input of message `{label}`",
+                input.len()
+            )));
+        }
+
+        Ok(DecodedInput { label, args })
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Builds the `SessionError::Encoding` for a failure (`err`) to encode the message (or the
+/// constructor) `label`, naming the contract (and its `address`, if known).
+///
+/// If there is no such message in the metadata, the closest label is suggested instead - calling a
+/// message of another contract is a frequent mistake.
+pub(crate) fn encoding_error(
+    transcoder: &ContractMessageTranscoder,
+    address: Option<String>,
+    label: &str,
+    constructor: bool,
+    err: impl Display,
+) -> SessionError {
+    let spec = transcoder.metadata().spec();
+    let labels = match constructor {
+        true => spec
+            .constructors()
+            .iter()
+            .map(|c| c.label())
+            .collect::<Vec<_>>(),
+        false => spec.messages().iter().map(|m| m.label()).collect(),
+    };
+    let kind = if constructor {
+        "constructor"
+    } else {
+        "message"
+    };
+
+    let contract = contract_name(transcoder).unwrap_or_else(|| "<unknown>".to_string());
+    let contract = match address {
+        Some(address) => format!("contract `{contract}` at {address}"),
+        None => format!("contract `{contract}`"),
+    };
+
+    if labels.iter().any(|other| *other == label) {
+        return SessionError::Encoding(format!("{err} ({kind} `{label}` of {contract})"));
+    }
+    let suggestion = labels
+        .iter()
+        .min_by_key(|other| edit_distance(label, other))
+        .map(|closest| format!(". Did you mean `{closest}`?"))
+        .unwrap_or_default();
+    SessionError::Encoding(format!(
+        "No {kind} `{label}` in the metadata of {contract}{suggestion}"
+    ))
+}
+
+/// Returns the name of the contract, i.e. the name of its storage struct.
+fn contract_name(transcoder: &ContractMessageTranscoder) -> Option<String> {
+    let layout = serde_json::to_value(transcoder.metadata().layout()).ok()?;
+    layout
+        .pointer("/root/layout/struct/name")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn assert_unique<'a>(kind: &str, selectors: impl Iterator<Item = (Vec<u8>, &'a String)>) {
+    let mut seen = BTreeMap::new();
+    for (selector, label) in selectors {
+        if let Some(other) = seen.insert(selector.clone(), label) {
+            panic!(
+                "Selector collision: {kind}s `{other}` and `{label}` share selector {selector:?}"
+            );
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct TranscoderRegistry<Contract: Ord> {
     transcoders: BTreeMap<Contract, Arc<ContractMessageTranscoder>>,
+    /// Contracts using the transcoder of another contract (see `Session::alias_transcoder`).
+    aliases: BTreeMap<Contract, Contract>,
 }
 
 impl<Contract: Ord> TranscoderRegistry<Contract> {
     pub fn new() -> Self {
         Self {
             transcoders: BTreeMap::new(),
+            aliases: BTreeMap::new(),
         }
     }
 
@@ -17,7 +209,80 @@ impl<Contract: Ord> TranscoderRegistry<Contract> {
         self.transcoders.insert(contract, Arc::clone(transcoder));
     }
 
+    /// Returns the transcoder for `contract`, following its alias, if any.
     pub fn get(&self, contract: &Contract) -> Option<Arc<ContractMessageTranscoder>> {
+        let contract = self.aliases.get(contract).unwrap_or(contract);
         self.transcoders.get(contract).map(Arc::clone)
     }
+
+    pub fn alias(&mut self, from: Contract, to: Contract) -> Option<Contract> {
+        self.aliases.insert(from, to)
+    }
+
+    pub fn remove_alias(&mut self, from: &Contract) -> Option<Contract> {
+        self.aliases.remove(from)
+    }
+
+    pub fn remove(&mut self, contract: &Contract) -> Option<Arc<ContractMessageTranscoder>> {
+        self.transcoders.remove(contract)
+    }
+
+    pub fn clear(&mut self) {
+        self.transcoders.clear();
+    }
+
+    pub fn contains(&self, contract: &Contract) -> bool {
+        self.get(contract).is_some()
+    }
+
+    pub fn registered_addresses(&self) -> impl Iterator<Item = &Contract> {
+        self.transcoders.keys()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::Fixture;
+
+    /// Selector of `emit(value: u32)` of `Fixture::EventEmitter`.
+    const EMIT: [u8; 4] = [0x07, 0x32, 0x9e, 0xfa];
+
+    fn emit_input(value: u32) -> Vec<u8> {
+        [EMIT.as_slice(), &value.to_le_bytes()].concat()
+    }
+
+    #[test]
+    fn input_is_decoded_into_the_message_and_its_arguments() {
+        let transcoder = Fixture::EventEmitter.bundle().transcoder;
+
+        let decoded = transcoder.decode_input(&emit_input(7)).unwrap();
+        assert_eq!(decoded.label, "emit");
+        assert_eq!(decoded.args, vec![("value".to_string(), Value::UInt(7))]);
+        assert_eq!(decoded.to_string(), "emit(value: 7)");
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        let transcoder = Fixture::EventEmitter.bundle().transcoder;
+
+        for input in [
+            // Shorter than a selector.
+            EMIT[..3].to_vec(),
+            // Unknown selector.
+            [[0; 4].as_slice(), &7u32.to_le_bytes()].concat(),
+            // Argument cut short.
+            emit_input(7)[..6].to_vec(),
+            // Trailing bytes.
+            [emit_input(7).as_slice(), &[1]].concat(),
+        ] {
+            assert!(
+                matches!(
+                    transcoder.decode_input(&input),
+                    Err(SessionError::Decoding(_))
+                ),
+                "Input {input:?} should be rejected"
+            );
+        }
+    }
 }
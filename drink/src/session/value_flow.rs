@@ -0,0 +1,141 @@
+//! Normalized view of the value transfers that happened during a contract interaction.
+
+use frame_support::sp_runtime::traits::UniqueSaturatedInto;
+use frame_system::Config as SysConfig;
+use ink_sandbox::{pallet_balances, pallet_contracts, AccountIdFor, Sandbox};
+
+use crate::{record::EventBatch, session::Session};
+
+/// What kind of value movement a `ValueFlow` is.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ValueFlowKind {
+    /// Plain transfer (e.g. an endowment or a transfer made by a contract).
+    Transfer,
+    /// Storage deposit charged from `from` and held on `to` (the contract).
+    StorageDepositHeld,
+    /// Storage deposit released from `from` (the contract) back to `to`.
+    StorageDepositReleased,
+}
+
+/// A single value movement: `from` paid `amount` to `to`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ValueFlow<AccountId> {
+    /// The paying account.
+    pub from: AccountId,
+    /// The receiving account.
+    pub to: AccountId,
+    /// The amount.
+    pub amount: u128,
+    /// Kind of the movement.
+    pub kind: ValueFlowKind,
+}
+
+impl<R> EventBatch<R>
+where
+    R: pallet_balances::Config + pallet_contracts::Config,
+    <R as SysConfig>::RuntimeEvent:
+        TryInto<pallet_balances::Event<R>> + TryInto<pallet_contracts::Event<R>>,
+{
+    /// Returns the value transfers that happened during the contract interaction, in event order.
+    ///
+    /// Flows are derived from the balances `Transfer` and the contracts storage deposit events. A
+    /// storage deposit is reported once (as a deposit), even if the runtime also emitted a plain
+    /// transfer event for it.
+    pub fn value_flows(&self) -> Vec<ValueFlow<AccountIdFor<R>>> {
+        let mut flows: Vec<(ValueFlow<AccountIdFor<R>>, bool)> = vec![];
+
+        for record in self.all_events() {
+            let flow = match record.event.clone().try_into() {
+                Ok(pallet_balances::Event::<R>::Transfer { from, to, amount }) => ValueFlow {
+                    from,
+                    to,
+                    amount: amount.unique_saturated_into(),
+                    kind: ValueFlowKind::Transfer,
+                },
+                _ => match record.event.clone().try_into() {
+                    Ok(pallet_contracts::Event::<R>::StorageDepositTransferredAndHeld {
+                        from,
+                        to,
+                        amount,
+                    }) => ValueFlow {
+                        from,
+                        to,
+                        amount: amount.unique_saturated_into(),
+                        kind: ValueFlowKind::StorageDepositHeld,
+                    },
+                    Ok(pallet_contracts::Event::<R>::StorageDepositTransferredAndReleased {
+                        from,
+                        to,
+                        amount,
+                    }) => ValueFlow {
+                        from,
+                        to,
+                        amount: amount.unique_saturated_into(),
+                        kind: ValueFlowKind::StorageDepositReleased,
+                    },
+                    _ => continue,
+                },
+            };
+
+            // Pair a deposit with the plain transfer event describing the same movement (if any).
+            let twin = flows.iter_mut().rev().find(|(other, paired)| {
+                !*paired
+                    && other.from == flow.from
+                    && other.to == flow.to
+                    && other.amount == flow.amount
+                    && (other.kind == ValueFlowKind::Transfer)
+                        != (flow.kind == ValueFlowKind::Transfer)
+            });
+            match twin {
+                Some((other, paired)) => {
+                    *paired = true;
+                    if other.kind == ValueFlowKind::Transfer {
+                        other.kind = flow.kind;
+                    }
+                }
+                None => flows.push((flow, false)),
+            }
+        }
+
+        flows.into_iter().map(|(flow, _)| flow).collect()
+    }
+}
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: pallet_balances::Config + pallet_contracts::Config,
+    <T::Runtime as SysConfig>::RuntimeEvent:
+        TryInto<pallet_balances::Event<T::Runtime>> + TryInto<pallet_contracts::Event<T::Runtime>>,
+{
+    /// Runs `action` and asserts that the contract interactions it performed moved exactly
+    /// `amount` from `from` to `to` (in a single transfer or deposit). Returns whatever `action`
+    /// returned.
+    ///
+    /// ```rust, ignore
+    /// session.assert_value_flow(alice, contract, 100, |session| {
+    ///     session.call::<_, ()>("deposit", NO_ARGS, Some(100))
+    /// })??;
+    /// ```
+    pub fn assert_value_flow<V>(
+        &mut self,
+        from: AccountIdFor<T::Runtime>,
+        to: AccountIdFor<T::Runtime>,
+        amount: u128,
+        action: impl FnOnce(&mut Self) -> V,
+    ) -> V {
+        let start = self.record().event_batches().len();
+        let result = action(self);
+        let flows = self.record().event_batches()[start..]
+            .iter()
+            .flat_map(EventBatch::value_flows)
+            .collect::<Vec<_>>();
+
+        assert!(
+            flows
+                .iter()
+                .any(|flow| flow.from == from && flow.to == to && flow.amount == amount),
+            "No value flow of {amount} from {from:?} to {to:?}. Observed flows: {flows:?}"
+        );
+        result
+    }
+}
@@ -0,0 +1,116 @@
+//! Read-only access to a session, for assertion helpers.
+//!
+//! ```rust, ignore
+//! fn assert_balance(view: &SessionView<MinimalSandbox>, who: &AccountId32, expected: u128) {
+//!     assert_eq!(view.free_balance(who), expected);
+//! }
+//!
+//! let view = session.view();
+//! assert_balance(&view, &alice, 100);
+//! assert_balance(&view, &bob, 0);
+//! ```
+
+use std::{
+    cell::{Ref, RefCell},
+    sync::Arc,
+};
+
+use contract_transcode::ContractMessageTranscoder;
+use frame_support::traits::fungible::Inspect;
+use ink_sandbox::{AccountIdFor, Sandbox};
+
+use crate::{
+    pallet_contracts::{Config, Pallet as Contracts},
+    record::{BalanceOf, Record},
+    session::{HashFor, Session},
+};
+
+/// A read-only view of a session, obtained with `Session::view`.
+///
+/// All the methods take `&self`, so the view can be shared by any number of helpers, none of
+/// which can perform contract interactions or change the sandbox state. Reading the runtime
+/// storage still has to enter the externalities of the sandbox, which needs exclusive access:
+/// the view holds the session borrowed for its whole lifetime.
+pub struct SessionView<'a, T: Sandbox>
+where
+    T::Runtime: Config,
+{
+    session: RefCell<&'a mut Session<T>>,
+}
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config,
+{
+    /// Returns a read-only view of the session.
+    pub fn view(&mut self) -> SessionView<'_, T> {
+        SessionView {
+            session: RefCell::new(self),
+        }
+    }
+}
+
+impl<T: Sandbox> SessionView<'_, T>
+where
+    T::Runtime: Config,
+{
+    /// Returns the record of the session.
+    pub fn record(&self) -> Ref<'_, Record<T::Runtime>> {
+        Ref::map(self.session.borrow(), |session| session.record())
+    }
+
+    /// Returns the actor of the session.
+    pub fn actor(&self) -> AccountIdFor<T::Runtime> {
+        self.session.borrow().get_actor()
+    }
+
+    /// Returns the transcoder registered for the contract at `address`.
+    pub fn transcoder(
+        &self,
+        address: &AccountIdFor<T::Runtime>,
+    ) -> Option<Arc<ContractMessageTranscoder>> {
+        self.session.borrow().transcoders.get(address)
+    }
+
+    /// Returns the address of the contract registered under `name`.
+    pub fn address_of(&self, name: &str) -> Option<AccountIdFor<T::Runtime>> {
+        self.session.borrow().address_of(name)
+    }
+
+    /// Returns the free balance of `who`.
+    pub fn free_balance(&self, who: &AccountIdFor<T::Runtime>) -> BalanceOf<T::Runtime> {
+        self.read(|| <T::Runtime as Config>::Currency::balance(who))
+    }
+
+    /// Returns the total issuance of the native currency.
+    pub fn total_issuance(&self) -> BalanceOf<T::Runtime> {
+        self.read(<T::Runtime as Config>::Currency::total_issuance)
+    }
+
+    /// Returns the hash of the code currently run by the contract at `address` (`None` if there is
+    /// no contract).
+    pub fn code_hash_of(&self, address: &AccountIdFor<T::Runtime>) -> Option<HashFor<T::Runtime>> {
+        self.read(|| Contracts::<T::Runtime>::code_hash(address))
+    }
+
+    /// Returns `true` if there is a contract at `address`.
+    pub fn contract_exists(&self, address: &AccountIdFor<T::Runtime>) -> bool {
+        self.code_hash_of(address).is_some()
+    }
+
+    /// Runs `f` within the externalities of the sandbox, like `Session::execute_with`, but
+    /// discards whatever it writes to the storage.
+    pub fn query<V>(&self, f: impl FnOnce() -> V) -> V {
+        self.session
+            .borrow_mut()
+            .recorder
+            .sandbox
+            .dry_run(|sandbox| sandbox.execute_with(f))
+    }
+
+    /// Runs `f`, which only reads the storage, within the externalities of the sandbox. Cheaper
+    /// than `query`, since nothing has to be reverted.
+    fn read<V>(&self, f: impl FnOnce() -> V) -> V {
+        self.session.borrow_mut().recorder.sandbox.execute_with(f)
+    }
+}
@@ -0,0 +1,53 @@
+//! Lints of the contract interactions, recorded as `Warning`s.
+//!
+//! ```rust, ignore
+//! session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+//! session.assert_no_warnings();
+//! ```
+
+use frame_support::weights::Weight;
+use ink_sandbox::Sandbox;
+
+use crate::{pallet_contracts::Config, record::WarningKind, session::Session};
+
+impl<T: Sandbox> Session<T>
+where
+    T::Runtime: Config,
+{
+    /// Panics (listing them) if any warnings have been recorded (see `Record::warnings`).
+    #[track_caller]
+    pub fn assert_no_warnings(&self) {
+        let warnings = self.record().warnings();
+        if !warnings.is_empty() {
+            let warnings = warnings
+                .iter()
+                .map(|warning| format!("  - {warning}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            panic!("Contract interactions have produced warnings:\n{warnings}");
+        }
+    }
+
+    /// Records a warning if the gas usage of the last (successful) interaction, performed with
+    /// `limit`, is close to the limit.
+    pub(crate) fn check_gas_usage(&mut self, limit: Weight, consumed: Weight, required: Weight) {
+        let kind = if near_limit(consumed, limit) {
+            WarningKind::GasConsumedNearLimit { consumed, limit }
+        } else if near_limit(required, limit) {
+            WarningKind::GasRequiredNearLimit { required, limit }
+        } else {
+            return;
+        };
+
+        #[cfg(feature = "tracing-logs")]
+        tracing::warn!(?kind, "gas usage close to the limit");
+
+        self.recorder.record.push_warning(kind);
+    }
+}
+
+/// Whether either component of `gas` is within 5% of the respective component of `limit`.
+fn near_limit(gas: Weight, limit: Weight) -> bool {
+    let near = |gas: u64, limit: u64| gas as u128 * 20 >= limit as u128 * 19;
+    near(gas.ref_time(), limit.ref_time()) || near(gas.proof_size(), limit.proof_size())
+}
@@ -0,0 +1,89 @@
+//! Conversions between `Weight` and human-friendly units.
+//!
+//! ```rust, ignore
+//! use drink::weights::{assert_weight_le, millis, proof_kb, HumanWeight};
+//!
+//! let limit = millis(50) + proof_kb(64);
+//! println!("{}", HumanWeight(limit)); // 50.000 ms, 64.00 KiB
+//! assert_weight_le(consumed, limit, 10);
+//! ```
+
+use std::fmt;
+
+use frame_support::weights::{
+    constants::{WEIGHT_REF_TIME_PER_MICROS, WEIGHT_REF_TIME_PER_MILLIS},
+    Weight,
+};
+
+const BYTES_PER_KB: u64 = 1024;
+
+/// Weight of `n` milliseconds of execution time (and no proof size).
+pub fn millis(n: u64) -> Weight {
+    Weight::from_parts(n.saturating_mul(WEIGHT_REF_TIME_PER_MILLIS), 0)
+}
+
+/// Weight of `n` microseconds of execution time (and no proof size).
+pub fn micros(n: u64) -> Weight {
+    Weight::from_parts(n.saturating_mul(WEIGHT_REF_TIME_PER_MICROS), 0)
+}
+
+/// Weight of `n` KiB of proof size (and no execution time).
+pub fn proof_kb(n: u64) -> Weight {
+    Weight::from_parts(0, n.saturating_mul(BYTES_PER_KB))
+}
+
+/// Displays both dimensions of a `Weight` with units, e.g. `1.234 ms, 12.50 KiB`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct HumanWeight(pub Weight);
+
+impl fmt::Display for HumanWeight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ref_time = self.0.ref_time();
+        let time = if ref_time >= WEIGHT_REF_TIME_PER_MILLIS {
+            format!(
+                "{:.3} ms",
+                ref_time as f64 / WEIGHT_REF_TIME_PER_MILLIS as f64
+            )
+        } else {
+            format!(
+                "{:.3} µs",
+                ref_time as f64 / WEIGHT_REF_TIME_PER_MICROS as f64
+            )
+        };
+
+        let proof_size = self.0.proof_size();
+        let proof = if proof_size >= BYTES_PER_KB * BYTES_PER_KB {
+            format!(
+                "{:.2} MiB",
+                proof_size as f64 / (BYTES_PER_KB * BYTES_PER_KB) as f64
+            )
+        } else if proof_size >= BYTES_PER_KB {
+            format!("{:.2} KiB", proof_size as f64 / BYTES_PER_KB as f64)
+        } else {
+            format!("{proof_size} B")
+        };
+
+        // Padding, so that the weight can be aligned in tables.
+        f.pad(&format!("{time}, {proof}"))
+    }
+}
+
+/// Panics unless both dimensions of `actual` are at most the ones of `expected`, increased by
+/// `tolerance_percent` percent.
+#[track_caller]
+pub fn assert_weight_le(actual: Weight, expected: Weight, tolerance_percent: u64) {
+    let allowed = |expected: u64| {
+        let allowed = expected as u128 * (100 + tolerance_percent as u128) / 100;
+        allowed.min(u64::MAX as u128) as u64
+    };
+    let allowed = Weight::from_parts(allowed(expected.ref_time()), allowed(expected.proof_size()));
+
+    if !actual.all_lte(allowed) {
+        panic!(
+            "Weight {} exceeds {} (with {tolerance_percent}% tolerance: {})",
+            HumanWeight(actual),
+            HumanWeight(expected),
+            HumanWeight(allowed),
+        );
+    }
+}
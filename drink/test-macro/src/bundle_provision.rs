@@ -34,6 +34,22 @@ impl BundleProviderGenerator {
         }
     }
 
+    /// Adds the bundle of the contract `name`. Fails if a different bundle is already provided
+    /// under the same name.
+    pub fn add_bundle(&mut self, name: String, path: PathBuf) -> Result<(), String> {
+        let variant = name.to_case(Case::Pascal);
+        match self.bundles.get(&variant) {
+            Some(existing) if *existing != path => Err(format!(
+                "Contract `{name}` is already provided (bundle: {})",
+                existing.display()
+            )),
+            _ => {
+                self.bundles.insert(variant, path);
+                Ok(())
+            }
+        }
+    }
+
     pub fn generate_bundle_provision(&self, enum_item: ItemEnum) -> TokenStream2 {
         let enum_name = &enum_item.ident;
         let enum_vis = &enum_item.vis;
@@ -44,7 +60,10 @@ impl BundleProviderGenerator {
             Some(root_name) => {
                 let local_bundle = self.bundles[root_name].to_str().expect("Invalid path");
                 quote! {
-                    pub fn local() -> ::drink::DrinkResult<::drink::session::ContractBundle> {
+                    pub fn local() -> ::std::result::Result<
+                        ::drink::session::ContractBundle,
+                        ::drink::session::error::SessionError,
+                    > {
                         ::drink::session::ContractBundle::load(#local_bundle)
                     }
                 }
@@ -65,6 +84,8 @@ impl BundleProviderGenerator {
             .unzip();
 
         quote! {
+            ::drink::__require_session_feature!();
+
             #(#enum_attrs)*
             #[derive(Copy, Clone, PartialEq, Eq, Debug)]
             #enum_vis enum #enum_name {
@@ -74,7 +95,10 @@ impl BundleProviderGenerator {
             impl #enum_name {
                 #local
 
-                pub fn bundle(self) -> ::drink::DrinkResult<::drink::session::ContractBundle> {
+                pub fn bundle(self) -> ::std::result::Result<
+                    ::drink::session::ContractBundle,
+                    ::drink::session::error::SessionError,
+                > {
                     match self {
                         #(#matches)*
                     }
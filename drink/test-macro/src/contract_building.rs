@@ -1,6 +1,8 @@
 use std::{
     collections::{hash_map::Entry, HashMap},
-    path::PathBuf,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    process::Command,
     sync::{Mutex, OnceLock},
 };
 
@@ -9,16 +11,66 @@ use contract_build::{
     BuildArtifacts, BuildMode, ExecuteArgs, Features, ImageVariant, ManifestPath, Network,
     OptimizationPasses, OutputType, Target, UnstableFlags, Verbosity, DEFAULT_MAX_MEMORY_PAGES,
 };
+use contract_metadata::ContractMetadata;
+use serde_json::Value;
+use syn::LitStr;
 
-use crate::bundle_provision::BundleProviderGenerator;
+use crate::{bundle_provision::BundleProviderGenerator, SynResult};
 
 /// Contract package differentiator.
 const INK_AS_DEPENDENCY_FEATURE: &str = "ink-as-dependency";
 
-/// Stores the manifest paths of all contracts that have already been built.
+/// Environment variable overriding the maximum number of memory pages of the built contracts.
+const MAX_MEMORY_PAGES_ENV: &str = "DRINK_MAX_MEMORY_PAGES";
+/// Environment variable disabling the validation of the built contracts (if set to `true`).
+const SKIP_WASM_VALIDATION_ENV: &str = "DRINK_SKIP_WASM_VALIDATION";
+/// Environment variable enabling the full output of the contract builds (if set to `1`).
+const BUILD_VERBOSE_ENV: &str = "DRINK_BUILD_VERBOSE";
+
+/// How many trailing lines of the compiler output are shown when a contract fails to build.
+const FAILURE_OUTPUT_LINES: usize = 100;
+
+/// The ink! metadata versions that `drink` can parse (exposed to `drink` with
+/// `__supported_metadata_versions!`).
+pub const SUPPORTED_METADATA_VERSIONS: RangeInclusive<u64> = 4..=5;
+
+/// Stores the manifest paths of all contracts that have already been built (with given options).
 ///
 /// This prevents from building the same contract for every testcase separately.
-static CONTRACTS_BUILT: OnceLock<Mutex<HashMap<PathBuf, (String, PathBuf)>>> = OnceLock::new();
+static CONTRACTS_BUILT: OnceLock<Mutex<HashMap<(PathBuf, BuildOptions), (String, PathBuf)>>> =
+    OnceLock::new();
+
+/// Options of the contract build, configurable with the macro attributes or environment variables.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct BuildOptions {
+    /// Maximum number of memory pages available to the contracts.
+    pub max_memory_pages: u32,
+    /// Whether to skip the validation of the built code.
+    pub skip_wasm_validation: bool,
+}
+
+impl BuildOptions {
+    /// Resolves the build options: attribute values take precedence over the environment
+    /// variables, which take precedence over the defaults.
+    pub fn resolve(max_memory_pages: Option<u32>, skip_wasm_validation: Option<bool>) -> Self {
+        let from_env = |name: &str| std::env::var(name).ok();
+
+        Self {
+            max_memory_pages: max_memory_pages
+                .or_else(|| {
+                    from_env(MAX_MEMORY_PAGES_ENV).map(|value| {
+                        value.parse().unwrap_or_else(|_| {
+                            panic!("Invalid value of `{MAX_MEMORY_PAGES_ENV}`: `{value}`")
+                        })
+                    })
+                })
+                .unwrap_or(DEFAULT_MAX_MEMORY_PAGES),
+            skip_wasm_validation: skip_wasm_validation
+                .or_else(|| from_env(SKIP_WASM_VALIDATION_ENV).map(|value| value == "true"))
+                .unwrap_or(false),
+        }
+    }
+}
 
 /// Build the current package with `cargo contract build --release` (if it is a contract package),
 /// as well as all its contract dependencies. Return a collection of paths to corresponding
@@ -28,14 +80,14 @@ static CONTRACTS_BUILT: OnceLock<Mutex<HashMap<PathBuf, (String, PathBuf)>>> = O
 ///
 /// A contract dependency, is a package defined in the `Cargo.toml` file with the
 /// `ink-as-dependency` feature enabled.
-pub fn build_contracts() -> BundleProviderGenerator {
+pub fn build_contracts(options: BuildOptions) -> BundleProviderGenerator {
     let metadata = MetadataCommand::new()
         .exec()
         .expect("Error invoking `cargo metadata`");
 
     let (maybe_root, contract_deps) = get_contract_crates(&metadata);
-    let maybe_root = maybe_root.map(build_contract_crate);
-    let contract_deps = contract_deps.map(build_contract_crate);
+    let maybe_root = maybe_root.map(|pkg| build_contract_crate(pkg, options));
+    let contract_deps = contract_deps.map(|pkg| build_contract_crate(pkg, options));
 
     BundleProviderGenerator::new(
         maybe_root.clone().into_iter().chain(contract_deps),
@@ -43,6 +95,55 @@ pub fn build_contracts() -> BundleProviderGenerator {
     )
 }
 
+/// Build the contract package at `path` (relative to the manifest directory of the crate being
+/// compiled) without any features. Return its name and the path to its `.contract` file.
+///
+/// Unlike the contract dependencies, the package doesn't need the `ink-as-dependency` feature.
+pub fn build_extra_contract(path: &LitStr, options: BuildOptions) -> SynResult<(String, PathBuf)> {
+    let fail = |reason: String| {
+        syn::Error::new(
+            path.span(),
+            format!(
+                "Error resolving extra contract `{}`: {reason}",
+                path.value()
+            ),
+        )
+    };
+
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| fail("`CARGO_MANIFEST_DIR` is not set".to_string()))?;
+    let manifest_path = Path::new(&manifest_dir)
+        .join(path.value())
+        .join("Cargo.toml");
+    let manifest_path = manifest_path
+        .canonicalize()
+        .map_err(|err| fail(format!("{}: {err}", manifest_path.display())))?;
+
+    let metadata = MetadataCommand::new()
+        .manifest_path(&manifest_path)
+        .no_deps()
+        .exec()
+        .map_err(|err| fail(format!("Error invoking `cargo metadata`: {err}")))?;
+    let package = metadata
+        .packages
+        .iter()
+        .find(|package| package.manifest_path.as_std_path() == manifest_path)
+        .ok_or_else(|| {
+            fail(format!(
+                "{} is not a package manifest",
+                manifest_path.display()
+            ))
+        })?;
+
+    Ok(build_contract_crate(
+        FeaturedPackage {
+            package,
+            features_on: vec![],
+        },
+        options,
+    ))
+}
+
 /// Contract package together with the features it should be built with.
 struct FeaturedPackage<'metadata> {
     package: &'metadata Package,
@@ -101,24 +202,28 @@ fn get_contract_crates(
     )
 }
 
-fn build_contract_crate(pkg: FeaturedPackage) -> (String, PathBuf) {
+fn build_contract_crate(pkg: FeaturedPackage, options: BuildOptions) -> (String, PathBuf) {
     let manifest_path = get_manifest_path(pkg.package);
     let mut features = Features::default();
-    for feature in pkg.features_on {
-        features.push(&feature);
+    for feature in &pkg.features_on {
+        features.push(feature);
     }
 
     match CONTRACTS_BUILT
         .get_or_init(|| Mutex::new(HashMap::new()))
         .lock()
         .expect("Error locking mutex")
-        .entry(manifest_path.clone().into())
+        .entry((manifest_path.clone().into(), options))
     {
         Entry::Occupied(ready) => ready.get().clone(),
         Entry::Vacant(todo) => {
+            let verbose = std::env::var(BUILD_VERBOSE_ENV).is_ok_and(|value| value == "1");
             let args = ExecuteArgs {
-                manifest_path,
-                verbosity: Verbosity::Default,
+                manifest_path: manifest_path.clone(),
+                verbosity: match verbose {
+                    true => Verbosity::Verbose,
+                    false => Verbosity::Quiet,
+                },
                 build_mode: BuildMode::Release,
                 features,
                 network: Network::Online,
@@ -128,17 +233,26 @@ fn build_contract_crate(pkg: FeaturedPackage) -> (String, PathBuf) {
                 keep_debug_symbols: false,
                 extra_lints: false,
                 output_type: OutputType::HumanReadable,
-                skip_wasm_validation: false,
+                skip_wasm_validation: options.skip_wasm_validation,
                 target: Target::Wasm,
-                max_memory_pages: DEFAULT_MAX_MEMORY_PAGES,
+                max_memory_pages: options.max_memory_pages,
                 image: ImageVariant::Default,
             };
 
-            let result = contract_build::execute(args).expect("Error building contract");
+            let result = contract_build::execute(args).unwrap_or_else(|err| {
+                panic!(
+                    "Error building contract `{}` (manifest: {}, features: [{}]): {err:?}\n\n{}",
+                    pkg.package.name,
+                    manifest_path.as_ref().display(),
+                    pkg.features_on.join(", "),
+                    compiler_output(&manifest_path, &pkg.features_on),
+                )
+            });
             let bundle_path = result
                 .metadata_result
                 .expect("Metadata should have been generated")
                 .dest_bundle;
+            check_metadata_version(&pkg.package.name, &bundle_path);
 
             let new_entry = (pkg.package.name.clone(), bundle_path);
             todo.insert(new_entry.clone());
@@ -147,6 +261,66 @@ fn build_contract_crate(pkg: FeaturedPackage) -> (String, PathBuf) {
     }
 }
 
+/// Fail the build right away if the bundle could not be loaded in the test, because of its ink!
+/// metadata version.
+fn check_metadata_version(package: &str, bundle_path: &Path) {
+    let metadata = ContractMetadata::load(bundle_path)
+        .unwrap_or_else(|err| panic!("Error loading the bundle of `{package}`: {err:?}"));
+    let version = match metadata.abi.get("version") {
+        Some(Value::Number(version)) => version.as_u64(),
+        Some(Value::String(version)) => version.parse().ok(),
+        // Before ink! 4, the metadata was nested under a version key, like `{"V3": {...}}`.
+        _ => metadata
+            .abi
+            .keys()
+            .find_map(|key| key.strip_prefix('V')?.parse().ok()),
+    };
+
+    match version {
+        Some(version) if SUPPORTED_METADATA_VERSIONS.contains(&version) => {}
+        _ => panic!(
+            "Contract `{package}` has ink! metadata version {}, but only versions {}-{} are \
+             supported. Use a compatible ink! version",
+            version.map_or_else(|| "unknown".to_string(), |version| version.to_string()),
+            SUPPORTED_METADATA_VERSIONS.start(),
+            SUPPORTED_METADATA_VERSIONS.end(),
+        ),
+    }
+}
+
+/// Returns the last lines of the compiler output for the package.
+///
+/// `contract_build` doesn't capture the output of the build it runs, so the package is checked
+/// once more (with the same features), this time with the output captured.
+fn compiler_output(manifest_path: &ManifestPath, features: &[String]) -> String {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let mut command = Command::new(cargo);
+    command
+        .arg("check")
+        .arg("--manifest-path")
+        .arg(manifest_path.as_ref())
+        .arg("--color=never");
+    if !features.is_empty() {
+        command.arg("--features").arg(features.join(","));
+    }
+
+    let output = match command.output() {
+        Ok(output) => output,
+        Err(err) => return format!("(compiler output unavailable: {err})"),
+    };
+    let output = String::from_utf8_lossy(&output.stderr).into_owned()
+        + &String::from_utf8_lossy(&output.stdout);
+    let lines = output.lines().collect::<Vec<_>>();
+    let skipped = lines.len().saturating_sub(FAILURE_OUTPUT_LINES);
+
+    let mut tail = match skipped {
+        0 => "Compiler output:\n".to_string(),
+        _ => format!("Compiler output (last {FAILURE_OUTPUT_LINES} lines):\n"),
+    };
+    tail.push_str(&lines[skipped..].join("\n"));
+    tail
+}
+
 fn get_manifest_path(package: &Package) -> ManifestPath {
     ManifestPath::new(package.manifest_path.clone().into_std_path_buf())
         .unwrap_or_else(|_| panic!("Error resolving manifest path for package {}", package.name))
@@ -9,9 +9,11 @@ use darling::{ast::NestedMeta, FromMeta};
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
-use syn::{ItemEnum, ItemFn};
+use syn::{ItemEnum, ItemFn, LitStr};
 
-use crate::contract_building::build_contracts;
+use crate::contract_building::{
+    build_contracts, build_extra_contract, BuildOptions, SUPPORTED_METADATA_VERSIONS,
+};
 
 type SynResult<T> = Result<T, syn::Error>;
 
@@ -19,7 +21,8 @@ type SynResult<T> = Result<T, syn::Error>;
 ///
 /// # Requirements
 ///
-/// - Your crate must have `drink` in its dependencies (and it shouldn't be renamed).
+/// - Your crate must have `drink` in its dependencies (and it shouldn't be renamed), with the
+///   `session` feature enabled (it is by default).
 /// - You mustn't import `drink::test` in the scope, where the macro is used. In other words, you
 ///   should always use the macro only with a qualified path `#[drink::test]`.
 /// - Your crate cannot be part of a cargo workspace.
@@ -50,6 +53,45 @@ type SynResult<T> = Result<T, syn::Error>;
 ///
 /// By default, the macro will use `drink::minimal::MinimalSandbox`.
 ///
+/// # Build options
+///
+/// - `max_memory_pages` - maximum number of memory pages available to the built contracts
+///   (defaults to the `DRINK_MAX_MEMORY_PAGES` environment variable, or the `cargo contract`
+///   default if it is not set),
+/// - `skip_wasm_validation` - skips validation of the built code (defaults to the
+///   `DRINK_SKIP_WASM_VALIDATION` environment variable being `true`).
+///
+/// The builds are quiet, unless the `DRINK_BUILD_VERBOSE` environment variable is set to `1`. If
+/// a contract fails to build, the macro panics with the tail of the compiler output, together with
+/// the manifest path and the features used.
+///
+/// Since the built artifacts are shared, use the same values in `#[drink::contract_bundle_provider]`
+/// (or just use the environment variables).
+///
+/// ```rust, ignore
+/// #[drink::test(max_memory_pages = 32)]
+/// fn testcase_with_big_contract(mut session: Session) { /* ... */ }
+/// ```
+///
+/// # Variants
+///
+/// With `variants(real, mocked)`, the macro generates one test per variant, named with the
+/// variant suffix (e.g. `testcase_real` and `testcase_mocked`), so that they can be filtered
+/// separately. The contracts are built once for all of them. The testcase function takes the
+/// `drink::TestVariant` as its second argument and can branch on it when setting up the
+/// dependencies, while the rest of the body is shared:
+///
+/// ```rust, ignore
+/// #[drink::test(variants(real, mocked))]
+/// fn testcase(mut session: Session, variant: TestVariant) {
+///     let token = match variant {
+///         TestVariant::Real => session.deploy_bundle(token_bundle, "new", &args, NO_SALT, NO_ENDOWMENT)?,
+///         TestVariant::Mocked => session.mocking_api().deploy(token_mock),
+///     };
+///     // ...
+/// }
+/// ```
+///
 /// # Example
 ///
 /// ```rust, ignore
@@ -71,6 +113,16 @@ pub fn test(attr: TokenStream, item: TokenStream) -> TokenStream {
 #[derive(FromMeta)]
 struct TestAttributes {
     sandbox: Option<syn::Path>,
+    max_memory_pages: Option<u32>,
+    skip_wasm_validation: Option<bool>,
+    variants: Option<darling::util::PathList>,
+}
+
+#[derive(FromMeta)]
+struct BundleProviderAttributes {
+    max_memory_pages: Option<u32>,
+    skip_wasm_validation: Option<bool>,
+    extra: Option<syn::Expr>,
 }
 
 /// Auxiliary function to enter ?-based error propagation.
@@ -78,7 +130,10 @@ fn test_internal(attr: TokenStream2, item: TokenStream2) -> SynResult<TokenStrea
     let item_fn = syn::parse2::<ItemFn>(item)?;
     let macro_args = TestAttributes::from_list(&NestedMeta::parse_meta_list(attr)?)?;
 
-    build_contracts();
+    build_contracts(BuildOptions::resolve(
+        macro_args.max_memory_pages,
+        macro_args.skip_wasm_validation,
+    ));
 
     let fn_vis = item_fn.vis;
     let fn_attrs = item_fn.attrs;
@@ -94,14 +149,53 @@ fn test_internal(attr: TokenStream2, item: TokenStream2) -> SynResult<TokenStrea
         .sandbox
         .unwrap_or(syn::parse2(quote! { ::drink::minimal::MinimalSandbox })?);
 
-    Ok(quote! {
-        #[test]
-        #(#fn_attrs)*
-        #fn_vis #fn_async #fn_const #fn_unsafety fn #fn_name #fn_generics () #fn_output {
-            let mut session = Session::<#sandbox>::default();
-            #fn_block
+    let Some(variants) = macro_args.variants else {
+        return Ok(quote! {
+            #[test]
+            #(#fn_attrs)*
+            #fn_vis #fn_async #fn_const #fn_unsafety fn #fn_name #fn_generics () #fn_output {
+                ::drink::__require_session_feature!();
+                let mut session = Session::<#sandbox>::default();
+                #fn_block
+            }
+        });
+    };
+
+    let variant_binding = match item_fn.sig.inputs.iter().nth(1) {
+        Some(syn::FnArg::Typed(arg)) => arg.pat.clone(),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &item_fn.sig.inputs,
+                "A test with variants must take the variant as its second argument, e.g. \
+                 `variant: TestVariant`",
+            ))
         }
-    })
+    };
+
+    let mut tests = vec![];
+    for variant in variants.iter() {
+        let unknown_variant = || {
+            syn::Error::new_spanned(variant, "Unknown test variant, expected `real` or `mocked`")
+        };
+        let suffix = variant.get_ident().ok_or_else(unknown_variant)?;
+        let marker = match suffix.to_string().as_str() {
+            "real" => quote! { ::drink::TestVariant::Real },
+            "mocked" => quote! { ::drink::TestVariant::Mocked },
+            _ => return Err(unknown_variant()),
+        };
+        let test_name = quote::format_ident!("{}_{}", fn_name, suffix);
+        tests.push(quote! {
+            #[test]
+            #(#fn_attrs)*
+            #fn_vis #fn_async #fn_const #fn_unsafety fn #test_name #fn_generics () #fn_output {
+                ::drink::__require_session_feature!();
+                let mut session = Session::<#sandbox>::default();
+                let #variant_binding = #marker;
+                #fn_block
+            }
+        });
+    }
+    Ok(quote! { #(#tests)* })
 }
 
 /// Defines a contract bundle provider.
@@ -127,7 +221,25 @@ fn test_internal(attr: TokenStream2, item: TokenStream2) -> SynResult<TokenStrea
 /// Besides that, the enum will receive a method `bundle(self)` that returns the contract bundle
 /// for corresponding contract variant.
 ///
-/// Both methods return `DrinkResult<ContractBundle>`.
+/// Both methods return `Result<ContractBundle, SessionError>`.
+///
+/// The macro accepts the same build options as `#[drink::test]` (`max_memory_pages` and
+/// `skip_wasm_validation`).
+///
+/// # Extra contracts
+///
+/// Contract packages that are not dependencies of the current crate (e.g. attacker contracts or
+/// old versions kept in sibling directories) can be listed with the `extra` attribute. Paths are
+/// relative to the directory of the current crate's `Cargo.toml`. Every package is built without
+/// any features (and with the same caching as the dependencies) and gets a variant named after the
+/// package.
+///
+/// ```rust, ignore
+/// #[drink::contract_bundle_provider(extra = ["../attack-contracts/reentrant", "../v1/token"])]
+/// enum BundleProvider {}
+///
+/// let attacker = BundleProvider::Reentrant.bundle()?;
+/// ```
 ///
 /// # Example
 ///
@@ -150,16 +262,70 @@ pub fn contract_bundle_provider(attr: TokenStream, item: TokenStream) -> TokenSt
     }
 }
 
+/// Expands to the range of ink! metadata versions that `drink` supports.
+///
+/// The range is defined here, so that the bundles built by the macros are checked against the same
+/// versions as the ones loaded by `drink::session::ContractBundle`.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __supported_metadata_versions(_input: TokenStream) -> TokenStream {
+    let start = proc_macro2::Literal::u64_suffixed(*SUPPORTED_METADATA_VERSIONS.start());
+    let end = proc_macro2::Literal::u64_suffixed(*SUPPORTED_METADATA_VERSIONS.end());
+    quote! { #start..=#end }.into()
+}
+
 /// Auxiliary function to enter ?-based error propagation.
 fn contract_bundle_provider_internal(
-    _attr: TokenStream2,
+    attr: TokenStream2,
     item: TokenStream2,
 ) -> SynResult<TokenStream2> {
     let enum_item = parse_bundle_enum(item)?;
-    let bundle_registry = build_contracts();
+    let macro_args = BundleProviderAttributes::from_list(&NestedMeta::parse_meta_list(attr)?)?;
+    let extra_paths = parse_extra_paths(macro_args.extra)?;
+    let options =
+        BuildOptions::resolve(macro_args.max_memory_pages, macro_args.skip_wasm_validation);
+
+    let mut bundle_registry = build_contracts(options);
+    for path in extra_paths {
+        let (name, bundle) = build_extra_contract(&path, options)?;
+        bundle_registry.add_bundle(name, bundle).map_err(|reason| {
+            syn::Error::new(
+                path.span(),
+                format!("Error adding extra contract `{}`: {reason}", path.value()),
+            )
+        })?;
+    }
     Ok(bundle_registry.generate_bundle_provision(enum_item))
 }
 
+/// Parses the value of the `extra` attribute: an array of string literals.
+fn parse_extra_paths(extra: Option<syn::Expr>) -> SynResult<Vec<LitStr>> {
+    let Some(extra) = extra else {
+        return Ok(vec![]);
+    };
+    let syn::Expr::Array(paths) = extra else {
+        return Err(syn::Error::new_spanned(
+            extra,
+            "`extra` must be an array of paths, e.g. `extra = [\"../other-contract\"]`",
+        ));
+    };
+
+    paths
+        .elems
+        .into_iter()
+        .map(|path| match path {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(path),
+                ..
+            }) => Ok(path),
+            other => Err(syn::Error::new_spanned(
+                other,
+                "Extra contract path must be a string literal",
+            )),
+        })
+        .collect()
+}
+
 fn parse_bundle_enum(item: TokenStream2) -> SynResult<ItemEnum> {
     let enum_item = syn::parse2::<ItemEnum>(item)?;
 
@@ -15,6 +15,7 @@ mod flipper {
     impl Flipper {
         #[ink(constructor)]
         pub fn new(init: bool) -> Self {
+            Self::env().emit_event(Flipped { new_value: init });
             Self { value: init }
         }
 
@@ -26,6 +27,13 @@ mod flipper {
             });
         }
 
+        /// Flips the value twice, emitting an event for each flip.
+        #[ink(message)]
+        pub fn flip_twice(&mut self) {
+            self.flip();
+            self.flip();
+        }
+
         #[ink(message)]
         pub fn get(&self) -> bool {
             self.value
@@ -35,9 +43,12 @@ mod flipper {
 
 #[cfg(test)]
 mod tests {
-    use std::error::Error;
+    use std::{cell::RefCell, error::Error, rc::Rc};
 
-    use drink::session::{Session, NO_ARGS, NO_ENDOWMENT};
+    use drink::{
+        sandbox_api::prelude::*,
+        session::{contract_transcode::Value, EventStats, Session, NO_ARGS, NO_ENDOWMENT},
+    };
 
     #[drink::contract_bundle_provider]
     enum BundleProvider {}
@@ -63,4 +74,183 @@ mod tests {
 
         Ok(())
     }
+
+    #[drink::test]
+    fn events_can_be_checked_in_order(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let address = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["false"],
+            vec![],
+            NO_ENDOWMENT,
+        )?;
+        session.call::<_, ()>("flip_twice", NO_ARGS, NO_ENDOWMENT)??;
+
+        let flipped_to = |value| ("Flipped", Some(vec![("new_value", Value::Bool(value))]));
+        session.assert_events_in_order(&address, &[flipped_to(true), flipped_to(false)]);
+        // Not necessarily one right after another, and possibly by the name only.
+        session.assert_events_in_order(&address, &[flipped_to(false)]);
+        session.assert_events_in_order(&address, &[("Flipped", None), ("Flipped", None)]);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    #[should_panic(expected = "do not match the expected order")]
+    fn events_out_of_order_are_reported(mut session: Session) {
+        let address = session
+            .deploy_bundle(
+                BundleProvider::local().unwrap(),
+                "new",
+                &["false"],
+                vec![],
+                NO_ENDOWMENT,
+            )
+            .unwrap();
+        session
+            .call::<_, ()>("flip_twice", NO_ARGS, NO_ENDOWMENT)
+            .unwrap()
+            .unwrap();
+
+        let flipped_to = |value| ("Flipped", Some(vec![("new_value", Value::Bool(value))]));
+        session.assert_events_in_order(&address, &[flipped_to(false), flipped_to(true)]);
+    }
+
+    #[drink::test]
+    fn we_can_inspect_constructor_events(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let bundle = BundleProvider::local()?;
+
+        let outcome =
+            session.deploy_full(bundle.clone(), "new", &["true"], vec![], NO_ENDOWMENT)?;
+
+        assert_eq!(&outcome.address, session.record().last_deploy_return());
+        assert_eq!(outcome.deployer, session.get_actor());
+        assert_eq!(
+            outcome.gas_consumed,
+            session.record().last_deploy_result().gas_consumed
+        );
+        // Only the constructor event is there.
+        let contract_events = outcome.events.contract_events_decoded(&bundle.transcoder);
+        assert_eq!(contract_events.len(), 1);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn observers_see_events_of_every_interaction(
+        mut session: Session,
+    ) -> Result<(), Box<dyn Error>> {
+        let bundle = BundleProvider::local()?;
+        let address =
+            session.deploy_bundle(bundle.clone(), "new", &["false"], vec![], NO_ENDOWMENT)?;
+
+        let seen = Rc::new(RefCell::new(vec![]));
+        let collected = Rc::clone(&seen);
+        let handle = session.on_contract_event(Some(address.clone()), move |event| {
+            collected.borrow_mut().push(event.to_string())
+        });
+        let counter = Rc::new(RefCell::new(0));
+        let counted = Rc::clone(&counter);
+        session.on_contract_event(None, move |_| *counted.borrow_mut() += 1);
+
+        session.call("flip", NO_ARGS, NO_ENDOWMENT)??;
+        assert_eq!(seen.borrow().len(), 1);
+        assert!(seen.borrow()[0].contains("true"));
+
+        // The removed observer is not called anymore, the other one is.
+        assert!(session.remove_event_observer(handle));
+        assert!(!session.remove_event_observer(handle));
+        session.call("flip", NO_ARGS, NO_ENDOWMENT)??;
+        assert_eq!(seen.borrow().len(), 1);
+        assert_eq!(*counter.borrow(), 2);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    #[should_panic(expected = "Contract event observer panicked on event #0")]
+    fn observer_panics_are_attributed(mut session: Session) {
+        let bundle = BundleProvider::local().unwrap();
+        session
+            .deploy_bundle(bundle, "new", &["false"], vec![], NO_ENDOWMENT)
+            .unwrap();
+        session.on_contract_event(None, |_| panic!("unexpected flip"));
+        let _ = session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT);
+    }
+
+    #[drink::test]
+    fn events_from_earlier_blocks_are_kept(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let bundle = BundleProvider::local()?;
+
+        session.deploy_bundle(bundle.clone(), "new", &["false"], vec![], NO_ENDOWMENT)?;
+        let first_block = session.record().last_event_batch().block_number();
+
+        // New blocks reset the events in the runtime, but not in the record.
+        session.sandbox().build_blocks(3);
+        session.call("flip", NO_ARGS, NO_ENDOWMENT)??;
+        session.sandbox().build_block();
+        session.call("flip", NO_ARGS, NO_ENDOWMENT)??;
+
+        let record = session.record();
+        let in_first_block = record.event_batches_in_block(first_block);
+        assert_eq!(in_first_block.len(), 1);
+        assert_eq!(in_first_block[0].contract_events().len(), 1);
+        assert_eq!(record.event_batches_in_block(first_block + 3).len(), 1);
+
+        // The constructor event and both flips.
+        assert_eq!(session.all_contract_events().len(), 3);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn dry_runs_do_not_leak_events(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let bundle = BundleProvider::local()?;
+
+        let address = session.deploy_bundle(bundle, "new", &["false"], vec![], NO_ENDOWMENT)?;
+        session.dry_run_call(address.clone(), "flip", NO_ARGS, NO_ENDOWMENT)?;
+        session.call::<_, bool>("get", NO_ARGS, NO_ENDOWMENT)??;
+        session.dry_run_call(address.clone(), "flip", NO_ARGS, NO_ENDOWMENT)?;
+        session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+        session.dry_run_call(address, "flip", NO_ARGS, NO_ENDOWMENT)?;
+
+        // Only the constructor and the real flip have emitted events.
+        let contract_events = session
+            .record()
+            .event_batches()
+            .iter()
+            .map(|batch| batch.contract_events().len())
+            .collect::<Vec<_>>();
+        assert_eq!(contract_events, vec![1, 0, 1]);
+        assert_eq!(session.all_contract_events().len(), 2);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn event_sizes_are_counted_per_interaction(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let bundle = BundleProvider::local()?;
+
+        session.deploy_bundle(bundle, "new", &["false"], vec![], NO_ENDOWMENT)?;
+        session.call("flip", NO_ARGS, NO_ENDOWMENT)??;
+        session.call::<_, bool>("get", NO_ARGS, NO_ENDOWMENT)??;
+
+        // Every `Flipped` event has a single `bool` field and a signature topic.
+        let flipped = EventStats {
+            emitted_event_count: 1,
+            emitted_event_bytes: 1 + 32,
+        };
+        assert_eq!(
+            session.record().event_stats(),
+            vec![flipped, flipped, EventStats::default()]
+        );
+
+        // The stats are exported along with the record.
+        let json = session.record_json();
+        assert_eq!(json["interactions"][1]["emitted_event_count"], 1);
+        assert_eq!(json["interactions"][1]["emitted_event_bytes"], 1 + 32);
+        assert_eq!(json["interactions"][2]["emitted_event_count"], 0);
+
+        Ok(())
+    }
 }
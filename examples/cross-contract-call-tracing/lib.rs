@@ -61,16 +61,27 @@ mod contract {
 
 #[cfg(test)]
 mod tests {
-    use std::{cell::RefCell, error::Error};
+    use std::{
+        cell::RefCell,
+        error::Error,
+        panic::{self, AssertUnwindSafe},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
 
     use drink::{
         pallet_contracts_debugging::{TracingExt, TracingExtT},
-        session::{contract_transcode::Value, Session, NO_ARGS, NO_ENDOWMENT},
+        session::{
+            contract_transcode::Value, ContractMessageTranscoderExt, Session, NO_ARGS,
+            NO_ENDOWMENT, NO_SALT,
+        },
         AccountId32,
     };
     use ink::storage::traits::Storable;
 
-    #[drink::contract_bundle_provider]
+    #[drink::contract_bundle_provider(extra = ["../flipper"])]
     enum BundleProvider {}
 
     thread_local! {
@@ -93,11 +104,13 @@ mod tests {
             let transcoder = BundleProvider::local().unwrap().transcoder;
 
             let data_decoded = if is_call {
-                transcoder.decode_contract_message(&mut input_data.as_slice())
+                transcoder.decode_input(&input_data).unwrap().to_string()
             } else {
-                transcoder.decode_contract_constructor(&mut input_data.as_slice())
-            }
-            .unwrap();
+                transcoder
+                    .decode_contract_constructor(&mut input_data.as_slice())
+                    .unwrap()
+                    .to_string()
+            };
 
             let return_decoded = if is_call {
                 let call_name = if contract_address
@@ -170,4 +183,82 @@ mod tests {
 
         Ok(())
     }
+
+    /// Counts the calls it has seen.
+    #[derive(Clone, Default)]
+    struct CountingTracer(Arc<AtomicUsize>);
+    impl TracingExtT for CountingTracer {
+        fn after_call(&self, _: Vec<u8>, _: bool, _: Vec<u8>, _: Vec<u8>) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    struct PanickingTracer;
+    impl TracingExtT for PanickingTracer {
+        fn after_call(&self, _: Vec<u8>, _: bool, _: Vec<u8>, _: Vec<u8>) {
+            panic!("Tracer failed");
+        }
+    }
+
+    #[drink::test]
+    fn all_added_tracers_are_called(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let (first, second) = (CountingTracer::default(), CountingTracer::default());
+        session.add_tracer(first.clone());
+        session.add_tracer(second.clone());
+
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            NO_ARGS,
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        assert_eq!(first.0.load(Ordering::Relaxed), 1);
+        assert_eq!(second.0.load(Ordering::Relaxed), 1);
+
+        // Setting the tracing extension drops the tracers added before.
+        session.set_tracing_extension(TracingExt(Box::new(CountingTracer::default())));
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            NO_ARGS,
+            vec![1],
+            NO_ENDOWMENT,
+        )?;
+        assert_eq!(first.0.load(Ordering::Relaxed), 1);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn tracers_run_even_if_one_of_them_panics(mut session: Session) {
+        let counter = CountingTracer::default();
+        session.add_tracer(PanickingTracer);
+        session.add_tracer(counter.clone());
+
+        let bundle = BundleProvider::local().unwrap();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            session.deploy_bundle(bundle, "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)
+        }));
+
+        assert!(
+            result.is_err(),
+            "The panic of the tracer should be propagated"
+        );
+        assert_eq!(counter.0.load(Ordering::Relaxed), 1);
+    }
+
+    #[drink::test]
+    fn contracts_from_extra_paths_are_provided(mut session: Session) -> Result<(), Box<dyn Error>> {
+        session.deploy_bundle(
+            BundleProvider::Flipper.bundle()?,
+            "new",
+            &["true"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        assert!(session.call::<_, bool>("get", NO_ARGS, NO_ENDOWMENT)??);
+
+        Ok(())
+    }
 }
@@ -2,16 +2,42 @@
 
 #[ink::contract]
 mod counter {
+    use ink::storage::Lazy;
+
+    /// Percentage with two decimal places (`1250` is 12.5%).
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub struct Percent(pub u32);
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum CounterError {
+        InitTooBig,
+        NotOwner,
+    }
+
     #[ink(storage)]
     pub struct Counter {
         value: u32,
+        owner: Lazy<AccountId>,
     }
 
     impl Counter {
         #[ink(constructor)]
         pub fn new(init: u32) -> Self {
             assert!(init < 10);
-            Self { value: init }
+            let mut owner = Lazy::new();
+            owner.set(&Self::env().caller());
+            Self { value: init, owner }
+        }
+
+        /// Like `new`, but returns an error instead of panicking.
+        #[ink(constructor)]
+        pub fn try_new(init: u32) -> Result<Self, CounterError> {
+            if init >= 10 {
+                return Err(CounterError::InitTooBig);
+            }
+            Ok(Self::new(init))
         }
 
         #[ink(message)]
@@ -19,24 +45,71 @@ mod counter {
             self.value = self.value.saturating_add(1);
         }
 
+        #[ink(message)]
+        pub fn increase_by(&mut self, percent: Percent) {
+            let increase = self.value as u64 * percent.0 as u64 / 10_000;
+            self.value = self.value.saturating_add(increase as u32);
+        }
+
+        /// Adds 1 in even blocks and 2 in odd ones. Returns the added amount.
+        #[ink(message)]
+        pub fn increment_depending_on_block(&mut self) -> u32 {
+            let increase = 1 + self.env().block_number() % 2;
+            self.value = self.value.saturating_add(increase);
+            increase
+        }
+
         #[ink(message)]
         pub fn get(&self) -> u32 {
             self.value
         }
+
+        /// Resets the value. Only the owner (the deployer) can do it.
+        #[ink(message)]
+        pub fn reset(&mut self) -> Result<(), CounterError> {
+            if Some(self.env().caller()) != self.owner.get() {
+                return Err(CounterError::NotOwner);
+            }
+            self.value = 0;
+            Ok(())
+        }
+
+        /// Returns the value, but only to the owner (the deployer).
+        #[ink(message)]
+        pub fn get_as_owner(&self) -> Option<u32> {
+            (Some(self.env().caller()) == self.owner.get()).then_some(self.value)
+        }
+
+        /// Always panics, after reporting the value in the debug buffer.
+        #[ink(message)]
+        pub fn crash(&self) {
+            ink::env::debug_println!("value: {}", self.value);
+            panic!("crashing on purpose");
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use drink::{
-        frame_support::sp_runtime::ModuleError,
-        minimal::{MinimalSandbox, RuntimeCall},
+        frame_support::{
+            pallet_prelude::{Decode, Encode},
+            sp_runtime::ModuleError,
+        },
+        minimal::{MinimalSandbox, MinimalSandboxRuntime, RuntimeCall},
         pallet_balances,
         sandbox_api::prelude::*,
-        session::{Session, NO_ARGS, NO_ENDOWMENT, NO_SALT},
-        AccountId32, DispatchError, Sandbox,
+        session::{
+            contract_transcode::{Tuple, Value},
+            error::SessionError,
+            Session, StateDiff, WarningKind, NO_ARGS, NO_ENDOWMENT, NO_SALT,
+        },
+        weights::{assert_weight_le, micros, millis, proof_kb, HumanWeight},
+        AccountId32, DispatchError, Sandbox, Weight,
     };
 
+    use super::counter::CounterError;
+
     #[drink::contract_bundle_provider]
     enum BundleProvider {}
 
@@ -86,6 +159,566 @@ mod tests {
         Ok(())
     }
 
+    #[drink::test]
+    fn constructor_errors_can_be_decoded(
+        mut session: Session,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let error: CounterError = session.deploy_and_expect_error(
+            BundleProvider::local()?,
+            "try_new",
+            &["10"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        assert_eq!(error, CounterError::InitTooBig);
+
+        // `Ok(..)` returned by the constructor is a successful deployment.
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "try_new",
+            &["5"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        assert_eq!(session.call::<_, u32>("get", NO_ARGS, NO_ENDOWMENT)??, 5);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn calls_can_be_retried_until_they_succeed(
+        mut session: Session,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["0"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+
+        // Every attempt happens in a new block, so one of the first two has to add 2.
+        let (attempt, result) = session.call_until(
+            2,
+            "increment_depending_on_block",
+            NO_ARGS,
+            NO_ENDOWMENT,
+            |result| matches!(result, Ok(2)),
+        )?;
+        assert!(attempt <= 2);
+        assert_eq!(result, Ok(2));
+        // The failed attempt has been rolled back.
+        assert_eq!(session.call::<_, u32>("get", NO_ARGS, NO_ENDOWMENT)??, 2);
+
+        let result = session.call_until::<_, u32>(
+            3,
+            "increment_depending_on_block",
+            NO_ARGS,
+            NO_ENDOWMENT,
+            |_| false,
+        );
+        assert!(matches!(
+            result,
+            Err(SessionError::AttemptsExhausted { attempts: 3, .. })
+        ));
+        assert_eq!(session.call::<_, u32>("get", NO_ARGS, NO_ENDOWMENT)??, 2);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn too_low_deposit_limit_is_diagnosed(
+        mut session: Session,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // Let us check how much deposit the deployment really needs.
+        let required = session
+            .dry_run_deployment(
+                BundleProvider::local()?,
+                "new",
+                &["5"],
+                NO_SALT,
+                NO_ENDOWMENT,
+            )?
+            .storage_deposit
+            .charge_or_zero();
+
+        // Now, let us set a limit that is insufficient for storing the contract.
+        session.set_storage_deposit_limit(Some(1));
+        let result = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["5"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        );
+
+        // The error should tell us both the limit and the required deposit.
+        assert!(matches!(
+            result,
+            Err(SessionError::StorageDepositTooLow { limit: 1, required: r }) if r == required
+        ));
+
+        // With diagnostics disabled, we get the plain pallet error.
+        session.set_diagnostics(false);
+        let result = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["5"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        );
+        assert!(matches!(result, Err(SessionError::DeploymentFailed(_))));
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn dry_runs_ignore_deposit_limit_unless_asked(
+        mut session: Session,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        session.set_storage_deposit_limit(Some(1));
+
+        // By default, a dry run tells us what the deployment would really cost...
+        let unchecked = session.dry_run_deployment(
+            BundleProvider::local()?,
+            "new",
+            &["5"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        assert!(unchecked.result.is_ok());
+        assert!(unchecked.storage_deposit.charge_or_zero() > 1);
+
+        // ...but we can also check how it would behave with a limit.
+        let limited = session.dry_run_deployment_with_limit(
+            BundleProvider::local()?,
+            "new",
+            &["5"],
+            NO_SALT,
+            NO_ENDOWMENT,
+            1,
+        )?;
+        assert!(limited.result.is_err());
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn too_low_gas_limit_is_diagnosed(
+        mut session: Session,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let address = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["5"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        let required = session
+            .dry_run_call(address.clone(), "increment", NO_ARGS, NO_ENDOWMENT)?
+            .gas_required;
+
+        // Let us give the call far too little gas.
+        let limit = micros(1) + proof_kb(1);
+        session.set_gas_limit(limit);
+        let result =
+            session.call_with_address::<_, ()>(address, "increment", NO_ARGS, NO_ENDOWMENT);
+
+        // The error should tell us how much gas the call really needs.
+        assert!(matches!(
+            result,
+            Err(SessionError::GasLimitTooLow { limit: l, required: r }) if l == limit && r == required
+        ));
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("limit is 1.000 µs, 1.00 KiB"));
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn gas_limit_can_be_scaled(mut session: Session) -> Result<(), Box<dyn std::error::Error>> {
+        let address = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["5"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        let required = session
+            .dry_run_call(address.clone(), "increment", NO_ARGS, NO_ENDOWMENT)?
+            .gas_required;
+        assert!(session.max_block_weight().all_gte(required));
+
+        // Leave only a tiny fraction of the gas limit for the call.
+        let gas_limit = session.get_gas_limit();
+        session.set_gas_scale(1, 1_000_000);
+        assert_eq!(session.get_gas_limit(), gas_limit);
+
+        // Dry runs see the scaled limit too.
+        let dry_run = session.dry_run_call(address.clone(), "increment", NO_ARGS, NO_ENDOWMENT)?;
+        assert!(dry_run.result.is_err());
+
+        let result =
+            session.call_with_address::<_, ()>(address.clone(), "increment", NO_ARGS, NO_ENDOWMENT);
+        assert!(matches!(
+            result,
+            Err(SessionError::GasLimitTooLow { limit, required: r })
+                if limit == session.effective_gas_limit() && r == required
+        ));
+
+        // Back to the full limit.
+        session.set_gas_scale(1, 1);
+        session.call_with_address::<_, ()>(address, "increment", NO_ARGS, NO_ENDOWMENT)??;
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn deployments_and_calls_can_have_separate_gas_limits(
+        session: Session,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let gas_limit = session.get_gas_limit();
+        let call_gas_limit = Weight::from_parts(1_000, 1_000);
+        let mut session = session.with_call_gas_limit(call_gas_limit);
+        assert_eq!(session.get_deploy_gas_limit(), gas_limit);
+
+        // The constructor gets the whole gas limit, while the call gets only a tiny one.
+        let address = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["5"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        let result =
+            session.call_with_address::<_, ()>(address.clone(), "increment", NO_ARGS, NO_ENDOWMENT);
+        assert!(matches!(
+            result,
+            Err(SessionError::GasLimitTooLow { limit, .. }) if limit == call_gas_limit
+        ));
+
+        // The record knows which limit was in effect.
+        let limits = session
+            .record()
+            .interactions()
+            .iter()
+            .map(|interaction| interaction.gas_limit)
+            .collect::<Vec<_>>();
+        assert_eq!(limits, [gas_limit, call_gas_limit]);
+
+        // Without its own limit, the call falls back to the common one.
+        assert_eq!(session.set_call_gas_limit(None), Some(call_gas_limit));
+        session.call_with_address::<_, ()>(address, "increment", NO_ARGS, NO_ENDOWMENT)??;
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn gas_usage_close_to_the_limit_is_reported(
+        mut session: Session,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let address = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["5"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        session.call_with_address::<_, ()>(
+            address.clone(),
+            "increment",
+            NO_ARGS,
+            NO_ENDOWMENT,
+        )??;
+        // The default limit is far away.
+        session.assert_no_warnings();
+
+        let required = session
+            .dry_run_call(address.clone(), "increment", NO_ARGS, NO_ENDOWMENT)?
+            .gas_required;
+        session.set_gas_limit(required);
+        session.call_with_address::<_, ()>(address, "increment", NO_ARGS, NO_ENDOWMENT)??;
+
+        let [warning] = session.record().warnings() else {
+            panic!("Expected a single warning");
+        };
+        assert_eq!(warning.interaction, 2);
+        assert!(matches!(
+            warning.kind,
+            WarningKind::GasConsumedNearLimit { limit, .. }
+                | WarningKind::GasRequiredNearLimit { limit, .. } if limit == required
+        ));
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn interactions_can_be_checked_against_a_block_budget(
+        mut session: Session,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let address = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["5"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        let consumed = session
+            .dry_run_call(address.clone(), "increment", NO_ARGS, NO_ENDOWMENT)?
+            .gas_consumed;
+
+        // Two increments fit in the budget, three don't.
+        let budget = consumed.saturating_mul(5) / 2;
+        let mut session = session.with_block_budget(budget);
+        assert_eq!(session.remaining_block_budget(), Some(budget));
+        for _ in 0..2 {
+            session.call_with_address::<_, ()>(
+                address.clone(),
+                "increment",
+                NO_ARGS,
+                NO_ENDOWMENT,
+            )??;
+        }
+        // Dry runs are not accounted.
+        session.dry_run_call(address.clone(), "increment", NO_ARGS, NO_ENDOWMENT)?;
+        assert!(session.remaining_block_budget().unwrap().any_lt(consumed));
+
+        let result =
+            session.call_with_address::<_, ()>(address.clone(), "increment", NO_ARGS, NO_ENDOWMENT);
+        assert!(matches!(
+            result,
+            Err(SessionError::BlockBudgetExceeded { used, budget: b }) if b == budget && used.any_gt(budget)
+        ));
+
+        // A new block comes with a fresh budget.
+        session.sandbox().build_block();
+        assert_eq!(session.remaining_block_budget(), Some(budget));
+
+        // Exceeding the budget can be just reported.
+        assert!(session.set_fail_on_block_budget(false));
+        for _ in 0..3 {
+            session.call_with_address::<_, ()>(
+                address.clone(),
+                "increment",
+                NO_ARGS,
+                NO_ENDOWMENT,
+            )??;
+        }
+        assert!(matches!(
+            session.record().warnings(),
+            [warning] if matches!(warning.kind, WarningKind::BlockBudgetExceeded { .. })
+        ));
+        let message = session.record().warnings()[0].to_string();
+        assert!(message.contains(&format!("over the block budget {}", HumanWeight(budget))));
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn deposit_limits_are_recorded(mut session: Session) -> Result<(), Box<dyn std::error::Error>> {
+        session.set_storage_deposit_limit(Some(1_000_000_000_000));
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["5"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        session.call::<_, ()>("increment", NO_ARGS, NO_ENDOWMENT)??;
+        session.assert_all_calls_deposit_checked();
+
+        session.set_storage_deposit_limit(None);
+        session.call::<_, ()>("increment", NO_ARGS, NO_ENDOWMENT)??;
+        assert_eq!(
+            session.record().deposit_limits(),
+            [Some(1_000_000_000_000), Some(1_000_000_000_000), None]
+        );
+
+        Ok(())
+    }
+
+    #[drink::test]
+    #[should_panic(expected = "Interactions #1 ran without a storage deposit limit")]
+    fn unchecked_deposits_are_reported(mut session: Session) {
+        session.set_storage_deposit_limit(Some(1_000_000_000_000));
+        session
+            .deploy_bundle(
+                BundleProvider::local().unwrap(),
+                "new",
+                &["5"],
+                NO_SALT,
+                NO_ENDOWMENT,
+            )
+            .unwrap();
+        session.set_storage_deposit_limit(None);
+        session
+            .call::<_, ()>("increment", NO_ARGS, NO_ENDOWMENT)
+            .unwrap()
+            .unwrap();
+
+        session.assert_all_calls_deposit_checked();
+    }
+
+    #[test]
+    fn weights_can_be_written_in_human_friendly_units() {
+        assert_eq!(millis(2), Weight::from_parts(2_000_000_000, 0));
+        assert_eq!(
+            micros(3) + proof_kb(4),
+            Weight::from_parts(3_000_000, 4_096)
+        );
+
+        assert_eq!(
+            HumanWeight(millis(1) + micros(234) + proof_kb(12)).to_string(),
+            "1.234 ms, 12.00 KiB"
+        );
+        assert_eq!(
+            HumanWeight(Weight::from_parts(1_500_000, 512)).to_string(),
+            "1.500 µs, 512 B"
+        );
+
+        // Within the tolerance.
+        assert_weight_le(millis(105), millis(100), 5);
+        assert_weight_le(millis(99) + proof_kb(1), millis(100) + proof_kb(1), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds 100.000 ms, 0 B (with 5% tolerance: 105.000 ms, 0 B)")]
+    fn weights_over_the_tolerance_are_reported() {
+        assert_weight_le(millis(106), millis(100), 5);
+    }
+
+    #[drink::test]
+    fn permission_checks_can_be_tested_at_once(
+        mut session: Session,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["5"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+
+        let strangers = [AccountId32::new([2; 32]), AccountId32::new([3; 32])];
+        session.assert_denied_for(&strangers, "reset", NO_ARGS, CounterError::NotOwner);
+
+        // The attempts have not changed anything, and the owner can still reset the counter.
+        assert_eq!(session.call::<_, u32>("get", NO_ARGS, NO_ENDOWMENT)??, 5);
+        assert_eq!(
+            session.call::<_, Result<(), CounterError>>("reset", NO_ARGS, NO_ENDOWMENT)??,
+            Ok(())
+        );
+        assert_eq!(session.call::<_, u32>("get", NO_ARGS, NO_ENDOWMENT)??, 0);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    #[should_panic(expected = "should have been denied, but it succeeded")]
+    fn permission_checks_name_the_allowed_actor(mut session: Session) {
+        session
+            .deploy_bundle(
+                BundleProvider::local().unwrap(),
+                "new",
+                &["5"],
+                NO_SALT,
+                NO_ENDOWMENT,
+            )
+            .unwrap();
+
+        let owner = session.get_actor();
+        session.assert_denied_for(&[owner], "reset", NO_ARGS, CounterError::NotOwner);
+    }
+
+    #[drink::test]
+    fn we_can_see_what_a_call_would_change(
+        mut session: Session,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let address = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["5"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+
+        // Dry-run `increment` and check which storage entries it would modify.
+        let (result, diff) =
+            session.dry_run_call_with_diff(address.clone(), "increment", NO_ARGS, NO_ENDOWMENT)?;
+        assert!(!result.did_revert());
+
+        let changes = &diff.storage[&address];
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].before.as_deref(), Some(&5u32.to_le_bytes()[..]));
+        assert_eq!(changes[0].after.as_deref(), Some(&6u32.to_le_bytes()[..]));
+
+        // Nothing was committed.
+        let value: u32 =
+            session.call_with_address(address.clone(), "get", NO_ARGS, NO_ENDOWMENT)??;
+        assert_eq!(value, 5);
+
+        // A read-only message changes nothing.
+        let (_, diff) = session.dry_run_call_with_diff(address, "get", NO_ARGS, NO_ENDOWMENT)?;
+        assert!(diff.is_empty());
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn changes_since_a_snapshot_can_be_checked(
+        mut session: Session,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let snapshot = session.snapshot();
+        let address = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["5"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        let diff = session.diff_since(&snapshot);
+        assert_eq!(diff.instantiated, vec![address.clone()]);
+        assert_eq!(diff.uploaded_codes.len(), 1);
+
+        let snapshot = session.snapshot();
+        session.call::<_, ()>("increment", NO_ARGS, NO_ENDOWMENT)??;
+        let diff = session.diff_since(&snapshot);
+        assert_eq!(diff.storage[&address].len(), 1);
+        assert!(diff.instantiated.is_empty() && diff.uploaded_codes.is_empty());
+        diff.assert_only(&[address, session.get_actor()]);
+
+        // The diff can be stored and compared later.
+        let decoded = StateDiff::<MinimalSandboxRuntime>::decode(&mut &diff.encode()[..])?;
+        assert_eq!(decoded.storage, diff.storage);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    #[should_panic(expected = "Unexpected state changes: storage of")]
+    fn unexpected_changes_are_reported(mut session: Session) {
+        let address = session
+            .deploy_bundle(
+                BundleProvider::local().unwrap(),
+                "new",
+                &["5"],
+                NO_SALT,
+                NO_ENDOWMENT,
+            )
+            .unwrap();
+
+        let snapshot = session.snapshot();
+        session
+            .call::<_, ()>("increment", NO_ARGS, NO_ENDOWMENT)
+            .unwrap()
+            .unwrap();
+        let diff = session.diff_since(&snapshot);
+        assert!(diff.storage.contains_key(&address));
+        diff.assert_only(&[]);
+    }
+
     #[test]
     fn we_can_dry_run_normal_runtime_transaction() {
         let mut sandbox = MinimalSandbox::default();
@@ -113,4 +746,189 @@ mod tests {
         assert_eq!(sandbox.free_balance(&bob), initial_balance);
         assert!(sandbox.events().is_empty());
     }
+
+    #[drink::test]
+    fn event_batches_know_the_interaction_outcome(
+        mut session: Session,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["1"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        let outcome = session
+            .record()
+            .last_event_batch()
+            .outcome()
+            .expect("Deployment should have an outcome");
+        assert!(outcome.success && !outcome.revert);
+        assert_eq!(
+            outcome.weight,
+            session.record().last_deploy_result().gas_consumed
+        );
+
+        // The constructor traps for too big an initial value.
+        assert!(session
+            .deploy_bundle(
+                BundleProvider::local()?,
+                "new",
+                &["10"],
+                NO_SALT,
+                NO_ENDOWMENT
+            )
+            .is_err());
+        assert!(session
+            .record()
+            .last_event_batch()
+            .outcome()
+            .is_some_and(|outcome| !outcome.success));
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn traps_carry_the_panic_message(
+        mut session: Session,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["5"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+
+        let Err(SessionError::ContractTrapped {
+            message,
+            last_debug_output,
+        }) = session.call::<_, ()>("crash", NO_ARGS, NO_ENDOWMENT)
+        else {
+            panic!("Expected the call to trap");
+        };
+        assert!(message.unwrap().contains("crashing on purpose"));
+        assert_eq!(last_debug_output[0], "value: 5");
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn calling_a_missing_contract_is_reported(
+        mut session: Session,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let nobody = AccountId32::new([42; 32]);
+        // Even with a transcoder at hand, there is no contract to call.
+        session.set_transcoder(nobody.clone(), &BundleProvider::local()?.transcoder);
+
+        let result =
+            session.call_with_address::<_, u32>(nobody.clone(), "get", NO_ARGS, NO_ENDOWMENT);
+        assert!(matches!(result, Err(SessionError::ContractNotFound(_))));
+        let result = session.dry_run_call(nobody.clone(), "get", NO_ARGS, NO_ENDOWMENT);
+        assert!(matches!(result, Err(SessionError::ContractNotFound(_))));
+
+        // `call_raw` leaves it to the pallet.
+        let result = session.call_raw::<_, u32>(nobody, "get", NO_ARGS, NO_ENDOWMENT);
+        assert!(matches!(result, Err(SessionError::CallFailed(_))));
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn we_can_dry_run_as_another_actor(
+        mut session: Session,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let bob = AccountId32::new([2; 32]);
+        let address = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["5"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+
+        let decode = |data: &[u8]| <Result<Option<u32>, ink::LangError>>::decode(&mut &data[..]);
+
+        let as_owner =
+            session.dry_run_call(address.clone(), "get_as_owner", NO_ARGS, NO_ENDOWMENT)?;
+        assert_eq!(decode(&as_owner.result?.data)?, Ok(Some(5)));
+
+        let as_bob =
+            session.dry_run_call_as(bob.clone(), address, "get_as_owner", NO_ARGS, NO_ENDOWMENT)?;
+        assert_eq!(decode(&as_bob.result?.data)?, Ok(None));
+
+        // The session actor didn't change.
+        assert_ne!(session.get_actor(), bob);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn custom_arguments_can_have_their_own_encoders(
+        mut session: Session,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let address = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["5"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+
+        // Without an encoder, `Percent` has to be passed in its internal representation.
+        assert!(matches!(
+            session.dry_run_call(address.clone(), "increase_by", &["200%"], NO_ENDOWMENT),
+            Err(SessionError::Encoding(_))
+        ));
+
+        session.register_arg_encoder("dry_running::counter::Percent", |arg| {
+            let percent = arg
+                .strip_suffix('%')
+                .ok_or("missing `%`")?
+                .parse::<f64>()
+                .map_err(|err| err.to_string())?;
+            let basis_points = Value::UInt((percent * 100.0) as u128);
+            Ok(Value::Tuple(Tuple::new(
+                Some("Percent"),
+                vec![basis_points],
+            )))
+        });
+        session.call::<_, ()>("increase_by", &["200%"], NO_ENDOWMENT)??;
+        assert_eq!(session.call::<_, u32>("get", NO_ARGS, NO_ENDOWMENT)??, 15);
+
+        // Encoder errors are reported as encoding errors.
+        assert!(matches!(
+            session.call::<_, ()>("increase_by", &["200"], NO_ENDOWMENT),
+            Err(SessionError::Encoding(_))
+        ));
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn we_can_dry_run_with_encoded_input(
+        mut session: Session,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let bundle = BundleProvider::local()?;
+
+        // Encode the constructor input ourselves and dry-run the deployment.
+        let data = bundle.transcoder.encode("new", ["5"])?;
+        let result = session.dry_run_deploy_raw(bundle.wasm.clone(), data, NO_SALT, NO_ENDOWMENT);
+        assert!(!result.result?.result.did_revert());
+
+        // Now, deploy the contract for real and call `get` with an encoded selector.
+        let address =
+            session.deploy_bundle(bundle.clone(), "new", &["5"], NO_SALT, NO_ENDOWMENT)?;
+        let recorded_calls = session.record().call_results().len();
+
+        let data = bundle.transcoder.encode("get", NO_ARGS)?;
+        let result = session.dry_run_call_raw(address, data, NO_ENDOWMENT);
+        let value = <Result<u32, ink::LangError>>::decode(&mut &result.result?.data[..])?;
+        assert_eq!(value, Ok(5));
+
+        // Nothing was recorded.
+        assert_eq!(session.record().call_results().len(), recorded_calls);
+
+        Ok(())
+    }
 }
@@ -32,9 +32,22 @@ mod flipper {
 
 #[cfg(test)]
 mod tests {
-    use std::error::Error;
+    use std::{cell::RefCell, error::Error, rc::Rc, time::Instant};
 
-    use drink::session::{Session, NO_ARGS, NO_ENDOWMENT, NO_SALT};
+    use drink::{
+        estimate::{estimate_call, CallState},
+        frame_support::traits::fungible::Mutate,
+        minimal::{MinimalSandbox, MinimalSandboxRuntime},
+        pallet_balances,
+        results::InteractionOutput,
+        sandbox_api::prelude::*,
+        session::{
+            bundle::SUPPORTED_METADATA_VERSIONS, contract_transcode::Value, error::SessionError,
+            hooks::Hook, CallTarget, ContractBundle, Session, SessionBuilder, SessionView, NO_ARGS,
+            NO_ENDOWMENT, NO_SALT,
+        },
+        AccountId32, Sandbox, Weight,
+    };
 
     #[drink::contract_bundle_provider]
     enum BundleProvider {}
@@ -54,6 +67,64 @@ mod tests {
         Ok(())
     }
 
+    #[drink::test]
+    fn storage_can_be_decoded(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let address = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["true"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        let stored_value = |session: &mut Session| -> Result<Value, Box<dyn Error>> {
+            let Value::Map(storage) = session.decode_contract_storage(&address)? else {
+                panic!("The storage should be decoded as a struct");
+            };
+            assert_eq!(storage.ident().as_deref(), Some("Flipper"));
+            Ok(storage
+                .get_by_str("value")
+                .cloned()
+                .expect("No `value` field"))
+        };
+
+        assert_eq!(stored_value(&mut session)?, Value::Bool(true));
+        session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+        assert_eq!(stored_value(&mut session)?, Value::Bool(false));
+
+        Ok(())
+    }
+
+    #[test]
+    fn bundles_with_old_metadata_are_rejected() -> Result<(), Box<dyn Error>> {
+        let bundle = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/target/ink/flipper.contract"
+        ))?;
+        assert!(ContractBundle::from_bytes(&bundle).is_ok());
+
+        // A bundle produced for ink! 3, with the metadata nested under `V3`.
+        let legacy = format!(
+            r#"{{
+                "source": {{
+                    "hash": "0x{}",
+                    "language": "ink! 3.4.0",
+                    "compiler": "rustc 1.68.0"
+                }},
+                "contract": {{ "name": "flipper", "version": "0.1.0", "authors": [] }},
+                "V3": {{}}
+            }}"#,
+            "00".repeat(32)
+        );
+        let result = ContractBundle::from_bytes(legacy.as_bytes());
+        assert!(matches!(
+            result,
+            Err(SessionError::UnsupportedMetadataVersion { found: 3, supported })
+                if supported == SUPPORTED_METADATA_VERSIONS
+        ));
+
+        Ok(())
+    }
+
     #[drink::test]
     fn flipping(mut session: Session) -> Result<(), Box<dyn Error>> {
         let contract = BundleProvider::Flipper.bundle()?;
@@ -71,4 +142,618 @@ mod tests {
 
         Ok(())
     }
+
+    #[drink::test]
+    fn record_can_be_queried_before_any_call(mut session: Session) -> Result<(), Box<dyn Error>> {
+        assert!(session.record().try_last_deploy_result().is_none());
+
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["true"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+
+        let record = session.record();
+        assert!(record.try_last_deploy_return().is_some());
+        assert!(record.try_last_call_result().is_none());
+        assert!(record.try_last_call_return().is_none());
+        assert!(record.try_last_event_batch().is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "No call results in the record: it holds 0 deploys")]
+    fn missing_results_are_explained() {
+        Session::<MinimalSandbox>::default()
+            .record()
+            .last_call_result();
+    }
+
+    #[drink::test]
+    fn record_knows_which_messages_were_called(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let bundle = BundleProvider::local()?;
+        let address =
+            session.deploy_bundle(bundle.clone(), "new", &["true"], NO_SALT, NO_ENDOWMENT)?;
+        assert_eq!(
+            session
+                .record()
+                .report_uncovered(&address, &bundle.transcoder),
+            vec!["flip".to_string(), "get".to_string()]
+        );
+
+        session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+        session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+
+        let coverage = session.record().call_coverage();
+        let flips = coverage[&(address.clone(), "flip".to_string())];
+        assert_eq!((flips.calls, flips.successes, flips.reverts), (2, 2, 0));
+        assert_eq!(
+            session
+                .record()
+                .report_uncovered(&address, &bundle.transcoder),
+            vec!["get".to_string()]
+        );
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn misspelled_messages_are_explained(mut session: Session) -> Result<(), Box<dyn Error>> {
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["true"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+
+        let Err(SessionError::Encoding(message)) =
+            session.call::<_, ()>("flipp", NO_ARGS, NO_ENDOWMENT)
+        else {
+            panic!("Calling a missing message should fail to encode");
+        };
+        assert!(message.contains("No message `flipp`"));
+        assert!(message.contains("contract `Flipper`"));
+        assert!(message.contains("Did you mean `flip`?"));
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn record_keeps_callers(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let alice = MinimalSandbox::default_actor();
+        let bob = AccountId32::new([2; 32]);
+        session
+            .sandbox()
+            .mint_into(&bob, 1_000_000_000_000)
+            .expect("Minting should succeed");
+
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["true"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+        session.set_actor(bob.clone());
+        session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+        session.call::<_, bool>("get", NO_ARGS, NO_ENDOWMENT)??;
+
+        let record = session.record();
+        assert_eq!(record.deployers(), &[alice.clone()]);
+        assert_eq!(record.callers(), &[alice.clone(), bob.clone(), bob.clone()]);
+        assert_eq!(record.calls_by(&alice).count(), 1);
+        assert_eq!(record.calls_by(&bob).count(), 2);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn record_knows_the_block_of_every_interaction(
+        mut session: Session,
+    ) -> Result<(), Box<dyn Error>> {
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["true"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        session.sandbox().build_blocks(2);
+        session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+        session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+
+        let blocks = session
+            .record()
+            .blocks()
+            .iter()
+            .map(|block| block.number)
+            .collect::<Vec<_>>();
+        assert_eq!(blocks[1], blocks[0] + 2);
+        assert_eq!(blocks[2], blocks[1]);
+
+        // Event batches know their block as well.
+        assert_eq!(
+            session.record().last_event_batch().block_number(),
+            blocks[2]
+        );
+
+        // So do the JSON export and the summary of the record.
+        let json = session.record_json();
+        assert_eq!(json["interactions"][1]["block"]["number"], blocks[1]);
+        let summary = format!("{:?}", session.record());
+        assert!(summary.contains(&format!(
+            "#1 call by {:?} in block {}",
+            session.get_actor(),
+            blocks[1]
+        )));
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn repeated_calls_can_run_in_unique_contexts(
+        mut session: Session,
+    ) -> Result<(), Box<dyn Error>> {
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["true"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        assert!(!session.set_unique_context(true));
+        session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+        session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+
+        let blocks = session
+            .record()
+            .blocks()
+            .iter()
+            .map(|block| block.number)
+            .collect::<Vec<_>>();
+        assert_eq!(blocks[1], blocks[0] + 1);
+        assert_eq!(blocks[2], blocks[1] + 1);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn time_can_be_frozen_across_blocks(mut session: Session) -> Result<(), Box<dyn Error>> {
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["true"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        session.sandbox().set_timestamp(1_000);
+        session.freeze_time();
+        assert_eq!(session.now(), 1_000);
+
+        session.advance_blocks(100);
+        session.sandbox().build_block();
+        assert_eq!(session.now(), 1_000);
+
+        // The contract observes the frozen timestamp as well.
+        session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+        let blocks = session.record().blocks();
+        assert_eq!(blocks[1].number, blocks[0].number + 101);
+        assert_eq!(blocks[1].timestamp, 1_000);
+
+        session.unfreeze_time();
+        assert!(!session.is_time_frozen());
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn dry_runs_observe_the_frozen_time(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let bundle = BundleProvider::local()?;
+        let contract =
+            session.deploy_bundle(bundle.clone(), "new", &["true"], NO_SALT, NO_ENDOWMENT)?;
+        session.sandbox().set_timestamp(1_000);
+        session.freeze_time();
+
+        // Blocks built directly with the sandbox move the timestamp, but no dry run sees it.
+        session.sandbox().build_block();
+        let data = bundle.transcoder.encode("new", ["false"])?;
+        session.dry_run_deploy_raw(bundle.wasm.clone(), data, vec![1], NO_ENDOWMENT);
+        assert_eq!(session.sandbox().get_timestamp(), 1_000);
+
+        session.sandbox().build_block();
+        session.dry_run_call_with_diff(contract.clone(), "flip", NO_ARGS, NO_ENDOWMENT)?;
+        assert_eq!(session.sandbox().get_timestamp(), 1_000);
+
+        session.sandbox().build_block();
+        let data = bundle.transcoder.encode("flip", NO_ARGS)?;
+        session.dry_run_call_raw(contract, data, NO_ENDOWMENT);
+        assert_eq!(session.sandbox().get_timestamp(), 1_000);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn calls_can_be_scheduled(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let address = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["true"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        let now = session.record().blocks()[0].number;
+        let keeper = AccountId32::new([2; 32]);
+        let target = CallTarget::Address(address);
+
+        session.schedule_call(
+            now + 2,
+            target.clone(),
+            "flip",
+            NO_ARGS,
+            keeper.clone(),
+            None,
+        );
+        let cancelled = session.schedule_call(
+            now + 2,
+            target.clone(),
+            "flip",
+            NO_ARGS,
+            keeper.clone(),
+            None,
+        );
+        session.schedule_call(now + 3, target, "unknown", NO_ARGS, keeper.clone(), None);
+        assert_eq!(session.pending_scheduled_calls().len(), 3);
+        assert!(session.cancel_scheduled_call(cancelled).is_some());
+
+        session.advance_blocks(1);
+        assert!(session.call::<_, bool>("get", NO_ARGS, NO_ENDOWMENT)??);
+        session.advance_blocks(2);
+        assert!(!session.call::<_, bool>("get", NO_ARGS, NO_ENDOWMENT)??);
+
+        // The scheduled `flip` is recorded between the two `get` calls.
+        assert_eq!(session.record().scheduled_interactions(), [2]);
+        assert_eq!(session.record().callers()[1], keeper);
+        // The failed call has not interrupted the block advancement.
+        let [failure] = session.scheduled_call_failures() else {
+            panic!("Expected a single failure");
+        };
+        assert_eq!(failure.call.message, "unknown");
+        assert!(session.pending_scheduled_calls().is_empty());
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn hooks_can_observe_and_veto_interactions(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let seen = Rc::new(RefCell::new(vec![]));
+
+        let log = Rc::clone(&seen);
+        session.add_hook(Hook::after_deploy(move |info, result| {
+            assert!(info.callee.is_none() && result.result.is_ok());
+            log.borrow_mut().push(info.label.clone());
+            Ok(())
+        }));
+        let log = Rc::clone(&seen);
+        session.add_hook(Hook::after_call(move |info, result| {
+            assert!(info.callee.is_some() && result.result.is_ok());
+            log.borrow_mut().push(info.label.clone());
+            Ok(())
+        }));
+        session.add_hook(Hook::before_call(|info| match info.label.as_str() {
+            "flip" => Err("flipping is frozen".to_string()),
+            _ => Ok(()),
+        }));
+
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["true"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        assert!(session.call::<_, bool>("get", NO_ARGS, NO_ENDOWMENT)??);
+        assert!(matches!(
+            session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT),
+            Err(SessionError::HookRejected(reason)) if reason == "flipping is frozen"
+        ));
+
+        // The vetoed call has not been executed.
+        assert_eq!(session.record().call_results().len(), 1);
+        assert_eq!(*seen.borrow(), vec!["new".to_string(), "get".to_string()]);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn all_interaction_results_can_be_checked_at_once(
+        mut session: Session,
+    ) -> Result<(), Box<dyn Error>> {
+        let address = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["true"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+        session.call::<_, bool>("get", NO_ARGS, NO_ENDOWMENT)??;
+
+        let results = session.record().interaction_results();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].output(), InteractionOutput::Deployed(&address));
+        assert_eq!(results[2].output(), InteractionOutput::Returned(&[0, 0]));
+
+        let max_gas = session.get_gas_limit();
+        for result in results {
+            assert!(!result.did_revert());
+            assert!(result.gas_consumed().all_lte(max_gas));
+        }
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn interactions_can_be_measured(session: Session) -> Result<(), Box<dyn Error>> {
+        assert!(session.metrics().is_none());
+
+        let mut session = session.with_metrics();
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["true"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+        session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+        session.call::<_, bool>("get", NO_ARGS, NO_ENDOWMENT)??;
+
+        let metrics = session.metrics().expect("Metrics should be enabled");
+        assert_eq!(metrics.constructors["new"].count, 1);
+        assert_eq!(metrics.messages["flip"].count, 2);
+        assert_eq!(metrics.messages["get"].count, 1);
+        assert_eq!(metrics.total().count, 4);
+        assert!(metrics.messages["flip"].gas_consumed.ref_time() > 0);
+        assert!(metrics.to_string().contains("new (constructor)"));
+
+        Ok(())
+    }
+
+    fn assert_flipper_deployed(view: &SessionView<MinimalSandbox>, address: &AccountId32) {
+        assert!(view.contract_exists(address));
+        assert!(view.transcoder(address).is_some());
+        assert!(view.record().deploy_returns().contains(address));
+    }
+
+    #[drink::test]
+    fn helpers_can_share_a_read_only_view(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let address = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["true"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        let bob = AccountId32::new([2; 32]);
+
+        let view = session.view();
+        assert_flipper_deployed(&view, &address);
+        assert!(view.free_balance(&view.actor()) > 0);
+
+        // Whatever a query writes is discarded.
+        let minted =
+            view.query(|| pallet_balances::Pallet::<MinimalSandboxRuntime>::mint_into(&bob, 1_000));
+        assert!(minted.is_ok());
+        assert_eq!(view.free_balance(&bob), 0);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn branches_evolve_independently(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let bob = AccountId32::new([2; 32]);
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["true"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+
+        let mut branch = session.branch();
+        assert_eq!(branch.record().call_results().len(), 1);
+
+        // One continuation flips again and pays Bob, the other does not.
+        session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+        session
+            .sandbox()
+            .mint_into(&bob, 1_000_000_000_000)
+            .expect("Minting should succeed");
+
+        assert!(session.call::<_, bool>("get", NO_ARGS, NO_ENDOWMENT)??);
+        assert!(!branch.call::<_, bool>("get", NO_ARGS, NO_ENDOWMENT)??);
+        assert_ne!(
+            session.sandbox().free_balance(&bob),
+            branch.sandbox().free_balance(&bob)
+        );
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn state_survives_export_and_import(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let bundle = BundleProvider::local()?;
+        let transcoder = bundle.transcoder.clone();
+        let address = session.deploy_bundle(bundle, "new", &["true"], NO_SALT, NO_ENDOWMENT)?;
+        session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+
+        let path = std::env::temp_dir().join("flipper-state.scale");
+        session.export_state(&path)?;
+
+        let mut restored = Session::<MinimalSandbox>::default();
+        restored.import_state(&path)?;
+        restored.set_transcoder(address.clone(), &transcoder);
+
+        // Exporting the imported state gives exactly the same dump.
+        let reexported_path = std::env::temp_dir().join("flipper-state-reexported.scale");
+        restored.export_state(&reexported_path)?;
+        assert_eq!(std::fs::read(&path)?, std::fs::read(&reexported_path)?);
+
+        // Both sessions behave identically.
+        for each in [&mut session, &mut restored] {
+            each.call_with_address::<_, ()>(address.clone(), "flip", NO_ARGS, NO_ENDOWMENT)??;
+        }
+        let original: bool =
+            session.call_with_address(address.clone(), "get", NO_ARGS, NO_ENDOWMENT)??;
+        let imported: bool =
+            restored.call_with_address(address, "get", NO_ARGS, NO_ENDOWMENT)??;
+        assert_eq!(original, imported);
+        assert_eq!(
+            session.record().last_call_result().gas_consumed,
+            restored.record().last_call_result().gas_consumed
+        );
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn calls_can_be_estimated(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let bob = AccountId32::new([2; 32]);
+
+        // In a fresh sandbox.
+        let fresh = CallState::Fresh {
+            constructor: "new".to_string(),
+            args: vec!["true".to_string()],
+        };
+        let estimate = estimate_call(
+            BundleProvider::local()?,
+            fresh,
+            "flip",
+            NO_ARGS,
+            bob.clone(),
+        )?;
+        assert!(estimate.gas_required.any_gt(Weight::zero()));
+        assert!(!estimate.reverted);
+        assert_eq!(estimate.to_json()["reverted"], false);
+
+        // In an exported state.
+        let bundle = BundleProvider::local()?;
+        let address =
+            session.deploy_bundle(bundle.clone(), "new", &["true"], NO_SALT, NO_ENDOWMENT)?;
+        session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+        let path = std::env::temp_dir().join("flipper-estimate-state.scale");
+        session.export_state(&path)?;
+
+        let imported = CallState::Imported { path, address };
+        let estimate = estimate_call(bundle, imported, "get", NO_ARGS, bob)?;
+        assert_eq!(estimate.returned.to_string(), "Ok(false)");
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn invariants_are_checked_after_every_interaction(
+        mut session: Session,
+    ) -> Result<(), Box<dyn Error>> {
+        let issuance = session.total_issuance();
+        session.check_invariant(move |session| match session.total_issuance() == issuance {
+            true => Ok(()),
+            false => Err("total issuance has changed".to_string()),
+        });
+
+        let address = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["true"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        session.check_invariant(move |session| {
+            let value: bool = session
+                .call_with_address(address.clone(), "get", NO_ARGS, NO_ENDOWMENT)
+                .map_err(|err| err.to_string())?
+                .map_err(|err| format!("{err:?}"))?;
+            match value {
+                true => Ok(()),
+                false => Err("flipper is down".to_string()),
+            }
+        });
+
+        let result = session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT);
+        assert!(matches!(
+            result,
+            Err(SessionError::InvariantViolated(violation)) if violation.contains("call to `flip`")
+        ));
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn recorded_scenario_can_be_replayed(mut session: Session) -> Result<(), Box<dyn Error>> {
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &["true"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+        session.call::<_, bool>("get", NO_ARGS, NO_ENDOWMENT)??;
+
+        let scenario = session.record().scenario();
+        assert_eq!(scenario.interactions.len(), 3);
+
+        // In a fresh session, everything goes exactly the same way.
+        let report =
+            Session::<MinimalSandbox>::default().replay(&scenario, BundleProvider::local()?);
+        assert!(report.is_clean(), "{report:?}");
+
+        // In the original session, the deployment collides with the existing contract, so the
+        // calls go to the already flipped instance.
+        let report = session.replay(&scenario, BundleProvider::local()?);
+        assert!(!report.is_clean());
+
+        Ok(())
+    }
+
+    /// Compares the time of a hot loop of calls with and without the mocking extension: skipping the
+    /// extension must not make the calls slower (with a 10% margin for the timing noise).
+    ///
+    /// Run with `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore]
+    fn mocking_extension_overhead() -> Result<(), Box<dyn Error>> {
+        const CALLS: u32 = 1_000;
+
+        let measure = |mut session: Session<MinimalSandbox>| -> Result<_, Box<dyn Error>> {
+            session.deploy_bundle(
+                BundleProvider::local()?,
+                "new",
+                &["true"],
+                NO_SALT,
+                NO_ENDOWMENT,
+            )?;
+            let start = Instant::now();
+            for _ in 0..CALLS {
+                session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)??;
+            }
+            Ok(start.elapsed())
+        };
+
+        let with_mocking = measure(SessionBuilder::new().build())?;
+        let without_mocking = measure(SessionBuilder::new().without_mocking().build())?;
+
+        assert!(
+            without_mocking < with_mocking * 11 / 10,
+            "{CALLS} calls took {without_mocking:?} without mocking and {with_mocking:?} with it"
+        );
+
+        Ok(())
+    }
 }
@@ -37,13 +37,25 @@ mod proxy {
 
 #[cfg(test)]
 mod tests {
-    use std::error::Error;
+    use std::{
+        error::Error,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
 
     use drink::{
+        minimal::MinimalSandbox,
         mock_message,
-        session::{mocking_api::MockingApi, Session, NO_ARGS, NO_ENDOWMENT, NO_SALT},
-        ContractMock,
+        pallet_contracts_debugging::InterceptingExtT,
+        session::{
+            abi, error::SessionError, mock::selector_of, mocking_api::MockingApi, AbiKind,
+            ContractMessageTranscoderExt, Session, SessionBuilder, NO_ARGS, NO_ENDOWMENT, NO_SALT,
+        },
+        AccountId32, ContractMock, MockGasStrategy, Weight,
     };
+    use scale::Encode;
 
     use crate::CALLEE_SELECTOR;
 
@@ -71,4 +83,378 @@ mod tests {
 
         Ok(())
     }
+
+    #[drink::test]
+    #[should_panic(expected = "Unmocked selector called: forward_call")]
+    fn calling_unmocked_message_fails_loudly(mut session: Session) {
+        // We mock the whole interface of the proxy contract itself, without providing any logic.
+        let transcoder = BundleProvider::local().unwrap().transcoder;
+        let mock_address = session
+            .mocking_api()
+            .deploy(ContractMock::from_metadata(&transcoder));
+        session.set_transcoder(mock_address.clone(), &transcoder);
+
+        // The call fails with the label of the message that we forgot to mock.
+        let _ = session.call_with_address::<_, (u8, u8)>(
+            mock_address.clone(),
+            "forward_call",
+            &[mock_address.to_string()],
+            NO_ENDOWMENT,
+        );
+    }
+
+    #[drink::test]
+    fn spy_forwards_unmocked_calls(mut session: Session) -> Result<(), Box<dyn Error>> {
+        // The innermost callee is a plain mock.
+        let callee = session.mocking_api().deploy(
+            ContractMock::new().with_message(CALLEE_SELECTOR, mock_message(|()| (4u8, 1u8))),
+        );
+
+        // The spy stands in front of a real proxy contract, without mocking anything.
+        let proxy =
+            session.deploy_bundle(BundleProvider::local()?, "new", NO_ARGS, NO_SALT, None)?;
+        let spy = session
+            .mocking_api()
+            .deploy(ContractMock::new().proxy_to(proxy));
+        let transcoder = BundleProvider::local()?.transcoder;
+        session.set_transcoder(spy.clone(), &transcoder);
+
+        // The call goes through the spy to the proxy, and then to the callee.
+        let result: (u8, u8) = session.call_with_address(
+            spy.clone(),
+            "forward_call",
+            &[callee.to_string()],
+            NO_ENDOWMENT,
+        )??;
+        assert_eq!(result, (4, 1));
+
+        // The spy has seen both the input and the real output.
+        let calls = session.mocking_api().mock_calls(&spy);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].selector, selector_of("forward_call"));
+        assert_eq!(calls[0].input, callee.encode());
+        assert_eq!(calls[0].output, Some(Ok::<_, ()>((4u8, 1u8)).encode()));
+        assert!(calls[0].proxied);
+
+        // With the transcoder of the spy, the logged input can be decoded back.
+        let decoded = session.decode_input(&spy, &calls[0].data())?;
+        assert_eq!(decoded.label, "forward_call");
+        assert_eq!(decoded.args.len(), 1);
+        assert_eq!(decoded.args[0].0, "callee");
+
+        Ok(())
+    }
+
+    /// Counts the calls it is consulted on, without intercepting any of them.
+    struct CallCounter(Arc<AtomicUsize>);
+
+    impl InterceptingExtT for CallCounter {
+        fn intercept_call(&self, _: Vec<u8>, _: bool, _: Vec<u8>) -> Vec<u8> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            None::<()>.encode()
+        }
+    }
+
+    #[test]
+    fn interceptors_coexist_with_mocks() -> Result<(), Box<dyn Error>> {
+        for (mocks_last, expected_count) in [(false, 1), (true, 2)] {
+            let mut session = SessionBuilder::<MinimalSandbox>::new()
+                .mocks_last(mocks_last)
+                .build();
+            let count = Arc::new(AtomicUsize::new(0));
+            session.add_interceptor(CallCounter(count.clone()));
+
+            let callee = session.mocking_api().deploy(
+                ContractMock::new().with_message(CALLEE_SELECTOR, mock_message(|()| (4u8, 1u8))),
+            );
+            session.deploy_bundle(BundleProvider::local()?, "new", NO_ARGS, NO_SALT, None)?;
+            count.store(0, Ordering::SeqCst);
+
+            // The mock still answers the nested call. The counter is consulted on the call to the
+            // proxy and, only if the mocks are consulted last, on the (mocked) nested call.
+            let result: (u8, u8) =
+                session.call("forward_call", &[callee.to_string()], NO_ENDOWMENT)??;
+            assert_eq!(result, (4, 1));
+            assert_eq!(count.load(Ordering::SeqCst), expected_count);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn stateful_mocks_withstand_concurrent_sessions() {
+        const SESSIONS: usize = 4;
+        const CALLS: u8 = 50;
+        let served = Arc::new(AtomicUsize::new(0));
+
+        let sessions = (0..SESSIONS).map(|_| {
+            let served = served.clone();
+            std::thread::spawn(move || {
+                let mut session = Session::<MinimalSandbox>::default();
+                // Every mock counts its own calls, while all of them count the calls together.
+                let calls = AtomicUsize::new(0);
+                let callee = session
+                    .mocking_api()
+                    .deploy(ContractMock::new().with_message(
+                        CALLEE_SELECTOR,
+                        mock_message(move |()| {
+                            served.fetch_add(1, Ordering::SeqCst);
+                            let call = calls.fetch_add(1, Ordering::SeqCst) as u8;
+                            (call, call)
+                        }),
+                    ));
+                let bundle = BundleProvider::local().expect("Bundle should be available");
+                session
+                    .deploy_bundle(bundle, "new", NO_ARGS, NO_SALT, None)
+                    .expect("Deployment should succeed");
+
+                for call in 0..CALLS {
+                    let result: (u8, u8) = session
+                        .call("forward_call", &[callee.to_string()], NO_ENDOWMENT)
+                        .expect("Call should succeed")
+                        .expect("Call should not be reverted");
+                    assert_eq!(result, (call, call));
+                }
+                assert_eq!(
+                    session.mocking_api().mock_calls(&callee).len(),
+                    CALLS as usize
+                );
+            })
+        });
+        for session in sessions.collect::<Vec<_>>() {
+            session.join().expect("Session should not panic");
+        }
+
+        assert_eq!(served.load(Ordering::SeqCst), SESSIONS * CALLS as usize);
+    }
+
+    /// Returns the gas consumed by `forward_call` to a mock charging gas with `gas_strategy`.
+    fn forward_call_gas(gas_strategy: MockGasStrategy) -> Result<Weight, Box<dyn Error>> {
+        let mut session = Session::<MinimalSandbox>::default();
+        session.set_mock_gas_strategy(gas_strategy);
+        let callee = session.mocking_api().deploy(
+            ContractMock::new().with_message(CALLEE_SELECTOR, mock_message(|()| (4u8, 1u8))),
+        );
+
+        let result: (u8, u8) = session
+            .deploy_bundle_and(BundleProvider::local()?, "new", NO_ARGS, NO_SALT, None)?
+            .call_and("forward_call", &[callee.to_string()], NO_ENDOWMENT)?
+            .record()
+            .last_call_return_decoded()?
+            .expect("Call was successful");
+        // Charging gas does not change the output, nor how the call is logged.
+        assert_eq!(result, (4, 1));
+        assert_eq!(session.mocking_api().mock_calls(&callee).len(), 1);
+
+        Ok(session.record().last_call_result().gas_consumed)
+    }
+
+    #[test]
+    fn mocked_calls_charge_gas_according_to_the_strategy() -> Result<(), Box<dyn Error>> {
+        const CHARGE: u64 = 20_000_000_000;
+        let free = forward_call_gas(MockGasStrategy::Zero)?;
+        let charged = forward_call_gas(MockGasStrategy::Fixed(Weight::from_parts(CHARGE, 0)))?;
+        let doubled = forward_call_gas(MockGasStrategy::Fixed(Weight::from_parts(2 * CHARGE, 0)))?;
+
+        // Besides the charge, the caller pays for running the mock contract.
+        assert!(charged.ref_time() >= free.ref_time() + CHARGE);
+        // Which costs the same for every charge, while the charge itself is burnt accurately.
+        let difference = doubled.ref_time() - charged.ref_time();
+        assert!(difference >= CHARGE, "{difference} < {CHARGE}");
+        assert!(
+            difference < CHARGE + CHARGE / 10,
+            "{difference} exceeds {CHARGE} by 10%"
+        );
+
+        // The nested call has only the selector as input, so 4 bytes are charged.
+        let proportional = forward_call_gas(MockGasStrategy::ProportionalToInput(
+            Weight::from_parts(CHARGE / 4, 0),
+        ))?;
+        assert!(proportional.ref_time().abs_diff(charged.ref_time()) < CHARGE / 10);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn real_responses_can_be_recorded_and_replayed(
+        mut session: Session,
+    ) -> Result<(), Box<dyn Error>> {
+        let callee = session.mocking_api().deploy(
+            ContractMock::new().with_message(CALLEE_SELECTOR, mock_message(|()| (4u8, 1u8))),
+        );
+        let other_callee = session.mocking_api().deploy(
+            ContractMock::new().with_message(CALLEE_SELECTOR, mock_message(|()| (2u8, 3u8))),
+        );
+        let proxy =
+            session.deploy_bundle(BundleProvider::local()?, "new", NO_ARGS, NO_SALT, None)?;
+
+        // Record how the real proxy responds.
+        session.mocking_api().start_recording(proxy.clone());
+        session.call_with_address::<_, (u8, u8)>(
+            proxy.clone(),
+            "forward_call",
+            &[callee.to_string()],
+            NO_ENDOWMENT,
+        )??;
+        let recording = session.mocking_api().finish_recording(&proxy);
+        assert_eq!(recording.len(), 1);
+        let response = recording
+            .response(selector_of("forward_call"), &callee.encode())
+            .expect("The response should have been recorded");
+        assert!(response.gas_consumed.is_some());
+
+        // The replaying mock answers the recorded input by itself, and forwards the other inputs
+        // to the real proxy.
+        let replay = session
+            .mocking_api()
+            .deploy(ContractMock::from_recording(recording).proxy_to(proxy));
+        session.set_transcoder(replay.clone(), &BundleProvider::local()?.transcoder);
+
+        let replayed: (u8, u8) = session.call_with_address(
+            replay.clone(),
+            "forward_call",
+            &[callee.to_string()],
+            NO_ENDOWMENT,
+        )??;
+        assert_eq!(replayed, (4, 1));
+        let forwarded: (u8, u8) = session.call_with_address(
+            replay.clone(),
+            "forward_call",
+            &[other_callee.to_string()],
+            NO_ENDOWMENT,
+        )??;
+        assert_eq!(forwarded, (2, 3));
+
+        let calls = session.mocking_api().mock_calls(&replay);
+        assert_eq!(
+            calls.iter().map(|call| call.proxied).collect::<Vec<_>>(),
+            vec![false, true]
+        );
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn replayed_calls_charge_the_recorded_gas(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let callee = session.mocking_api().deploy(
+            ContractMock::new().with_message(CALLEE_SELECTOR, mock_message(|()| (4u8, 1u8))),
+        );
+        let proxy =
+            session.deploy_bundle(BundleProvider::local()?, "new", NO_ARGS, NO_SALT, None)?;
+
+        session.mocking_api().start_recording(proxy.clone());
+        session.call_with_address::<_, (u8, u8)>(
+            proxy.clone(),
+            "forward_call",
+            &[callee.to_string()],
+            NO_ENDOWMENT,
+        )??;
+        let recording = session.mocking_api().finish_recording(&proxy);
+        let recorded = recording
+            .response(selector_of("forward_call"), &callee.encode())
+            .and_then(|response| response.gas_consumed)
+            .expect("The gas should have been recorded");
+
+        let replay = session
+            .mocking_api()
+            .deploy(ContractMock::from_recording(recording));
+        session.set_transcoder(replay.clone(), &BundleProvider::local()?.transcoder);
+        let replayed: (u8, u8) = session.call_with_address(
+            replay,
+            "forward_call",
+            &[callee.to_string()],
+            NO_ENDOWMENT,
+        )??;
+        assert_eq!(replayed, (4, 1));
+
+        let consumed = session.record().last_call_result().gas_consumed;
+        assert!(consumed.ref_time() >= recorded.ref_time());
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn existing_contracts_can_be_partially_mocked(
+        mut session: Session,
+    ) -> Result<(), Box<dyn Error>> {
+        let callee = session.mocking_api().deploy(
+            ContractMock::new().with_message(CALLEE_SELECTOR, mock_message(|()| (4u8, 1u8))),
+        );
+        let proxy =
+            session.deploy_bundle(BundleProvider::local()?, "new", NO_ARGS, NO_SALT, None)?;
+
+        // Messages that are not mocked are still executed by the contract.
+        let unrelated = ContractMock::new().with_message(CALLEE_SELECTOR, mock_message(|()| 0u8));
+        session
+            .mocking_api()
+            .mock_existing_contract(unrelated, proxy.clone())?;
+        let result: (u8, u8) =
+            session.call("forward_call", &[callee.to_string()], NO_ENDOWMENT)??;
+        assert_eq!(result, (4, 1));
+
+        // The mocked ones are answered by the mock instead.
+        let mock = ContractMock::new().with_message(
+            selector_of("forward_call"),
+            mock_message(|_callee: [u8; 32]| (7u8, 7u8)),
+        );
+        session
+            .mocking_api()
+            .mock_existing_contract(mock, proxy.clone())?;
+        let result: (u8, u8) =
+            session.call("forward_call", &[callee.to_string()], NO_ENDOWMENT)??;
+        assert_eq!(result, (7, 7));
+        assert_eq!(session.mocking_api().mock_calls(&proxy).len(), 1);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn mocking_a_missing_contract_fails(mut session: Session) {
+        let result = session
+            .mocking_api()
+            .mock_existing_contract(ContractMock::new(), AccountId32::new([9; 32]));
+        assert!(matches!(result, Err(SessionError::ContractNotFound(_))));
+    }
+
+    #[drink::test]
+    fn solidity_encoded_calls_are_supported(mut session: Session) {
+        // A mock standing in for a contract with a Solidity-compatible ABI.
+        let transfer = abi::selector("transfer(address,uint256)");
+        assert_eq!(transfer, [0xa9, 0x05, 0x9c, 0xbb]);
+        let mock = ContractMock::new().with_message(
+            transfer,
+            mock_message(|(to, amount): ([u8; 32], [u8; 32])| (to[31], amount[31])),
+        );
+        let mock_address = session.mocking_api().deploy(mock);
+        session.set_abi(mock_address.clone(), AbiKind::Solidity);
+
+        session
+            .call_with_address::<_, ()>(
+                mock_address,
+                "transfer(address,uint256)",
+                &["0x1111111111111111111111111111111111111111", "42"],
+                NO_ENDOWMENT,
+            )
+            .expect("Call should succeed")
+            .unwrap();
+
+        // The mock received ABI-encoded words: a left-padded address and a big-endian number.
+        assert_eq!(session.record().last_call_return(), &[0, 0x11, 42]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Selector collision")]
+    fn registering_the_same_selector_twice_fails_loudly() {
+        let _ = ContractMock::new()
+            .with_message(CALLEE_SELECTOR, mock_message(|()| (1u8, 1u8)))
+            .with_message(CALLEE_SELECTOR, mock_message(|()| (2u8, 2u8)));
+    }
+
+    #[test]
+    fn proxy_metadata_has_no_selector_collisions() {
+        BundleProvider::local()
+            .unwrap()
+            .transcoder
+            .assert_no_selector_collisions();
+    }
 }
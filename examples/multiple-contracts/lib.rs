@@ -41,7 +41,17 @@ mod balance_checker {
 mod tests {
     use std::error::Error;
 
-    use drink::session::{Session, NO_ARGS, NO_ENDOWMENT, NO_SALT};
+    use drink::{
+        chaos::{FailurePolicy, InjectedFailure, InjectedFailureKind},
+        helpers::psp22::{assert_psp22_balance, Psp22Mock},
+        sandbox_api::prelude::*,
+        scenario::LoadRunner,
+        session::{
+            error::SessionError, mocking_api::MockingApi, CallTarget, Session, SystemManifest,
+            NO_ARGS, NO_ENDOWMENT, NO_SALT,
+        },
+        AccountId32, TestVariant,
+    };
 
     #[drink::contract_bundle_provider]
     enum BundleProvider {}
@@ -70,4 +80,548 @@ mod tests {
 
         Ok(())
     }
+
+    #[drink::test]
+    fn code_hashes_of_deployments_are_recorded(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let token_contract = session.deploy_bundle(
+            BundleProvider::Psp22.bundle()?,
+            "new",
+            &["10", "None", "None", "1"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        let user_account = session.get_actor();
+        let checker_contract = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &[user_account.to_string(), token_contract.to_string()],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        // Calls do not instantiate anything.
+        let _: u128 = session.call("check", NO_ARGS, NO_ENDOWMENT)??;
+
+        let code_hashes = session.record().deploy_code_hashes().to_vec();
+        assert_eq!(
+            code_hashes,
+            vec![
+                session.code_hash_of(&token_contract).unwrap(),
+                session.code_hash_of(&checker_contract).unwrap(),
+            ]
+        );
+        assert_ne!(code_hashes[0], code_hashes[1]);
+
+        Ok(())
+    }
+
+    #[drink::test(variants(real, mocked))]
+    fn balance_is_checked_with_real_and_mocked_token(
+        mut session: Session,
+        variant: TestVariant,
+    ) -> Result<(), Box<dyn Error>> {
+        let user_account = session.get_actor();
+        let token_contract = match variant {
+            TestVariant::Real => session.deploy_bundle(
+                BundleProvider::Psp22.bundle()?,
+                "new",
+                &["10", "None", "None", "1"],
+                NO_SALT,
+                NO_ENDOWMENT,
+            )?,
+            TestVariant::Mocked => {
+                let token = Psp22Mock::new().with_balance(user_account.clone(), 10);
+                session.mocking_api().deploy(token.mock())
+            }
+        };
+
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &[user_account.to_string(), token_contract.to_string()],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        let balance: u128 = session.call("check", NO_ARGS, NO_ENDOWMENT)??;
+        assert_eq!(balance, 10);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn failures_can_be_injected(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let token_contract = session.deploy_bundle(
+            BundleProvider::Psp22.bundle()?,
+            "new",
+            &["10", "None", "None", "1"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        let user_account = session.get_actor();
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &[user_account.to_string(), token_contract.to_string()],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+
+        // The nested call to the token traps, which aborts the whole call.
+        session.inject_failure(FailurePolicy::TrapCallee(token_contract.clone()));
+        assert!(session
+            .call::<_, u128>("check", NO_ARGS, NO_ENDOWMENT)
+            .is_err());
+        assert_eq!(
+            session.record().injected_failures(),
+            [InjectedFailure {
+                interaction: 2,
+                callee: token_contract.clone(),
+                kind: InjectedFailureKind::Trap,
+            }]
+        );
+
+        // The policy has been removed after triggering.
+        assert_eq!(
+            session.call::<_, u128>("check", NO_ARGS, NO_ENDOWMENT)??,
+            10
+        );
+        assert_eq!(session.record().injected_failures().len(), 1);
+
+        // The second call (the nested one) runs out of gas.
+        session.inject_failure(FailurePolicy::OutOfGasAt(2));
+        assert!(session
+            .call::<_, u128>("check", NO_ARGS, NO_ENDOWMENT)
+            .is_err());
+        let injected = &session.record().injected_failures()[1];
+        assert_eq!(injected.interaction, 4);
+        assert_eq!(injected.callee, token_contract);
+        assert_eq!(injected.kind, InjectedFailureKind::OutOfGas);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn token_can_be_mocked(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let user_account = session.get_actor();
+        let token = Psp22Mock::new().with_balance(user_account.clone(), 10);
+        let token_contract = session.mocking_api().deploy(token.mock());
+
+        let checker_contract = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            &[user_account.to_string(), token_contract.to_string()],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        let balance: u128 = session.call("check", NO_ARGS, NO_ENDOWMENT)??;
+        assert_eq!(balance, 10);
+
+        // The token keeps its state between the calls. With the transcoder of a real token, the
+        // mock can be called directly too.
+        session.set_transcoder(
+            token_contract.clone(),
+            &BundleProvider::Psp22.bundle()?.transcoder,
+        );
+        let bob = AccountId32::new([2; 32]);
+        token.set_caller(user_account.clone());
+        let sent: Result<(), Vec<u8>> = session.call_with_address(
+            token_contract.clone(),
+            "PSP22::transfer",
+            &[bob.to_string(), "3".to_string(), "[]".to_string()],
+            NO_ENDOWMENT,
+        )??;
+        assert!(sent.is_ok());
+        let balance: u128 =
+            session.call_with_address(checker_contract, "check", NO_ARGS, NO_ENDOWMENT)??;
+        assert_eq!(balance, 7);
+
+        assert_eq!(token.balance_of(&bob), 3);
+        assert_psp22_balance(&mut session, &token_contract, &bob, 3);
+        assert_eq!(token.total_supply(), 10);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn balances_of_real_tokens_can_be_asserted(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let token_contract = session.deploy_bundle(
+            BundleProvider::Psp22.bundle()?,
+            "new",
+            &["10", "None", "None", "1"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        let user_account = session.get_actor();
+        assert_psp22_balance(&mut session, &token_contract, &user_account, 10);
+        assert_psp22_balance(&mut session, &token_contract, &AccountId32::new([2; 32]), 0);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn contracts_can_be_referred_to_by_name(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let token_contract = session.deploy_bundle_as(
+            "token",
+            BundleProvider::Psp22.bundle()?,
+            "new",
+            &["10", "None", "None", "1"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        assert_eq!(session.address_of("token"), Some(token_contract.clone()));
+
+        let user_account = session.get_actor();
+        session.deploy_bundle_as(
+            "checker",
+            BundleProvider::local()?,
+            "new",
+            &[user_account.to_string(), token_contract.to_string()],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+
+        // Names are unique within a session.
+        let result = session.deploy_bundle_as(
+            "token",
+            BundleProvider::Psp22.bundle()?,
+            "new",
+            &["10", "None", "None", "1"],
+            vec![1],
+            NO_ENDOWMENT,
+        );
+        assert!(matches!(result, Err(SessionError::NameTaken(name)) if name == "token"));
+
+        let balance: u128 = session.call_to("checker", "check", NO_ARGS, NO_ENDOWMENT)??;
+        assert_eq!(balance, 10);
+
+        // The names are exported together with the record.
+        let json = session.record_json();
+        assert_eq!(json["names"]["token"], token_contract.to_string());
+        assert_eq!(json["interactions"][2]["message"], "check");
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn whole_system_can_be_deployed_at_once(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let user_account = session.get_actor().to_string();
+        // `checker` refers to `token`, so it is deployed after it.
+        let manifest = SystemManifest::new()
+            .deploy(
+                "checker",
+                BundleProvider::local()?,
+                "new",
+                &[user_account.as_str(), "{{token}}"],
+            )
+            .deploy(
+                "token",
+                BundleProvider::Psp22.bundle()?,
+                "new",
+                &["10", "None", "None", "1"],
+            );
+        let addresses = session.deploy_system(manifest)?;
+
+        assert_eq!(
+            session.address_of("token"),
+            Some(addresses["token"].clone())
+        );
+        assert_eq!(session.record().deploy_returns()[0], addresses["token"]);
+        let balance: u128 = session.call_to("checker", "check", NO_ARGS, NO_ENDOWMENT)??;
+        assert_eq!(balance, 10);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn system_deployment_problems_are_reported(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let user_account = session.get_actor().to_string();
+
+        // Nothing is deployed if the references form a cycle.
+        let manifest = SystemManifest::new()
+            .deploy(
+                "a",
+                BundleProvider::local()?,
+                "new",
+                &[user_account.as_str(), "{{b}}"],
+            )
+            .deploy(
+                "b",
+                BundleProvider::local()?,
+                "new",
+                &[user_account.as_str(), "{{a}}"],
+            );
+        let result = session.deploy_system(manifest);
+        assert!(matches!(
+            result,
+            Err(SessionError::CyclicDeployments(cycle)) if cycle == ["a", "b", "a"]
+        ));
+        assert!(session.contract_names().is_empty());
+
+        // A failed deployment is named, while the earlier ones stay registered.
+        let manifest = SystemManifest::new()
+            .deploy(
+                "token",
+                BundleProvider::Psp22.bundle()?,
+                "new",
+                &["10", "None", "None", "1"],
+            )
+            .deploy(
+                "checker",
+                BundleProvider::local()?,
+                "no_such_constructor",
+                &[user_account.as_str(), "{{token}}"],
+            );
+        let result = session.deploy_system(manifest);
+        assert!(matches!(
+            result,
+            Err(SessionError::SystemDeploymentFailed { name, .. }) if name == "checker"
+        ));
+        assert!(session.address_of("token").is_some());
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn contracts_can_be_redeployed(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let token_contract = session.deploy_bundle_as(
+            "token",
+            BundleProvider::Psp22.bundle()?,
+            "new",
+            &["10", "None", "None", "1"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        let user_account = session.get_actor();
+        let checker_contract = session.deploy_bundle_as(
+            "checker",
+            BundleProvider::local()?,
+            "new",
+            &[user_account.to_string(), token_contract.to_string()],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+
+        // The new code has the same storage layout, so the contract keeps working with its
+        // storage, under the same address.
+        let layout_changed = session.redeploy_bundle("checker", BundleProvider::local()?)?;
+        assert!(!layout_changed);
+        assert_eq!(session.address_of("checker"), Some(checker_contract));
+        let balance: u128 = session.call_to("checker", "check", NO_ARGS, NO_ENDOWMENT)??;
+        assert_eq!(balance, 10);
+
+        // Replacing the token with a completely different contract is flagged.
+        assert!(session.redeploy_bundle("token", BundleProvider::local()?)?);
+
+        // The token runs the checker code now, but the record remembers what it was deployed with.
+        let [token_hash, checker_hash] = session.record().deploy_code_hashes() else {
+            panic!("Expected two deployments");
+        };
+        let (token_hash, checker_hash) = (*token_hash, *checker_hash);
+        assert_ne!(token_hash, checker_hash);
+        assert_eq!(session.code_hash_of(&token_contract), Some(checker_hash));
+
+        // Other instances of the token are not affected by the upgrade.
+        let another_token = session.deploy_bundle(
+            BundleProvider::Psp22.bundle()?,
+            "new",
+            &["10", "None", "None", "1"],
+            vec![1],
+            NO_ENDOWMENT,
+        )?;
+        assert_eq!(session.code_hash_of(&another_token), Some(token_hash));
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn instances_of_the_same_code_can_be_found_and_terminated(
+        mut session: Session,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut tokens = vec![];
+        for salt in 0..3u8 {
+            tokens.push(session.deploy_bundle(
+                BundleProvider::Psp22.bundle()?,
+                "new",
+                &["10", "None", "None", "1"],
+                vec![salt],
+                NO_ENDOWMENT,
+            )?);
+        }
+        let token_hash = session
+            .code_hash_of(&tokens[0])
+            .expect("Token should exist");
+
+        let mut instances = session.instances_of(token_hash);
+        instances.sort();
+        tokens.sort();
+        assert_eq!(instances, tokens);
+
+        let beneficiary = AccountId32::new([7; 32]);
+        assert_eq!(
+            session.terminate_all_instances_of(token_hash, beneficiary.clone())?,
+            3
+        );
+        assert!(session.instances_of(token_hash).is_empty());
+        assert!(session.code_hash_of(&tokens[0]).is_none());
+        // The released storage deposits went to the beneficiary.
+        assert!(session.sandbox().free_balance(&beneficiary) > 0);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn transcoders_of_terminated_contracts_can_be_evicted(
+        session: Session,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut session = session.with_transcoder_eviction(true);
+        let token = session.deploy_bundle(
+            BundleProvider::Psp22.bundle()?,
+            "new",
+            &["10", "None", "None", "1"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        assert!(session.has_transcoder(&token));
+        assert_eq!(session.transcoded_addresses(), [token.clone()]);
+
+        let token_hash = session.code_hash_of(&token).expect("Token should exist");
+        session.terminate_all_instances_of(token_hash, AccountId32::new([7; 32]))?;
+        assert!(!session.has_transcoder(&token));
+
+        // The error lists the contracts that do have a transcoder.
+        let other = AccountId32::new([8; 32]);
+        session.set_transcoder(other.clone(), &BundleProvider::Psp22.bundle()?.transcoder);
+        let Err(SessionError::NoTranscoder { registered, .. }) = session.decode_input(&token, &[])
+        else {
+            panic!("Expected a missing transcoder");
+        };
+        assert_eq!(registered, [format!("{other:?}")]);
+
+        assert!(session.remove_transcoder(&other).is_some());
+        assert!(session.transcoded_addresses().is_empty());
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn repeated_calls_can_use_templates(mut session: Session) -> Result<(), Box<dyn Error>> {
+        session.deploy_bundle_as(
+            "token",
+            BundleProvider::Psp22.bundle()?,
+            "new",
+            &["100", "None", "None", "1"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        let bob = AccountId32::new([2; 32]);
+
+        // Typos are caught when the template is defined.
+        let result = session.define_call_template(
+            "send",
+            CallTarget::Name("token".to_string()),
+            "PSP22::trasnfer",
+            vec![bob.to_string(), "1".to_string(), "[]".to_string()],
+        );
+        assert!(matches!(result, Err(SessionError::InvalidTemplate(_))));
+
+        session.define_call_template(
+            "send",
+            CallTarget::Name("token".to_string()),
+            "PSP22::transfer",
+            vec![bob.to_string(), "1".to_string(), "[]".to_string()],
+        )?;
+
+        // Send 1 token with the defaults, then 5 more with the amount overridden.
+        let sent: Result<(), Vec<u8>> = session.call_template("send", &[])??;
+        assert!(sent.is_ok());
+        let sent: Result<(), Vec<u8>> =
+            session.call_template("send", &[(1, "5".to_string())])??;
+        assert!(sent.is_ok());
+
+        let balance: u128 = session.call_to(
+            "token",
+            "PSP22::balance_of",
+            &[bob.to_string()],
+            NO_ENDOWMENT,
+        )??;
+        assert_eq!(balance, 6);
+
+        // Templates are exported along with the record.
+        let json = session.record_json();
+        assert_eq!(json["call_templates"]["send"]["target"]["name"], "token");
+        assert_eq!(json["call_templates"]["send"]["message"], "PSP22::transfer");
+        assert_eq!(json["call_templates"]["send"]["default_args"][1], "1");
+
+        // Only the existing arguments can be overridden.
+        let result = session.call_template::<()>("send", &[(3, "5".to_string())]);
+        assert!(matches!(result, Err(SessionError::InvalidTemplate(_))));
+        let result = session.call_template::<()>("receive", &[]);
+        assert!(matches!(result, Err(SessionError::UnknownTemplate(_))));
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn token_survives_a_load_test(mut session: Session) -> Result<(), Box<dyn Error>> {
+        const SUPPLY: u128 = 1_000_000;
+        let token = session.deploy_bundle(
+            BundleProvider::Psp22.bundle()?,
+            "new",
+            &[SUPPLY.to_string().as_str(), "None", "None", "1"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+
+        // The deployer holds all the tokens, the other 49 users have only some native funds.
+        let mut users = vec![session.get_actor()];
+        for i in 1..50u8 {
+            let user = AccountId32::new([i; 32]);
+            session
+                .sandbox()
+                .mint_into(&user, 1_000_000_000_000)
+                .expect("Minting should succeed");
+            users.push(user);
+        }
+        let (recipients, spenders) = (users.clone(), users.clone());
+
+        let checked_token = token.clone();
+        let stats = LoadRunner::new(token)
+            .message("PSP22::transfer", 70, move |rng| {
+                vec![
+                    rng.pick(&recipients).to_string(),
+                    rng.below(100).to_string(),
+                    "[]".to_string(),
+                ]
+            })
+            .message("PSP22::approve", 20, move |rng| {
+                vec![rng.pick(&spenders).to_string(), "10".to_string()]
+            })
+            .message("PSP22::total_supply", 10, |_| vec![])
+            .actors(users)
+            .calls(200)
+            .advance_block_every(10)
+            .seed(7)
+            .invariant(move |session| {
+                let supply: u128 = session
+                    .call_with_address(
+                        checked_token.clone(),
+                        "PSP22::total_supply",
+                        NO_ARGS,
+                        NO_ENDOWMENT,
+                    )
+                    .map_err(|err| err.to_string())?
+                    .map_err(|err| format!("{err:?}"))?;
+                match supply {
+                    SUPPLY => Ok(()),
+                    other => Err(format!("total supply changed to {other}")),
+                }
+            })
+            .run(&mut session);
+
+        assert_eq!(stats.calls.values().sum::<usize>(), 200);
+        assert!(stats.total_gas.ref_time() > 0);
+        // Users without tokens cannot transfer them.
+        assert!(stats.failures.contains_key("reverted"));
+
+        Ok(())
+    }
 }
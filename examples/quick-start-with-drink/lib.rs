@@ -42,10 +42,9 @@ mod flipper {
 /// We put `drink`-based tests as usual unit tests, into a test module.
 #[cfg(test)]
 mod tests {
-    use drink::{
-        sandbox_api::contracts_api::decode_debug_buffer,
-        session::{Session, NO_ARGS, NO_ENDOWMENT, NO_SALT},
-    };
+    // `drink::prelude` brings in everything that is commonly needed in tests: the `Session`, the
+    // constants, the sandbox types and utilities.
+    use drink::prelude::*;
 
     /// `drink` automatically discovers all the contract projects that your tests will need. For
     /// every such dependency (including the contract from the current crate), it will generate a
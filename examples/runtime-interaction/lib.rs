@@ -1,11 +1,18 @@
 #[cfg(test)]
 mod tests {
     use drink::{
-        minimal::{MinimalSandbox, RuntimeCall},
+        fixtures::Fixture,
+        frame_support::{pallet_prelude::Decode, sp_runtime::DigestItem, traits::FindAuthor},
+        frame_system::{self, EventRecord},
+        minimal::{MinimalSandbox, MinimalSandboxRuntime, RuntimeCall, RuntimeEvent},
         pallet_balances, pallet_contracts,
         pallet_contracts::Determinism,
         sandbox_api::prelude::*,
-        AccountId32, Sandbox,
+        session::{
+            block_author::DigestAuthor, contract_transcode::Value, error::SessionError,
+            EventSource, Session, NO_ARGS, NO_ENDOWMENT, NO_SALT,
+        },
+        AccountId32, Recorder, Sandbox,
     };
 
     #[test]
@@ -38,6 +45,26 @@ mod tests {
         assert_eq!(sandbox.free_balance(&BOB), initial_balance + 100);
     }
 
+    #[test]
+    fn we_can_read_runtime_storage_from_a_session() {
+        let mut session = Session::<MinimalSandbox>::default();
+        let total_issuance = || pallet_balances::TotalIssuance::<MinimalSandboxRuntime>::get();
+
+        // Any pallet storage can be read within the externalities of the session's sandbox.
+        let initial_issuance = session.execute_with(total_issuance);
+
+        // Session methods must be called outside of `execute_with`.
+        session
+            .sandbox()
+            .mint_into(&AccountId32::new([2u8; 32]), 1_000_000_000_000)
+            .expect("Minting should succeed");
+
+        assert_eq!(
+            session.execute_with(total_issuance),
+            initial_issuance + 1_000_000_000_000
+        );
+    }
+
     #[test]
     fn we_can_work_with_the_contracts_pallet_in_low_level() {
         let mut sandbox = MinimalSandbox::default();
@@ -64,6 +91,366 @@ mod tests {
             .expect("Failed to remove a contract");
     }
 
+    #[test]
+    fn we_can_record_interactions_without_a_session() {
+        // The recorder wraps a raw sandbox and keeps the results and events of the interactions.
+        let mut recorder = Recorder::new(MinimalSandbox::default());
+        let actor = MinimalSandbox::default_actor();
+        let gas_limit = MinimalSandbox::default_gas_limit();
+
+        let address = recorder
+            .record(|sandbox| {
+                sandbox.deploy_contract(
+                    wat::parse_str(CONTRACT).unwrap(),
+                    0,
+                    vec![],
+                    vec![],
+                    actor.clone(),
+                    gas_limit,
+                    None,
+                )
+            })
+            .result
+            .expect("Deployment should succeed")
+            .account_id;
+        recorder.record(|sandbox| {
+            sandbox.call_contract(
+                address.clone(),
+                0,
+                vec![],
+                actor.clone(),
+                gas_limit,
+                None,
+                Determinism::Enforced,
+            )
+        });
+
+        // All the `Record` accessors are available on the recorder.
+        assert_eq!(recorder.deploy_returns(), &[address]);
+        assert_eq!(recorder.callers(), &[actor]);
+        assert_eq!(recorder.last_call_return(), &[0u8; 4]);
+        assert!(recorder
+            .last_event_batch()
+            .outcome()
+            .is_some_and(|outcome| outcome.success));
+    }
+
+    #[test]
+    fn recorded_events_can_be_decoded_with_external_types() {
+        let mut recorder = Recorder::new(MinimalSandbox::default());
+        let actor = MinimalSandbox::default_actor();
+
+        let address = recorder
+            .record(|sandbox| {
+                sandbox.deploy_contract(
+                    wat::parse_str(CONTRACT).unwrap(),
+                    1_000_000,
+                    vec![],
+                    vec![],
+                    actor.clone(),
+                    MinimalSandbox::default_gas_limit(),
+                    None,
+                )
+            })
+            .result
+            .expect("Deployment should succeed")
+            .account_id;
+
+        // Here we use the runtime types, but any types with the same encoding would do (e.g. the
+        // ones generated by `subxt` from `Session::runtime_metadata`).
+        type Hash = <MinimalSandboxRuntime as frame_system::Config>::Hash;
+        let events = recorder
+            .last_event_batch()
+            .decode_with(|mut encoded| EventRecord::<RuntimeEvent, Hash>::decode(&mut encoded))
+            .expect("Events should decode");
+        assert!(events.iter().any(|record| matches!(
+            &record.event,
+            RuntimeEvent::Balances(pallet_balances::Event::Transfer { to, amount: 1_000_000, .. })
+                if *to == address
+        )));
+
+        // The metadata starts with the `meta` magic number.
+        let metadata = Session::<MinimalSandbox>::default().runtime_metadata();
+        assert_eq!(&metadata[..4], b"meta");
+    }
+
+    #[test]
+    fn fixtures_can_be_used_through_the_session() -> Result<(), Box<dyn std::error::Error>> {
+        let mut session = Session::<MinimalSandbox>::default();
+
+        let toggler = Fixture::Toggler.bundle();
+        let toggler_address =
+            session.deploy_bundle(toggler.clone(), "new", &["false"], NO_SALT, NO_ENDOWMENT)?;
+        session.call::<_, ()>("toggle", NO_ARGS, NO_ENDOWMENT)??;
+        assert!(session.call::<_, bool>("get", NO_ARGS, NO_ENDOWMENT)??);
+
+        // The proxy has no messages of its own, but we can call the toggler through it.
+        let proxy = session.deploy_bundle(
+            Fixture::Proxy.bundle(),
+            "new",
+            &[toggler_address.to_string()],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        session.set_transcoder(proxy.clone(), &toggler.transcoder);
+        session.call_with_address::<_, ()>(proxy, "toggle", NO_ARGS, NO_ENDOWMENT)??;
+        assert!(!session.call_with_address::<_, bool>(
+            toggler_address,
+            "get",
+            NO_ARGS,
+            NO_ENDOWMENT
+        )??);
+
+        // Events of the emitter can be decoded with its metadata.
+        let emitter = Fixture::EventEmitter.bundle();
+        session.deploy_bundle(emitter.clone(), "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)?;
+        session.call::<_, ()>("emit", &["42"], NO_ENDOWMENT)??;
+        let events = session
+            .record()
+            .last_event_batch()
+            .contract_events_decoded(&emitter.transcoder);
+        assert_eq!(events.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn fixtures_can_be_deployed_without_metadata() {
+        let mut sandbox = MinimalSandbox::default();
+        for fixture in Fixture::ALL {
+            let result = sandbox.deploy_contract(
+                fixture.wasm(),
+                0,
+                vec![],
+                fixture.name().as_bytes().to_vec(),
+                MinimalSandbox::default_actor(),
+                MinimalSandbox::default_gas_limit(),
+                None,
+            );
+            assert!(
+                result.result.is_ok(),
+                "{} should be deployable",
+                fixture.name()
+            );
+        }
+    }
+
+    #[test]
+    fn reentrancy_can_be_detected() -> Result<(), Box<dyn std::error::Error>> {
+        let mut session = Session::<MinimalSandbox>::default().with_reentrancy_detection();
+        let toggler = session.deploy_bundle(
+            Fixture::Toggler.bundle(),
+            "new",
+            &["false"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        let relay = session.deploy_bundle(
+            Fixture::Relay.bundle(),
+            "new",
+            NO_ARGS,
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+
+        // The encoded `relay(toggler, toggle())`.
+        let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{b:02x}")).collect::<String>();
+        let relay_to_toggler = format!("0xd2cdf8a3{}108dd592ec", hex(toggler.as_ref()));
+
+        // The relay calls the toggler: nothing suspicious.
+        session.call::<_, ()>(
+            "relay",
+            &[toggler.to_string(), "0x8dd592ec".to_string()],
+            NO_ENDOWMENT,
+        )??;
+        assert!(session.record().reentrancies().is_empty());
+
+        // The relay calls itself, and then the toggler.
+        let reentering_args = [relay.to_string(), relay_to_toggler];
+        session.call::<_, ()>("relay", &reentering_args, NO_ENDOWMENT)??;
+        let [reentrancy] = session.record().reentrancies() else {
+            panic!("Expected a single re-entrancy");
+        };
+        assert_eq!(reentrancy.interaction, 3);
+        assert_eq!(reentrancy.chain, vec![relay.clone(), relay.clone()]);
+
+        session.set_fail_on_reentrancy(true);
+        let result = session.call::<_, ()>("relay", &reentering_args, NO_ENDOWMENT);
+        assert!(
+            matches!(result, Err(SessionError::ReentrancyDetected { chain }) if chain.len() == 2)
+        );
+
+        // Allowed re-entrancy is neither reported nor failing.
+        session.allow_reentrancy(relay);
+        session.call::<_, ()>("relay", &reentering_args, NO_ENDOWMENT)??;
+        assert_eq!(session.record().reentrancies().len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn contracts_can_be_called_via_forwarder() -> Result<(), Box<dyn std::error::Error>> {
+        let mut session = Session::<MinimalSandbox>::default();
+        let toggler = session.deploy_bundle(
+            Fixture::Toggler.bundle(),
+            "new",
+            &["false"],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+
+        // The call is encoded with the toggler's transcoder, and the value reaches the toggler.
+        let balance = session.sandbox().free_balance(&toggler);
+        session.call_via_forwarder::<_, ()>(toggler.clone(), "toggle", NO_ARGS, Some(100))??;
+        assert!(session.call_via_forwarder::<_, bool>(toggler.clone(), "get", NO_ARGS, None)??);
+        assert_eq!(session.sandbox().free_balance(&toggler), balance + 100);
+
+        // The forwarder is deployed once, and it doesn't become the default callee.
+        let forwarder = session.forwarder()?;
+        assert_eq!(session.record().interactions().len(), 3);
+        assert!(session.call::<_, bool>("get", NO_ARGS, NO_ENDOWMENT)??);
+
+        // `forward_twice` toggles the toggler back.
+        let toggle_twice = [
+            toggler.to_string(),
+            "0x8dd592ec".to_string(),
+            "0".to_string(),
+        ];
+        session.call_with_address::<_, ()>(forwarder, "forward_twice", &toggle_twice, None)??;
+        assert!(!session.call::<_, bool>("get", NO_ARGS, NO_ENDOWMENT)??);
+
+        Ok(())
+    }
+
+    #[test]
+    fn delegate_proxies_use_the_aliased_transcoder() -> Result<(), Box<dyn std::error::Error>> {
+        let mut session = Session::<MinimalSandbox>::default();
+        let logic = session.deploy_bundle(
+            Fixture::EventEmitter.bundle(),
+            "new",
+            NO_ARGS,
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        let code_hash = session.code_hash_of(&logic).expect("Logic should exist");
+        let proxy = session.deploy_bundle(
+            Fixture::DelegateProxy.bundle(),
+            "new",
+            &[format!("{code_hash:?}")],
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+
+        // The proxy's own metadata doesn't know the messages of the logic.
+        assert!(session
+            .call_with_address::<_, ()>(proxy.clone(), "emit", &["42"], NO_ENDOWMENT)
+            .is_err());
+
+        session.alias_transcoder(proxy.clone(), logic.clone());
+        session.call_with_address::<_, ()>(proxy.clone(), "emit", &["42"], NO_ENDOWMENT)??;
+
+        // The event is emitted by the proxy, but decoded with the transcoder of the logic.
+        assert_eq!(
+            session
+                .record()
+                .last_event_batch()
+                .contract_events_by(&proxy)
+                .len(),
+            1
+        );
+        session.assert_events_in_order(
+            &proxy,
+            &[("Emitted", Some(vec![("value", Value::UInt(42))]))],
+        );
+
+        // The selector of `emit`, followed by the value.
+        let input = [0x07, 0x32, 0x9e, 0xfa, 42, 0, 0, 0];
+        assert_eq!(session.decode_input(&proxy, &input)?.label, "emit");
+
+        assert_eq!(session.remove_transcoder_alias(&proxy), Some(logic));
+        assert!(session.decode_input(&proxy, &input).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn block_hook_events_are_kept_apart() -> Result<(), Box<dyn std::error::Error>> {
+        let mut session = Session::<MinimalSandbox>::default();
+        session.deploy_bundle(
+            Fixture::EventEmitter.bundle(),
+            "new",
+            NO_ARGS,
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        let deployed_in = session.record().last_event_batch().block_number();
+        session.advance_blocks(3);
+        session.call::<_, ()>("emit", &["42"], NO_ENDOWMENT)??;
+
+        // The pallets of the minimal runtime don't emit events from their block hooks, so only the
+        // interactions have batches.
+        assert!(session.record().block_hook_batches().is_empty());
+        let batch = session.record().last_event_batch();
+        assert_eq!(batch.source(), EventSource::Interaction);
+        assert_eq!(batch.block_number(), deployed_in + 3);
+        assert!(batch.outcome().is_some_and(|outcome| outcome.success));
+
+        Ok(())
+    }
+
+    #[test]
+    fn environment_is_reported() {
+        let report = Session::<MinimalSandbox>::default().environment();
+
+        assert_eq!(report, drink::environment_report());
+        assert!(!report.drink_version.is_empty());
+        assert!(report.features.contains(&"session"));
+        assert!(report
+            .to_string()
+            .starts_with(&format!("drink {}", report.drink_version)));
+    }
+
+    #[test]
+    fn block_author_can_be_set() {
+        let mut session = Session::<MinimalSandbox>::default();
+        assert_eq!(session.block_author(), None);
+
+        let alice = AccountId32::new([1; 32]);
+        let bob = AccountId32::new([2; 32]);
+        session.set_block_author(alice);
+        session.set_block_author(bob.clone());
+        assert_eq!(session.block_author(), Some(bob.clone()));
+
+        // Runtime code can find the author in the digest.
+        let author = session.execute_with(|| {
+            let digest = frame_system::Pallet::<MinimalSandboxRuntime>::digest();
+            <DigestAuthor as FindAuthor<AccountId32>>::find_author(
+                digest.logs().iter().filter_map(DigestItem::as_pre_runtime),
+            )
+        });
+        assert_eq!(author, Some(bob));
+
+        // A new block has no author until it is set again.
+        session.sandbox().build_block();
+        assert_eq!(session.block_author(), None);
+    }
+
+    #[test]
+    fn rejected_code_is_reported() {
+        let mut session = Session::<MinimalSandbox>::default();
+
+        // The contract declares far more memory pages than the runtime allows.
+        let result = session.upload(wat::parse_str(TOO_MUCH_MEMORY_CONTRACT).unwrap());
+        assert!(matches!(result, Err(SessionError::CodeRejected)));
+    }
+
+    const TOO_MUCH_MEMORY_CONTRACT: &str = r#"
+    (module
+	(import "env" "memory" (memory 1 1024))
+	(func (export "deploy"))
+	(func (export "call"))
+    )"#;
+
     /// This is just a dummy contract code, that does nothing. It is written in WAT, a text format
     /// for WebAssembly. We need to have some valid contract bytes in order for `upload_contract`
     /// to succeed.
@@ -0,0 +1,205 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+#[ink::contract]
+mod storage {
+    use ink::storage::Mapping;
+
+    #[ink(storage)]
+    pub struct Storage {
+        entries: Mapping<u32, u32>,
+    }
+
+    impl Storage {
+        #[ink(constructor)]
+        #[allow(clippy::new_without_default)]
+        pub fn new() -> Self {
+            Self {
+                entries: Mapping::new(),
+            }
+        }
+
+        #[ink(message)]
+        pub fn insert(&mut self, key: u32, value: u32) {
+            self.entries.insert(key, &value);
+        }
+
+        #[ink(message)]
+        pub fn remove(&mut self, key: u32) {
+            self.entries.remove(key);
+        }
+
+        #[ink(message)]
+        pub fn close(&mut self) {
+            self.env().terminate_contract(self.env().caller())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use drink::{
+        pallet_contracts::StorageDeposit,
+        session::{
+            DepositChange, Session, ValueFlow, ValueFlowKind, NO_ARGS, NO_ENDOWMENT, NO_SALT,
+        },
+    };
+
+    #[drink::contract_bundle_provider]
+    enum BundleProvider {}
+
+    #[drink::test]
+    fn inserting_charges_and_removing_refunds(mut session: Session) -> Result<(), Box<dyn Error>> {
+        session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            NO_ARGS,
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+
+        // A new entry has to be paid for.
+        session.call::<_, ()>("insert", &["1", "2"], NO_ENDOWMENT)??;
+        let charged = session.record().last_storage_deposit();
+        assert!(charged > 0);
+
+        // Overwriting the entry with a value of the same size costs nothing.
+        session.expect_storage_deposit(StorageDeposit::Charge(0), |session| {
+            session.call::<_, ()>("insert", &["1", "3"], NO_ENDOWMENT)
+        })??;
+
+        // Removing the entry gives the deposit back.
+        session.expect_storage_deposit(StorageDeposit::Refund(charged as u128), |session| {
+            session.call::<_, ()>("remove", &["1"], NO_ENDOWMENT)
+        })??;
+        assert_eq!(session.record().last_storage_deposit(), -charged);
+        assert_eq!(
+            session.record().last_call_deposit(),
+            DepositChange::Refund(charged as u128)
+        );
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn overwriting_and_removing_are_storage_neutral(
+        mut session: Session,
+    ) -> Result<(), Box<dyn Error>> {
+        let address = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            NO_ARGS,
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        session.call::<_, ()>("insert", &["1", "2"], NO_ENDOWMENT)??;
+
+        session.assert_storage_neutral(&address, |session| {
+            session.call::<_, ()>("insert", &["1", "3"], NO_ENDOWMENT)
+        })??;
+        session.assert_storage_neutral(&address, |session| {
+            session.call::<_, ()>("remove", &["1"], NO_ENDOWMENT)
+        })??;
+
+        Ok(())
+    }
+
+    #[drink::test]
+    #[should_panic(expected = "entries (4 bytes). Grown entries: 0x")]
+    fn storage_growth_is_reported(mut session: Session) {
+        let address = session
+            .deploy_bundle(
+                BundleProvider::local().unwrap(),
+                "new",
+                NO_ARGS,
+                NO_SALT,
+                NO_ENDOWMENT,
+            )
+            .unwrap();
+
+        let _ = session.assert_storage_neutral(&address, |session| {
+            session.call::<_, ()>("insert", &["1", "2"], NO_ENDOWMENT)
+        });
+    }
+
+    #[test]
+    fn deposits_of_any_runtime_can_be_normalized() {
+        // E.g. a runtime with `u64` balances.
+        let deposit = StorageDeposit::<u64>::Charge(u64::MAX);
+        let normalized = DepositChange::from_storage_deposit(&deposit);
+        assert_eq!(normalized, DepositChange::Charge(u64::MAX as u128));
+        assert_eq!(normalized.signed(), u64::MAX as i128);
+
+        let deposit = StorageDeposit::<u64>::Refund(7);
+        assert_eq!(DepositChange::from_storage_deposit(&deposit).signed(), -7);
+
+        // Amounts that do not fit into `i128` saturate when signed.
+        assert_eq!(DepositChange::Refund(u128::MAX).signed(), -i128::MAX);
+    }
+
+    #[drink::test]
+    fn storage_deposits_show_up_as_value_flows(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let contract = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            NO_ARGS,
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        let actor = session.get_actor();
+
+        // The deposit is moved from the caller to the contract, and is distinguishable from plain
+        // transfers.
+        session.call::<_, ()>("insert", &["1", "2"], NO_ENDOWMENT)??;
+        let charged = session.record().last_storage_deposit() as u128;
+        let deposits = session
+            .record()
+            .last_event_batch()
+            .value_flows()
+            .into_iter()
+            .filter(|flow| flow.kind != ValueFlowKind::Transfer)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            deposits,
+            vec![ValueFlow {
+                from: actor.clone(),
+                to: contract.clone(),
+                amount: charged,
+                kind: ValueFlowKind::StorageDepositHeld,
+            }]
+        );
+
+        // Removing the entry moves the deposit back.
+        session.assert_value_flow(contract, actor, charged, |session| {
+            session.call::<_, ()>("remove", &["1"], NO_ENDOWMENT)
+        })??;
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn terminating_reaps_the_contract_account(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let contract = session.deploy_bundle(
+            BundleProvider::local()?,
+            "new",
+            NO_ARGS,
+            NO_SALT,
+            NO_ENDOWMENT,
+        )?;
+        assert!(session
+            .record()
+            .last_event_batch()
+            .new_accounts()
+            .contains(&contract));
+
+        session.call::<_, ()>("close", NO_ARGS, NO_ENDOWMENT)??;
+        assert!(session.existential_deposit() > 0);
+        assert_eq!(
+            session.record().last_event_batch().reaped_accounts(),
+            vec![contract]
+        );
+
+        Ok(())
+    }
+}
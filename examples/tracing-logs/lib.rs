@@ -0,0 +1,139 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+#[ink::contract]
+mod counter {
+    #[ink(storage)]
+    pub struct Counter {
+        value: u32,
+    }
+
+    impl Counter {
+        #[ink(constructor)]
+        #[allow(clippy::new_without_default)]
+        pub fn new() -> Self {
+            Self { value: 0 }
+        }
+
+        #[ink(message)]
+        pub fn bump(&mut self) {
+            self.value += 1;
+        }
+
+        #[ink(message)]
+        pub fn get(&self) -> u32 {
+            self.value
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        error::Error,
+        fmt::Debug,
+        sync::{Arc, Mutex},
+    };
+
+    use drink::{
+        minimal::MinimalSandbox,
+        session::{Session, NO_ARGS, NO_ENDOWMENT, NO_SALT},
+    };
+    use tracing::{
+        field::{Field, Visit},
+        Event, Subscriber,
+    };
+    use tracing_subscriber::{
+        layer::{Context, SubscriberExt},
+        registry::LookupSpan,
+        Layer,
+    };
+
+    #[drink::contract_bundle_provider]
+    enum BundleProvider {}
+
+    /// A single event, as seen by the subscriber.
+    #[derive(Debug)]
+    struct CapturedEvent {
+        target: String,
+        span: Option<String>,
+        fields: BTreeMap<String, String>,
+    }
+
+    impl CapturedEvent {
+        fn message(&self) -> &str {
+            self.fields.get("message").map(String::as_str).unwrap_or("")
+        }
+    }
+
+    /// A `tracing` layer that stores all the events it receives.
+    #[derive(Clone, Default)]
+    struct CapturingLayer {
+        events: Arc<Mutex<Vec<CapturedEvent>>>,
+    }
+
+    impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for CapturingLayer {
+        fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+            let mut fields = FieldCollector::default();
+            event.record(&mut fields);
+
+            self.events.lock().unwrap().push(CapturedEvent {
+                target: event.metadata().target().to_string(),
+                span: ctx.event_span(event).map(|span| span.name().to_string()),
+                fields: fields.0,
+            });
+        }
+    }
+
+    #[derive(Default)]
+    struct FieldCollector(BTreeMap<String, String>);
+
+    impl Visit for FieldCollector {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{value:?}"));
+        }
+    }
+
+    #[test]
+    fn session_emits_structured_events() -> Result<(), Box<dyn Error>> {
+        let layer = CapturingLayer::default();
+        let subscriber = tracing_subscriber::registry().with(layer.clone());
+
+        tracing::subscriber::with_default(subscriber, || -> Result<(), Box<dyn Error>> {
+            Session::<MinimalSandbox>::default()
+                .deploy_bundle_and(
+                    BundleProvider::local()?,
+                    "new",
+                    NO_ARGS,
+                    NO_SALT,
+                    NO_ENDOWMENT,
+                )?
+                .call_and("bump", NO_ARGS, NO_ENDOWMENT)?;
+            Ok(())
+        })?;
+
+        let events = layer.events.lock().unwrap();
+        let find = |message: &str| {
+            events
+                .iter()
+                .find(|event| event.message() == message)
+                .unwrap_or_else(|| panic!("Missing `{message}` event in {events:?}"))
+        };
+
+        assert!(find("session created").target.starts_with("drink::session"));
+        assert!(find("transcoder registered")
+            .fields
+            .contains_key("contract"));
+
+        let deployed = find("contract deployed");
+        assert_eq!(deployed.span.as_deref(), Some("deploy"));
+        assert!(deployed.fields.contains_key("address"));
+
+        let called = find("contract called");
+        assert_eq!(called.span.as_deref(), Some("call_internal"));
+        assert!(called.fields.contains_key("gas_consumed"));
+
+        Ok(())
+    }
+}